@@ -0,0 +1,36 @@
+//! Embeds build details reported by `enseal version`.
+
+use std::env;
+use std::fs;
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    let target = env::var("TARGET").unwrap_or_else(|_| "unknown".to_string());
+    println!("cargo:rustc-env=ENSEAL_BUILD_TARGET={target}");
+
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    for (krate, var) in [
+        ("age", "ENSEAL_AGE_VERSION"),
+        ("magic-wormhole", "ENSEAL_WORMHOLE_VERSION"),
+    ] {
+        let version = locked_version(&lock, krate).unwrap_or("unknown");
+        println!("cargo:rustc-env={var}={version}");
+    }
+}
+
+/// Find the resolved version of a package in Cargo.lock.
+fn locked_version<'a>(lock: &'a str, krate: &str) -> Option<&'a str> {
+    let name_line = format!("name = \"{krate}\"");
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == name_line {
+            return lines
+                .next()?
+                .trim()
+                .strip_prefix("version = \"")?
+                .strip_suffix('"');
+        }
+    }
+    None
+}
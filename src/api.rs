@@ -0,0 +1,122 @@
+//! High-level API for embedding enseal in other Rust programs.
+//!
+//! These wrap the `crypto` and `transfer` plumbing the CLI uses, so a payload
+//! sealed here opens with `enseal receive` and vice versa.
+//!
+//! ```
+//! use enseal::api;
+//! use enseal::keys::identity::EnsealIdentity;
+//!
+//! let sender = EnsealIdentity::generate();
+//! let recipient = EnsealIdentity::generate();
+//!
+//! let wire = api::encrypt_env_to("API_KEY=abc123\n", &[&recipient.age_recipient], &sender)?;
+//! assert_eq!(api::decrypt_env(&wire, &recipient)?, "API_KEY=abc123\n");
+//! # Ok::<(), enseal::error::EnsealError>(())
+//! ```
+
+use crate::cli::input::PayloadFormat;
+use crate::crypto::envelope::Envelope;
+use crate::error::{EnsealError, Result};
+use crate::keys::identity::{EnsealIdentity, TrustedKey};
+use crate::transfer;
+
+pub use crate::crypto::signing::{open_signed, seal_signed};
+
+/// Encrypt `.env` content to `recipients` and sign it with `sender`.
+///
+/// Returns signed wire bytes, the same format as file drops (`.env.age`)
+/// and relay pushes.
+#[allow(dead_code)]
+pub fn encrypt_env_to(
+    content: &str,
+    recipients: &[&age::x25519::Recipient],
+    sender: &EnsealIdentity,
+) -> Result<Vec<u8>> {
+    let envelope = Envelope::seal(content, PayloadFormat::Env, None)?;
+    seal_signed(&envelope, recipients, sender)
+}
+
+/// Verify and decrypt wire bytes produced by [`encrypt_env_to`], returning
+/// the `.env` content.
+///
+/// The signature is checked against the key embedded in the payload; use
+/// [`open_signed`] to also require a specific trusted sender.
+///
+/// ```
+/// use enseal::api;
+/// use enseal::error::EnsealError;
+/// use enseal::keys::identity::EnsealIdentity;
+///
+/// let sender = EnsealIdentity::generate();
+/// let recipient = EnsealIdentity::generate();
+/// let wire = api::encrypt_env_to("DB_PASSWORD=s3cret\n", &[&recipient.age_recipient], &sender)?;
+///
+/// let stranger = EnsealIdentity::generate();
+/// assert!(matches!(api::decrypt_env(&wire, &stranger), Err(EnsealError::Decrypt(_))));
+/// # Ok::<(), EnsealError>(())
+/// ```
+#[allow(dead_code)]
+pub fn decrypt_env(bytes: &[u8], identity: &EnsealIdentity) -> Result<String> {
+    let (envelope, _) = open_signed(bytes, identity, None, Some(Envelope::FILEDROP_MAX_AGE))?;
+    Ok(envelope.payload)
+}
+
+/// Encrypt `.env` content to `recipients` and push it to each recipient's
+/// channel on an enseal relay. Recipients pick it up with `enseal receive
+/// --listen` or [`receive_via_relay`].
+#[allow(dead_code)]
+pub async fn share_via_relay(
+    content: &str,
+    recipients: &[TrustedKey],
+    sender: &EnsealIdentity,
+    relay_url: &str,
+) -> Result<()> {
+    let age_recipients: Vec<&age::x25519::Recipient> =
+        recipients.iter().map(|k| &k.age_recipient).collect();
+    let wire = encrypt_env_to(content, &age_recipients, sender)?;
+    for recipient in recipients {
+        transfer::relay::push(
+            &wire,
+            relay_url,
+            &recipient.channel_id(),
+            transfer::retry::DEFAULT_RETRIES,
+            transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .map_err(network_error)?;
+    }
+    Ok(())
+}
+
+/// Wait on `identity`'s relay channel for one payload and return the
+/// decrypted `.env` content.
+#[allow(dead_code)]
+pub async fn receive_via_relay(relay_url: &str, identity: &EnsealIdentity) -> Result<String> {
+    let data = listen_relay(
+        relay_url,
+        identity,
+        transfer::retry::DEFAULT_RETRIES,
+        transfer::DEFAULT_TIMEOUT,
+    )
+    .await?;
+    let (envelope, _) = open_signed(&data, identity, None, Some(Envelope::NETWORK_MAX_AGE))?;
+    Ok(envelope.payload)
+}
+
+/// Wait up to `timeout` on `identity`'s relay channel and return the raw
+/// wire bytes.
+pub async fn listen_relay(
+    relay_url: &str,
+    identity: &EnsealIdentity,
+    retries: u32,
+    timeout: std::time::Duration,
+) -> Result<Vec<u8>> {
+    transfer::relay::listen(relay_url, &identity.channel_id(), retries, timeout)
+        .await
+        .map_err(network_error)
+}
+
+fn network_error(e: anyhow::Error) -> EnsealError {
+    EnsealError::Network(format!("{:#}", e))
+}
@@ -1,9 +1,11 @@
 use std::path::Path;
 
-use anyhow::{bail, Result};
+use anyhow::Result;
 use clap::Args;
 
+use crate::cli::{exit, verify_manifest};
 use crate::env::{self, diff};
+use crate::transfer::manifest::{self, Discrepancy};
 use crate::ui::display;
 
 #[derive(Args)]
@@ -15,14 +17,30 @@ pub struct CheckArgs {
     /// Path to .env.example to check against (default: .env.example)
     #[arg(long, default_value = ".env.example")]
     pub example: String,
+
+    /// Also fail on vars in the .env file that are not in .env.example
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Check against the var names in a `share --manifest` record instead
+    /// of .env.example; missing and extra vars both fail (values and hash
+    /// are not compared)
+    #[arg(long, value_name = "MANIFEST", conflicts_with_all = ["example", "strict"])]
+    pub against: Option<String>,
 }
 
 pub fn run(args: CheckArgs) -> Result<()> {
     if !Path::new(&args.file).exists() {
-        bail!("{} not found", args.file);
+        return Err(exit::not_found(format!("{} not found", args.file)));
+    }
+    if let Some(ref manifest) = args.against {
+        return check_against(&args.file, manifest);
     }
     if !Path::new(&args.example).exists() {
-        bail!("{} not found (required for check)", args.example);
+        return Err(exit::not_found(format!(
+            "{} not found (required for check)",
+            args.example
+        )));
     }
 
     let env_content = std::fs::read_to_string(&args.file)?;
@@ -31,9 +49,12 @@ pub fn run(args: CheckArgs) -> Result<()> {
     let env_file = env::parser::parse(&env_content)?;
     let example_file = env::parser::parse(&example_content)?;
 
+    // Left is the example: only_left = missing, only_right = extra
     let d = diff::diff(&example_file, &env_file);
+    let missing = &d.only_left;
+    let extra = &d.only_right;
 
-    if d.only_left.is_empty() {
+    if missing.is_empty() && (!args.strict || extra.is_empty()) {
         display::ok(&format!(
             "all {} vars from {} present in {}",
             example_file.var_count(),
@@ -43,23 +64,86 @@ pub fn run(args: CheckArgs) -> Result<()> {
         return Ok(());
     }
 
-    display::error(&format!(
-        "missing from {} (present in {}):",
-        args.file, args.example
-    ));
-    for key in &d.only_left {
-        eprintln!("  {}", key);
-    }
-
-    if !d.only_right.is_empty() {
-        display::warning(&format!(
-            "extra in {} (not in {}):",
+    if !missing.is_empty() {
+        display::error(&format!(
+            "missing from {} (present in {}):",
             args.file, args.example
         ));
-        for key in &d.only_right {
+        for key in missing {
             eprintln!("  {}", key);
         }
     }
 
-    bail!("{} variables missing from {}", d.only_left.len(), args.file);
+    if !extra.is_empty() {
+        let header = format!("extra in {} (not in {}):", args.file, args.example);
+        if args.strict {
+            display::error(&header);
+        } else {
+            display::warning(&header);
+        }
+        for key in extra {
+            eprintln!("  {}", key);
+        }
+    }
+
+    if missing.is_empty() {
+        return Err(exit::validation(format!(
+            "{} variables in {} not in {}",
+            extra.len(),
+            args.file,
+            args.example
+        )));
+    }
+    Err(exit::validation(format!(
+        "{} variables missing from {}",
+        missing.len(),
+        args.file
+    )))
+}
+
+/// `--against`: the .env must hold exactly the manifest's declared vars.
+fn check_against(file: &str, manifest_path: &str) -> Result<()> {
+    let manifest = verify_manifest::load(manifest_path)?;
+    let env_file = env::parser::parse(&std::fs::read_to_string(file)?)?;
+
+    let (mut missing, mut extra) = (Vec::new(), Vec::new());
+    for discrepancy in manifest::key_discrepancies(&manifest, &env_file) {
+        match discrepancy {
+            Discrepancy::Missing(key) => missing.push(key),
+            Discrepancy::Extra(key) => extra.push(key),
+            Discrepancy::HashMismatch { .. } => {}
+        }
+    }
+
+    if missing.is_empty() && extra.is_empty() {
+        display::ok(&format!(
+            "{} has exactly the {} vars declared in {}",
+            file,
+            manifest.vars.len(),
+            manifest_path
+        ));
+        return Ok(());
+    }
+
+    if !missing.is_empty() {
+        display::error(&format!(
+            "missing from {} (declared in {}):",
+            file, manifest_path
+        ));
+        for key in &missing {
+            eprintln!("  {}", key);
+        }
+    }
+    if !extra.is_empty() {
+        display::error(&format!("extra in {} (not in {}):", file, manifest_path));
+        for key in &extra {
+            eprintln!("  {}", key);
+        }
+    }
+    Err(exit::validation(format!(
+        "{} missing and {} extra variables in {}",
+        missing.len(),
+        extra.len(),
+        file
+    )))
 }
@@ -0,0 +1,260 @@
+use anyhow::{bail, Context, Result};
+use clap::{Args, ValueEnum};
+use indexmap::IndexMap;
+use yaml_rust2::parser::{Event, Parser};
+use yaml_rust2::scanner::TScalarStyle;
+
+use crate::env::{self, Entry, EnvFile};
+use crate::ui::display;
+
+#[derive(Args)]
+pub struct ConvertArgs {
+    /// Path to the input file (default: .env)
+    #[arg(default_value = ".env")]
+    pub file: String,
+
+    /// Format of the input file
+    #[arg(long, value_enum, default_value = "env")]
+    pub from: Format,
+
+    /// Format to write
+    #[arg(long, value_enum, default_value = "env")]
+    pub to: Format,
+
+    /// Write output to file instead of stdout
+    #[arg(long)]
+    pub output: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Env,
+    Json,
+    Yaml,
+}
+
+pub fn run(args: ConvertArgs) -> Result<()> {
+    if args.from == args.to {
+        return Err(crate::cli::exit::usage(
+            "--from and --to are the same format; nothing to convert",
+        ));
+    }
+
+    let content = std::fs::read_to_string(&args.file)
+        .map_err(|e| crate::cli::exit::read_failed(&args.file, e))?;
+    let pairs = read_pairs(&content, args.from)?;
+    let output = write_pairs(&pairs, args.to)?;
+
+    if let Some(path) = &args.output {
+        crate::fsutil::write_secret_file(path, output.as_bytes())
+            .map_err(|e| anyhow::anyhow!("failed to write '{}': {}", path, e))?;
+        display::ok(&format!("{} variables written to {}", pairs.len(), path));
+    } else {
+        print!("{}", output);
+    }
+
+    Ok(())
+}
+
+/// Read key-value pairs in file order. A repeated key keeps its first
+/// position and its last value, matching `EnvFile::get`.
+fn read_pairs(content: &str, format: Format) -> Result<IndexMap<String, String>> {
+    match format {
+        Format::Env => {
+            let env_file = env::parser::parse(content)?;
+            Ok(env_file
+                .vars()
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect())
+        }
+        Format::Json => {
+            let map: IndexMap<String, serde_json::Value> =
+                serde_json::from_str(content).context("input is not a flat JSON object")?;
+            map.into_iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        serde_json::Value::String(s) => s,
+                        serde_json::Value::Number(n) => n.to_string(),
+                        serde_json::Value::Bool(b) => b.to_string(),
+                        _ => bail!("value of '{}' must be a string, number, or boolean", key),
+                    };
+                    Ok((key, value))
+                })
+                .collect()
+        }
+        Format::Yaml => read_yaml(content),
+    }
+}
+
+/// Read a flat YAML mapping from parser events, so scalars keep their source
+/// text: `ZIP: 01234` stays `01234` instead of becoming the number 1234.
+fn read_yaml(content: &str) -> Result<IndexMap<String, String>> {
+    let not_flat = "input is not a flat YAML mapping";
+    let mut parser = Parser::new_from_str(content);
+    let mut pairs = IndexMap::new();
+    let mut key: Option<String> = None;
+    let (mut in_mapping, mut seen_mapping) = (false, false);
+    loop {
+        let (event, _) = parser.next_token().context(not_flat)?;
+        match event {
+            Event::StreamEnd => break,
+            Event::MappingStart(..) if !seen_mapping => {
+                in_mapping = true;
+                seen_mapping = true;
+            }
+            Event::MappingEnd if in_mapping => in_mapping = false,
+            Event::Scalar(text, style, ..) if in_mapping => match key.take() {
+                None => key = Some(text),
+                Some(key) => {
+                    if style == TScalarStyle::Plain
+                        && ["", "~", "null", "Null", "NULL"].contains(&text.as_str())
+                    {
+                        bail!("value of '{}' must be a string, number, or boolean", key);
+                    }
+                    pairs.insert(key, text);
+                }
+            },
+            Event::MappingStart(..) | Event::SequenceStart(..) | Event::Alias(_) if in_mapping => {
+                match key {
+                    Some(key) => {
+                        bail!("value of '{}' must be a string, number, or boolean", key)
+                    }
+                    None => bail!(not_flat),
+                }
+            }
+            Event::Nothing | Event::StreamStart | Event::DocumentStart | Event::DocumentEnd => {}
+            _ => bail!(not_flat),
+        }
+    }
+    Ok(pairs)
+}
+
+/// Write a YAML mapping with every key and value double-quoted. A JSON
+/// string is a valid YAML double-quoted scalar, so serde_json does the escaping.
+fn write_yaml(pairs: &IndexMap<String, String>) -> Result<String> {
+    if pairs.is_empty() {
+        return Ok("{}\n".to_string());
+    }
+    let mut out = String::new();
+    for (key, value) in pairs {
+        out.push_str(&format!(
+            "{}: {}\n",
+            serde_json::to_string(key)?,
+            serde_json::to_string(value)?
+        ));
+    }
+    Ok(out)
+}
+
+/// Serialize pairs; values are always emitted as strings so nothing like
+/// `007` or `true` is reinterpreted by the consumer.
+fn write_pairs(pairs: &IndexMap<String, String>, format: Format) -> Result<String> {
+    match format {
+        Format::Env => {
+            let mut env_file = EnvFile::new();
+            for (key, value) in pairs {
+                if key.is_empty() || key.contains(['=', '#']) || key.contains(char::is_whitespace) {
+                    bail!("'{}' is not a valid .env key", key);
+                }
+                env_file.entries.push(Entry::KeyValue {
+                    key: key.clone(),
+                    value: value.clone(),
+                    comment: None,
+                });
+            }
+            Ok(env_file.to_string())
+        }
+        Format::Json => Ok(serde_json::to_string_pretty(pairs)? + "\n"),
+        Format::Yaml => write_yaml(pairs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str =
+        "DB_URL=postgres://u:p@h/db?sslmode=require\nZIP=02134\nGREETING=\"say \\\"hi\\\"\"\nDEBUG=true\n";
+
+    fn expected() -> IndexMap<String, String> {
+        [
+            ("DB_URL", "postgres://u:p@h/db?sslmode=require"),
+            ("ZIP", "02134"),
+            ("GREETING", "say \"hi\""),
+            ("DEBUG", "true"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn env_to_json_keeps_order_and_strings() {
+        let pairs = read_pairs(SAMPLE, Format::Env).unwrap();
+        let json = write_pairs(&pairs, Format::Json).unwrap();
+        assert!(json.find("DB_URL").unwrap() < json.find("ZIP").unwrap());
+        assert!(json.contains("\"ZIP\": \"02134\""));
+        assert!(json.contains("\"DEBUG\": \"true\""));
+        assert_eq!(read_pairs(&json, Format::Json).unwrap(), expected());
+    }
+
+    #[test]
+    fn env_to_yaml_round_trip() {
+        let pairs = read_pairs(SAMPLE, Format::Env).unwrap();
+        let yaml = write_pairs(&pairs, Format::Yaml).unwrap();
+        assert_eq!(read_pairs(&yaml, Format::Yaml).unwrap(), expected());
+        let keys: Vec<_> = read_pairs(&yaml, Format::Yaml)
+            .unwrap()
+            .into_keys()
+            .collect();
+        assert_eq!(keys, vec!["DB_URL", "ZIP", "GREETING", "DEBUG"]);
+        assert!(yaml.contains("\"ZIP\": \"02134\"\n"));
+        assert!(yaml.contains("\"DEBUG\": \"true\"\n"));
+    }
+
+    #[test]
+    fn yaml_plain_scalars_keep_their_text() {
+        let yaml = "ZIP: 01234\nRATE: 1.50\nDEBUG: yes\nNAME: 'o''brien'\n";
+        let pairs = read_pairs(yaml, Format::Yaml).unwrap();
+        assert_eq!(pairs["ZIP"], "01234");
+        assert_eq!(pairs["RATE"], "1.50");
+        assert_eq!(pairs["DEBUG"], "yes");
+        assert_eq!(pairs["NAME"], "o'brien");
+    }
+
+    #[test]
+    fn nested_or_null_yaml_rejected() {
+        for yaml in [
+            "A:\n  B: c\n",
+            "A: [1, 2]\n",
+            "A:\n",
+            "A: ~\n",
+            "- a\n",
+            "plain\n",
+        ] {
+            assert!(read_pairs(yaml, Format::Yaml).is_err(), "{}", yaml);
+        }
+        // Quoted nulls are ordinary strings
+        assert_eq!(
+            read_pairs("A: 'null'\n", Format::Yaml).unwrap()["A"],
+            "null"
+        );
+    }
+
+    #[test]
+    fn json_to_env_round_trip() {
+        let json = serde_json::to_string(&expected()).unwrap();
+        let pairs = read_pairs(&json, Format::Json).unwrap();
+        let env_text = write_pairs(&pairs, Format::Env).unwrap();
+        assert_eq!(read_pairs(&env_text, Format::Env).unwrap(), expected());
+    }
+
+    #[test]
+    fn nested_json_rejected() {
+        assert!(read_pairs(r#"{"A": {"B": "c"}}"#, Format::Json).is_err());
+        assert!(read_pairs(r#"{"A B": "c"}"#, Format::Json)
+            .and_then(|p| write_pairs(&p, Format::Env))
+            .is_err());
+    }
+}
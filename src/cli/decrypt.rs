@@ -3,6 +3,8 @@ use clap::Args;
 
 use crate::crypto::at_rest;
 use crate::env;
+use crate::error::EnsealError;
+use crate::fsutil::write_secret_file;
 use crate::keys::identity::EnsealIdentity;
 use crate::keys::store::KeyStore;
 use crate::ui::display;
@@ -20,45 +22,101 @@ pub struct DecryptArgs {
     /// Overwrite existing files without prompting
     #[arg(long)]
     pub force: bool,
+
+    /// If the current key can't decrypt, try keys archived by `keys rotate-self`
+    #[arg(long)]
+    pub try_archived: bool,
 }
 
-pub fn run(args: DecryptArgs) -> Result<()> {
-    let raw_content = std::fs::read(&args.file)
-        .map_err(|e| anyhow::anyhow!("failed to read '{}': {}", args.file, e))?;
+pub fn run(args: DecryptArgs, store: &KeyStore) -> Result<()> {
+    let raw_content =
+        std::fs::read(&args.file).map_err(|e| crate::cli::exit::read_failed(&args.file, e))?;
 
     // Auto-detect format before loading identity (fail fast on unencrypted files)
-    let is_whole_file = at_rest::is_age_encrypted(&raw_content);
-
-    let text = if !is_whole_file {
-        let t = String::from_utf8(raw_content.clone())
-            .map_err(|_| anyhow::anyhow!("file is not valid UTF-8 and not age-encrypted"))?;
-        if !at_rest::is_per_var_encrypted(&t) {
-            anyhow::bail!(
-                "file '{}' doesn't appear to be encrypted (not age format, no ENC[age:...] values)",
-                args.file
-            );
+    let text = per_var_text(&args.file, &raw_content)?;
+
+    let mut identities = vec![EnsealIdentity::load(store)?];
+    if args.try_archived {
+        identities.extend(EnsealIdentity::load_archived(store)?);
+    }
+
+    match text {
+        None => decrypt_whole_file(&args, &raw_content, &identities),
+        Some(text) => decrypt_per_var(&args, &text, &identities),
+    }
+}
+
+/// Run `decrypt` with each identity in turn (current key first). If none
+/// succeeds, the current key's error is reported.
+fn with_any_identity<T>(
+    identities: &[EnsealIdentity],
+    decrypt: impl Fn(&EnsealIdentity) -> Result<T, EnsealError>,
+) -> Result<T> {
+    let mut first_error = None;
+    for (i, identity) in identities.iter().enumerate() {
+        match decrypt(identity) {
+            Ok(value) => {
+                if i > 0 {
+                    display::warning(&format!(
+                        "decrypted with archived key {}; re-encrypt to your current key",
+                        identity.fingerprint()
+                    ));
+                }
+                return Ok(value);
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
         }
-        Some(t)
-    } else {
-        None
-    };
+    }
+    Err(first_error
+        .expect("at least the current identity is tried")
+        .into())
+}
 
-    let store = KeyStore::open()?;
-    let identity = EnsealIdentity::load(&store)?;
+/// Classify an at-rest encrypted file: `None` for whole-file age ciphertext,
+/// the text for per-variable `ENC[age:...]` files. Errors if it's neither.
+fn per_var_text(path: &str, raw_content: &[u8]) -> Result<Option<String>> {
+    if at_rest::is_whole_file_encrypted(raw_content) {
+        return Ok(None);
+    }
+    let text = String::from_utf8(raw_content.to_vec())
+        .map_err(|_| anyhow::anyhow!("file is not valid UTF-8 and not age-encrypted"))?;
+    if !at_rest::is_per_var_encrypted(&text) {
+        bail!(
+            "file '{}' doesn't appear to be encrypted (not age format, no ENC[age:...] values)",
+            path
+        );
+    }
+    Ok(Some(text))
+}
 
-    if is_whole_file {
-        decrypt_whole_file(&args, &raw_content, &identity)
-    } else {
-        decrypt_per_var(&args, text.as_ref().unwrap(), &identity)
+/// Decrypt an at-rest encrypted .env file (either format) without writing
+/// any plaintext to disk.
+pub fn decrypt_in_memory(path: &str, identity: &EnsealIdentity) -> Result<env::EnvFile> {
+    let raw_content = std::fs::read(path).map_err(|e| crate::cli::exit::read_failed(path, e))?;
+    match per_var_text(path, &raw_content)? {
+        None => {
+            let plaintext = at_rest::decrypt_whole_file(&raw_content, &identity.age_identity)?;
+            let text = String::from_utf8(plaintext)
+                .map_err(|_| anyhow::anyhow!("decrypted '{}' is not valid UTF-8", path))?;
+            Ok(env::parser::parse(&text)?)
+        }
+        Some(text) => Ok(at_rest::decrypt_per_var(
+            &env::parser::parse(&text)?,
+            &identity.age_identity,
+        )?),
     }
 }
 
 fn decrypt_whole_file(
     args: &DecryptArgs,
     ciphertext: &[u8],
-    identity: &EnsealIdentity,
+    identities: &[EnsealIdentity],
 ) -> Result<()> {
-    let plaintext = at_rest::decrypt_whole_file(ciphertext, &identity.age_identity)?;
+    let plaintext = with_any_identity(identities, |identity| {
+        at_rest::decrypt_whole_file(ciphertext, &identity.age_identity)
+    })?;
 
     let output_path = args.output.clone().unwrap_or_else(|| {
         if args.file.ends_with(".encrypted") {
@@ -88,31 +146,11 @@ fn decrypt_whole_file(
     Ok(())
 }
 
-/// Write a file containing secrets with restrictive permissions (0600 on Unix).
-fn write_secret_file(path: &str, content: &[u8]) -> Result<()> {
-    #[cfg(unix)]
-    {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-        use std::os::unix::fs::OpenOptionsExt;
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .mode(0o600)
-            .open(path)?;
-        file.write_all(content)?;
-    }
-    #[cfg(not(unix))]
-    {
-        std::fs::write(path, content)?;
-    }
-    Ok(())
-}
-
-fn decrypt_per_var(args: &DecryptArgs, content: &str, identity: &EnsealIdentity) -> Result<()> {
+fn decrypt_per_var(args: &DecryptArgs, content: &str, identities: &[EnsealIdentity]) -> Result<()> {
     let env_file = env::parser::parse(content)?;
-    let decrypted = at_rest::decrypt_per_var(&env_file, &identity.age_identity)?;
+    let decrypted = with_any_identity(identities, |identity| {
+        at_rest::decrypt_per_var(&env_file, &identity.age_identity)
+    })?;
     let output_str = decrypted.to_string();
 
     let output_path = args.output.clone().unwrap_or_else(|| args.file.clone());
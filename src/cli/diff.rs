@@ -2,7 +2,12 @@ use anyhow::{bail, Result};
 use clap::Args;
 use console::style;
 
-use crate::env::{self, diff as env_diff};
+use crate::cli::exit;
+use crate::crypto::at_rest;
+use crate::env::{self, diff as env_diff, EnvFile};
+use crate::keys::identity::EnsealIdentity;
+use crate::keys::store::KeyStore;
+use crate::ui::display;
 
 #[derive(Args)]
 pub struct DiffArgs {
@@ -11,26 +16,70 @@ pub struct DiffArgs {
 
     /// Second .env file
     pub file2: String,
+
+    /// Also report keys whose values differ (decrypts encrypted files in memory)
+    #[arg(long)]
+    pub values: bool,
+
+    /// Allow --values on plaintext files
+    #[arg(long, requires = "values")]
+    pub i_understand: bool,
+
+    /// Treat keys differing only by case as the same (API_KEY = api_key)
+    #[arg(long)]
+    pub ignore_case: bool,
+
+    /// Drop this prefix from keys before comparing (APP_FOO = FOO)
+    #[arg(long, value_name = "PREFIX")]
+    pub strip_prefix: Option<String>,
 }
 
-pub fn run(args: DiffArgs) -> Result<()> {
-    if !std::path::Path::new(&args.file1).exists() {
-        bail!("{} not found", args.file1);
-    }
-    if !std::path::Path::new(&args.file2).exists() {
-        bail!("{} not found", args.file2);
+pub fn run(args: DiffArgs, store: &KeyStore) -> Result<()> {
+    for file in [&args.file1, &args.file2] {
+        if !std::path::Path::new(file).exists() {
+            return Err(exit::not_found(format!("{} not found", file)));
+        }
     }
 
-    let content1 = std::fs::read_to_string(&args.file1)?;
-    let content2 = std::fs::read_to_string(&args.file2)?;
+    let (env1, env2) = if args.values {
+        load_for_values(&args, store)?
+    } else {
+        let content1 =
+            std::fs::read_to_string(&args.file1).map_err(|e| exit::read_failed(&args.file1, e))?;
+        let content2 =
+            std::fs::read_to_string(&args.file2).map_err(|e| exit::read_failed(&args.file2, e))?;
+        (
+            env::parser::parse(&content1)?,
+            env::parser::parse(&content2)?,
+        )
+    };
 
-    let env1 = env::parser::parse(&content1)?;
-    let env2 = env::parser::parse(&content2)?;
+    let normalize = env_diff::Normalize {
+        ignore_case: args.ignore_case,
+        strip_prefix: args.strip_prefix.clone(),
+    };
+    let (env1, env2) = if normalize.is_noop() {
+        (env1, env2)
+    } else {
+        (
+            normalized(&env1, &normalize, &args.file1),
+            normalized(&env2, &normalize, &args.file2),
+        )
+    };
 
     let d = env_diff::diff(&env1, &env2);
+    let changed = if args.values {
+        env_diff::changed_values(&env1, &env2)
+    } else {
+        Vec::new()
+    };
 
-    if d.only_left.is_empty() && d.only_right.is_empty() {
-        eprintln!("no differences (both files have the same keys)");
+    if d.only_left.is_empty() && d.only_right.is_empty() && changed.is_empty() {
+        if args.values {
+            eprintln!("no differences (same keys and values)");
+        } else {
+            eprintln!("no differences (both files have the same keys)");
+        }
         return Ok(());
     }
 
@@ -45,7 +94,69 @@ pub fn run(args: DiffArgs) -> Result<()> {
             args.file2
         );
     }
+    for key in &changed {
+        println!("{} {:<30} (changed)", style("~").yellow(), key);
+    }
 
     // Exit with code 1 when differences exist (standard diff convention)
     std::process::exit(1)
 }
+
+/// Canonicalize `env`'s keys, warning about distinct keys merged into one.
+fn normalized(env: &EnvFile, normalize: &env_diff::Normalize, path: &str) -> EnvFile {
+    let (env, collisions) = env_diff::normalize(env, normalize);
+    for collision in collisions {
+        display::warning(&format!(
+            "{}: {} all normalize to '{}' (the last one is compared)",
+            path,
+            collision.sources.join(", "),
+            collision.name
+        ));
+    }
+    env
+}
+
+/// Load both files with their real values. Encrypted files (whole-file age
+/// or per-variable) are decrypted in memory with the local identity;
+/// plaintext files need `--i-understand`.
+fn load_for_values(args: &DiffArgs, store: &KeyStore) -> Result<(EnvFile, EnvFile)> {
+    let encrypted1 = is_encrypted(&args.file1)?;
+    let encrypted2 = is_encrypted(&args.file2)?;
+    let both_encrypted = encrypted1 && encrypted2;
+    if !both_encrypted && !args.i_understand {
+        let plain = if encrypted1 { &args.file2 } else { &args.file1 };
+        bail!(
+            "'{}' is not encrypted; --values on plaintext files compares secret values directly \
+             (pass --i-understand to proceed)",
+            plain
+        );
+    }
+
+    let identity = if encrypted1 || encrypted2 {
+        Some(EnsealIdentity::load(store)?)
+    } else {
+        None
+    };
+    let load = |path: &str, encrypted: bool| -> Result<EnvFile> {
+        match (&identity, encrypted) {
+            (Some(identity), true) => crate::cli::decrypt::decrypt_in_memory(path, identity),
+            _ => {
+                let content =
+                    std::fs::read_to_string(path).map_err(|e| exit::read_failed(path, e))?;
+                Ok(env::parser::parse(&content)?)
+            }
+        }
+    };
+    Ok((
+        load(&args.file1, encrypted1)?,
+        load(&args.file2, encrypted2)?,
+    ))
+}
+
+fn is_encrypted(path: &str) -> Result<bool> {
+    let raw = std::fs::read(path).map_err(|e| exit::read_failed(path, e))?;
+    if at_rest::is_whole_file_encrypted(&raw) {
+        return Ok(true);
+    }
+    Ok(std::str::from_utf8(&raw).is_ok_and(at_rest::is_per_var_encrypted))
+}
@@ -0,0 +1,264 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+use clap::Args;
+use console::style;
+
+use crate::crypto::envelope::Envelope;
+use crate::keys::store::KeyStore;
+use crate::transfer;
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Relay to check (default: ENSEAL_RELAY or the configured relay)
+    #[arg(long, env = "ENSEAL_RELAY")]
+    pub relay: Option<String>,
+}
+
+/// How long to wait for the relay's `/health` endpoint.
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One line of the checklist.
+#[derive(Debug)]
+struct Check {
+    status: Status,
+    name: &'static str,
+    detail: String,
+}
+
+impl Check {
+    fn new(status: Status, name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            name,
+            detail: detail.into(),
+        }
+    }
+}
+
+pub fn run(args: DoctorArgs, store: &KeyStore) -> Result<()> {
+    let mut checks = vec![identity(store)];
+    checks.extend(key_permissions(store));
+    checks.push(trusted_keys(store));
+    checks.push(project_config(std::path::Path::new(".")));
+    if let Some(relay) = crate::config::user::relay_url(args.relay)? {
+        checks.extend(relay_checks(&relay));
+    }
+
+    for check in &checks {
+        let mark = match check.status {
+            Status::Pass => style("pass").green().bold(),
+            Status::Warn => style("warn").yellow().bold(),
+            Status::Fail => style("fail").red().bold(),
+        };
+        println!("[{}] {:<16}{}", mark, check.name, check.detail);
+    }
+
+    let failures = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failures > 0 {
+        bail!("{} check(s) failed", failures);
+    }
+    Ok(())
+}
+
+fn identity(store: &KeyStore) -> Check {
+    if store.is_initialized() {
+        Check::new(
+            Status::Pass,
+            "identity",
+            format!("'{}' initialized", store.identity_name()),
+        )
+    } else {
+        Check::new(
+            Status::Fail,
+            "identity",
+            format!(
+                "no identity '{}' found (run `enseal keys init`)",
+                store.identity_name()
+            ),
+        )
+    }
+}
+
+/// Private key files must not be readable by group or others.
+#[cfg(unix)]
+fn key_permissions(store: &KeyStore) -> Option<Check> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if !store.is_initialized() {
+        return None;
+    }
+    for path in [store.age_private_key_path(), store.sign_private_key_path()] {
+        let mode = match std::fs::metadata(&path) {
+            Ok(meta) => meta.permissions().mode() & 0o777,
+            Err(e) => {
+                return Some(Check::new(
+                    Status::Fail,
+                    "key permissions",
+                    format!("cannot read {}: {}", path.display(), e),
+                ))
+            }
+        };
+        if mode & 0o077 != 0 {
+            return Some(Check::new(
+                Status::Fail,
+                "key permissions",
+                format!(
+                    "{} is mode {:o} (run `chmod 600 {}`)",
+                    path.display(),
+                    mode,
+                    path.display()
+                ),
+            ));
+        }
+    }
+    Some(Check::new(
+        Status::Pass,
+        "key permissions",
+        "private keys are 0600",
+    ))
+}
+
+#[cfg(not(unix))]
+fn key_permissions(_store: &KeyStore) -> Option<Check> {
+    None
+}
+
+fn trusted_keys(store: &KeyStore) -> Check {
+    match store.list_trusted() {
+        Ok(trusted) if trusted.is_empty() => Check::new(
+            Status::Warn,
+            "trusted keys",
+            "none (import one with `enseal keys import`)",
+        ),
+        Ok(trusted) => Check::new(
+            Status::Pass,
+            "trusted keys",
+            format!("{} trusted", trusted.len()),
+        ),
+        Err(e) => Check::new(
+            Status::Fail,
+            "trusted keys",
+            format!("cannot list: {:#}", e),
+        ),
+    }
+}
+
+fn project_config(dir: &std::path::Path) -> Check {
+    if dir.join(".enseal.toml").exists() {
+        Check::new(Status::Pass, "project config", ".enseal.toml found")
+    } else {
+        Check::new(
+            Status::Warn,
+            "project config",
+            "no .enseal.toml (schema validation and project defaults unavailable)",
+        )
+    }
+}
+
+/// Reachability of the relay, and our clock against its clock.
+fn relay_checks(relay: &str) -> Vec<Check> {
+    let health = match transfer::relay::health(relay, HEALTH_TIMEOUT) {
+        Ok(health) => health,
+        Err(e) => return vec![Check::new(Status::Fail, "relay", format!("{:#}", e))],
+    };
+    let mut detail = format!(
+        "{} reachable ({} {})",
+        relay, health.service, health.version
+    );
+    if let (Some(uptime), Some(channels)) = (health.uptime_secs, health.active_channels) {
+        detail.push_str(&format!(
+            ", up {}s, {} waiting channel(s)",
+            uptime, channels
+        ));
+    }
+    let reachable = Check::new(Status::Pass, "relay", detail);
+
+    let skew = match health.unix_time {
+        Some(relay_time) => clock_skew(relay_time, unix_now()),
+        None => Check::new(
+            Status::Warn,
+            "clock skew",
+            "relay does not report its time (upgrade the relay to check)",
+        ),
+    };
+    vec![reachable, skew]
+}
+
+fn clock_skew(relay_time: u64, local_time: u64) -> Check {
+    let skew = relay_time.abs_diff(local_time);
+    let direction = if local_time > relay_time {
+        "ahead of"
+    } else {
+        "behind"
+    };
+    if skew > Envelope::CLOCK_SKEW_TOLERANCE {
+        Check::new(
+            Status::Warn,
+            "clock skew",
+            format!(
+                "local clock is {}s {} the relay; envelopes may be rejected as expired or from the future",
+                skew, direction
+            ),
+        )
+    } else {
+        Check::new(Status::Pass, "clock skew", format!("{}s", skew))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::identity::EnsealIdentity;
+    use tempfile::TempDir;
+
+    #[test]
+    fn detects_missing_identity() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        assert_eq!(identity(&store).status, Status::Fail);
+        assert!(key_permissions(&store).is_none());
+
+        EnsealIdentity::generate().save(&store).unwrap();
+        assert_eq!(identity(&store).status, Status::Pass);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn flags_readable_private_key() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        EnsealIdentity::generate().save(&store).unwrap();
+        assert_eq!(key_permissions(&store).unwrap().status, Status::Pass);
+
+        let path = store.sign_private_key_path();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let check = key_permissions(&store).unwrap();
+        assert_eq!(check.status, Status::Fail);
+        assert!(check.detail.contains("644"));
+    }
+
+    #[test]
+    fn warns_on_clock_skew() {
+        assert_eq!(clock_skew(1_000, 1_030).status, Status::Pass);
+        let check = clock_skew(1_000, 1_120);
+        assert_eq!(check.status, Status::Warn);
+        assert!(check.detail.contains("120s ahead of"));
+    }
+}
@@ -1,9 +1,10 @@
 use anyhow::{bail, Result};
-use clap::Args;
+use clap::{Args, ValueEnum};
 
-use crate::crypto::at_rest;
+use crate::crypto::{at_rest, sealed_box};
 use crate::env;
-use crate::keys::identity::EnsealIdentity;
+use crate::fsutil::write_secret_file;
+use crate::keys::identity::{EnsealIdentity, TrustedKey};
 use crate::keys::store::KeyStore;
 use crate::ui::display;
 
@@ -17,29 +18,94 @@ pub struct EncryptArgs {
     #[arg(long, short)]
     pub output: Option<String>,
 
+    /// Replace the input file with its encrypted form (atomic rename)
+    #[arg(long, conflicts_with = "output")]
+    pub in_place: bool,
+
     /// Per-variable encryption (keys visible, values encrypted)
     #[arg(long)]
     pub per_var: bool,
 
-    /// Encrypt to specific recipient(s) (can be repeated)
+    /// Ciphertext format: age, or compat (an XChaCha20-Poly1305 sealed box
+    /// for systems that can't parse age)
+    #[arg(long, value_enum, default_value_t = Format::Age, conflicts_with = "per_var")]
+    pub format: Format,
+
+    /// ASCII-armor the age output (-----BEGIN AGE ENCRYPTED FILE-----)
+    #[arg(long, overrides_with = "binary", conflicts_with = "per_var")]
+    pub armor: bool,
+
+    /// Write binary age output (the default; overrides an earlier --armor)
+    #[arg(long, overrides_with = "armor")]
+    pub binary: bool,
+
+    /// Encrypt to specific recipient(s) (can be repeated; @FILE reads a list)
     #[arg(long)]
     pub to: Vec<String>,
 
+    /// Also encrypt to every member of a `[roles]` entry in .enseal.toml
+    #[arg(long, value_name = "ROLE")]
+    pub to_role: Option<String>,
+
+    /// Treat --to names strictly as trusted identities (skip alias and group lookup)
+    #[arg(long, requires = "to", conflicts_with = "to_role")]
+    pub literal: bool,
+
+    /// Accept a recipient whose trusted key changed since it was pinned, and re-pin it
+    #[arg(long)]
+    pub accept_key_change: bool,
+
     /// Overwrite existing files without prompting
     #[arg(long)]
     pub force: bool,
 }
 
-pub fn run(args: EncryptArgs) -> Result<()> {
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Age,
+    Compat,
+}
+
+pub fn run(args: EncryptArgs, store: &KeyStore) -> Result<()> {
     let content = std::fs::read_to_string(&args.file)
-        .map_err(|e| anyhow::anyhow!("failed to read '{}': {}", args.file, e))?;
+        .map_err(|e| crate::cli::exit::read_failed(&args.file, e))?;
+
+    let mut to = args.to.clone();
+    if let Some(ref role) = args.to_role {
+        to.extend_from_slice(crate::config::Manifest::load(None)?.role_members(role)?);
+    }
+
+    if args.format == Format::Compat {
+        if args.armor {
+            bail!("--armor applies to age output; a --format compat sealed box is already text");
+        }
+        let resolved = resolve_recipients(store, &to, args.literal, args.accept_key_change)?;
+        let mut recipients = resolved.raw;
+        for id in &resolved.trusted {
+            recipients.push(x25519_recipient(store, id)?);
+        }
+        let recipient_refs: Vec<&age::x25519::Recipient> = recipients.iter().collect();
+        let ciphertext = sealed_box::seal(content.as_bytes(), &recipient_refs)?;
+        return write_whole_file(&args, &content, &ciphertext, "sealed box");
+    }
 
     // Collect recipients: either from --to flags or use own key
-    let recipients = resolve_recipients(&args.to)?;
-    let recipient_refs: Vec<&age::x25519::Recipient> = recipients.iter().collect();
+    let resolved = resolve_recipients(store, &to, args.literal, args.accept_key_change)?;
+    let mut recipients: Vec<Box<at_rest::SharedRecipient>> = Vec::new();
+    for id in &resolved.trusted {
+        recipients.extend(crate::keys::identity::load_recipients(store, id)?);
+    }
+    recipients.extend(
+        resolved
+            .raw
+            .into_iter()
+            .map(|r| Box::new(r) as Box<at_rest::SharedRecipient>),
+    );
+    let recipient_refs: Vec<&at_rest::SharedRecipient> =
+        recipients.iter().map(|r| r.as_ref()).collect();
 
     if args.per_var {
-        encrypt_per_var(&args, &content, &recipient_refs)
+        encrypt_per_var(&args, store, &content, &recipient_refs)
     } else {
         encrypt_whole_file(&args, &content, &recipient_refs)
     }
@@ -48,17 +114,40 @@ pub fn run(args: EncryptArgs) -> Result<()> {
 fn encrypt_whole_file(
     args: &EncryptArgs,
     content: &str,
-    recipients: &[&age::x25519::Recipient],
+    recipients: &[&at_rest::SharedRecipient],
 ) -> Result<()> {
-    let ciphertext = at_rest::encrypt_whole_file(content.as_bytes(), recipients)?;
+    let ciphertext = if args.armor {
+        at_rest::encrypt_whole_file_armored(content.as_bytes(), recipients)?
+    } else {
+        at_rest::encrypt_whole_file(content.as_bytes(), recipients)?
+    };
+    let scheme = if args.armor {
+        "armored age key"
+    } else {
+        "age key"
+    };
+    write_whole_file(args, content, &ciphertext, scheme)
+}
 
-    let output_path = args
-        .output
-        .clone()
-        .unwrap_or_else(|| format!("{}.encrypted", args.file));
+/// Write whole-file ciphertext to the output path and report it.
+/// `scheme` names the format in the summary line.
+fn write_whole_file(
+    args: &EncryptArgs,
+    content: &str,
+    ciphertext: &[u8],
+    scheme: &str,
+) -> Result<()> {
+    let output_path = match &args.output {
+        Some(output) => output.clone(),
+        None if args.in_place => args.file.clone(),
+        None => format!("{}.encrypted", args.file),
+    };
 
-    check_overwrite(&output_path, args.force)?;
-    write_secret_file(&output_path, &ciphertext)
+    // --in-place is an explicit request to replace the input
+    if !args.in_place {
+        check_overwrite(&output_path, args.force)?;
+    }
+    write_secret_file(&output_path, ciphertext)
         .map_err(|e| anyhow::anyhow!("failed to write '{}': {}", output_path, e))?;
 
     let env_file = env::parser::parse(content).ok();
@@ -66,11 +155,11 @@ fn encrypt_whole_file(
 
     if var_count > 0 {
         display::ok(&format!(
-            "{} encrypted ({} variables, age key)",
-            output_path, var_count
+            "{} encrypted ({} variables, {})",
+            output_path, var_count, scheme
         ));
     } else {
-        display::ok(&format!("{} encrypted (age key)", output_path));
+        display::ok(&format!("{} encrypted ({})", output_path, scheme));
     }
 
     Ok(())
@@ -78,8 +167,9 @@ fn encrypt_whole_file(
 
 fn encrypt_per_var(
     args: &EncryptArgs,
+    store: &KeyStore,
     content: &str,
-    recipients: &[&age::x25519::Recipient],
+    recipients: &[&at_rest::SharedRecipient],
 ) -> Result<()> {
     let env_file = env::parser::parse(content)?;
 
@@ -88,15 +178,19 @@ fn encrypt_per_var(
         bail!("file already contains per-variable encrypted values");
     }
 
-    let encrypted = at_rest::encrypt_per_var(&env_file, recipients)?;
+    let encrypted = at_rest::encrypt_per_var_routed(&env_file, recipients, |name| {
+        recipients_for(store, name, args.accept_key_change)
+    })?;
     let output_str = encrypted.to_string();
 
     let output_path = args.output.clone().unwrap_or_else(|| args.file.clone());
 
-    if output_path == args.file {
+    if output_path == args.file && !args.in_place {
         display::warning("per-var encryption will replace the plaintext file in-place");
     }
-    check_overwrite(&output_path, args.force)?;
+    if !args.in_place {
+        check_overwrite(&output_path, args.force)?;
+    }
     write_secret_file(&output_path, output_str.as_bytes())
         .map_err(|e| anyhow::anyhow!("failed to write '{}': {}", output_path, e))?;
 
@@ -109,30 +203,8 @@ fn encrypt_per_var(
     Ok(())
 }
 
-/// Write a file with restrictive permissions (0600 on Unix).
-fn write_secret_file(path: &str, content: &[u8]) -> Result<()> {
-    #[cfg(unix)]
-    {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-        use std::os::unix::fs::OpenOptionsExt;
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .mode(0o600)
-            .open(path)?;
-        file.write_all(content)?;
-    }
-    #[cfg(not(unix))]
-    {
-        std::fs::write(path, content)?;
-    }
-    Ok(())
-}
-
 /// Check if the target file exists and handle overwrite confirmation.
-fn check_overwrite(path: &str, force: bool) -> Result<()> {
+pub fn check_overwrite(path: &str, force: bool) -> Result<()> {
     if !std::path::Path::new(path).exists() {
         return Ok(());
     }
@@ -155,31 +227,76 @@ fn check_overwrite(path: &str, force: bool) -> Result<()> {
     Ok(())
 }
 
-/// Resolve recipients from --to flags or use own key.
-fn resolve_recipients(to: &[String]) -> Result<Vec<age::x25519::Recipient>> {
+/// What `--to` names: bare `age1...` keys (used as-is, plus our own key so
+/// the sender can decrypt too) and the trusted identities the remaining
+/// names resolve to, already checked against their pins.
+struct Recipients {
+    raw: Vec<age::x25519::Recipient>,
+    trusted: Vec<String>,
+}
+
+fn resolve_recipients(
+    store: &KeyStore,
+    to: &[String],
+    literal: bool,
+    accept_key_change: bool,
+) -> Result<Recipients> {
     if to.is_empty() {
         // Use own key
-        let store = KeyStore::open()?;
-        let identity = EnsealIdentity::load(&store)?;
-        return Ok(vec![identity.age_recipient]);
+        return Ok(Recipients {
+            raw: vec![EnsealIdentity::load(store)?.age_recipient],
+            trusted: Vec::new(),
+        });
     }
 
-    let store = KeyStore::open()?;
-    let mut recipients = Vec::new();
-
-    for name in to {
-        let identities = crate::keys::resolve_to_identities(name)?;
-        for id in &identities {
-            let trusted = crate::keys::identity::TrustedKey::load(&store, id)?;
-            recipients.push(trusted.age_recipient);
+    // Bare `age1...` strings are used as-is; everything else is a name
+    let mut raw = Vec::new();
+    let mut names = Vec::new();
+    for value in to {
+        match crate::keys::identity::parse_raw_recipient(value) {
+            Some(recipient) => {
+                display::warning(&crate::keys::identity::raw_recipient_warning(value));
+                raw.push(recipient);
+            }
+            None => names.push(value.clone()),
         }
     }
 
+    let trusted = crate::keys::resolve_recipient_args(store, &names, literal)?;
+    crate::keys::pin::check(store, &trusted, accept_key_change)?;
+
     // Also include own key so the sender can decrypt too
     if store.is_initialized() {
-        let identity = EnsealIdentity::load(&store)?;
-        recipients.push(identity.age_recipient);
+        raw.push(EnsealIdentity::load(store)?.age_recipient);
+    }
+    Ok(Recipients { raw, trusted })
+}
+
+/// The X25519 key of a trusted identity, for a sealed box: it has no SSH
+/// recipient type.
+fn x25519_recipient(store: &KeyStore, id: &str) -> Result<age::x25519::Recipient> {
+    let content = crate::keys::identity::read_trusted(store, id)?;
+    if !crate::keys::identity::parse_ssh_keys(&content).is_empty() {
+        bail!(
+            "'{}' is an SSH key entry; --format compat needs an enseal key or age1... recipient",
+            id
+        );
     }
+    Ok(TrustedKey::parse(id, &content)?.age_recipient)
+}
 
+/// Resolve a single alias, group, identity, or `@file` list to its age
+/// recipients (enseal keys or SSH keys).
+fn recipients_for(
+    store: &KeyStore,
+    name: &str,
+    accept_key_change: bool,
+) -> Result<Vec<Box<at_rest::SharedRecipient>>> {
+    let identities = crate::keys::resolve_recipient_args(store, &[name.to_string()], false)?;
+    crate::keys::pin::check(store, &identities, accept_key_change)?;
+    let mut recipients = Vec::new();
+    for id in identities {
+        recipients.extend(crate::keys::identity::load_recipients(store, &id)?);
+    }
     Ok(recipients)
 }
@@ -0,0 +1,156 @@
+//! Process exit codes, so scripts can tell failure categories apart.
+//!
+//! `main` maps a command's error to an [`ExitCode`] with [`ExitCode::of`].
+//! Most errors are classified by what's in their chain ([`EnsealError`],
+//! I/O and network errors); CLI checks that don't produce one of those tag
+//! their error with [`usage`], [`validation`] or [`not_found`].
+
+use std::fmt::Display;
+
+use crate::error::EnsealError;
+
+/// Exit status of a failed command. Success is 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Anything not covered below.
+    Failure = 1,
+    /// Invalid or conflicting arguments (clap's own errors also exit 2).
+    Usage = 2,
+    /// The input parsed but failed a check: schema, `check`, malformed .env.
+    Validation = 3,
+    /// Encryption, decryption, signature or freshness failure.
+    Crypto = 4,
+    /// Relay/rendezvous connection failure or timeout.
+    Network = 5,
+    /// A file, identity or key doesn't exist.
+    NotFound = 6,
+}
+
+impl ExitCode {
+    /// The exit code for `err`: the first categorized error in its chain.
+    pub fn of(err: &anyhow::Error) -> Self {
+        err.chain()
+            .find_map(|cause| {
+                if let Some(coded) = cause.downcast_ref::<CodedError>() {
+                    Some(coded.code)
+                } else if let Some(e) = cause.downcast_ref::<EnsealError>() {
+                    Some(match e {
+                        EnsealError::Parse(_) => Self::Validation,
+                        EnsealError::Encrypt(_)
+                        | EnsealError::Decrypt(_)
+                        | EnsealError::SignatureVerification(_)
+                        | EnsealError::Expired(_)
+                        | EnsealError::Replay(_) => Self::Crypto,
+                        EnsealError::KeyNotFound(_) => Self::NotFound,
+                        EnsealError::Io(e) => Self::of_io(e),
+                        EnsealError::Network(_) => Self::Network,
+                    })
+                } else if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+                    Some(Self::of_io(e))
+                } else if cause
+                    .downcast_ref::<tokio_tungstenite::tungstenite::Error>()
+                    .is_some()
+                    || cause.downcast_ref::<ureq::Error>().is_some()
+                {
+                    Some(Self::Network)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(Self::Failure)
+    }
+
+    fn of_io(err: &std::io::Error) -> Self {
+        use std::io::ErrorKind;
+        match err.kind() {
+            ErrorKind::NotFound => Self::NotFound,
+            ErrorKind::TimedOut
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted => Self::Network,
+            _ => Self::Failure,
+        }
+    }
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+    fn from(code: ExitCode) -> Self {
+        Self::from(code as u8)
+    }
+}
+
+/// An error tagged with the exit code it should produce.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct CodedError {
+    pub code: ExitCode,
+    message: String,
+}
+
+fn coded(code: ExitCode, message: impl Into<String>) -> anyhow::Error {
+    CodedError {
+        code,
+        message: message.into(),
+    }
+    .into()
+}
+
+/// Invalid or conflicting arguments (exit 2).
+pub fn usage(message: impl Into<String>) -> anyhow::Error {
+    coded(ExitCode::Usage, message)
+}
+
+/// The input failed a check (exit 3).
+pub fn validation(message: impl Into<String>) -> anyhow::Error {
+    coded(ExitCode::Validation, message)
+}
+
+/// Something the command needs doesn't exist (exit 6).
+pub fn not_found(message: impl Into<String>) -> anyhow::Error {
+    coded(ExitCode::NotFound, message)
+}
+
+/// "failed to read 'path': ...", exiting 6 when the file doesn't exist.
+pub fn read_failed(path: impl Display, err: std::io::Error) -> anyhow::Error {
+    coded(
+        ExitCode::of_io(&err),
+        format!("failed to read '{}': {}", path, err),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn classifies_error_chains() {
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert_eq!(
+            ExitCode::of(&read_failed("x.env", missing)),
+            ExitCode::NotFound
+        );
+        assert_eq!(
+            ExitCode::of(&usage("--a and --b are mutually exclusive")),
+            ExitCode::Usage
+        );
+
+        let decrypt: anyhow::Error = EnsealError::Decrypt("wrong key".to_string()).into();
+        assert_eq!(
+            ExitCode::of(&decrypt.context("failed to decrypt")),
+            ExitCode::Crypto
+        );
+
+        let timeout: anyhow::Result<()> =
+            Err(EnsealError::Network("relay receive timed out".to_string()).into());
+        assert_eq!(
+            ExitCode::of(&timeout.context("receive failed").unwrap_err()),
+            ExitCode::Network
+        );
+
+        assert_eq!(
+            ExitCode::of(&anyhow::anyhow!("something else")),
+            ExitCode::Failure
+        );
+    }
+}
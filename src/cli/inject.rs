@@ -1,10 +1,13 @@
-use std::collections::HashMap;
-use std::process::{Command, Stdio};
+use std::collections::{BTreeMap, HashMap};
+use std::process::{Command, ExitStatus, Stdio};
 
 use anyhow::{bail, Result};
 use clap::Args;
 
 use crate::cli::input::PayloadFormat;
+use crate::cli::receive::{
+    check_freshness, check_replay, code_from_stdin, report_sender, FreshnessArgs,
+};
 use crate::crypto::envelope::Envelope;
 use crate::crypto::signing::SignedEnvelope;
 use crate::keys;
@@ -13,64 +16,246 @@ use crate::ui::display;
 
 #[derive(Args)]
 pub struct InjectArgs {
-    /// Wormhole share code or path to .env.age file
+    /// Wormhole share code or path to .env.age file (`-` reads it from stdin)
     pub code: Option<String>,
 
     /// Listen for incoming identity-mode transfer (requires --relay)
     #[arg(long)]
     pub listen: bool,
 
+    /// With --listen: after each command exits, listen for the next transfer
+    #[arg(long = "loop", requires = "listen")]
+    pub r#loop: bool,
+
+    /// Decrypt a committed at-rest encrypted .env file (whole-file or
+    /// per-variable) and inject it, without writing plaintext
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["code", "listen"])]
+    pub env_file: Option<String>,
+
     /// Separator between inject args and the command to run
     #[arg(
         last = true,
-        required = true,
+        required_unless_present_any = ["print", "dry_run"],
         value_name = "CMD",
         num_args = 1..,
     )]
     pub command: Vec<String>,
 
+    /// Print `export KEY='value'` lines for `eval` instead of running a command
+    #[arg(long, visible_alias = "dotenv-export", conflicts_with = "command")]
+    pub print: bool,
+
+    /// Receive and list the variable names that would be injected, without
+    /// running the command
+    #[arg(long, conflicts_with_all = ["print", "loop"])]
+    pub dry_run: bool,
+
     /// Use specific relay server
     #[arg(long, env = "ENSEAL_RELAY")]
     pub relay: Option<String>,
 
+    /// Retry failed connections to the relay/rendezvous server this many times
+    #[arg(long, default_value_t = crate::transfer::retry::DEFAULT_RETRIES, value_name = "N")]
+    pub retries: u32,
+
+    /// Give up if the transfer hasn't completed within this many seconds
+    /// (with --listen, bounds each wait)
+    #[arg(long, visible_alias = "listen-timeout", default_value_t = crate::transfer::DEFAULT_TIMEOUT.as_secs(), value_name = "SECS")]
+    pub timeout: u64,
+
+    #[command(flatten)]
+    pub freshness: FreshnessArgs,
+
+    /// Fill missing keys from `default` values in the .enseal.toml schema
+    #[arg(long)]
+    pub apply_defaults: bool,
+
+    /// Prepend this prefix to every injected variable name (e.g. APP_)
+    #[arg(long, value_name = "PREFIX")]
+    pub env_prefix: Option<String>,
+
+    /// Inject names like PATH or LD_PRELOAD that change how the child runs
+    #[arg(long)]
+    pub allow_unsafe_names: bool,
+
+    /// Don't forward SIGINT/SIGTERM to the child (for commands that manage
+    /// their own process group)
+    #[arg(long)]
+    pub no_signal_forward: bool,
+
     /// Minimal output
     #[arg(long, short)]
     pub quiet: bool,
 }
 
-pub async fn run(args: InjectArgs) -> Result<()> {
-    if args.command.is_empty() {
+pub async fn run(mut args: InjectArgs, store: &keys::store::KeyStore) -> Result<()> {
+    if args.command.is_empty() && !args.print && !args.dry_run {
         bail!("no command specified. Usage: enseal inject <code> -- <command>");
     }
 
-    if !args.listen && args.code.is_none() {
-        bail!("provide a wormhole code or use --listen. Usage: enseal inject <code> -- <command>");
+    if !args.listen && args.code.is_none() && args.env_file.is_none() {
+        bail!(
+            "provide a wormhole code, --listen, or --env-file. Usage: enseal inject <code> -- <command>"
+        );
     }
 
     if args.listen && args.code.is_some() {
-        bail!("--listen and a wormhole code are mutually exclusive");
+        return Err(crate::cli::exit::usage(
+            "--listen and a wormhole code are mutually exclusive",
+        ));
     }
 
-    // 1. Receive the envelope
-    let envelope = if args.listen {
-        listen_mode(&args).await?
-    } else {
-        receive_envelope(&args).await?
+    args.freshness.warn_if_ignored(args.quiet);
+
+    if args.listen {
+        args.relay = crate::config::user::relay_url(args.relay.take())?;
+    }
+
+    // Read the code line now, before the child inherits the rest of stdin
+    if args.code.as_deref() == Some("-") {
+        args.code = Some(code_from_stdin()?);
+    }
+
+    if args.r#loop {
+        return listen_loop(&args, store).await;
+    }
+
+    // 1. Receive the envelope (or decrypt the committed file) and
+    // 2. extract secrets as env vars
+    let (secrets, created) = match &args.env_file {
+        Some(path) => (
+            apply_prefix(decrypt_env_file(store, path)?, args.env_prefix.as_deref())?,
+            None,
+        ),
+        None => {
+            let trusted = keys::TrustedIndex::load(store);
+            let envelope = if args.listen {
+                listen_mode(&args, store, &trusted).await?
+            } else {
+                receive_envelope(&args, store, &trusted).await?
+            };
+            (
+                extract_secrets(&envelope, args.env_prefix.as_deref())?,
+                Some(envelope.created_display()),
+            )
+        }
     };
 
-    // 2. Extract secrets as env vars
-    let secrets = extract_secrets(&envelope)?;
+    // 3. Spawn child with secrets in env
+    if let Some(status) = inject(&args, secrets, created)? {
+        exit_with(status);
+    }
+    Ok(())
+}
+
+/// Pause before listening again after the relay failed, so an unreachable
+/// relay isn't hammered.
+const RELISTEN_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// `--listen --loop`: inject each incoming transfer in turn, waiting for
+/// the command to exit before listening again. A timeout or dropped relay
+/// connection just restarts the listen, and a transfer that fails to
+/// verify (bad, stale or replayed) or is refused (reserved names, invalid
+/// defaults) is reported and skipped.
+async fn listen_loop(args: &InjectArgs, store: &keys::store::KeyStore) -> Result<()> {
+    let relay_url = listen_relay_url(args)?;
+    let own_identity = keys::identity::EnsealIdentity::load(store)?;
+    loop {
+        let data = match listen_once(args, relay_url, &own_identity).await {
+            Ok(data) => data,
+            Err(e) => {
+                if !args.quiet {
+                    display::warning(&format!("{:#}; listening again", e));
+                }
+                tokio::time::sleep(RELISTEN_DELAY).await;
+                continue;
+            }
+        };
+        // Reloaded for each transfer: keys may be trusted while the loop runs
+        let trusted = keys::TrustedIndex::load(store);
+        let opened =
+            open_pushed(args, store, &trusted, &own_identity, &data).and_then(|envelope| {
+                let secrets = extract_secrets(&envelope, args.env_prefix.as_deref())?;
+                Ok((secrets, envelope.created_display()))
+            });
+        let (secrets, created) = match opened {
+            Ok(opened) => opened,
+            Err(e) => {
+                display::warning(&format!("skipped incoming transfer: {:#}", e));
+                continue;
+            }
+        };
+        match inject(args, secrets, Some(created)) {
+            Ok(Some(status)) if !status.success() && !args.quiet => {
+                display::warning(&format!("command exited with {}", status));
+            }
+            Ok(_) => {}
+            Err(e) => display::warning(&format!("skipped incoming transfer: {:#}", e)),
+        }
+    }
+}
+
+/// Fill defaults, check names, then print the exports or run the command.
+/// Returns the command's exit status (None with --print).
+fn inject(
+    args: &InjectArgs,
+    mut secrets: HashMap<String, String>,
+    created: Option<String>,
+) -> Result<Option<ExitStatus>> {
+    if args.apply_defaults {
+        let schema = crate::cli::receive::schema_for_defaults()?;
+        let prefix = args.env_prefix.as_deref().unwrap_or_default();
+        let defaults = crate::env::schema::missing_defaults(&schema, |key| {
+            secrets.contains_key(&format!("{}{}", prefix, key))
+        })?;
+        let defaults = apply_prefix(defaults.into_iter().collect(), args.env_prefix.as_deref())?;
+        if !defaults.is_empty() && !args.quiet {
+            display::info(
+                "Defaults:",
+                &format!("{} filled from schema", defaults.len()),
+            );
+        }
+        secrets.extend(defaults);
+    }
+
+    check_reserved_names(&secrets, args.allow_unsafe_names)?;
+
+    if args.print {
+        print!("{}", export_lines(&secrets)?);
+        return Ok(None);
+    }
+
+    if args.dry_run {
+        let names: BTreeMap<_, _> = secrets.iter().collect();
+        for name in names.keys() {
+            println!("{}", name);
+        }
+        if !args.quiet {
+            display::info("Secrets:", &format!("{} variables", names.len()));
+            if !args.command.is_empty() {
+                display::info("Command:", &args.command.join(" "));
+            }
+            display::ok("dry run: command was not run");
+        }
+        return Ok(None);
+    }
 
     if !args.quiet {
         display::info("Secrets:", &format!("{} variables", secrets.len()));
+        if let Some(created) = created {
+            display::info("Created:", &created);
+        }
         display::ok("injecting into process environment");
     }
 
-    // 3. Spawn child with secrets in env
-    run_child(&args.command, &secrets)
+    run_child(&args.command, &secrets, !args.no_signal_forward).map(Some)
 }
 
-async fn receive_envelope(args: &InjectArgs) -> Result<Envelope> {
+async fn receive_envelope(
+    args: &InjectArgs,
+    store: &keys::store::KeyStore,
+    trusted: &keys::TrustedIndex,
+) -> Result<Envelope> {
     let code = args
         .code
         .as_deref()
@@ -80,11 +265,11 @@ async fn receive_envelope(args: &InjectArgs) -> Result<Envelope> {
     let is_file = std::path::Path::new(code).exists() && code.ends_with(".age");
 
     if is_file {
-        let store = keys::store::KeyStore::open()?;
-        let own_identity = keys::identity::EnsealIdentity::load(&store)?;
+        let own_identity = keys::identity::EnsealIdentity::load(store)?;
         let path = std::path::Path::new(code);
 
-        let metadata = std::fs::metadata(path)?;
+        let metadata =
+            std::fs::metadata(path).map_err(|e| crate::cli::exit::read_failed(code, e))?;
         if metadata.len() > 16 * 1024 * 1024 {
             bail!(
                 "file too large ({} bytes, max 16 MiB): {}",
@@ -92,49 +277,47 @@ async fn receive_envelope(args: &InjectArgs) -> Result<Envelope> {
                 path.display()
             );
         }
-        let data = std::fs::read(path)?;
+        let data = std::fs::read(path).map_err(|e| crate::cli::exit::read_failed(code, e))?;
         let signed = SignedEnvelope::from_bytes(&data)?;
-        let trusted_sender = keys::find_trusted_sender(&store, &signed);
-
-        let (envelope, sender_pubkey) =
-            transfer::filedrop::read_from_bytes(&data, &own_identity, trusted_sender.as_ref())?;
+        let trusted_sender = trusted.find_sender(&signed);
+
+        let (envelope, _) = crate::crypto::signing::open_signed(
+            &data,
+            &own_identity,
+            trusted_sender,
+            args.freshness.limit(Envelope::FILEDROP_MAX_AGE),
+        )?;
+        report_sender(store, &signed, trusted_sender, args.quiet)?;
         if !args.quiet {
-            if let Some(ref trusted) = trusted_sender {
-                display::info("From:", &trusted.identity);
-            } else {
-                display::warning(&format!(
-                    "received from unknown sender (signing key: {}...)",
-                    &sender_pubkey[..20.min(sender_pubkey.len())]
-                ));
-            }
             display::ok("signature verified, file decrypted");
         }
         Ok(envelope)
     } else {
         // Receive raw bytes once, then determine mode by trying to parse
-        let data = transfer::wormhole::receive_raw(code, args.relay.as_deref()).await?;
-        let store = keys::store::KeyStore::open()?;
+        let data = transfer::wormhole::receive_raw(
+            code,
+            args.relay.as_deref(),
+            args.retries,
+            std::time::Duration::from_secs(args.timeout),
+        )
+        .await?;
 
         // Try identity mode: parse as SignedEnvelope
         if store.is_initialized() {
             if let Ok(signed) = SignedEnvelope::from_bytes(&data) {
-                let own_identity = keys::identity::EnsealIdentity::load(&store)?;
-                let sender_pubkey = signed.sender_sign_pubkey.clone();
-                let trusted_sender = keys::find_trusted_sender(&store, &signed);
-
-                let inner_bytes = signed.open(&own_identity, trusted_sender.as_ref())?;
-                let envelope = Envelope::from_bytes(&inner_bytes)?;
-                envelope.check_age(300)?;
-
+                let own_identity = keys::identity::EnsealIdentity::load(store)?;
+                let trusted_sender = trusted.find_sender(&signed);
+
+                let (envelope, _) = crate::crypto::signing::open_signed(
+                    &data,
+                    &own_identity,
+                    trusted_sender,
+                    args.freshness.limit(Envelope::NETWORK_MAX_AGE),
+                )?;
+
+                check_replay(store, &envelope, &args.freshness)?;
+                report_sender(store, &signed, trusted_sender, args.quiet)?;
                 if !args.quiet {
-                    if let Some(ref trusted) = trusted_sender {
-                        display::info("From:", &trusted.identity);
-                    } else {
-                        display::warning(&format!(
-                            "received from unknown sender (signing key: {}...)",
-                            &sender_pubkey[..20.min(sender_pubkey.len())]
-                        ));
-                    }
                     display::ok("signature verified");
                 }
                 return Ok(envelope);
@@ -148,54 +331,118 @@ async fn receive_envelope(args: &InjectArgs) -> Result<Envelope> {
             );
         }
         let envelope = Envelope::from_bytes(&data)?;
-        envelope.check_age(300)?;
+        check_freshness(&envelope, &args.freshness)?;
+        check_replay(store, &envelope, &args.freshness)?;
         Ok(envelope)
     }
 }
 
-async fn listen_mode(args: &InjectArgs) -> Result<Envelope> {
-    let relay_url = args
-        .relay
+async fn listen_mode(
+    args: &InjectArgs,
+    store: &keys::store::KeyStore,
+    trusted: &keys::TrustedIndex,
+) -> Result<Envelope> {
+    let relay_url = listen_relay_url(args)?;
+    let own_identity = keys::identity::EnsealIdentity::load(store)?;
+    let data = listen_once(args, relay_url, &own_identity).await?;
+    open_pushed(args, store, trusted, &own_identity, &data)
+}
+
+fn listen_relay_url(args: &InjectArgs) -> Result<&str> {
+    args.relay
         .as_deref()
-        .ok_or_else(|| anyhow::anyhow!("--listen requires --relay or ENSEAL_RELAY"))?;
+        .ok_or_else(|| anyhow::anyhow!("--listen requires --relay or ENSEAL_RELAY"))
+}
 
-    let store = keys::store::KeyStore::open()?;
-    let own_identity = keys::identity::EnsealIdentity::load(&store)?;
+/// Wait on our relay channel for the next push and return its raw bytes.
+async fn listen_once(
+    args: &InjectArgs,
+    relay_url: &str,
+    own_identity: &keys::identity::EnsealIdentity,
+) -> Result<Vec<u8>> {
     let channel_id = own_identity.channel_id();
 
     if !args.quiet {
+        warn_on_clock_skew(relay_url).await;
         display::info("Listening on:", relay_url);
         display::info("Channel:", &channel_id[..12]);
         display::ok("waiting for incoming transfer...");
     }
 
-    let data = transfer::relay::listen(relay_url, &channel_id).await?;
+    let data = crate::api::listen_relay(
+        relay_url,
+        own_identity,
+        args.retries,
+        std::time::Duration::from_secs(args.timeout),
+    )
+    .await?;
+    Ok(data)
+}
 
-    // Parse and verify signed envelope
-    let signed = SignedEnvelope::from_bytes(&data)?;
-    let sender_pubkey = signed.sender_sign_pubkey.clone();
-    let trusted_sender = keys::find_trusted_sender(&store, &signed);
+/// Verify and decrypt a pushed signed envelope, rejecting stale or replayed ones.
+fn open_pushed(
+    args: &InjectArgs,
+    store: &keys::store::KeyStore,
+    trusted: &keys::TrustedIndex,
+    own_identity: &keys::identity::EnsealIdentity,
+    data: &[u8],
+) -> Result<Envelope> {
+    let signed = SignedEnvelope::from_bytes(data)?;
+    let trusted_sender = trusted.find_sender(&signed);
+
+    let (envelope, _) = crate::crypto::signing::open_signed(
+        data,
+        own_identity,
+        trusted_sender,
+        args.freshness.limit(Envelope::NETWORK_MAX_AGE),
+    )?;
+    check_replay(store, &envelope, &args.freshness)?;
+
+    report_sender(store, &signed, trusted_sender, args.quiet)?;
+    if !args.quiet {
+        display::ok("signature verified");
+    }
 
-    let inner_bytes = signed.open(&own_identity, trusted_sender.as_ref())?;
-    let envelope = Envelope::from_bytes(&inner_bytes)?;
-    envelope.check_age(300)?;
+    Ok(envelope)
+}
 
-    if !args.quiet {
-        if let Some(ref trusted) = trusted_sender {
-            display::info("From:", &trusted.identity);
-        } else {
+/// Warn if our clock and the relay's disagree by more than `check_age`
+/// tolerates, since envelopes would then fail with confusing age errors.
+/// A relay that can't be probed is left for the transfer itself to report.
+async fn warn_on_clock_skew(relay_url: &str) {
+    let probe = transfer::relay::clock_skew(relay_url, std::time::Duration::from_secs(5)).await;
+    match probe {
+        Ok(Some(skew)) if skew.unsigned_abs() > Envelope::CLOCK_SKEW_TOLERANCE => {
+            let direction = if skew > 0 { "ahead of" } else { "behind" };
             display::warning(&format!(
-                "received from unknown sender (signing key: {}...)",
-                &sender_pubkey[..20.min(sender_pubkey.len())]
+                "local clock is {}s {} the relay; envelopes may be rejected as expired \
+                 or from the future (check NTP, or use --max-age)",
+                skew.unsigned_abs(),
+                direction
             ));
         }
-        display::ok("signature verified");
+        Ok(_) => {}
+        Err(e) => tracing::debug!("clock skew probe failed: {:#}", e),
     }
+}
 
-    Ok(envelope)
+fn decrypt_env_file(store: &keys::store::KeyStore, path: &str) -> Result<HashMap<String, String>> {
+    let identity = keys::identity::EnsealIdentity::load(store)?;
+    let env_file = crate::cli::decrypt::decrypt_in_memory(path, &identity)?;
+    let secrets: HashMap<String, String> = env_file
+        .vars()
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    if secrets.is_empty() {
+        bail!("no secrets found in '{}'", path);
+    }
+    Ok(secrets)
 }
 
-fn extract_secrets(envelope: &Envelope) -> Result<HashMap<String, String>> {
+/// Secrets to inject from a received envelope, with `prefix` (`--env-prefix`)
+/// prepended to every name.
+fn extract_secrets(envelope: &Envelope, prefix: Option<&str>) -> Result<HashMap<String, String>> {
     let mut secrets = HashMap::new();
 
     match envelope.format {
@@ -205,14 +452,13 @@ fn extract_secrets(envelope: &Envelope) -> Result<HashMap<String, String>> {
                 secrets.insert(key.to_string(), value.to_string());
             }
         }
+        PayloadFormat::Binary => {
+            bail!("cannot inject a binary file as an environment variable");
+        }
         PayloadFormat::Raw => {
             // For raw payloads, check if there's a label to use as key
             if let Some(ref label) = envelope.metadata.label {
-                // Validate label is a valid env var name
-                if label.is_empty()
-                    || label.starts_with(|c: char| c.is_ascii_digit())
-                    || !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
-                {
+                if !is_env_var_name(label) {
                     bail!(
                         "label '{}' is not a valid env var name (use A-Z, 0-9, _). \
                          Sender should use --as KEY instead",
@@ -233,10 +479,106 @@ fn extract_secrets(envelope: &Envelope) -> Result<HashMap<String, String>> {
         bail!("no secrets found in received payload");
     }
 
-    Ok(secrets)
+    apply_prefix(secrets, prefix)
+}
+
+/// Prepend `prefix` to every name, refusing any result that isn't a valid
+/// env var name.
+fn apply_prefix(
+    secrets: HashMap<String, String>,
+    prefix: Option<&str>,
+) -> Result<HashMap<String, String>> {
+    let Some(prefix) = prefix else {
+        return Ok(secrets);
+    };
+    secrets
+        .into_iter()
+        .map(|(key, value)| {
+            let name = format!("{}{}", prefix, key);
+            if !is_env_var_name(&name) {
+                bail!(
+                    "--env-prefix '{}' turns '{}' into '{}', which is not a valid env var name \
+                     (use A-Z, 0-9, _)",
+                    prefix,
+                    key,
+                    name
+                );
+            }
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// Variables that change how a process is loaded or how its shell parses
+/// commands. Names starting with `DYLD_` (macOS loader) are also reserved.
+const RESERVED_NAMES: &[&str] = &["PATH", "LD_PRELOAD", "LD_LIBRARY_PATH", "IFS"];
+
+pub fn is_reserved_name(name: &str) -> bool {
+    RESERVED_NAMES.contains(&name) || name.starts_with("DYLD_")
+}
+
+/// Refuse to inject reserved names unless explicitly allowed; a sender
+/// controlling LD_PRELOAD or PATH controls what the child actually runs.
+fn check_reserved_names(secrets: &HashMap<String, String>, allow: bool) -> Result<()> {
+    let mut reserved: Vec<&str> = secrets
+        .keys()
+        .map(String::as_str)
+        .filter(|k| is_reserved_name(k))
+        .collect();
+    if reserved.is_empty() {
+        return Ok(());
+    }
+    reserved.sort_unstable();
+    let names = reserved.join(", ");
+    if !allow {
+        bail!(
+            "refusing to inject {}: these variables change which code the child \
+             runs or how it parses commands. Re-run with --allow-unsafe-names if \
+             you trust the sender",
+            names
+        );
+    }
+    display::warning(&format!(
+        "injecting {} (--allow-unsafe-names); the child will run with the sender's values",
+        names
+    ));
+    Ok(())
+}
+
+fn is_env_var_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
-fn run_child(command: &[String], secrets: &HashMap<String, String>) -> Result<()> {
+/// Render secrets as `export KEY='value'` lines, sorted by key, safe to `eval`.
+/// Keys that aren't shell identifiers are rejected rather than quoted.
+fn export_lines(secrets: &HashMap<String, String>) -> Result<String> {
+    let sorted: BTreeMap<_, _> = secrets.iter().collect();
+    let mut out = String::new();
+    for (key, value) in sorted {
+        if !is_env_var_name(key) {
+            bail!(
+                "'{}' is not a valid shell variable name; cannot print it",
+                key
+            );
+        }
+        out.push_str(&format!(
+            "export {}='{}'\n",
+            key,
+            value.replace('\'', "'\\''")
+        ));
+    }
+    Ok(out)
+}
+
+/// Run the command with `secrets` in its environment and wait for it,
+/// forwarding SIGINT/SIGTERM to it on Unix when `forward_signals` is set.
+fn run_child(
+    command: &[String],
+    secrets: &HashMap<String, String>,
+    forward_signals: bool,
+) -> Result<ExitStatus> {
     let mut child = Command::new(&command[0])
         .args(&command[1..])
         .envs(secrets)
@@ -248,14 +590,26 @@ fn run_child(command: &[String], secrets: &HashMap<String, String>) -> Result<()
 
     // Set up signal forwarding on Unix
     #[cfg(unix)]
-    {
+    if forward_signals {
         setup_signal_forwarding(child.id());
     }
+    #[cfg(not(unix))]
+    let _ = forward_signals;
+
+    let status = child.wait();
+
+    #[cfg(unix)]
+    if forward_signals {
+        stop_signal_forwarding();
+    }
 
-    let status = child.wait()?;
+    Ok(status?)
+}
 
-    // On Unix, if the child was killed by a signal, re-raise it so the
-    // parent process reports the correct termination reason to callers.
+/// Exit with the child's status. On Unix a child killed by a signal is
+/// re-raised so callers see the same termination; on Windows there are no
+/// signals and the child's exit code is always passed through.
+fn exit_with(status: ExitStatus) -> ! {
     #[cfg(unix)]
     {
         use std::os::unix::process::ExitStatusExt;
@@ -274,11 +628,14 @@ fn run_child(command: &[String], secrets: &HashMap<String, String>) -> Result<()
     std::process::exit(status.code().unwrap_or(1));
 }
 
+/// Child that SIGINT/SIGTERM are forwarded to (0 when none is running).
+#[cfg(unix)]
+static CHILD_PID: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
 #[cfg(unix)]
 fn setup_signal_forwarding(child_pid: u32) {
-    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::atomic::Ordering;
 
-    static CHILD_PID: AtomicU32 = AtomicU32::new(0);
     CHILD_PID.store(child_pid, Ordering::SeqCst);
 
     // Use sigaction instead of signal for reliable handler persistence
@@ -302,3 +659,89 @@ fn setup_signal_forwarding(child_pid: u32) {
         }
     }
 }
+
+/// Restore default SIGINT/SIGTERM handling once the child has exited, so
+/// a signal while `--loop` is listening stops enseal itself.
+#[cfg(unix)]
+fn stop_signal_forwarding() {
+    CHILD_PID.store(0, std::sync::atomic::Ordering::SeqCst);
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
+        libc::signal(libc::SIGTERM, libc::SIG_DFL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn export_lines_quote_values() {
+        let out = export_lines(&secrets(&[
+            ("GREETING", "hello world"),
+            ("QUOTE", "it's here"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            out,
+            "export GREETING='hello world'\nexport QUOTE='it'\\''s here'\n"
+        );
+    }
+
+    #[test]
+    fn reserved_names_refused_unless_allowed() {
+        let ld = secrets(&[("LD_PRELOAD", "/tmp/evil.so"), ("TOKEN", "x")]);
+        let err = check_reserved_names(&ld, false).unwrap_err();
+        assert!(err.to_string().contains("LD_PRELOAD"), "{}", err);
+        check_reserved_names(&ld, true).unwrap();
+
+        assert!(check_reserved_names(&secrets(&[("DYLD_INSERT_LIBRARIES", "x")]), false).is_err());
+        check_reserved_names(&secrets(&[("PATHNAME", "x")]), false).unwrap();
+    }
+
+    fn envelope(payload: &str, format: PayloadFormat, label: Option<&str>) -> Envelope {
+        Envelope::seal(payload, format, label.map(str::to_string)).unwrap()
+    }
+
+    #[test]
+    fn env_prefix_applies_to_every_key() {
+        let env = envelope("DB_URL=postgres://\nTOKEN=abc\n", PayloadFormat::Env, None);
+        let injected = extract_secrets(&env, Some("APP_")).unwrap();
+        assert_eq!(
+            injected,
+            secrets(&[("APP_DB_URL", "postgres://"), ("APP_TOKEN", "abc")])
+        );
+
+        let raw = envelope("sk-123", PayloadFormat::Raw, Some("API_KEY"));
+        let injected = extract_secrets(&raw, Some("VITE_")).unwrap();
+        assert_eq!(injected, secrets(&[("VITE_API_KEY", "sk-123")]));
+
+        assert_eq!(extract_secrets(&env, None).unwrap()["TOKEN"], "abc");
+    }
+
+    #[test]
+    fn env_prefix_rejects_invalid_names() {
+        let env = envelope("TOKEN=abc\n", PayloadFormat::Env, None);
+        for prefix in ["1APP_", "APP-", "MY APP_"] {
+            let err = extract_secrets(&env, Some(prefix)).unwrap_err();
+            assert!(
+                err.to_string().contains("not a valid env var name"),
+                "{}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn export_lines_reject_unsafe_keys() {
+        assert!(export_lines(&secrets(&[("A;rm -rf /", "x")])).is_err());
+        assert!(export_lines(&secrets(&[("1ABC", "x")])).is_err());
+    }
+}
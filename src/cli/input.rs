@@ -15,6 +15,8 @@ pub enum PayloadFormat {
     Raw,
     /// One or more KEY=VALUE pairs.
     Kv,
+    /// Arbitrary bytes (a keystore, a `.p12`), base64-encoded on the wire.
+    Binary,
 }
 
 /// Resolved input ready for transfer.
@@ -23,6 +25,11 @@ pub struct PayloadInput {
     pub content: String,
     pub format: PayloadFormat,
     pub label: Option<String>,
+    /// File the content was read from (None for --secret and stdin).
+    pub source: Option<String>,
+    /// Verbatim bytes of a binary file sent with --no-filter (`content` is
+    /// empty then).
+    pub bytes: Option<Vec<u8>>,
 }
 
 /// Determine what to send and its format.
@@ -32,6 +39,7 @@ pub fn select_input(
     as_key: Option<&str>,
     label: Option<&str>,
     file: Option<&str>,
+    no_filter: bool,
     quiet: bool,
 ) -> Result<PayloadInput> {
     // Validate label early if provided
@@ -46,7 +54,9 @@ pub fn select_input(
 
     // Reject incompatible flag combinations early
     if secret.is_some() && as_key.is_some() {
-        bail!("--as cannot be used with --secret. Use --secret KEY=VALUE instead");
+        return Err(crate::cli::exit::usage(
+            "--as cannot be used with --secret. Use --secret KEY=VALUE instead",
+        ));
     }
     if as_key.is_some() && secret.is_none() && std::io::stdin().is_terminal() {
         bail!(
@@ -75,6 +85,8 @@ pub fn select_input(
                     content: secret.to_string(),
                     format: PayloadFormat::Kv,
                     label: label.map(|s| s.to_string()),
+                    source: None,
+                    bytes: None,
                 });
             }
         }
@@ -83,6 +95,8 @@ pub fn select_input(
             content: secret.to_string(),
             format: PayloadFormat::Raw,
             label: label.map(|s| s.to_string()),
+            source: None,
+            bytes: None,
         });
     }
 
@@ -119,6 +133,8 @@ pub fn select_input(
                 content: format!("{key}={buf}"),
                 format: PayloadFormat::Kv,
                 label: label.map(|s| s.to_string()),
+                source: None,
+                bytes: None,
             });
         }
 
@@ -135,15 +151,39 @@ pub fn select_input(
             content: buf,
             format,
             label: label.map(|s| s.to_string()),
+            source: None,
+            bytes: None,
         });
     }
 
     // 3. File argument or default .env
-    let path = file.unwrap_or(".env");
+    read_file_input(file.unwrap_or(".env"), label, no_filter)
+}
+
+/// Read a file argument. Binary files are refused unless `no_filter` is
+/// set, in which case their bytes are carried verbatim.
+fn read_file_input(path: &str, label: Option<&str>, no_filter: bool) -> Result<PayloadInput> {
     if !std::path::Path::new(path).exists() {
-        bail!("{} not found", path);
+        return Err(crate::cli::exit::not_found(format!("{} not found", path)));
+    }
+    let bytes = std::fs::read(path).map_err(|e| crate::cli::exit::read_failed(path, e))?;
+    if looks_binary(&bytes) {
+        if !no_filter {
+            bail!(
+                "{} looks like a binary file, not .env content. \
+                 Use --no-filter to send it verbatim",
+                path
+            );
+        }
+        return Ok(PayloadInput {
+            content: String::new(),
+            format: PayloadFormat::Binary,
+            label: label.map(|s| s.to_string()),
+            source: Some(path.to_string()),
+            bytes: Some(bytes),
+        });
     }
-    let content = std::fs::read_to_string(path)?;
+    let content = String::from_utf8(bytes).expect("checked by looks_binary");
     if content.trim().is_empty() {
         bail!("{} is empty", path);
     }
@@ -151,9 +191,91 @@ pub fn select_input(
         content,
         format: PayloadFormat::Env,
         label: label.map(|s| s.to_string()),
+        source: Some(path.to_string()),
+        bytes: None,
     })
 }
 
+/// Share of control characters above which text is treated as binary.
+const MAX_CONTROL_RATIO: f64 = 0.1;
+
+/// Whether file content looks binary: not valid UTF-8, or dominated by
+/// control characters other than ordinary whitespace.
+fn looks_binary(bytes: &[u8]) -> bool {
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return true;
+    };
+    let total = text.chars().count();
+    if total == 0 {
+        return false;
+    }
+    let control = text
+        .chars()
+        .filter(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+        .count();
+    control as f64 / total as f64 > MAX_CONTROL_RATIO
+}
+
+/// Maximum output accepted from a `--pipe-through` command (10 MB).
+const MAX_PIPE_OUTPUT: u64 = 10 * 1024 * 1024;
+
+/// Run `command` with `content` on its stdin and return its stdout.
+///
+/// The command is split into words with shell quoting rules (so quoted
+/// arguments may contain spaces) and executed directly, not through a shell.
+/// Its stderr is passed through so prompts and errors stay visible.
+pub fn pipe_through(command: &str, content: &str) -> Result<String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let words = shell_words::split(command)
+        .map_err(|e| anyhow::anyhow!("invalid --pipe-through command: {}", e))?;
+    let Some((program, args)) = words.split_first() else {
+        bail!("--pipe-through command is empty");
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("failed to start '{}': {}", program, e))?;
+
+    // Feed stdin from a separate thread so a command that writes before it
+    // finishes reading cannot deadlock against us.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = content.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let mut output = Vec::new();
+    child
+        .stdout
+        .take()
+        .expect("stdout is piped")
+        .take(MAX_PIPE_OUTPUT + 1)
+        .read_to_end(&mut output)?;
+    if output.len() as u64 > MAX_PIPE_OUTPUT {
+        let _ = child.kill();
+        let _ = child.wait();
+        bail!("--pipe-through output exceeds maximum size (10 MB)");
+    }
+
+    let status = child.wait()?;
+    // A command may legitimately exit without reading all of its input
+    let _ = writer.join();
+    if !status.success() {
+        bail!("--pipe-through command '{}' failed ({})", program, status);
+    }
+
+    let output = String::from_utf8(output)
+        .map_err(|_| anyhow::anyhow!("--pipe-through output is not valid UTF-8"))?;
+    if output.trim().is_empty() {
+        bail!("--pipe-through command produced no output");
+    }
+    Ok(output)
+}
+
 /// Attempt to parse a string as .env content using dotenvy.
 /// Returns true if the string contains at least one valid KEY=VALUE pair.
 fn try_parse_dotenv(s: &str) -> bool {
@@ -193,4 +315,61 @@ mod tests {
     fn try_parse_dotenv_comments_only() {
         assert!(!try_parse_dotenv("# just a comment\n# another"));
     }
+
+    #[test]
+    fn looks_binary_detects_non_text() {
+        assert!(looks_binary(&[0xff, 0xfe, 0x00, 0x01]));
+        assert!(looks_binary(b"\x00\x01\x02\x03KEY=value"));
+        assert!(!looks_binary(b"API_KEY=abc123\r\n\tDB=x\n"));
+        assert!(!looks_binary("NAME=caf\u{e9}\n".as_bytes()));
+        assert!(!looks_binary(b""));
+    }
+
+    #[test]
+    fn binary_file_needs_no_filter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("cert.p12");
+        let bytes = [0x30u8, 0x82, 0x0a, 0x00, 0xff, 0xfe, 0x00];
+        std::fs::write(&path, bytes).unwrap();
+        let path = path.to_str().unwrap();
+
+        let err = read_file_input(path, None, false).unwrap_err();
+        assert!(err.to_string().contains("--no-filter"));
+
+        let input = read_file_input(path, None, true).unwrap();
+        assert_eq!(input.format, PayloadFormat::Binary);
+        assert_eq!(input.bytes.as_deref(), Some(&bytes[..]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipe_through_transforms_sealed_content() {
+        let output = pipe_through("tr a-z A-Z", "db_host=localhost\n").unwrap();
+        assert_eq!(output, "DB_HOST=LOCALHOST\n");
+
+        let envelope =
+            crate::crypto::envelope::Envelope::seal(&output, PayloadFormat::Env, None).unwrap();
+        assert_eq!(envelope.payload, "DB_HOST=LOCALHOST\n");
+        assert_eq!(envelope.metadata.var_count, Some(1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipe_through_keeps_quoted_arguments_whole() {
+        let output = pipe_through("sed 's/old value/new value/'", "KEY=old value\n").unwrap();
+        assert_eq!(output, "KEY=new value\n");
+        assert!(pipe_through("sed 's/unterminated", "KEY=value").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipe_through_failing_command() {
+        let err = pipe_through("false", "KEY=value").unwrap_err();
+        assert!(err.to_string().contains("failed"));
+    }
+
+    #[test]
+    fn pipe_through_empty_command() {
+        assert!(pipe_through("   ", "KEY=value").is_err());
+    }
 }
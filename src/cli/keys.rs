@@ -1,10 +1,17 @@
-use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
 use base64::Engine;
 use clap::{Parser, Subcommand};
 
 use crate::keys::alias;
 use crate::keys::group;
-use crate::keys::identity::{format_pubkey_file, EnsealIdentity, TrustedKey};
+use crate::keys::identity::{
+    bundle_expiry, fingerprint_words, format_pubkey_file, format_sign_key_minisign,
+    format_sign_key_openssh, format_ssh_key_file, is_expired, parse_ssh_keys, read_trusted,
+    ssh_fingerprint, verify_bundle, CheckOutcome, EnsealIdentity, FingerprintFormat, TrustedKey,
+};
 use crate::keys::store::KeyStore;
 use crate::ui::display;
 
@@ -17,15 +24,76 @@ pub struct KeysArgs {
 #[derive(Subcommand)]
 pub enum KeysCommand {
     /// Generate your keypair
-    Init,
+    Init {
+        /// Name of the local identity to create (default: the --identity selection)
+        #[arg(long)]
+        name: Option<String>,
+    },
+
+    /// Generate a fresh keypair, archiving the current one for old files
+    RotateSelf,
 
     /// Print your public key bundle (for sharing with teammates)
-    Export,
+    Export {
+        /// Write the bundle to this file instead of stdout
+        #[arg(long)]
+        output: Option<String>,
+
+        /// Also render the bundle as a QR code (on stderr)
+        #[arg(long)]
+        qr: bool,
+
+        /// Export only the signing public key, for pinning or identifying it
+        /// elsewhere (enseal signatures can't be checked by other tools)
+        #[arg(long, value_enum, value_name = "FORMAT")]
+        sign_format: Option<SignFormat>,
+    },
 
     /// Add a colleague's public key to trusted keys
     Import {
-        /// Path to a .pub file
-        file: String,
+        /// Path to a .pub file, or an http(s):// URL serving one
+        #[arg(required_unless_present_any = ["github", "json"])]
+        file: Option<String>,
+
+        /// Import many keys from a JSON array of {identity, age, sign, expires?}
+        /// objects ('-' reads stdin)
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["file", "github", "name", "verify_against"])]
+        json: Option<String>,
+
+        /// Import the ssh-ed25519 keys a GitHub user publishes (encrypt-only)
+        #[arg(long, value_name = "USER", conflicts_with_all = ["file", "verify_against"])]
+        github: Option<String>,
+
+        /// Identity name to store the key under (required for URLs;
+        /// defaults to the file name stem or GitHub user)
+        #[arg(long = "as", value_name = "NAME")]
+        name: Option<String>,
+
+        /// Skip confirmation prompt (for scripted workflows)
+        #[arg(long)]
+        yes: bool,
+
+        /// Import without prompting only if the key's fingerprint matches this one
+        #[arg(long, value_name = "FINGERPRINT")]
+        verify_against: Option<String>,
+    },
+
+    /// Trust a sender by name: the last unknown sender (--tofu) or explicit keys
+    Trust {
+        /// Identity name to store the key under
+        name: String,
+
+        /// Use the last unknown sender seen by receive or inject
+        #[arg(long, conflicts_with_all = ["age", "sign"], required_unless_present = "age")]
+        tofu: bool,
+
+        /// The sender's age public key (age1...)
+        #[arg(long, requires = "sign")]
+        age: Option<String>,
+
+        /// The sender's ed25519 signing key (base64)
+        #[arg(long, requires = "age")]
+        sign: Option<String>,
 
         /// Skip confirmation prompt (for scripted workflows)
         #[arg(long)]
@@ -33,7 +101,11 @@ pub enum KeysCommand {
     },
 
     /// Show all trusted keys and aliases
-    List,
+    List {
+        /// Print the listing as JSON
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Remove a trusted key
     Remove {
@@ -41,8 +113,41 @@ pub enum KeysCommand {
         identity: String,
     },
 
+    /// Check a .pub bundle (keys and fingerprint comment) before importing it
+    VerifyBundle {
+        /// Path to the .pub file
+        file: String,
+    },
+
+    /// Remove trusted keys whose `expires:` date has passed
+    PruneExpired {
+        /// List what would be removed without removing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Show your key fingerprint
-    Fingerprint,
+    Fingerprint {
+        /// Render the fingerprint as words for reading aloud
+        #[arg(long, conflicts_with = "format")]
+        words: bool,
+
+        /// Display encoding (default: `fingerprint_format` from config.toml,
+        /// else base64). Stored and compared fingerprints are always base64
+        #[arg(long, value_enum, alias = "fingerprint-algorithm")]
+        format: Option<FingerprintFormat>,
+
+        /// Print `identity<TAB>fingerprint` for yourself and every trusted key
+        #[arg(long, conflicts_with = "words")]
+        all: bool,
+    },
+
+    /// Print the age recipients of a group, one per line, for `age -R`.
+    /// An alias or single identity works too and prints its own recipient(s)
+    ExportRecipients {
+        /// Group name (or alias / trusted identity)
+        name: String,
+    },
 
     /// Map a short name to a full identity
     Alias {
@@ -60,6 +165,19 @@ pub enum KeysCommand {
     },
 }
 
+/// Encodings for `keys export --sign-format`. These are for pinning or
+/// identifying the key only: enseal signs over its own domain-separated
+/// message, so `ssh-keygen -Y verify` or `minisign -V` won't accept them.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SignFormat {
+    /// `ssh-ed25519 AAAA... <user>`, an `authorized_keys`-style line
+    Openssh,
+    /// A minisign public key file
+    Minisign,
+    /// The base64 key from the `sign: ed25519:` bundle line
+    Raw,
+}
+
 #[derive(Subcommand)]
 pub enum GroupCommand {
     /// Create a named recipient group
@@ -75,6 +193,10 @@ pub enum GroupCommand {
 
         /// Identity to add
         identity: String,
+
+        /// Add the member even if no trusted key, alias, or group has that name
+        #[arg(long)]
+        allow_missing: bool,
     },
 
     /// Remove an identity from a group
@@ -87,9 +209,14 @@ pub enum GroupCommand {
     },
 
     /// List groups or members of a specific group
+    #[command(alias = "show")]
     List {
         /// Show members of this group (omit to list all groups)
         name: Option<String>,
+
+        /// Show each member's key fingerprint
+        #[arg(long, requires = "name")]
+        fingerprints: bool,
     },
 
     /// Delete a group
@@ -99,69 +226,323 @@ pub enum GroupCommand {
     },
 }
 
-pub fn run(args: KeysArgs) -> Result<()> {
+pub fn run(args: KeysArgs, store: &KeyStore) -> Result<()> {
     match args.command {
-        KeysCommand::Init => cmd_init(),
-        KeysCommand::Export => cmd_export(),
-        KeysCommand::Import { file, yes } => cmd_import(&file, yes),
-        KeysCommand::List => cmd_list(),
-        KeysCommand::Remove { identity } => cmd_remove(&identity),
-        KeysCommand::Fingerprint => cmd_fingerprint(),
-        KeysCommand::Alias { name, identity } => cmd_alias(&name, &identity),
-        KeysCommand::Group { command } => cmd_group(command),
+        KeysCommand::Init { name } => cmd_init(store, name.as_deref()),
+        KeysCommand::RotateSelf => cmd_rotate_self(store),
+        KeysCommand::Export {
+            output,
+            qr,
+            sign_format,
+        } => cmd_export(store, output.as_deref(), qr, sign_format),
+        KeysCommand::Import {
+            file,
+            github,
+            name,
+            yes,
+            verify_against,
+            json,
+        } => match (github, json) {
+            (_, Some(json)) => cmd_import_json(store, &json, yes),
+            (Some(user), None) => cmd_import_github(store, &user, name.as_deref(), yes),
+            (None, None) => cmd_import(
+                store,
+                file.as_deref().unwrap_or_default(),
+                name.as_deref(),
+                yes,
+                verify_against.as_deref(),
+            ),
+        },
+        KeysCommand::Trust {
+            name,
+            tofu: _,
+            age,
+            sign,
+            yes,
+        } => cmd_trust(store, &name, age.zip(sign), yes),
+        KeysCommand::List { json } => cmd_list(store, json),
+        KeysCommand::Remove { identity } => cmd_remove(store, &identity),
+        KeysCommand::PruneExpired { dry_run } => cmd_prune_expired(store, dry_run),
+        KeysCommand::VerifyBundle { file } => cmd_verify_bundle(&file),
+        KeysCommand::Fingerprint { words, format, all } => {
+            cmd_fingerprint(store, words, format, all)
+        }
+        KeysCommand::ExportRecipients { name } => cmd_export_recipients(store, &name),
+        KeysCommand::Alias { name, identity } => cmd_alias(store, &name, &identity),
+        KeysCommand::Group { command } => cmd_group(store, command),
     }
 }
 
-fn cmd_init() -> Result<()> {
-    let store = KeyStore::open()?;
+fn cmd_init(store: &KeyStore, name: Option<&str>) -> Result<()> {
+    let store = match name {
+        Some(name) => store.clone().with_identity(name)?,
+        None => store.clone(),
+    };
 
     if store.is_initialized() {
-        display::warning(
-            "keys already initialized. Use 'enseal keys export' to view your public key.",
-        );
+        display::warning(&format!(
+            "identity '{}' already initialized. Use 'enseal keys export' to view your public key.",
+            store.identity_name()
+        ));
         return Ok(());
     }
 
     let identity = EnsealIdentity::generate();
     identity.save(&store)?;
 
-    display::ok("keypair generated");
+    display::ok(&format!("keypair generated ({})", store.identity_name()));
     println!();
     println!("  fingerprint: {}", identity.fingerprint());
-    println!("  keys stored in: {}", store.keys_dir().display());
+    println!("  keys stored in: {}", store.identity_dir().display());
     println!();
     println!("Share your public key with: enseal keys export");
 
     Ok(())
 }
 
-fn cmd_export() -> Result<()> {
-    let store = KeyStore::open()?;
-    let identity = EnsealIdentity::load(&store)?;
+fn cmd_rotate_self(store: &KeyStore) -> Result<()> {
+    let old = EnsealIdentity::load(store)?;
 
-    let age_pub = identity.age_recipient.to_string();
-    let sign_pub = base64::engine::general_purpose::STANDARD
-        .encode(identity.signing_key.verifying_key().to_bytes());
+    let timestamp = time::OffsetDateTime::now_utc()
+        .format(time::macros::format_description!(
+            "[year][month][day]T[hour][minute][second]Z"
+        ))
+        .context("failed to format timestamp")?;
+    let (new, archive_path) = rotate_self(store, &timestamp)?;
+
+    display::ok(&format!("keypair rotated ({})", store.identity_name()));
+    println!();
+    println!("  old fingerprint: {}", old.fingerprint());
+    println!("  new fingerprint: {}", new.fingerprint());
+    println!("  old keys archived in: {}", archive_path.display());
+    println!();
+    println!("Re-distribute your new public key: enseal keys export");
+    println!("Teammates must re-import it before sending to you again.");
+    println!("Decrypt files encrypted to the old key with: enseal decrypt --try-archived");
+
+    Ok(())
+}
+
+/// Archive the current identity under `archive/<timestamp>/` and install a
+/// freshly generated one in its place.
+/// Returns the new identity and the archive directory.
+fn rotate_self(store: &KeyStore, timestamp: &str) -> Result<(EnsealIdentity, PathBuf)> {
+    // Copy (not move) into the archive so the slot directory never looks
+    // uninitialized halfway through
+    let archive_path = store.archive_current(timestamp)?;
+    let identity = EnsealIdentity::generate();
+    identity.save(store)?;
+    Ok((identity, archive_path))
+}
+
+fn cmd_export(
+    store: &KeyStore,
+    output: Option<&str>,
+    qr: bool,
+    sign_format: Option<SignFormat>,
+) -> Result<()> {
+    let bundle = match sign_format {
+        Some(format) => export_sign_key(store, format)?,
+        None => export_from(store)?,
+    };
+    // Render first so an oversized bundle fails before anything is written
+    let qr_code = if qr { Some(render_qr(&bundle)?) } else { None };
+
+    match output {
+        Some(path) => {
+            write_public_file(path, &bundle)?;
+            display::ok(&format!("public key written to {}", path));
+        }
+        None => print!("{}", bundle),
+    }
+    if let Some(qr_code) = qr_code {
+        eprintln!("{}", qr_code);
+    }
+    Ok(())
+}
+
+/// Write a public key bundle. It holds nothing secret, so it's world-readable.
+fn write_public_file(path: &str, content: &str) -> Result<()> {
+    std::fs::write(path, content)
+        .map_err(|e| anyhow::anyhow!("failed to write '{}': {}", path, e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o644))?;
+    }
+    Ok(())
+}
+
+/// Render text as a terminal QR code (two rows per line).
+fn render_qr(text: &str) -> Result<String> {
+    let code = match qrcode::QrCode::new(text.as_bytes()) {
+        Ok(code) => code,
+        Err(qrcode::types::QrError::DataTooLong) => bail!(
+            "public key bundle is too large for a QR code ({} bytes). \
+             Share the file instead: enseal keys export --output <file>",
+            text.len()
+        ),
+        Err(e) => bail!("failed to render QR code: {}", e),
+    };
+    Ok(code
+        .render::<qrcode::render::unicode::Dense1x2>()
+        .quiet_zone(true)
+        .build())
+}
+
+/// Render the public key bundle for the store's selected identity.
+fn export_from(store: &KeyStore) -> Result<String> {
+    let identity = EnsealIdentity::load(store)?;
 
     // Use hostname or "unknown" as the identity label
     let hostname = username_or_unknown();
-    let content = format_pubkey_file(&hostname, &age_pub, &sign_pub);
-    print!("{}", content);
+    Ok(identity.pubkey_file(&hostname))
+}
+
+/// Render just the selected identity's signing public key.
+fn export_sign_key(store: &KeyStore, format: SignFormat) -> Result<String> {
+    let key = EnsealIdentity::load(store)?.signing_key.verifying_key();
+    Ok(match format {
+        SignFormat::Openssh => format_sign_key_openssh(&key, &username_or_unknown()),
+        SignFormat::Minisign => format_sign_key_minisign(&key),
+        SignFormat::Raw => format!(
+            "{}\n",
+            base64::engine::general_purpose::STANDARD.encode(key.to_bytes())
+        ),
+    })
+}
+
+fn cmd_import(
+    store: &KeyStore,
+    file: &str,
+    name: Option<&str>,
+    skip_confirm: bool,
+    verify_against: Option<&str>,
+) -> Result<()> {
+    import_into(store, file, name, skip_confirm, verify_against)
+}
+
+/// One key in a `keys import --json` array.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonKey {
+    identity: String,
+    /// age public key (age1...)
+    age: String,
+    /// ed25519 signing key, base64 (an `ed25519:` prefix is accepted)
+    sign: String,
+    /// Optional expiry date, YYYY-MM-DD
+    #[serde(default)]
+    expires: Option<String>,
+}
+
+impl JsonKey {
+    /// The `.pub` bundle for this entry, validated as a trusted key.
+    fn bundle(&self) -> Result<(String, TrustedKey)> {
+        crate::keys::store::validate_identity_name(&self.identity)?;
+        let sign = self.sign.strip_prefix("ed25519:").unwrap_or(&self.sign);
+        let mut content = format_pubkey_file(&self.identity, &self.age, sign);
+        if let Some(ref expires) = self.expires {
+            content.push_str(&format!("expires: {}\n", expires));
+            bundle_expiry(&content)?;
+        }
+        let trusted = TrustedKey::parse(&self.identity, &content)?;
+        Ok((content, trusted))
+    }
+}
 
+fn cmd_import_json(store: &KeyStore, path: &str, skip_confirm: bool) -> Result<()> {
+    let json = if path == "-" {
+        std::io::read_to_string(std::io::stdin()).context("failed to read stdin")?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| crate::cli::exit::read_failed(path, e))?
+    };
+    import_json_into(store, &json, skip_confirm)
+}
+
+/// Import every valid entry of a JSON key array after one confirmation.
+/// Invalid entries are reported and skipped; any failure fails the command.
+fn import_json_into(store: &KeyStore, json: &str, skip_confirm: bool) -> Result<()> {
+    let entries: Vec<JsonKey> = serde_json::from_str(json)
+        .map_err(|e| crate::cli::exit::validation(format!("invalid key JSON: {}", e)))?;
+    if entries.is_empty() {
+        return Err(crate::cli::exit::validation("key JSON has no entries"));
+    }
+
+    let mut valid = Vec::new();
+    let mut failed = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        match entry.bundle() {
+            Ok((content, trusted)) => valid.push((entry.identity.as_str(), content, trusted)),
+            Err(e) => {
+                display::error(&format!("entry {} ('{}'): {:#}", i + 1, entry.identity, e));
+                failed += 1;
+            }
+        }
+    }
+
+    if !valid.is_empty() {
+        println!("Importing {} public keys:", valid.len());
+        for (identity, _, trusted) in &valid {
+            println!("  {}  {}", identity, trusted.fingerprint());
+        }
+        println!();
+        if !skip_confirm && !confirm("Trust these keys?")? {
+            println!("import cancelled");
+            return Ok(());
+        }
+
+        store.ensure_dirs()?;
+        for (identity, content, _) in &valid {
+            let dest = store.trusted_key_path(identity)?;
+            match std::fs::write(&dest, content) {
+                Ok(()) => display::ok(&format!("imported key for '{}'", identity)),
+                Err(e) => {
+                    display::error(&format!("failed to write key for '{}': {}", identity, e));
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(crate::cli::exit::validation(format!(
+            "{} of {} entries failed to import",
+            failed,
+            entries.len()
+        )));
+    }
     Ok(())
 }
 
-fn cmd_import(file: &str, skip_confirm: bool) -> Result<()> {
-    let store = KeyStore::open()?;
-    let content = std::fs::read_to_string(file)
-        .map_err(|e| anyhow::anyhow!("failed to read '{}': {}", file, e))?;
+fn import_into(
+    store: &KeyStore,
+    file: &str,
+    name: Option<&str>,
+    skip_confirm: bool,
+    verify_against: Option<&str>,
+) -> Result<()> {
+    let is_url = file.starts_with("https://") || file.starts_with("http://");
 
-    // Extract identity from filename stem (e.g., alice@example.com.pub -> alice@example.com)
-    let path = std::path::Path::new(file);
-    let identity_name = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown");
+    // Identity from --as, else the filename stem (e.g., alice@example.com.pub -> alice@example.com)
+    let identity_name = match name {
+        Some(name) => name,
+        None if is_url => {
+            return Err(crate::cli::exit::usage(
+                "importing from a URL requires --as <name>",
+            ))
+        }
+        None => std::path::Path::new(file)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown"),
+    };
+
+    let content = if is_url {
+        fetch_pubkey(file)?
+    } else {
+        std::fs::read_to_string(file).map_err(|e| crate::cli::exit::read_failed(file, e))?
+    };
 
     // Validate identity name is safe for file paths
     crate::keys::store::validate_identity_name(identity_name)?;
@@ -173,9 +554,24 @@ fn cmd_import(file: &str, skip_confirm: bool) -> Result<()> {
     println!("Importing public key:");
     println!("  identity:    {}", identity_name);
     println!("  fingerprint: {}", trusted.fingerprint());
+    println!(
+        "  words:       {}",
+        fingerprint_words(&trusted.fingerprint())?.join(" ")
+    );
     println!();
 
-    if !skip_confirm && !confirm("Trust this key?")? {
+    // An expected fingerprint replaces the interactive check: match or abort
+    if let Some(expected) = verify_against {
+        if !fingerprint_matches(&trusted.fingerprint(), expected) {
+            bail!(
+                "fingerprint mismatch for '{}': expected {}, got {}. Key not imported",
+                identity_name,
+                expected.trim(),
+                trusted.fingerprint()
+            );
+        }
+        display::ok("fingerprint matches");
+    } else if !skip_confirm && !confirm("Trust this key?")? {
         println!("import cancelled");
         return Ok(());
     }
@@ -190,51 +586,331 @@ fn cmd_import(file: &str, skip_confirm: bool) -> Result<()> {
     Ok(())
 }
 
-fn cmd_list() -> Result<()> {
-    let store = KeyStore::open()?;
+fn cmd_trust(
+    store: &KeyStore,
+    name: &str,
+    keys: Option<(String, String)>,
+    skip_confirm: bool,
+) -> Result<()> {
+    let (age_pubkey, sign_pubkey) = match keys {
+        Some(keys) => keys,
+        None => crate::keys::tofu::last_unknown_sender(store)?,
+    };
+
+    // Show fingerprint and ask for confirmation, as for an import
+    let content = format_pubkey_file(name, &age_pubkey, &sign_pubkey);
+    let candidate = TrustedKey::parse(name, &content)?;
+    println!("Trusting sender:");
+    println!("  identity:    {}", name);
+    println!("  fingerprint: {}", candidate.fingerprint());
+    println!(
+        "  words:       {}",
+        fingerprint_words(&candidate.fingerprint())?.join(" ")
+    );
+    println!();
+    if !skip_confirm && !confirm("Trust this key?")? {
+        println!("trust cancelled");
+        return Ok(());
+    }
+
+    crate::keys::tofu::trust(store, name, &age_pubkey, &sign_pubkey)?;
+    display::ok(&format!("trusted key for '{}'", name));
+    Ok(())
+}
+
+/// Where `keys import --github` looks up `<user>.keys`.
+const GITHUB_URL: &str = "https://github.com";
+
+fn cmd_import_github(
+    store: &KeyStore,
+    user: &str,
+    name: Option<&str>,
+    skip_confirm: bool,
+) -> Result<()> {
+    import_github(store, GITHUB_URL, user, name, skip_confirm)
+}
+
+fn import_github(
+    store: &KeyStore,
+    base_url: &str,
+    user: &str,
+    name: Option<&str>,
+    skip_confirm: bool,
+) -> Result<()> {
+    validate_github_user(user)?;
+    let identity_name = name.unwrap_or(user);
+    crate::keys::store::validate_identity_name(identity_name)?;
+
+    let url = format!("{}/{}.keys", base_url.trim_end_matches('/'), user);
+    let published = fetch_pubkey(&url)?;
+
+    // Only ed25519 keys map onto age SSH recipients we want to trust;
+    // keep the type and key data, drop any comment
+    let mut keys = Vec::new();
+    let mut skipped = 0;
+    for line in published.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let mut fields = line.split_whitespace();
+        match (fields.next(), fields.next()) {
+            (Some("ssh-ed25519"), Some(data)) => keys.push(format!("ssh-ed25519 {}", data)),
+            _ => skipped += 1,
+        }
+    }
+    if keys.is_empty() {
+        bail!("GitHub user '{}' has no ssh-ed25519 keys at {}", user, url);
+    }
+    let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let content = format_ssh_key_file(identity_name, &url, &keys)?;
+
+    println!("Importing SSH keys from {}:", url);
+    println!("  identity:    {}", identity_name);
+    for key in &keys {
+        println!("  fingerprint: {}", ssh_fingerprint(key)?);
+    }
+    if skipped > 0 {
+        println!("  (skipped {} key(s) that are not ssh-ed25519)", skipped);
+    }
+    println!();
+
+    if !skip_confirm && !confirm("Trust these keys?")? {
+        println!("import cancelled");
+        return Ok(());
+    }
+
+    store.ensure_dirs()?;
+    std::fs::write(store.trusted_key_path(identity_name)?, &content)?;
+
+    display::ok(&format!(
+        "imported {} SSH key(s) for '{}' (encrypt only)",
+        keys.len(),
+        identity_name
+    ));
+
+    Ok(())
+}
+
+/// GitHub usernames: alphanumerics and single hyphens, at most 39 characters.
+fn validate_github_user(user: &str) -> Result<()> {
+    let valid = !user.is_empty()
+        && user.len() <= 39
+        && !user.starts_with('-')
+        && !user.ends_with('-')
+        && !user.contains("--")
+        && user.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid {
+        bail!("invalid GitHub username '{}'", user);
+    }
+    Ok(())
+}
+
+/// Maximum size of a public key bundle fetched from a URL (64 KiB).
+const MAX_PUBKEY_DOWNLOAD: u64 = 64 * 1024;
+
+/// Download a public key bundle over HTTP(S).
+fn fetch_pubkey(url: &str) -> Result<String> {
+    use std::io::Read;
+
+    if url.starts_with("http://") {
+        display::warning("fetching key over plain HTTP; verify the fingerprint out-of-band");
+    }
+
+    let response = ureq::get(url)
+        .timeout(std::time::Duration::from_secs(30))
+        .call()
+        .map_err(|e| anyhow::anyhow!("failed to fetch '{}': {}", url, e))?;
+
+    let mut body = String::new();
+    response
+        .into_reader()
+        .take(MAX_PUBKEY_DOWNLOAD + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| anyhow::anyhow!("failed to read response from '{}': {}", url, e))?;
+    if body.len() as u64 > MAX_PUBKEY_DOWNLOAD {
+        bail!(
+            "response from '{}' exceeds maximum key size ({} bytes)",
+            url,
+            MAX_PUBKEY_DOWNLOAD
+        );
+    }
+    Ok(body)
+}
+
+/// Everything `keys list` shows, gathered once and rendered as text or JSON.
+#[derive(serde::Serialize)]
+struct KeyListing {
+    #[serde(rename = "self")]
+    own: Option<ListedKey>,
+    identities: Vec<String>,
+    trusted: Vec<ListedKey>,
+    aliases: BTreeMap<String, String>,
+    groups: BTreeMap<String, Vec<String>>,
+}
+
+/// A key in the listing. A key that fails to load carries `error` instead
+/// of a fingerprint so one bad file doesn't hide the rest.
+#[derive(serde::Serialize)]
+struct ListedKey {
+    identity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ssh_fingerprints: Option<Vec<String>>,
+    /// Past the bundle's `expires:` date (see `keys prune-expired`).
+    expired: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ListedKey {
+    fn loaded(identity: &str, result: Result<String>) -> Self {
+        let (fingerprint, error) = match result {
+            Ok(fp) => (Some(fp), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        ListedKey {
+            identity: identity.to_string(),
+            fingerprint,
+            ssh_fingerprints: None,
+            expired: false,
+            error,
+        }
+    }
+}
+
+fn gather_listing(store: &KeyStore) -> Result<KeyListing> {
+    let own = store.is_initialized().then(|| {
+        ListedKey::loaded(
+            store.identity_name(),
+            EnsealIdentity::load(store)
+                .map(|id| id.fingerprint())
+                .map_err(Into::into),
+        )
+    });
+
+    let identities = store
+        .list_identities()?
+        .into_iter()
+        .filter(|name| name != store.identity_name())
+        .collect();
+
+    let today = time::OffsetDateTime::now_utc().date();
+    let trusted = store
+        .list_trusted()?
+        .iter()
+        .map(|name| {
+            let content = match read_trusted(store, name) {
+                Ok(content) => content,
+                Err(e) => return ListedKey::loaded(name, Err(e)),
+            };
+            let expired = match is_expired(&content, today) {
+                Ok(expired) => expired,
+                Err(e) => return ListedKey::loaded(name, Err(e)),
+            };
+            let ssh_keys = parse_ssh_keys(&content);
+            if !ssh_keys.is_empty() {
+                let fingerprints: Result<Vec<String>> =
+                    ssh_keys.into_iter().map(ssh_fingerprint).collect();
+                return match fingerprints {
+                    Ok(fingerprints) => ListedKey {
+                        identity: name.clone(),
+                        fingerprint: None,
+                        ssh_fingerprints: Some(fingerprints),
+                        expired,
+                        error: None,
+                    },
+                    Err(e) => ListedKey::loaded(name, Err(e)),
+                };
+            }
+            ListedKey {
+                expired,
+                ..ListedKey::loaded(
+                    name,
+                    TrustedKey::parse(name, &content).map(|key| key.fingerprint()),
+                )
+            }
+        })
+        .collect();
+
+    let aliases = alias::list(store)?.into_iter().collect();
+    let groups = group::list_groups(store)?
+        .into_iter()
+        .map(|(name, entry)| (name, entry.members))
+        .collect();
+
+    Ok(KeyListing {
+        own,
+        identities,
+        trusted,
+        aliases,
+        groups,
+    })
+}
+
+fn cmd_list(store: &KeyStore, json: bool) -> Result<()> {
+    let listing = gather_listing(store)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&listing)?);
+        return Ok(());
+    }
 
     // Own key
-    if store.is_initialized() {
-        let identity = EnsealIdentity::load(&store)?;
-        println!("Own key:");
-        println!("  fingerprint: {}", identity.fingerprint());
+    if let Some(own) = &listing.own {
+        println!("Own key ({}):", own.identity);
+        match (&own.fingerprint, &own.error) {
+            (Some(fp), _) => println!("  fingerprint: {}", fp),
+            (None, Some(e)) => println!("  error: {}", e),
+            (None, None) => {}
+        }
+        println!();
+    }
+
+    // Other local identities
+    if !listing.identities.is_empty() {
+        println!("Other local identities (select with --identity):");
+        for name in &listing.identities {
+            println!("  {}", name);
+        }
         println!();
     }
 
     // Trusted keys
-    let trusted = store.list_trusted()?;
-    if trusted.is_empty() {
+    if listing.trusted.is_empty() {
         println!("No trusted keys. Import with: enseal keys import <file>");
     } else {
         println!("Trusted keys:");
-        for name in &trusted {
-            match TrustedKey::load(&store, name) {
-                Ok(key) => println!("  {} ({})", name, key.fingerprint()),
-                Err(_) => println!("  {} (error reading key)", name),
+        for key in &listing.trusted {
+            let expired = if key.expired { " [expired]" } else { "" };
+            match (&key.fingerprint, &key.ssh_fingerprints) {
+                (_, Some(ssh)) => println!(
+                    "  {} (ssh, {} key(s), encrypt only){}",
+                    key.identity,
+                    ssh.len(),
+                    expired
+                ),
+                (Some(fp), None) => println!("  {} ({}){}", key.identity, fp, expired),
+                (None, None) => println!("  {} (error reading key)", key.identity),
             }
         }
     }
 
     // Aliases
-    let aliases = alias::list(&store)?;
-    if !aliases.is_empty() {
+    if !listing.aliases.is_empty() {
         println!();
         println!("Aliases:");
-        for (name, identity) in &aliases {
+        for (name, identity) in &listing.aliases {
             println!("  {} -> {}", name, identity);
         }
     }
 
     // Groups
-    let groups = group::list_groups(&store)?;
-    if !groups.is_empty() {
+    if !listing.groups.is_empty() {
         println!();
         println!("Groups:");
-        for (name, entry) in &groups {
-            if entry.members.is_empty() {
+        for (name, members) in &listing.groups {
+            if members.is_empty() {
                 println!("  {} (empty)", name);
             } else {
-                println!("  {} ({})", name, entry.members.join(", "));
+                println!("  {} ({})", name, members.join(", "));
             }
         }
     }
@@ -242,22 +918,30 @@ fn cmd_list() -> Result<()> {
     Ok(())
 }
 
-fn cmd_remove(identity: &str) -> Result<()> {
+fn cmd_remove(store: &KeyStore, identity: &str) -> Result<()> {
     crate::keys::store::validate_identity_name(identity)?;
-    let store = KeyStore::open()?;
     let path = store.trusted_key_path(identity)?;
 
     if !path.exists() {
         bail!("no trusted key found for '{}'", identity);
     }
 
-    std::fs::remove_file(&path)?;
+    remove_trusted(store, identity)?;
+    display::ok(&format!("removed trusted key for '{}'", identity));
+
+    Ok(())
+}
+
+/// Delete a trusted key along with aliases pointing to it and its group
+/// memberships.
+fn remove_trusted(store: &KeyStore, identity: &str) -> Result<()> {
+    std::fs::remove_file(store.trusted_key_path(identity)?)?;
 
     // Clean up aliases pointing to this identity
-    let aliases = alias::list(&store)?;
+    let aliases = alias::list(store)?;
     for (name, target) in &aliases {
         if target == identity {
-            let _ = alias::remove(&store, name);
+            let _ = alias::remove(store, name);
             display::warning(&format!(
                 "removed alias '{}' (pointed to removed key)",
                 name
@@ -266,46 +950,230 @@ fn cmd_remove(identity: &str) -> Result<()> {
     }
 
     // Clean up group memberships
-    let groups = group::list_groups(&store)?;
+    let groups = group::list_groups(store)?;
     for (name, entry) in &groups {
         if entry.members.contains(&identity.to_string()) {
-            let _ = group::remove_member(&store, name, identity);
+            let _ = group::remove_member(store, name, identity);
             display::warning(&format!("removed '{}' from group '{}'", identity, name));
         }
     }
+    Ok(())
+}
 
-    display::ok(&format!("removed trusted key for '{}'", identity));
+fn cmd_verify_bundle(file: &str) -> Result<()> {
+    let content =
+        std::fs::read_to_string(file).with_context(|| format!("failed to read {}", file))?;
+    let checks = verify_bundle(&content);
+
+    let mut failed = 0;
+    for check in &checks {
+        match &check.outcome {
+            CheckOutcome::Pass => display::ok(check.name),
+            CheckOutcome::Skipped(reason) => {
+                display::warning(&format!("{}: skipped ({})", check.name, reason))
+            }
+            CheckOutcome::Fail(reason) => {
+                failed += 1;
+                display::error(&format!("{}: {}", check.name, reason));
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} failed {} of {} checks", file, failed, checks.len());
+    }
+    display::ok(&format!("{} looks valid", file));
+    Ok(())
+}
 
+fn cmd_prune_expired(store: &KeyStore, dry_run: bool) -> Result<()> {
+    let today = time::OffsetDateTime::now_utc().date();
+    let pruned = prune_expired(store, today, dry_run)?;
+
+    if pruned.is_empty() {
+        display::ok("no expired trusted keys");
+        return Ok(());
+    }
+    for (identity, expires) in &pruned {
+        if dry_run {
+            display::info(
+                "Would prune:",
+                &format!("{} (expired {})", identity, expires),
+            );
+        } else {
+            display::ok(&format!("pruned '{}' (expired {})", identity, expires));
+        }
+    }
     Ok(())
 }
 
-fn cmd_fingerprint() -> Result<()> {
-    let store = KeyStore::open()?;
-    let identity = EnsealIdentity::load(&store)?;
-    println!("{}", identity.fingerprint());
+/// Remove every trusted key that expired before `today`, returning each
+/// one with its expiry date. With `dry_run`, nothing is removed.
+fn prune_expired(
+    store: &KeyStore,
+    today: time::Date,
+    dry_run: bool,
+) -> Result<Vec<(String, time::Date)>> {
+    let mut pruned = Vec::new();
+    for identity in store.list_trusted()? {
+        let expires = match read_trusted(store, &identity).and_then(|c| bundle_expiry(&c)) {
+            Ok(Some(expires)) if expires < today => expires,
+            Ok(_) => continue,
+            Err(e) => {
+                display::warning(&format!("skipping '{}': {:#}", identity, e));
+                continue;
+            }
+        };
+        if !dry_run {
+            remove_trusted(store, &identity)?;
+        }
+        pruned.push((identity, expires));
+    }
+    Ok(pruned)
+}
+
+fn cmd_fingerprint(
+    store: &KeyStore,
+    words: bool,
+    format: Option<FingerprintFormat>,
+    all: bool,
+) -> Result<()> {
+    if all {
+        let format = format.unwrap_or(crate::config::user::get()?.fingerprint_format);
+        for (identity, fingerprint) in all_fingerprints(store, format)? {
+            println!("{}\t{}", identity, fingerprint);
+        }
+        return Ok(());
+    }
+    let identity = EnsealIdentity::load(store)?;
+    if words {
+        println!("{}", fingerprint_words(&identity.fingerprint())?.join(" "));
+    } else {
+        let format = format.unwrap_or(crate::config::user::get()?.fingerprint_format);
+        println!("{}", identity.fingerprint_as(format));
+    }
     Ok(())
 }
 
-fn cmd_alias(name: &str, identity: &str) -> Result<()> {
-    let store = KeyStore::open()?;
-    alias::set(&store, name, identity)?;
+fn cmd_alias(store: &KeyStore, name: &str, identity: &str) -> Result<()> {
+    alias::set(store, name, identity)?;
     display::ok(&format!("alias '{}' -> '{}'", name, identity));
     Ok(())
 }
 
-fn cmd_group(command: GroupCommand) -> Result<()> {
-    let store = KeyStore::open()?;
+/// `(identity, fingerprint)` for your own key (as `self`) and each trusted
+/// key, in name order. Encrypt-only SSH entries list their SSH key
+/// fingerprints, comma-separated. Unreadable keys are skipped with a warning.
+fn all_fingerprints(store: &KeyStore, format: FingerprintFormat) -> Result<Vec<(String, String)>> {
+    let mut rows = Vec::new();
+    if store.is_initialized() {
+        let own = EnsealIdentity::load(store)?;
+        rows.push(("self".to_string(), own.fingerprint_as(format)));
+    }
+    for name in store.list_trusted()? {
+        let fingerprint = read_trusted(store, &name).and_then(|content| {
+            let ssh_keys = parse_ssh_keys(&content);
+            if ssh_keys.is_empty() {
+                return Ok(TrustedKey::parse(&name, &content)?.fingerprint_as(format));
+            }
+            let fingerprints = ssh_keys
+                .into_iter()
+                .map(ssh_fingerprint)
+                .collect::<Result<Vec<_>>>()?;
+            Ok(fingerprints.join(","))
+        });
+        match fingerprint {
+            Ok(fingerprint) => rows.push((name, fingerprint)),
+            Err(e) => display::warning(&format!("skipping '{}': {:#}", name, e)),
+        }
+    }
+    Ok(rows)
+}
 
+fn cmd_export_recipients(store: &KeyStore, name: &str) -> Result<()> {
+    let identities = crate::keys::resolve_to_identities(store, name)?;
+    for recipient in recipient_lines(store, &identities)? {
+        println!("{}", recipient);
+    }
+    Ok(())
+}
+
+/// The `age -R` lines for `identities`, deduplicated in order: the `age1...`
+/// key of an enseal bundle, or the SSH keys of an encrypt-only entry.
+fn recipient_lines(store: &KeyStore, identities: &[String]) -> Result<Vec<String>> {
+    let mut lines: Vec<String> = Vec::new();
+    for identity in identities {
+        let content = read_trusted(store, identity)?;
+        let ssh_keys = parse_ssh_keys(&content);
+        let recipients = if ssh_keys.is_empty() {
+            vec![TrustedKey::parse(identity, &content)?
+                .age_recipient
+                .to_string()]
+        } else {
+            ssh_keys.into_iter().map(String::from).collect()
+        };
+        for recipient in recipients {
+            if !lines.contains(&recipient) {
+                lines.push(recipient);
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// What a group member refers to, for `keys group list <name>`.
+#[derive(Debug, PartialEq)]
+enum MemberKey {
+    /// A nested group.
+    Group,
+    /// A trusted key, directly or through an alias.
+    Trusted {
+        identity: String,
+        fingerprint: String,
+    },
+    /// No trusted key (or alias to one) by that name.
+    Unresolved,
+}
+
+fn member_keys(
+    store: &KeyStore,
+    members: &[String],
+    format: FingerprintFormat,
+) -> Result<Vec<(String, MemberKey)>> {
+    members
+        .iter()
+        .map(|member| {
+            if group::get_members(store, member)?.is_some() {
+                return Ok((member.clone(), MemberKey::Group));
+            }
+            let identity = alias::resolve(store, member)?.unwrap_or_else(|| member.clone());
+            let key = match TrustedKey::load(store, &identity) {
+                Ok(key) => MemberKey::Trusted {
+                    fingerprint: key.fingerprint_as(format),
+                    identity,
+                },
+                Err(_) => MemberKey::Unresolved,
+            };
+            Ok((member.clone(), key))
+        })
+        .collect()
+}
+
+fn cmd_group(store: &KeyStore, command: GroupCommand) -> Result<()> {
     match command {
         GroupCommand::Create { name } => {
-            group::create(&store, &name)?;
+            group::create(store, &name)?;
             display::ok(&format!("created group '{}'", name));
         }
         GroupCommand::Add {
             group: grp,
             identity,
+            allow_missing,
         } => {
-            if group::add_member(&store, &grp, &identity)? {
+            if !allow_missing {
+                group::check_member(store, &identity)?;
+            }
+            if group::add_member(store, &grp, &identity)? {
                 display::ok(&format!("added '{}' to group '{}'", identity, grp));
             } else {
                 display::warning(&format!("'{}' is already a member of '{}'", identity, grp));
@@ -315,31 +1183,39 @@ fn cmd_group(command: GroupCommand) -> Result<()> {
             group: grp,
             identity,
         } => {
-            if group::remove_member(&store, &grp, &identity)? {
+            if group::remove_member(store, &grp, &identity)? {
                 display::ok(&format!("removed '{}' from group '{}'", identity, grp));
             } else {
                 display::warning(&format!("'{}' is not a member of '{}'", identity, grp));
             }
         }
-        GroupCommand::List { name } => {
+        GroupCommand::List { name, fingerprints } => {
             if let Some(name) = name {
-                match group::get_members(&store, &name)? {
-                    Some(members) => {
-                        println!("Group '{}':", name);
-                        if members.is_empty() {
-                            println!("  (no members)");
-                        } else {
-                            for m in &members {
-                                println!("  {}", m);
-                            }
-                        }
-                    }
-                    None => {
-                        bail!("group '{}' does not exist", name);
+                let Some(members) = group::get_members(store, &name)? else {
+                    bail!("group '{}' does not exist", name);
+                };
+                println!("Group '{}':", name);
+                if members.is_empty() {
+                    println!("  (no members)");
+                }
+                let format = crate::config::user::get()?.fingerprint_format;
+                for (member, key) in member_keys(store, &members, format)? {
+                    match key {
+                        MemberKey::Group => println!("  {} (group)", member),
+                        _ if !fingerprints => println!("  {}", member),
+                        MemberKey::Trusted {
+                            identity,
+                            fingerprint,
+                        } if identity == member => println!("  {}  {}", member, fingerprint),
+                        MemberKey::Trusted {
+                            identity,
+                            fingerprint,
+                        } => println!("  {} ({})  {}", member, identity, fingerprint),
+                        MemberKey::Unresolved => println!("  {}  (no key / unresolved)", member),
                     }
                 }
             } else {
-                let groups = group::list_groups(&store)?;
+                let groups = group::list_groups(store)?;
                 if groups.is_empty() {
                     println!("No groups. Create one with: enseal keys group create <name>");
                 } else {
@@ -351,7 +1227,7 @@ fn cmd_group(command: GroupCommand) -> Result<()> {
             }
         }
         GroupCommand::Delete { name } => {
-            if group::delete_group(&store, &name)? {
+            if group::delete_group(store, &name)? {
                 display::ok(&format!("deleted group '{}'", name));
             } else {
                 bail!("group '{}' does not exist", name);
@@ -362,6 +1238,14 @@ fn cmd_group(command: GroupCommand) -> Result<()> {
     Ok(())
 }
 
+/// Compare a computed fingerprint with a user-supplied one.
+/// The `SHA256:` prefix is optional on the expected value.
+fn fingerprint_matches(actual: &str, expected: &str) -> bool {
+    let expected = expected.trim();
+    let expected = expected.strip_prefix("SHA256:").unwrap_or(expected);
+    actual.strip_prefix("SHA256:").unwrap_or(actual) == expected
+}
+
 fn username_or_unknown() -> String {
     std::env::var("USER")
         .or_else(|_| std::env::var("USERNAME"))
@@ -379,3 +1263,463 @@ fn confirm(prompt: &str) -> Result<bool> {
         .interact()?;
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Write a teammate's public key file and return (path, fingerprint).
+    fn write_pubkey(dir: &TempDir, identity: &str) -> (String, String) {
+        let id = EnsealIdentity::generate();
+        let content = id.pubkey_file(identity);
+        let path = dir.path().join(format!("{}.pub", identity));
+        std::fs::write(&path, content).unwrap();
+        (path.to_string_lossy().into_owned(), id.fingerprint())
+    }
+
+    #[test]
+    fn identities_export_independently() {
+        let dir = TempDir::new().unwrap();
+        let personal = KeyStore::open_at(dir.path().to_path_buf());
+        let work = KeyStore::open_at(dir.path().to_path_buf())
+            .with_identity("work")
+            .unwrap();
+
+        assert!(!personal.is_initialized());
+        EnsealIdentity::generate().save(&personal).unwrap();
+        assert!(!work.is_initialized());
+        EnsealIdentity::generate().save(&work).unwrap();
+
+        let personal_export = export_from(&personal).unwrap();
+        let work_export = export_from(&work).unwrap();
+        assert_ne!(personal_export, work_export);
+        assert_eq!(
+            TrustedKey::parse("me", &work_export).unwrap().fingerprint(),
+            EnsealIdentity::load(&work).unwrap().fingerprint()
+        );
+        assert_eq!(
+            personal.list_identities().unwrap(),
+            vec!["default".to_string(), "work".to_string()]
+        );
+    }
+
+    #[test]
+    fn legacy_top_level_key_loads_as_default() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        let id = EnsealIdentity::generate();
+        id.save(&store).unwrap();
+
+        // Move the slot's files up to the pre-slot layout
+        let slot = store.identity_dir();
+        for entry in std::fs::read_dir(&slot).unwrap() {
+            let path = entry.unwrap().path();
+            std::fs::rename(&path, store.keys_dir().join(path.file_name().unwrap())).unwrap();
+        }
+        std::fs::remove_dir(&slot).unwrap();
+
+        assert_eq!(store.identity_dir(), store.keys_dir());
+        let loaded = EnsealIdentity::load(&store).unwrap();
+        assert_eq!(loaded.fingerprint(), id.fingerprint());
+        assert_eq!(
+            store.list_identities().unwrap(),
+            vec!["default".to_string()]
+        );
+    }
+
+    #[test]
+    fn reserved_identity_name_rejected() {
+        let dir = TempDir::new().unwrap();
+        for name in ["trusted", "archive"] {
+            assert!(KeyStore::open_at(dir.path().to_path_buf())
+                .with_identity(name)
+                .is_err());
+        }
+    }
+
+    /// Serve `body` once over HTTP on a local port and return the URL.
+    fn serve_once(body: String) -> String {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/alice.pub", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn import_from_url() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("store"));
+        let (path, fingerprint) = write_pubkey(&dir, "alice");
+        let url = serve_once(std::fs::read_to_string(&path).unwrap());
+
+        import_into(
+            &store,
+            &url,
+            Some("alice@example.com"),
+            false,
+            Some(&fingerprint),
+        )
+        .unwrap();
+        let stored = TrustedKey::load(&store, "alice@example.com").unwrap();
+        assert_eq!(stored.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn import_from_url_requires_name() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("store"));
+        let err = import_into(
+            &store,
+            "https://keys.example.com/alice.pub",
+            None,
+            true,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("--as"));
+    }
+
+    #[test]
+    fn import_with_matching_fingerprint() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("store"));
+        let (path, fingerprint) = write_pubkey(&dir, "alice@example.com");
+
+        import_into(&store, &path, None, false, Some(&fingerprint)).unwrap();
+        assert!(store
+            .trusted_key_path("alice@example.com")
+            .unwrap()
+            .exists());
+    }
+
+    #[test]
+    fn import_with_wrong_fingerprint_stores_nothing() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("store"));
+        let (path, _) = write_pubkey(&dir, "alice@example.com");
+        let other = EnsealIdentity::generate().fingerprint();
+
+        let err = import_into(&store, &path, None, false, Some(&other)).unwrap_err();
+        assert!(err.to_string().contains("fingerprint mismatch"));
+        assert!(!store
+            .trusted_key_path("alice@example.com")
+            .unwrap()
+            .exists());
+    }
+
+    fn json_key(identity: &str, id: &EnsealIdentity) -> serde_json::Value {
+        serde_json::json!({
+            "identity": identity,
+            "age": id.age_recipient.to_string(),
+            "sign": id.sign_pubkey_b64(),
+        })
+    }
+
+    #[test]
+    fn import_json_saves_every_entry() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        let (alice, bob) = (EnsealIdentity::generate(), EnsealIdentity::generate());
+        let mut bob_entry = json_key("bob", &bob);
+        bob_entry["expires"] = "2099-12-31".into();
+        let json = serde_json::json!([json_key("alice", &alice), bob_entry]).to_string();
+
+        import_json_into(&store, &json, true).unwrap();
+        let loaded = TrustedKey::load(&store, "alice").unwrap();
+        assert_eq!(loaded.fingerprint(), alice.fingerprint());
+        let loaded = TrustedKey::load(&store, "bob").unwrap();
+        assert_eq!(loaded.fingerprint(), bob.fingerprint());
+        let content = read_trusted(&store, "bob").unwrap();
+        assert!(bundle_expiry(&content).unwrap().is_some());
+    }
+
+    #[test]
+    fn import_json_reports_bad_entries_and_keeps_good_ones() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        let alice = EnsealIdentity::generate();
+        let mut bad = json_key("mallory", &EnsealIdentity::generate());
+        bad["age"] = "age1notakey".into();
+        let json = serde_json::json!([json_key("alice", &alice), bad]).to_string();
+
+        let err = import_json_into(&store, &json, true).unwrap_err();
+        assert!(err.to_string().contains("1 of 2 entries failed"), "{err}");
+        assert!(TrustedKey::load(&store, "alice").is_ok());
+        assert!(!store.trusted_key_path("mallory").unwrap().exists());
+    }
+
+    #[test]
+    fn fingerprint_prefix_is_optional() {
+        assert!(fingerprint_matches("SHA256:abc", "SHA256:abc"));
+        assert!(fingerprint_matches("SHA256:abc", " abc "));
+        assert!(!fingerprint_matches("SHA256:abc", "SHA256:abd"));
+    }
+
+    #[test]
+    fn import_github_keeps_ed25519_keys() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("store"));
+        let fixture = "ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAAAgQDlSLlKvX8OYaok\n\
+                       ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIJokjaEGZwk4eE1IRpXpEFJS5IX3urnbKLwMXQ0KqCtW\n";
+        let url = serve_once(fixture.to_string());
+        let base = url.trim_end_matches("/alice.pub");
+
+        import_github(&store, base, "alice", None, true).unwrap();
+
+        let content = read_trusted(&store, "alice").unwrap();
+        assert_eq!(
+            parse_ssh_keys(&content),
+            vec![
+                "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIJokjaEGZwk4eE1IRpXpEFJS5IX3urnbKLwMXQ0KqCtW"
+            ]
+        );
+        let recipients = crate::keys::identity::load_recipients(&store, "alice").unwrap();
+        let refs: Vec<&crate::crypto::at_rest::SharedRecipient> =
+            recipients.iter().map(|r| r.as_ref()).collect();
+        assert!(crate::crypto::at_rest::encrypt_whole_file(b"SECRET=1", &refs).is_ok());
+    }
+
+    #[test]
+    fn github_usernames_validated() {
+        assert!(validate_github_user("alice-dev").is_ok());
+        assert!(validate_github_user("../etc").is_err());
+        assert!(validate_github_user("-alice").is_err());
+        assert!(validate_github_user("").is_err());
+    }
+
+    #[test]
+    fn json_listing_covers_trusted_aliases_and_groups() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("keys"));
+        let me = EnsealIdentity::generate();
+        me.save(&store).unwrap();
+        let (path, fingerprint) = write_pubkey(&dir, "alice");
+        import_into(&store, &path, None, true, None).unwrap();
+        std::fs::write(store.trusted_key_path("broken").unwrap(), "garbage\n").unwrap();
+        alias::set(&store, "al", "alice").unwrap();
+        group::create(&store, "backend").unwrap();
+        group::add_member(&store, "backend", "alice").unwrap();
+
+        let listing = gather_listing(&store).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&listing).unwrap()).unwrap();
+
+        assert_eq!(json["self"]["fingerprint"], me.fingerprint());
+        let trusted = json["trusted"].as_array().unwrap();
+        let alice = trusted.iter().find(|k| k["identity"] == "alice").unwrap();
+        assert_eq!(alice["fingerprint"], fingerprint);
+        assert_eq!(alice["expired"], false);
+        let broken = trusted.iter().find(|k| k["identity"] == "broken").unwrap();
+        assert!(broken["error"].is_string());
+        assert!(broken.get("fingerprint").is_none());
+        assert_eq!(json["aliases"]["al"], "alice");
+        assert_eq!(json["groups"]["backend"], serde_json::json!(["alice"]));
+    }
+
+    #[test]
+    fn prune_removes_expired_keys_with_aliases_and_groups() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("keys"));
+        for (name, expires) in [("alice", "2026-01-31"), ("bob", "2026-02-01")] {
+            let (path, _) = write_pubkey(&dir, name);
+            import_into(&store, &path, None, true, None).unwrap();
+            let trusted = store.trusted_key_path(name).unwrap();
+            let mut content = std::fs::read_to_string(&trusted).unwrap();
+            content.push_str(&format!("expires: {}\n", expires));
+            std::fs::write(&trusted, content).unwrap();
+        }
+        alias::set(&store, "al", "alice").unwrap();
+        alias::set(&store, "b", "bob").unwrap();
+        group::create(&store, "backend").unwrap();
+        group::add_member(&store, "backend", "alice").unwrap();
+        group::add_member(&store, "backend", "bob").unwrap();
+        let today = time::macros::date!(2026 - 02 - 01);
+
+        let listed = prune_expired(&store, today, true).unwrap();
+        assert_eq!(
+            listed,
+            [("alice".to_string(), time::macros::date!(2026 - 01 - 31))]
+        );
+        assert!(store.trusted_key_path("alice").unwrap().exists());
+
+        let pruned = prune_expired(&store, today, false).unwrap();
+        assert_eq!(pruned, listed);
+        assert_eq!(store.list_trusted().unwrap(), ["bob"]);
+        let aliases = alias::list(&store).unwrap();
+        assert!(aliases.iter().all(|(name, _)| name != "al"));
+        assert!(aliases
+            .iter()
+            .any(|(name, target)| name == "b" && target == "bob"));
+        let groups = group::list_groups(&store).unwrap();
+        assert_eq!(groups[0].0, "backend");
+        assert_eq!(groups[0].1.members, ["bob"]);
+
+        assert!(prune_expired(&store, today, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn group_member_fingerprints_mark_unresolved_members() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("keys"));
+        let (path, fingerprint) = write_pubkey(&dir, "alice");
+        import_into(&store, &path, None, true, None).unwrap();
+        alias::set(&store, "al", "alice").unwrap();
+        group::create(&store, "ops").unwrap();
+        let members = ["alice", "al", "ghost", "ops"].map(String::from);
+
+        let keys = member_keys(&store, &members, FingerprintFormat::Base64).unwrap();
+        let alice = MemberKey::Trusted {
+            identity: "alice".to_string(),
+            fingerprint,
+        };
+        assert_eq!(keys[0], ("alice".to_string(), alice));
+        assert!(matches!(&keys[1].1, MemberKey::Trusted { identity, .. } if identity == "alice"));
+        assert_eq!(keys[2], ("ghost".to_string(), MemberKey::Unresolved));
+        assert_eq!(keys[3], ("ops".to_string(), MemberKey::Group));
+    }
+
+    #[test]
+    fn all_fingerprints_lists_self_and_trusted_keys() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("keys"));
+        let me = EnsealIdentity::generate();
+        me.save(&store).unwrap();
+        let (alice_path, alice) = write_pubkey(&dir, "alice");
+        let (bob_path, bob) = write_pubkey(&dir, "bob");
+        import_into(&store, &bob_path, None, true, None).unwrap();
+        import_into(&store, &alice_path, None, true, None).unwrap();
+
+        let rows = all_fingerprints(&store, FingerprintFormat::Base64).unwrap();
+        assert_eq!(
+            rows,
+            [
+                ("self".to_string(), me.fingerprint()),
+                ("alice".to_string(), alice),
+                ("bob".to_string(), bob),
+            ]
+        );
+
+        let hex = all_fingerprints(&store, FingerprintFormat::Hex).unwrap();
+        assert_eq!(hex[0].1, me.fingerprint_as(FingerprintFormat::Hex));
+    }
+
+    #[test]
+    fn export_file_matches_stdout() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("keys"));
+        EnsealIdentity::generate().save(&store).unwrap();
+        let bundle = export_from(&store).unwrap();
+
+        let path = dir.path().join("me.pub");
+        write_public_file(path.to_str().unwrap(), &bundle).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), bundle);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode, 0o644);
+        }
+
+        assert!(render_qr(&bundle).unwrap().lines().count() > 10);
+    }
+
+    #[test]
+    fn raw_sign_format_matches_bundle() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().join("keys"));
+        EnsealIdentity::generate().save(&store).unwrap();
+        let bundle = export_from(&store).unwrap();
+        let sign_line = bundle
+            .lines()
+            .find_map(|l| l.strip_prefix("sign: ed25519:"))
+            .unwrap();
+
+        let raw = export_sign_key(&store, SignFormat::Raw).unwrap();
+        assert_eq!(raw, format!("{}\n", sign_line));
+        let openssh = export_sign_key(&store, SignFormat::Openssh).unwrap();
+        assert!(openssh.starts_with("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAI"));
+    }
+
+    #[test]
+    fn oversized_qr_is_a_clear_error() {
+        let err = render_qr(&"x".repeat(8000)).unwrap_err();
+        assert!(
+            err.to_string().contains("too large for a QR code"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn rotate_self_archives_current_keys() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        let old = EnsealIdentity::generate();
+        old.save(&store).unwrap();
+
+        let (new, archive_path) = rotate_self(&store, "20260101T000000Z").unwrap();
+        assert_eq!(archive_path, store.archive_dir().join("20260101T000000Z"));
+        for name in [
+            "self.age.key",
+            "self.age.pub",
+            "self.sign.key",
+            "self.sign.pub",
+        ] {
+            assert!(archive_path.join(name).exists(), "{}", name);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(archive_path.join("self.age.key"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let current = EnsealIdentity::load(&store).unwrap();
+        assert_eq!(current.fingerprint(), new.fingerprint());
+        assert_ne!(current.fingerprint(), old.fingerprint());
+
+        // A second rotation in the same second doesn't clobber the first archive
+        let (_, second) = rotate_self(&store, "20260101T000000Z").unwrap();
+        assert_ne!(second, archive_path);
+        let archived = EnsealIdentity::load_archived(&store).unwrap();
+        assert_eq!(archived.len(), 2);
+        assert_eq!(archived[0].fingerprint(), new.fingerprint());
+        assert_eq!(archived[1].fingerprint(), old.fingerprint());
+    }
+
+    #[test]
+    fn archived_key_still_decrypts_old_ciphertext() {
+        use crate::crypto::at_rest;
+
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        let old = EnsealIdentity::generate();
+        old.save(&store).unwrap();
+        let ciphertext =
+            at_rest::encrypt_whole_file(b"SECRET=old\n", &[&old.age_recipient]).unwrap();
+
+        rotate_self(&store, "20260101T000000Z").unwrap();
+        let current = EnsealIdentity::load(&store).unwrap();
+        assert!(at_rest::decrypt_whole_file(&ciphertext, &current.age_identity).is_err());
+
+        let archived = EnsealIdentity::load_archived(&store).unwrap();
+        let plaintext =
+            at_rest::decrypt_whole_file(&ciphertext, &archived[0].age_identity).unwrap();
+        assert_eq!(plaintext, b"SECRET=old\n");
+    }
+}
@@ -1,7 +1,10 @@
 pub mod check;
+pub mod convert;
 pub mod decrypt;
 pub mod diff;
+pub mod doctor;
 pub mod encrypt;
+pub mod exit;
 pub mod inject;
 pub mod input;
 pub mod keys;
@@ -10,8 +13,12 @@ pub mod redact;
 #[cfg(feature = "server")]
 pub mod serve;
 pub mod share;
+pub mod sign;
 pub mod template;
 pub mod validate;
+pub mod verify;
+pub mod verify_manifest;
+pub mod version;
 
 use clap::{Parser, Subcommand};
 
@@ -32,8 +39,20 @@ pub struct Cli {
     /// Minimal output (for scripting)
     #[arg(long, short, global = true)]
     pub quiet: bool,
+
+    /// Local identity to use for your own keys (default: "default")
+    #[arg(
+        long = "identity",
+        id = "local_identity",
+        global = true,
+        env = "ENSEAL_IDENTITY",
+        value_name = "NAME"
+    )]
+    pub identity: Option<String>,
 }
 
+// Parsed once at startup; boxing the big args structs buys nothing
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 pub enum Command {
     /// Send a .env file, piped input, or inline secret
@@ -51,6 +70,9 @@ pub enum Command {
     /// Show missing/extra vars between two .env files (keys only)
     Diff(diff::DiffArgs),
 
+    /// Convert between .env, JSON, and YAML (values kept as strings)
+    Convert(convert::ConvertArgs),
+
     /// Output .env with values replaced by <REDACTED>
     Redact(redact::RedactArgs),
 
@@ -66,6 +88,15 @@ pub enum Command {
     /// Decrypt an at-rest encrypted .env file
     Decrypt(decrypt::DecryptArgs),
 
+    /// Sign a file with your key without encrypting it
+    Sign(sign::SignArgs),
+
+    /// Verify a file signed with `enseal sign`
+    Verify(verify::VerifyArgs),
+
+    /// Check a received .env against a `share --manifest` record (keys and hash)
+    VerifyManifest(verify_manifest::VerifyManifestArgs),
+
     /// Manage identity keys, aliases, and trusted keys
     Keys(keys::KeysArgs),
 
@@ -73,6 +104,12 @@ pub enum Command {
     #[cfg(feature = "server")]
     Serve(serve::ServeArgs),
 
+    /// Check the local setup (keys, permissions, relay, clock) for problems
+    Doctor(doctor::DoctorArgs),
+
+    /// Print build details (target, features, crate versions) for bug reports
+    Version,
+
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
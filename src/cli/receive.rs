@@ -5,16 +5,18 @@ use crate::cli::input::PayloadFormat;
 use crate::crypto::envelope::Envelope;
 use crate::crypto::signing::SignedEnvelope;
 use crate::env;
+use crate::fsutil::write_secret_file;
 use crate::keys;
 use crate::transfer;
 use crate::ui::display;
 
 #[derive(Args)]
 pub struct ReceiveArgs {
-    /// Wormhole share code or path to .env.age file
+    /// Wormhole share code or path to .env.age file (`-` reads it from stdin)
     pub code: String,
 
-    /// Write to specific file (overrides format-based default)
+    /// Write to this file (overrides format-based default); `-` writes the
+    /// payload verbatim to stdout, like --raw, whatever its format
     #[arg(long)]
     pub output: Option<String>,
 
@@ -22,65 +24,234 @@ pub struct ReceiveArgs {
     #[arg(long)]
     pub clipboard: bool,
 
+    /// With --clipboard: wait this many seconds, then clear the clipboard
+    /// if it still holds the secret (0 = never clear)
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 45,
+        requires = "clipboard"
+    )]
+    pub clear_after: u64,
+
     /// Print to stdout even for .env payloads (don't write file)
     #[arg(long)]
     pub no_write: bool,
 
+    /// Dump the payload byte-for-byte to stdout with no other output
+    /// (for piping, e.g. `docker run --env-file -`)
+    #[arg(long, conflicts_with_all = ["clipboard", "output", "no_write", "split"])]
+    pub raw: bool,
+
+    /// Write a multi-profile payload to one .env.<profile> file per profile
+    /// (in --output directory, default: current directory)
+    #[arg(long, conflicts_with_all = ["clipboard", "no_write"])]
+    pub split: bool,
+
+    /// Fill missing .env keys from `default` values in the .enseal.toml schema
+    #[arg(long, conflicts_with_all = ["raw", "split"])]
+    pub apply_defaults: bool,
+
+    /// Rewrite the keys of a .env or key=value payload before writing:
+    /// uppercase-keys, lowercase-keys, prefix:X, or strip-prefix:X
+    #[arg(long, value_name = "TRANSFORM", value_parser = env::transform::parse, conflicts_with = "raw")]
+    pub transform: Option<env::transform::KeyTransform>,
+
     /// Use specific relay server
     #[arg(long, env = "ENSEAL_RELAY")]
     pub relay: Option<String>,
 
+    /// The code came from `share --relay-code`: receive it from the enseal
+    /// relay (--relay, else `relay_url` in the config) instead of a wormhole
+    #[arg(long)]
+    pub relay_code: bool,
+
+    /// Retry failed connections to the relay/rendezvous server this many times
+    #[arg(long, default_value_t = crate::transfer::retry::DEFAULT_RETRIES, value_name = "N")]
+    pub retries: u32,
+
+    /// Give up if the transfer hasn't completed within this many seconds
+    #[arg(long, default_value_t = crate::transfer::DEFAULT_TIMEOUT.as_secs(), value_name = "SECS")]
+    pub timeout: u64,
+
     /// Overwrite existing files without prompting
     #[arg(long)]
     pub force: bool,
 
+    #[command(flatten)]
+    pub freshness: FreshnessArgs,
+
     /// Minimal output
     #[arg(long, short)]
     pub quiet: bool,
 }
 
-pub async fn run(args: ReceiveArgs) -> Result<()> {
+/// Replay-protection overrides shared by `receive` and `inject`.
+#[derive(Args)]
+pub struct FreshnessArgs {
+    /// Reject envelopes older than this many seconds
+    /// (default: 300 for network transfers, 86400 for file drops)
+    #[arg(long, value_name = "SECONDS")]
+    pub max_age: Option<u64>,
+
+    /// Accept envelopes of any age (only copies already received are
+    /// still refused)
+    #[arg(long, conflicts_with = "max_age")]
+    pub ignore_age: bool,
+}
+
+impl FreshnessArgs {
+    /// Maximum envelope age to enforce, or `None` when the check is disabled.
+    pub fn limit(&self, default: u64) -> Option<u64> {
+        if self.ignore_age {
+            None
+        } else {
+            Some(self.max_age.unwrap_or(default))
+        }
+    }
+
+    /// Warn loudly when replay protection is turned off (unless `quiet`).
+    pub fn warn_if_ignored(&self, quiet: bool) {
+        if self.ignore_age && !quiet {
+            display::warning(
+                "--ignore-age: envelope age is NOT checked. A captured payload could be replayed",
+            );
+        }
+    }
+}
+
+impl ReceiveArgs {
+    /// `--raw` or `--output -`: the payload bytes go to stdout, nothing else.
+    fn payload_to_stdout(&self) -> bool {
+        self.raw || self.output.as_deref() == Some("-")
+    }
+}
+
+pub async fn run(mut args: ReceiveArgs, store: &keys::store::KeyStore) -> Result<()> {
+    if args.output.as_deref() == Some("-")
+        && (args.clipboard || args.split || args.apply_defaults || args.transform.is_some())
+    {
+        return Err(crate::cli::exit::usage("--output - writes the payload verbatim to stdout; it can't be combined with --clipboard, --split, --apply-defaults or --transform"));
+    }
+    // --raw keeps stderr free of decoration, same as --quiet
+    args.quiet |= args.payload_to_stdout();
+    args.freshness.warn_if_ignored(args.quiet);
+
+    if args.code == "-" {
+        args.code = code_from_stdin()?;
+    }
+    if args.relay_code {
+        args.relay = crate::config::user::relay_url(args.relay.take())?;
+        if args.relay.is_none() {
+            return Err(crate::cli::exit::usage(
+                "--relay-code needs --relay or relay_url in the config",
+            ));
+        }
+    }
+
     // Detect mode: file drop (.env.age file) vs wormhole code
     let is_file = std::path::Path::new(&args.code).exists() && args.code.ends_with(".age");
 
+    let trusted = keys::TrustedIndex::load(store);
     let envelope = if is_file {
-        receive_filedrop(&args)?
+        receive_filedrop(&args, store, &trusted)?
     } else {
-        receive_wormhole(&args).await?
+        let envelope = receive_wormhole(&args, store, &trusted).await?;
+        check_replay(store, &envelope, &args.freshness)?;
+        envelope
     };
 
     output_envelope(&args, &envelope)
 }
 
-async fn receive_wormhole(args: &ReceiveArgs) -> Result<Envelope> {
+/// Longest line accepted as a share code from stdin.
+const MAX_CODE_LINE: usize = 4096;
+
+/// Read the share code for `receive -` / `inject -`: the first non-empty
+/// line of stdin, trimmed. On Unix stdin is read unbuffered, one byte at a
+/// time, so nothing past that line is consumed and `inject` can hand the
+/// rest of stdin to the child.
+pub fn code_from_stdin() -> Result<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::FromRawFd;
+        // SAFETY: fd 0 is open for the life of the process; ManuallyDrop
+        // keeps this File from closing it.
+        let stdin = std::mem::ManuallyDrop::new(unsafe { std::fs::File::from_raw_fd(0) });
+        read_code_line(&*stdin)
+    }
+    #[cfg(not(unix))]
+    {
+        read_code_line(std::io::stdin())
+    }
+}
+
+fn read_code_line(mut reader: impl std::io::Read) -> Result<String> {
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        let eof = reader
+            .read(&mut byte)
+            .context("failed to read share code from stdin")?
+            == 0;
+        if eof || byte[0] == b'\n' {
+            let code = String::from_utf8_lossy(&line).trim().to_string();
+            if !code.is_empty() {
+                return Ok(code);
+            }
+            if eof {
+                bail!("no share code on stdin");
+            }
+            line.clear();
+        } else {
+            line.push(byte[0]);
+            if line.len() > MAX_CODE_LINE {
+                bail!("share code on stdin is too long");
+            }
+        }
+    }
+}
+
+async fn receive_wormhole(
+    args: &ReceiveArgs,
+    store: &keys::store::KeyStore,
+    trusted: &keys::TrustedIndex,
+) -> Result<Envelope> {
     // Receive raw bytes once, then determine if it's identity or anonymous mode
     // by trying to parse as SignedEnvelope first.
-    let data = transfer::wormhole::receive_raw(&args.code, args.relay.as_deref()).await?;
-
-    let store = keys::store::KeyStore::open()?;
+    let timeout = std::time::Duration::from_secs(args.timeout);
+    let data = match (args.relay_code, &args.relay) {
+        (true, Some(relay_url)) => {
+            transfer::relay::receive(relay_url, &args.code, args.retries, timeout).await?
+        }
+        _ => {
+            transfer::wormhole::receive_raw(
+                &args.code,
+                args.relay.as_deref(),
+                args.retries,
+                timeout,
+            )
+            .await?
+        }
+    };
 
     // Try identity mode: parse as SignedEnvelope, verify, and decrypt
     if store.is_initialized() {
         if let Ok(signed) = SignedEnvelope::from_bytes(&data) {
-            let own_identity = keys::identity::EnsealIdentity::load(&store)?;
-            let sender_sign_pubkey = signed.sender_sign_pubkey.clone();
+            let own_identity = keys::identity::EnsealIdentity::load(store)?;
 
             // Look up sender in trusted keys to verify identity
-            let trusted_sender = keys::find_trusted_sender(&store, &signed);
+            let trusted_sender = trusted.find_sender(&signed);
 
-            let inner_bytes = signed.open(&own_identity, trusted_sender.as_ref())?;
-            let envelope = Envelope::from_bytes(&inner_bytes)?;
-            envelope.check_age(300)?;
+            let (envelope, _) = crate::crypto::signing::open_signed(
+                &data,
+                &own_identity,
+                trusted_sender,
+                args.freshness.limit(Envelope::NETWORK_MAX_AGE),
+            )?;
 
+            report_sender(store, &signed, trusted_sender, args.quiet)?;
             if !args.quiet {
-                if let Some(ref trusted) = trusted_sender {
-                    display::info("From:", &trusted.identity);
-                } else {
-                    display::warning(&format!(
-                        "received from unknown sender (signing key: {}...)",
-                        &sender_sign_pubkey[..20.min(sender_sign_pubkey.len())]
-                    ));
-                }
                 display::ok("signature verified");
             }
             return Ok(envelope);
@@ -92,13 +263,89 @@ async fn receive_wormhole(args: &ReceiveArgs) -> Result<Envelope> {
         display::warning("received unsigned (anonymous) payload -- sender identity not verified");
     }
     let envelope = Envelope::from_bytes(&data)?;
-    envelope.check_age(300)?;
+    check_freshness(&envelope, &args.freshness)?;
     Ok(envelope)
 }
 
-fn receive_filedrop(args: &ReceiveArgs) -> Result<Envelope> {
-    let store = keys::store::KeyStore::open()?;
-    let own_identity = keys::identity::EnsealIdentity::load(&store)?;
+/// Show who sent a verified payload. An unknown sender is remembered for
+/// `enseal keys trust <name> --tofu`, and in an interactive session the
+/// user is offered to trust them on the spot.
+pub fn report_sender(
+    store: &keys::store::KeyStore,
+    signed: &SignedEnvelope,
+    trusted_sender: Option<&keys::identity::TrustedKey>,
+    quiet: bool,
+) -> Result<()> {
+    if let Some(trusted) = trusted_sender {
+        if !quiet {
+            display::info("From:", &trusted.identity);
+        }
+        return Ok(());
+    }
+
+    let sign_pubkey = &signed.sender_sign_pubkey;
+    let recorded = keys::tofu::record_unknown_sender(store, &signed.sender_age_pubkey, sign_pubkey);
+    if quiet {
+        return Ok(());
+    }
+    if let Err(e) = recorded {
+        display::warning(&format!("could not record unknown sender: {:#}", e));
+    }
+    display::warning(&format!(
+        "received from unknown sender (signing key: {}...)",
+        &sign_pubkey[..20.min(sign_pubkey.len())]
+    ));
+
+    if !is_terminal::is_terminal(std::io::stdin()) || !is_terminal::is_terminal(std::io::stderr()) {
+        display::info("Trust them with:", "enseal keys trust <name> --tofu");
+        return Ok(());
+    }
+    let name: String = dialoguer::Input::new()
+        .with_prompt("Save this sender as (blank to skip)")
+        .allow_empty(true)
+        .interact_text()?;
+    let name = name.trim();
+    if !name.is_empty() {
+        let key = keys::tofu::trust(store, name, &signed.sender_age_pubkey, sign_pubkey)?;
+        display::ok(&format!("trusted '{}' ({})", name, key.fingerprint()));
+    }
+    Ok(())
+}
+
+/// Apply the network freshness window, honoring `--max-age`/`--ignore-age`.
+pub fn check_freshness(envelope: &Envelope, freshness: &FreshnessArgs) -> Result<()> {
+    match freshness.limit(Envelope::NETWORK_MAX_AGE) {
+        Some(max_age) => Ok(envelope.check_age(max_age)?),
+        None => Ok(()),
+    }
+}
+
+/// Reject an envelope that was already received over the network, so a
+/// captured transfer can't be replayed within its freshness window. File
+/// drops are exempt: reading the same file twice is normal.
+pub fn check_replay(
+    store: &keys::store::KeyStore,
+    envelope: &Envelope,
+    freshness: &FreshnessArgs,
+) -> Result<()> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    transfer::replay::check(
+        &store.seen_nonces_path(),
+        envelope,
+        freshness.limit(Envelope::NETWORK_MAX_AGE),
+        now,
+    )
+}
+
+fn receive_filedrop(
+    args: &ReceiveArgs,
+    store: &keys::store::KeyStore,
+    trusted: &keys::TrustedIndex,
+) -> Result<Envelope> {
+    let own_identity = keys::identity::EnsealIdentity::load(store)?;
 
     let path = std::path::Path::new(&args.code);
 
@@ -116,20 +363,17 @@ fn receive_filedrop(args: &ReceiveArgs) -> Result<Envelope> {
     let data =
         std::fs::read(path).with_context(|| format!("failed to read file: {}", path.display()))?;
     let signed = SignedEnvelope::from_bytes(&data)?;
-    let trusted_sender = keys::find_trusted_sender(&store, &signed);
+    let trusted_sender = trusted.find_sender(&signed);
 
-    let (envelope, sender_pubkey) =
-        transfer::filedrop::read_from_bytes(&data, &own_identity, trusted_sender.as_ref())?;
+    let (envelope, _) = crate::crypto::signing::open_signed(
+        &data,
+        &own_identity,
+        trusted_sender,
+        args.freshness.limit(Envelope::FILEDROP_MAX_AGE),
+    )?;
 
+    report_sender(store, &signed, trusted_sender, args.quiet)?;
     if !args.quiet {
-        if let Some(ref trusted) = trusted_sender {
-            display::info("From:", &trusted.identity);
-        } else {
-            display::warning(&format!(
-                "received from unknown sender (signing key: {}...)",
-                &sender_pubkey[..20.min(sender_pubkey.len())]
-            ));
-        }
         display::ok("signature verified, file decrypted");
     }
 
@@ -137,16 +381,74 @@ fn receive_filedrop(args: &ReceiveArgs) -> Result<Envelope> {
 }
 
 fn output_envelope(args: &ReceiveArgs, envelope: &Envelope) -> Result<()> {
-    let payload = &envelope.payload;
+    if envelope.is_binary() {
+        return output_binary(args, envelope);
+    }
+
+    if args.payload_to_stdout() {
+        return write_stdout(envelope.payload.as_bytes());
+    }
+
+    let mut var_count = envelope.metadata.var_count;
+    let filled;
+    let payload = if args.apply_defaults && envelope.format == PayloadFormat::Env {
+        let env_file = env::parser::parse(&envelope.payload)?;
+        let with_defaults = env::schema::apply_defaults(&env_file, &schema_for_defaults()?)?;
+        let added = with_defaults.var_count() - env_file.var_count();
+        if added > 0 && !args.quiet {
+            display::info("Defaults:", &format!("{} filled from schema", added));
+        }
+        var_count = Some(with_defaults.var_count());
+        filled = with_defaults.to_string();
+        &filled
+    } else {
+        &envelope.payload
+    };
+    let received = payload;
+
+    let transformed;
+    let payload = match &args.transform {
+        Some(transform) if matches!(envelope.format, PayloadFormat::Env | PayloadFormat::Kv) => {
+            let (env_file, collisions) =
+                env::transform::transform(&env::parser::parse(payload)?, transform);
+            for collision in &collisions {
+                display::warning(&format!(
+                    "--transform maps {} to the same key {}; the last one wins",
+                    collision.sources.join(", "),
+                    collision.name
+                ));
+            }
+            transformed = env_file.to_string();
+            &transformed
+        }
+        Some(_) => {
+            display::warning("--transform only applies to .env and key=value payloads; ignored");
+            payload
+        }
+        None => payload,
+    };
 
     // Show metadata
     if !args.quiet {
-        if let Some(count) = envelope.metadata.var_count {
+        if let Some(count) = var_count {
             display::info("Secrets:", &format!("{} variables", count));
         }
         if let Some(ref label) = envelope.metadata.label {
             display::info("Label:", label);
         }
+        if let Some(ref project) = envelope.metadata.project {
+            display::info("Project:", project);
+        }
+        display::info("Created:", &envelope.created_display());
+    }
+
+    if args.split {
+        let dir = std::path::Path::new(args.output.as_deref().unwrap_or("."));
+        let written = write_split(payload, dir, args.force)?;
+        for (path, count) in &written {
+            display::ok(&format!("{} secrets written to {}", count, path));
+        }
+        return Ok(());
     }
 
     // Handle clipboard
@@ -159,12 +461,17 @@ fn output_envelope(args: &ReceiveArgs, envelope: &Envelope) -> Result<()> {
         } else {
             display::ok("copied to clipboard");
         }
+        if args.clear_after > 0 {
+            clear_clipboard_later(&mut clipboard, payload, args.clear_after, args.quiet);
+        }
         return Ok(());
     }
 
     // Schema validation on receive (non-blocking warnings)
     if matches!(envelope.format, PayloadFormat::Env) {
-        validate_against_schema(payload, args.quiet);
+        warn_reserved_names(payload);
+        // The schema describes the keys as sent, before any --transform
+        validate_against_schema(received, args.quiet);
     }
 
     // Route output based on format
@@ -175,15 +482,15 @@ fn output_envelope(args: &ReceiveArgs, envelope: &Envelope) -> Result<()> {
             } else {
                 let path = args.output.as_deref().unwrap_or(".env");
                 check_overwrite(path, args.force)?;
-                write_secret_file(path, payload)?;
-                let count = envelope.metadata.var_count.unwrap_or(0);
+                write_secret_file(path, payload.as_bytes())?;
+                let count = var_count.unwrap_or(0);
                 display::ok(&format!("{} secrets written to {}", count, path));
             }
         }
         PayloadFormat::Raw => {
             if let Some(ref path) = args.output {
                 check_overwrite(path, args.force)?;
-                write_secret_file(path, payload)?;
+                write_secret_file(path, payload.as_bytes())?;
                 display::ok(&format!("written to {}", path));
             } else {
                 print!("{}", payload);
@@ -192,40 +499,118 @@ fn output_envelope(args: &ReceiveArgs, envelope: &Envelope) -> Result<()> {
         PayloadFormat::Kv => {
             if let Some(ref path) = args.output {
                 check_overwrite(path, args.force)?;
-                write_secret_file(path, payload)?;
+                write_secret_file(path, payload.as_bytes())?;
                 display::ok(&format!("written to {}", path));
             } else {
                 println!("{}", payload);
             }
         }
+        PayloadFormat::Binary => unreachable!("binary payloads go through output_binary"),
     }
 
     Ok(())
 }
 
-/// Write a file containing secrets with restrictive permissions (0600 on Unix).
-/// Uses atomic mode setting to avoid a TOCTOU window where the file is world-readable.
-fn write_secret_file(path: &str, content: &str) -> Result<()> {
-    #[cfg(unix)]
-    {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-        use std::os::unix::fs::OpenOptionsExt;
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .mode(0o600)
-            .open(path)?;
-        file.write_all(content.as_bytes())?;
+/// Write a binary payload's decoded bytes to `--output`.
+fn output_binary(args: &ReceiveArgs, envelope: &Envelope) -> Result<()> {
+    if args.clipboard || args.split {
+        bail!("received a binary file; use --output <path> to write it");
     }
-    #[cfg(not(unix))]
-    {
-        std::fs::write(path, content)?;
+    let bytes = envelope.payload_bytes()?;
+
+    if !args.quiet && !args.raw {
+        display::info("Size:", &format!("{} bytes (binary)", bytes.len()));
+        if let Some(ref label) = envelope.metadata.label {
+            display::info("Label:", label);
+        }
+        display::info("Created:", &envelope.created_display());
+    }
+
+    if args.payload_to_stdout() {
+        return write_stdout(&bytes);
     }
+    let Some(ref path) = args.output else {
+        bail!("received a binary file; use --output <path> to write it (or --output - for stdout)");
+    };
+    check_overwrite(path, args.force)?;
+    write_secret_file(path, &bytes)?;
+    display::ok(&format!("written to {}", path));
+    Ok(())
+}
+
+fn write_stdout(bytes: &[u8]) -> Result<()> {
+    use std::io::Write;
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(bytes)?;
+    stdout.flush()?;
     Ok(())
 }
 
+/// Block for `secs`, then clear the clipboard if it still holds `secret`.
+/// Staying alive also keeps the selection owned on X11/Wayland, where the
+/// clipboard contents disappear when the owning process exits.
+fn clear_clipboard_later(clipboard: &mut arboard::Clipboard, secret: &str, secs: u64, quiet: bool) {
+    if !quiet {
+        display::info(
+            "Clears in:",
+            &format!("{}s (keep this running; Ctrl-C leaves it in place)", secs),
+        );
+    }
+    std::thread::sleep(std::time::Duration::from_secs(secs));
+
+    let current = clipboard.get_text().ok();
+    if !should_clear(current.as_deref(), secret) {
+        if !quiet {
+            display::info("Clipboard:", "changed since copy, left untouched");
+        }
+        return;
+    }
+    match clipboard.set_text(String::new()) {
+        Ok(()) => {
+            if !quiet {
+                display::ok("clipboard cleared");
+            }
+        }
+        Err(e) => display::warning(&format!("failed to clear clipboard: {}", e)),
+    }
+}
+
+/// Only clear the clipboard if it still holds our secret, so a value the
+/// user copied afterwards is not stomped.
+fn should_clear(current: Option<&str>, secret: &str) -> bool {
+    current == Some(secret)
+}
+
+/// Write each profile of a multi-profile payload to `<dir>/.env.<profile>`.
+/// Returns the written paths with their variable counts.
+fn write_split(payload: &str, dir: &std::path::Path, force: bool) -> Result<Vec<(String, usize)>> {
+    let profiles = env::profile::unpack_bundle(payload)?.ok_or_else(|| {
+        anyhow::anyhow!("--split requires a multi-profile payload ({{\"profiles\": {{...}}}})")
+    })?;
+
+    // Validate everything before writing anything
+    let mut files = Vec::new();
+    for (name, content) in &profiles {
+        let count = env::parser::parse(content)
+            .with_context(|| format!("profile '{}' is not a valid .env file", name))?
+            .var_count();
+        let path = dir.join(format!(".env.{}", name));
+        files.push((path.to_string_lossy().into_owned(), content, count));
+    }
+
+    // Confirm every overwrite up front, so declining one leaves no profile written
+    for (path, _, _) in &files {
+        check_overwrite(path, force)?;
+    }
+
+    let mut written = Vec::new();
+    for (path, content, count) in files {
+        write_secret_file(&path, content.as_bytes())?;
+        written.push((path, count));
+    }
+    Ok(written)
+}
+
 /// Check if the target file exists and handle overwrite confirmation.
 fn check_overwrite(path: &str, force: bool) -> Result<()> {
     if !std::path::Path::new(path).exists() {
@@ -250,6 +635,29 @@ fn check_overwrite(path: &str, force: bool) -> Result<()> {
     Ok(())
 }
 
+/// Loud, non-blocking warning for keys like LD_PRELOAD in a received .env,
+/// since anything that later sources the file will pick them up.
+fn warn_reserved_names(payload: &str) {
+    let Ok(env_file) = env::parser::parse(payload) else {
+        return;
+    };
+    for key in env_file.keys() {
+        if crate::cli::inject::is_reserved_name(key) {
+            display::warning(&format!(
+                "payload sets {}, which changes how programs are loaded or run. \
+                 Check it before sourcing this file",
+                key
+            ));
+        }
+    }
+}
+
+/// Load the project schema for `--apply-defaults`, which needs one to mean anything.
+pub fn schema_for_defaults() -> Result<env::schema::Schema> {
+    env::schema::load_schema(None)?
+        .ok_or_else(|| anyhow::anyhow!("--apply-defaults needs a [schema] section in .enseal.toml"))
+}
+
 /// Run schema validation against received .env payload.
 /// Emits warnings but never blocks the receive.
 fn validate_against_schema(payload: &str, quiet: bool) {
@@ -267,11 +675,88 @@ fn validate_against_schema(payload: &str, quiet: bool) {
         Err(_) => return,
     };
 
-    let errors = env::schema::validate(&env_file, &schema);
+    let (errors, advisories): (Vec<_>, Vec<_>) = env::schema::validate(&env_file, &schema)
+        .into_iter()
+        .partition(|e| e.severity == env::validator::Severity::Error);
     if !errors.is_empty() {
         display::warning("received .env has schema validation issues:");
         for err in &errors {
             display::warning(&format!("  {}", err));
         }
     }
+    if !advisories.is_empty() {
+        display::warning("received .env has advisory schema warnings:");
+        for warn in &advisories {
+            display::warning(&format!("  {}", warn));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clears_only_unchanged_clipboard() {
+        assert!(should_clear(Some("hunter2"), "hunter2"));
+        assert!(!should_clear(Some("something else"), "hunter2"));
+        assert!(!should_clear(Some(""), "hunter2"));
+        assert!(!should_clear(None, "hunter2"));
+    }
+
+    #[test]
+    fn code_line_is_first_non_empty_line() {
+        let mut input: &[u8] = b"\n   \n  7-guitarist-revenge \nleft for the child\n";
+        assert_eq!(read_code_line(&mut input).unwrap(), "7-guitarist-revenge");
+        assert_eq!(input, b"left for the child\n");
+
+        assert_eq!(read_code_line(&b"7-a-b"[..]).unwrap(), "7-a-b");
+        assert!(read_code_line(&b"\n \n"[..]).is_err());
+    }
+
+    #[test]
+    fn split_writes_each_profile() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let payload = serde_json::json!({
+            "profiles": {
+                "staging": "DB_URL=postgres://staging\n",
+                "production": "DB_URL=postgres://prod\nDEBUG=false\n",
+            }
+        })
+        .to_string();
+
+        let written = write_split(&payload, dir.path(), false).unwrap();
+        assert_eq!(written.len(), 2);
+
+        let staging = std::fs::read_to_string(dir.path().join(".env.staging")).unwrap();
+        assert_eq!(staging, "DB_URL=postgres://staging\n");
+        let production = std::fs::read_to_string(dir.path().join(".env.production")).unwrap();
+        assert_eq!(production, "DB_URL=postgres://prod\nDEBUG=false\n");
+    }
+
+    #[test]
+    fn split_refusing_one_overwrite_writes_nothing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join(".env.staging"), "KEEP=1\n").unwrap();
+        let payload = serde_json::json!({
+            "profiles": {
+                "production": "DB_URL=postgres://prod\n",
+                "staging": "DB_URL=postgres://staging\n",
+            }
+        })
+        .to_string();
+
+        // Without --force and without a terminal the existing file is refused
+        assert!(write_split(&payload, dir.path(), false).is_err());
+        assert!(!dir.path().join(".env.production").exists());
+        let staging = std::fs::read_to_string(dir.path().join(".env.staging")).unwrap();
+        assert_eq!(staging, "KEEP=1\n");
+    }
+
+    #[test]
+    fn split_rejects_single_profile_payload() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(write_split("KEY=value\n", dir.path(), false).is_err());
+        assert!(!dir.path().join(".env").exists());
+    }
 }
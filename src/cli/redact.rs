@@ -1,6 +1,7 @@
-use anyhow::{bail, Result};
+use anyhow::Result;
 use clap::Args;
 
+use crate::cli::exit;
 use crate::env::{self, redact as env_redact};
 use crate::ui::display;
 
@@ -13,16 +14,40 @@ pub struct RedactArgs {
     /// Write output to file instead of stdout
     #[arg(long)]
     pub output: Option<String>,
+
+    /// Regex of keys whose values are kept as-is
+    #[arg(long, value_name = "REGEX")]
+    pub keep: Option<String>,
+
+    /// Regex of keys to redact; everything else is kept
+    #[arg(long, value_name = "REGEX")]
+    pub redact: Option<String>,
+
+    /// Only redact keys that look like secrets (names containing SECRET,
+    /// KEY, TOKEN or PASSWORD)
+    #[arg(long)]
+    pub secrets_only: bool,
 }
 
 pub fn run(args: RedactArgs) -> Result<()> {
     if !std::path::Path::new(&args.file).exists() {
-        bail!("{} not found", args.file);
+        return Err(exit::not_found(format!("{} not found", args.file)));
     }
 
-    let content = std::fs::read_to_string(&args.file)?;
+    let content =
+        std::fs::read_to_string(&args.file).map_err(|e| exit::read_failed(&args.file, e))?;
     let env_file = env::parser::parse(&content)?;
-    let redacted = env_redact::redact(&env_file);
+    let filtered = args.keep.is_some() || args.redact.is_some() || args.secrets_only;
+    let redacted = if filtered {
+        env_redact::redact_matching(
+            &env_file,
+            args.keep.as_deref(),
+            args.redact.as_deref(),
+            args.secrets_only,
+        )?
+    } else {
+        env_redact::redact(&env_file)
+    };
     let output = redacted.to_string();
 
     if let Some(path) = &args.output {
@@ -12,29 +12,42 @@ use crate::ui::display;
 #[cfg(feature = "server")]
 #[derive(Args)]
 pub struct ServeArgs {
-    /// Listen port
-    #[arg(long, default_value = "4443")]
-    pub port: u16,
+    /// TOML config file with relay settings (flags given here take precedence)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<String>,
 
-    /// Bind address
-    #[arg(long, default_value = "0.0.0.0")]
-    pub bind: String,
+    /// Listen port [default: 4443]
+    #[arg(long)]
+    pub port: Option<u16>,
 
-    /// Max concurrent channels
-    #[arg(long, default_value = "100")]
-    pub max_mailboxes: usize,
+    /// Bind address [default: 0.0.0.0]
+    #[arg(long)]
+    pub bind: Option<String>,
 
-    /// How long idle channels survive in seconds
-    #[arg(long, default_value = "300")]
-    pub channel_ttl: u64,
+    /// Max concurrent channels [default: 100]
+    #[arg(long)]
+    pub max_mailboxes: Option<usize>,
 
-    /// Max WebSocket message size in bytes
-    #[arg(long, default_value = "1048576")]
-    pub max_payload: usize,
+    /// How long idle channels survive in seconds [default: 300]
+    #[arg(long)]
+    pub channel_ttl: Option<u64>,
 
-    /// Max new connections per minute per IP
-    #[arg(long, default_value = "10")]
-    pub rate_limit: usize,
+    /// Max WebSocket message size in bytes [default: 1048576]
+    #[arg(long)]
+    pub max_payload: Option<usize>,
+
+    /// Max new connections per minute per IP [default: 10]
+    #[arg(long)]
+    pub rate_limit: Option<usize>,
+
+    /// Hold a sender's payload until the receiver connects (peers need not overlap)
+    #[arg(long, overrides_with = "no_store_and_forward")]
+    pub store_and_forward: bool,
+
+    /// Require both peers to be connected at once, even if the config file
+    /// enables store-and-forward
+    #[arg(long, overrides_with = "store_and_forward")]
+    pub no_store_and_forward: bool,
 
     /// Print server health check and exit
     #[arg(long)]
@@ -43,29 +56,25 @@ pub struct ServeArgs {
 
 #[cfg(feature = "server")]
 pub async fn run(args: ServeArgs) -> Result<()> {
+    let config = resolve_config(&args)?;
+
     if args.health {
-        return check_health(&args).await;
+        return check_health(&config).await;
     }
 
-    let addr = format!("{}:{}", args.bind, args.port);
+    let addr = format!("{}:{}", config.bind, config.port);
 
-    let config = server::ServerConfig {
-        port: args.port,
-        bind: args.bind.clone(),
-        max_channels: args.max_mailboxes,
-        channel_ttl_secs: args.channel_ttl,
-        max_payload_bytes: args.max_payload,
-        rate_limit_per_min: args.rate_limit,
-    };
+    display::ok(&format!("enseal relay listening on {}", addr));
+    eprintln!("  max channels:  {}", config.max_channels);
+    eprintln!("  channel TTL:   {}s", config.channel_ttl_secs);
+    eprintln!("  max payload:   {} bytes", config.max_payload_bytes);
+    eprintln!("  rate limit:    {}/min per IP", config.rate_limit_per_min);
+    if config.store_and_forward {
+        eprintln!("  mode:          store-and-forward");
+    }
 
     let app = server::build_router(config);
 
-    display::ok(&format!("enseal relay listening on {}", addr));
-    eprintln!("  max channels:  {}", args.max_mailboxes);
-    eprintln!("  channel TTL:   {}s", args.channel_ttl);
-    eprintln!("  max payload:   {} bytes", args.max_payload);
-    eprintln!("  rate limit:    {}/min per IP", args.rate_limit);
-
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     axum::serve(
         listener,
@@ -76,24 +85,138 @@ pub async fn run(args: ServeArgs) -> Result<()> {
     Ok(())
 }
 
+/// Build the effective server config.
+/// Precedence: built-in defaults < `--config` file < explicit CLI flags.
+#[cfg(feature = "server")]
+fn resolve_config(args: &ServeArgs) -> Result<server::ServerConfig> {
+    let mut config = match args.config {
+        Some(ref path) => server::ServerConfig::from_file(std::path::Path::new(path))?,
+        None => server::ServerConfig::default(),
+    };
+
+    if let Some(port) = args.port {
+        config.port = port;
+    }
+    if let Some(ref bind) = args.bind {
+        config.bind = bind.clone();
+    }
+    if let Some(max) = args.max_mailboxes {
+        config.max_channels = max;
+    }
+    if let Some(ttl) = args.channel_ttl {
+        config.channel_ttl_secs = ttl;
+    }
+    if let Some(max) = args.max_payload {
+        config.max_payload_bytes = max;
+    }
+    if let Some(rate) = args.rate_limit {
+        config.rate_limit_per_min = rate;
+    }
+    if args.store_and_forward {
+        config.store_and_forward = true;
+    }
+    if args.no_store_and_forward {
+        config.store_and_forward = false;
+    }
+
+    Ok(config)
+}
+
 #[cfg(feature = "server")]
-async fn check_health(args: &ServeArgs) -> Result<()> {
-    let _url = format!("http://{}:{}/health", args.bind, args.port);
+async fn check_health(config: &server::ServerConfig) -> Result<()> {
+    let _url = format!("http://{}:{}/health", config.bind, config.port);
     // Use a simple TCP check since we don't want to add reqwest as a dep
-    match tokio::net::TcpStream::connect(format!("{}:{}", args.bind, args.port)).await {
+    match tokio::net::TcpStream::connect(format!("{}:{}", config.bind, config.port)).await {
         Ok(_) => {
             display::ok(&format!(
                 "relay is reachable at {}:{}",
-                args.bind, args.port
+                config.bind, config.port
             ));
             Ok(())
         }
         Err(e) => {
             display::error(&format!(
                 "cannot connect to {}:{}: {}",
-                args.bind, args.port, e
+                config.bind, config.port, e
             ));
             std::process::exit(1);
         }
     }
 }
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use tempfile::TempDir;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        args: ServeArgs,
+    }
+
+    fn parse(args: &[&str]) -> ServeArgs {
+        TestCli::parse_from(std::iter::once("serve").chain(args.iter().copied())).args
+    }
+
+    fn write_config(dir: &TempDir) -> String {
+        let path = dir.path().join("relay.toml");
+        std::fs::write(
+            &path,
+            "port = 9000\nrate_limit_per_min = 50\nstore_and_forward = true\n",
+        )
+        .unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn defaults_without_config() {
+        let config = resolve_config(&parse(&[])).unwrap();
+        assert_eq!(config.port, 4443);
+        assert_eq!(config.rate_limit_per_min, 10);
+        assert!(!config.store_and_forward);
+    }
+
+    #[test]
+    fn loads_config_file() {
+        let dir = TempDir::new().unwrap();
+        let path = write_config(&dir);
+
+        let config = resolve_config(&parse(&["--config", &path])).unwrap();
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.rate_limit_per_min, 50);
+        assert!(config.store_and_forward);
+        // Unset fields keep their defaults
+        assert_eq!(config.channel_ttl_secs, 300);
+    }
+
+    #[test]
+    fn cli_flag_overrides_config_file() {
+        let dir = TempDir::new().unwrap();
+        let path = write_config(&dir);
+
+        let config = resolve_config(&parse(&["--config", &path, "--rate-limit", "5"])).unwrap();
+        assert_eq!(config.rate_limit_per_min, 5);
+        assert_eq!(config.port, 9000);
+    }
+
+    #[test]
+    fn no_store_and_forward_overrides_config_file() {
+        let dir = TempDir::new().unwrap();
+        let path = write_config(&dir);
+
+        let config =
+            resolve_config(&parse(&["--config", &path, "--no-store-and-forward"])).unwrap();
+        assert!(!config.store_and_forward);
+    }
+
+    #[test]
+    fn unknown_config_key_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("relay.toml");
+        std::fs::write(&path, "rate_limit = 5\n").unwrap();
+
+        assert!(resolve_config(&parse(&["--config", path.to_str().unwrap()])).is_err());
+    }
+}
@@ -1,15 +1,16 @@
-use anyhow::Result;
-use clap::Args;
+use anyhow::{Context, Result};
+use clap::{ArgGroup, Args};
 
-use crate::cli::input;
+use crate::cli::{exit, input};
 use crate::crypto::envelope::Envelope;
-use crate::crypto::signing::SignedEnvelope;
 use crate::env::{self, filter};
 use crate::keys;
 use crate::transfer;
+use crate::transfer::manifest::{ShareManifest, MODE_WORMHOLE};
 use crate::ui::display;
 
 #[derive(Args)]
+#[command(group(ArgGroup::new("recipients").args(["to", "to_role", "interactive"])))]
 pub struct ShareArgs {
     /// Path to .env file to share
     pub file: Option<String>,
@@ -22,20 +23,69 @@ pub struct ShareArgs {
     #[arg(long)]
     pub label: Option<String>,
 
+    /// Project the secrets belong to, shown to the receiver
+    /// (default: `[metadata] project` in .enseal.toml, else the directory name)
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// How long the receiver may accept the envelope, e.g. 30s, 10m, 2h, 1d
+    /// (default: the receiver's --max-age)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub expire: Option<u64>,
+
     /// Wrap raw string as KEY=<value> for .env-compatible receive
     #[arg(long, value_name = "KEY")]
     pub r#as: Option<String>,
 
-    /// Identity mode: encrypt to named recipient (alias or identity)
+    /// Identity mode: encrypt to named recipient (alias or identity),
+    /// or @FILE for a newline-separated list of recipients
     #[arg(long)]
     pub to: Option<String>,
 
+    /// Identity mode: encrypt to every member of a `[roles]` entry in .enseal.toml
+    #[arg(long, value_name = "ROLE")]
+    pub to_role: Option<String>,
+
+    /// Identity mode: pick recipients from your trusted keys, aliases and
+    /// groups with a fuzzy finder (needs a terminal)
+    #[arg(long, short, conflicts_with = "to")]
+    pub interactive: bool,
+
+    /// Treat --to strictly as a trusted identity (skip alias and group lookup)
+    #[arg(long, requires = "recipients")]
+    pub literal: bool,
+
+    /// Accept a recipient whose trusted key changed since it was pinned, and re-pin it
+    #[arg(long, requires = "recipients")]
+    pub accept_key_change: bool,
+
+    /// Abort unless every recipient's fingerprint starts with this prefix
+    #[arg(
+        long,
+        value_name = "PREFIX",
+        requires = "recipients",
+        alias = "recipients-fingerprint"
+    )]
+    pub confirm_fingerprint: Option<String>,
+
     /// File drop: write encrypted file instead of network transfer (identity mode)
     #[arg(long)]
     pub output: Option<String>,
 
-    /// Number of words in wormhole code (2-5)
-    #[arg(long, default_value = "2", value_parser = clap::value_parser!(u16).range(2..=5))]
+    /// Send with a code that was already shared, after the original `share`
+    /// died (works with --relay-code; wormhole codes can't be resumed and
+    /// this reports why)
+    #[arg(long, value_name = "CODE", conflicts_with_all = ["to", "dry_run"])]
+    pub resume: Option<String>,
+
+    /// Anonymous mode: send through the enseal relay (--relay, else
+    /// `relay_url` in the config) under a one-time code instead of a
+    /// wormhole code. The relay retires the code once it has been received
+    #[arg(long, conflicts_with_all = ["to", "to_role", "interactive", "output"])]
+    pub relay_code: bool,
+
+    /// Number of words in the share code (2-5, or up to 6 with --relay-code)
+    #[arg(long, default_value = "2", value_parser = clap::value_parser!(u16).range(2..=6))]
     pub words: u16,
 
     /// Regex to exclude vars
@@ -46,10 +96,26 @@ pub struct ShareArgs {
     #[arg(long)]
     pub include: Option<String>,
 
+    /// Regex to exclude vars by value (applied after key filters)
+    #[arg(long, value_name = "REGEX")]
+    pub exclude_value: Option<String>,
+
+    /// Regex to include only vars whose value matches (applied after key filters)
+    #[arg(long, value_name = "REGEX")]
+    pub include_value: Option<String>,
+
     /// Don't resolve ${VAR} references before sending
     #[arg(long)]
     pub no_interpolate: bool,
 
+    /// Send secret-named keys with empty values, warning only (the default)
+    #[arg(long, overrides_with = "no_allow_empty")]
+    pub allow_empty: bool,
+
+    /// Refuse to send if a secret-named key (e.g. API_KEY=) has an empty value
+    #[arg(long, overrides_with = "allow_empty")]
+    pub no_allow_empty: bool,
+
     /// Environment profile (resolves to .env.<name>)
     #[arg(long, value_name = "NAME")]
     pub env: Option<String>,
@@ -58,29 +124,121 @@ pub struct ShareArgs {
     #[arg(long)]
     pub no_filter: bool,
 
+    /// Don't warn when the input file is tracked by git
+    #[arg(long)]
+    pub no_git_check: bool,
+
+    /// Run input through a command (stdin -> stdout) before sending, e.g. 'op inject'
+    #[arg(long, value_name = "CMD")]
+    pub pipe_through: Option<String>,
+
     /// Use specific relay server
     #[arg(long, env = "ENSEAL_RELAY")]
     pub relay: Option<String>,
 
+    /// Retry failed connections to the relay/rendezvous server this many times
+    #[arg(long, default_value_t = crate::transfer::retry::DEFAULT_RETRIES, value_name = "N")]
+    pub retries: u32,
+
+    /// Give up if the transfer hasn't completed within this many seconds
+    #[arg(long, default_value_t = crate::transfer::DEFAULT_TIMEOUT.as_secs(), value_name = "SECS")]
+    pub timeout: u64,
+
+    /// Relay push in acked chunks of at most this many bytes (default: the
+    /// limit the relay reports, for payloads over 64 KiB)
+    #[arg(long, value_name = "BYTES", value_parser = clap::value_parser!(u64).range(1..))]
+    pub chunk_size: Option<u64>,
+
+    /// Relay push to a group: seal a separate copy for each recipient
+    /// so the group size is not visible in the ciphertext
+    #[arg(long)]
+    pub per_recipient: bool,
+
+    /// Show what would be sent (key names, count, recipients) without sending
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// After sending, write a JSON record of the share (mode, recipients'
+    /// fingerprints, variable names, payload hash, code) -- never values
+    #[arg(long, value_name = "FILE", conflicts_with = "dry_run")]
+    pub manifest: Option<String>,
+
     /// Minimal output
     #[arg(long, short)]
     pub quiet: bool,
 }
 
-pub async fn run(args: ShareArgs) -> Result<()> {
+pub async fn run(mut args: ShareArgs, store: &keys::store::KeyStore) -> Result<()> {
+    // Recipient arguments for identity mode; empty means anonymous mode
+    let recipients = match (&args.to, &args.to_role) {
+        (Some(to), _) => vec![to.clone()],
+        (None, Some(role)) => crate::config::Manifest::load(None)?
+            .role_members(role)?
+            .to_vec(),
+        (None, None) if args.interactive => pick_recipients(store)?,
+        (None, None) => Vec::new(),
+    };
+
+    // Identity mode falls back to the configured relay. Anonymous mode keeps
+    // --relay as the wormhole rendezvous override, and raw age recipients
+    // have no relay channel, so both are left alone.
+    if args.relay_code
+        || (!recipients.is_empty() && args.output.is_none() && raw_recipient(&recipients).is_none())
+    {
+        args.relay = crate::config::user::relay_url(args.relay.take())?;
+    }
+    if args.relay_code && args.relay.is_none() {
+        return Err(exit::usage(
+            "--relay-code needs --relay or relay_url in the config",
+        ));
+    }
+
+    if args.words > 5 && !args.relay_code {
+        return Err(exit::usage("--words above 5 requires --relay-code"));
+    }
+
+    if let Some(code) = &args.resume {
+        if !args.relay_code {
+            transfer::wormhole::resume(code)?;
+        }
+    }
+
     // Reject conflicting --env and file argument
     if args.env.is_some() && args.file.is_some() {
-        anyhow::bail!("--env and a file argument are mutually exclusive");
+        return Err(exit::usage(
+            "--env and a file argument are mutually exclusive",
+        ));
     }
 
     // --output requires --to (file drop is identity mode only)
-    if args.output.is_some() && args.to.is_none() {
-        anyhow::bail!("--output requires --to (file drop is only available in identity mode)");
+    if args.output.is_some() && recipients.is_empty() {
+        return Err(exit::usage(
+            "--output requires --to (file drop is only available in identity mode)",
+        ));
+    }
+
+    // --per-recipient only changes how relay pushes are sealed
+    if args.per_recipient && (recipients.is_empty() || args.relay.is_none()) {
+        return Err(exit::usage("--per-recipient requires --to and --relay"));
+    }
+    if args.chunk_size.is_some()
+        && ((recipients.is_empty() && !args.relay_code) || args.relay.is_none())
+    {
+        return Err(exit::usage(
+            "--chunk-size requires --relay-code, or --to and --relay",
+        ));
     }
 
     // --no-filter skips all processing; reject contradictory filter flags
-    if args.no_filter && (args.include.is_some() || args.exclude.is_some()) {
-        anyhow::bail!("--no-filter cannot be used with --include or --exclude");
+    if args.no_filter
+        && (args.include.is_some()
+            || args.exclude.is_some()
+            || args.include_value.is_some()
+            || args.exclude_value.is_some())
+    {
+        return Err(exit::usage(
+            "--no-filter cannot be used with --include/--exclude or value filters",
+        ));
     }
 
     // 1. Resolve file via profile if --env is set
@@ -92,21 +250,55 @@ pub async fn run(args: ShareArgs) -> Result<()> {
     };
 
     // 2. Detect and read input
-    let payload = input::select_input(
+    let mut payload = input::select_input(
         args.secret.as_deref(),
         args.r#as.as_deref(),
         args.label.as_deref(),
         file_arg.as_deref(),
+        args.no_filter,
         args.quiet,
     )?;
 
+    // Warn (without blocking) if the file is committed to git
+    if !args.quiet && !args.no_git_check {
+        if let Some(ref file) = payload.source {
+            if env::git::is_tracked(std::path::Path::new(file)) {
+                display::warning(&format!(
+                    "{} is tracked by git -- secrets may be in history",
+                    file
+                ));
+            }
+        }
+    }
+
+    // Optional preprocessing hook: the command's stdout becomes the payload
+    if let Some(ref command) = args.pipe_through {
+        if payload.bytes.is_some() {
+            anyhow::bail!("--pipe-through cannot be used with binary files");
+        }
+        payload.content = input::pipe_through(command, &payload.content)?;
+    }
+
     // 3. For .env payloads, parse, interpolate, and filter
     let content = if payload.format == input::PayloadFormat::Env && !args.no_filter {
         let env_file = env::parser::parse(&payload.content)?;
 
+        let empty = env::validator::empty_secrets(&env_file);
+        if args.no_allow_empty && !empty.is_empty() {
+            for issue in &empty {
+                display::error(&issue.message);
+            }
+            return Err(crate::cli::exit::validation(format!(
+                "{} secret(s) have empty values; fill them in or drop --no-allow-empty",
+                empty.len()
+            )));
+        }
+
         // Run validation warnings
         if !args.quiet {
-            let issues = env::validator::validate(&env_file);
+            let mut issues = env::validator::validate(&env_file);
+            issues.extend(env::validator::weak_secrets(&env_file));
+            issues.extend(empty);
             for issue in &issues {
                 display::warning(&issue.message);
             }
@@ -120,10 +312,18 @@ pub async fn run(args: ShareArgs) -> Result<()> {
         };
 
         // Apply filters
-        let filtered = filter::filter(&env_file, args.include.as_deref(), args.exclude.as_deref())?;
+        let filtered = filter::filter(
+            &env_file,
+            args.include.as_deref(),
+            args.exclude.as_deref(),
+            args.include_value.as_deref(),
+            args.exclude_value.as_deref(),
+        )?;
 
         if filtered.var_count() == 0 {
-            anyhow::bail!("all variables were filtered out (check --include/--exclude patterns)");
+            anyhow::bail!(
+                "all variables were filtered out (check --include/--exclude and value patterns)"
+            );
         }
 
         filtered.to_string()
@@ -132,7 +332,16 @@ pub async fn run(args: ShareArgs) -> Result<()> {
     };
 
     // 3. Create envelope
-    let envelope = Envelope::seal(&content, payload.format.clone(), payload.label.clone())?;
+    let envelope = match payload.bytes {
+        Some(ref bytes) => Envelope::seal_binary(bytes, payload.label.clone())?,
+        None => Envelope::seal(&content, payload.format.clone(), payload.label.clone())?,
+    }
+    .with_project(project_name(args.project.as_deref())?)
+    .with_expiry(args.expire);
+
+    if args.dry_run {
+        return dry_run(&args, store, &recipients, &envelope, &content);
+    }
 
     // 4. Display pre-send info
     if !args.quiet {
@@ -142,19 +351,260 @@ pub async fn run(args: ShareArgs) -> Result<()> {
         if let Some(ref label) = envelope.metadata.label {
             display::info("Label:", label);
         }
+        if let Some(ref project) = envelope.metadata.project {
+            display::info("Project:", project);
+        }
+        if let Some(secs) = args.expire {
+            display::info("Valid for:", &format!("{}s after sending", secs));
+        }
     }
 
-    // 5. Route based on mode: identity (--to) vs anonymous (wormhole)
-    if let Some(ref recipient_name) = args.to {
-        send_identity_mode(&args, &envelope, recipient_name).await
+    // 5. Route based on mode: identity (--to/--interactive) vs anonymous (wormhole)
+    let sent = if recipients.is_empty() {
+        send_anonymous_mode(&args, &envelope).await?
     } else {
-        send_anonymous_mode(&args, &envelope).await
+        send_identity_mode(&args, store, &envelope, &recipients).await?
+    };
+
+    if let Some(ref path) = args.manifest {
+        let manifest = ShareManifest::new(
+            &envelope,
+            sent.mode,
+            sent.recipients,
+            var_names(&envelope, &content),
+            sent.code,
+        );
+        let json =
+            serde_json::to_string_pretty(&manifest).context("failed to serialize manifest")?;
+        std::fs::write(path, json + "\n")
+            .with_context(|| format!("failed to write manifest {}", path))?;
+        if !args.quiet {
+            display::ok(&format!("manifest written to {}", path));
+        }
+    }
+    Ok(())
+}
+
+/// Relay pushes larger than this ask the relay for its message limit and
+/// are chunked to fit it, unless `--chunk-size` is given.
+const AUTO_CHUNK_THRESHOLD: usize = 64 * 1024;
+
+/// What a successful send did, for `--manifest`.
+struct Sent {
+    mode: &'static str,
+    /// Empty in anonymous mode.
+    recipients: Vec<transfer::audit::AuditRecipient>,
+    /// The wormhole code, if one was issued.
+    code: Option<String>,
+}
+
+/// Variable names in an .env or KEY=VALUE payload; empty for other formats.
+fn var_names(envelope: &Envelope, content: &str) -> Vec<String> {
+    if !matches!(
+        envelope.format,
+        input::PayloadFormat::Env | input::PayloadFormat::Kv
+    ) {
+        return Vec::new();
+    }
+    env::parser::parse(content)
+        .map(|env_file| env_file.keys().into_iter().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Everything `--interactive` offers: trusted identities, aliases and
+/// groups, sorted and deduplicated.
+fn recipient_choices(store: &keys::store::KeyStore) -> Result<Vec<String>> {
+    let mut choices = store.list_trusted()?;
+    choices.extend(
+        keys::alias::list(store)?
+            .into_iter()
+            .map(|(alias, _)| alias),
+    );
+    choices.extend(
+        keys::group::list_groups(store)?
+            .into_iter()
+            .map(|(group, _)| group),
+    );
+    choices.sort();
+    choices.dedup();
+    if choices.is_empty() {
+        anyhow::bail!("no trusted keys to pick from. Import one with: enseal keys import <file>");
+    }
+    Ok(choices)
+}
+
+/// Let the user pick one or more recipients with a fuzzy finder.
+fn pick_recipients(store: &keys::store::KeyStore) -> Result<Vec<String>> {
+    if !is_terminal::is_terminal(std::io::stdin()) || !is_terminal::is_terminal(std::io::stderr()) {
+        return Err(exit::usage(
+            "--interactive needs a terminal; pass --to <name> instead",
+        ));
+    }
+    const DONE: &str = "[done]";
+
+    let mut remaining = recipient_choices(store)?;
+    let mut picked: Vec<String> = Vec::new();
+    while !remaining.is_empty() {
+        let mut items: Vec<&str> = remaining.iter().map(String::as_str).collect();
+        if !picked.is_empty() {
+            items.insert(0, DONE);
+        }
+        let prompt = if picked.is_empty() {
+            "Send to".to_string()
+        } else {
+            format!("Send to {} and", picked.join(", "))
+        };
+        let selection = dialoguer::FuzzySelect::new()
+            .with_prompt(prompt)
+            .items(&items)
+            .default(0)
+            .interact_opt()?;
+        match selection {
+            None if picked.is_empty() => anyhow::bail!("no recipient selected"),
+            None => break,
+            Some(index) if items[index] == DONE => break,
+            Some(index) => {
+                let index = if picked.is_empty() { index } else { index - 1 };
+                picked.push(remaining.remove(index));
+            }
+        }
+    }
+    Ok(picked)
+}
+
+/// A single bare `age1...` recipient argument.
+fn raw_recipient(recipients: &[String]) -> Option<age::x25519::Recipient> {
+    match recipients {
+        [only] => keys::identity::parse_raw_recipient(only),
+        _ => None,
     }
 }
 
-async fn send_anonymous_mode(args: &ShareArgs, envelope: &Envelope) -> Result<()> {
+/// Project tag for the envelope: `--project`, else `[metadata] project` from
+/// .enseal.toml, else the current directory's name. A missing .enseal.toml
+/// is fine; a broken one is an error.
+fn project_name(explicit: Option<&str>) -> Result<Option<String>> {
+    if let Some(project) = explicit {
+        return Ok(Some(project.to_string()));
+    }
+    if let Some(project) = crate::config::Manifest::load(None)?.metadata.project {
+        return Ok(Some(project));
+    }
+    Ok(std::env::current_dir().ok().and_then(|dir| {
+        dir.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }))
+}
+
+/// Parse a duration like `30s`, `10m`, `2h`, or `1d` (bare numbers are
+/// seconds) into seconds.
+fn parse_duration(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (digits, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => value.split_at(idx),
+        None => (value, "s"),
+    };
+    let amount: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid duration '{}' (e.g. 30s, 10m, 2h, 1d)", value))?;
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => {
+            return Err(format!(
+                "unknown duration unit '{}' (use s, m, h, or d)",
+                unit
+            ))
+        }
+    };
+    match amount.checked_mul(scale) {
+        Some(0) => Err("duration must be greater than zero".to_string()),
+        Some(secs) => Ok(secs),
+        None => Err(format!("duration '{}' is too large", value)),
+    }
+}
+
+/// Report what `share` would send, without touching the network or disk.
+/// Key names go to stdout (one per line); values are never printed.
+fn dry_run(
+    args: &ShareArgs,
+    store: &keys::store::KeyStore,
+    recipients: &[String],
+    envelope: &Envelope,
+    content: &str,
+) -> Result<()> {
+    let format = match envelope.format {
+        input::PayloadFormat::Env => "env file",
+        input::PayloadFormat::Kv => "KEY=VALUE pairs",
+        input::PayloadFormat::Raw => "raw string",
+        input::PayloadFormat::Binary => "binary file",
+    };
+    let size = if envelope.is_binary() {
+        envelope.payload_bytes()?.len()
+    } else {
+        content.len()
+    };
+
+    if !args.quiet {
+        display::info("Format:", format);
+        match envelope.metadata.var_count {
+            Some(count) => display::info("Secrets:", &format!("{} variables", count)),
+            None => display::info("Size:", &format!("{} bytes", size)),
+        }
+        if let Some(ref label) = envelope.metadata.label {
+            display::info("Label:", label);
+        }
+        if let Some(ref project) = envelope.metadata.project {
+            display::info("Project:", project);
+        }
+
+        match recipients {
+            [] => match (args.relay_code, &args.relay) {
+                (true, Some(relay)) => display::info(
+                    "Route:",
+                    &format!("relay code via {} (anonymous mode)", relay),
+                ),
+                _ => display::info("Route:", "wormhole (anonymous mode)"),
+            },
+            [recipient_name] if raw_recipient(recipients).is_some() => {
+                display::info("To:", &format!("{} (raw age recipient)", recipient_name));
+            }
+            _ => {
+                let identities = keys::resolve_recipient_args(store, recipients, args.literal)?;
+                for id in &identities {
+                    let trusted = keys::identity::TrustedKey::load(store, id)?;
+                    display::info("To:", &format!("{} ({})", id, trusted.fingerprint()));
+                }
+                let route = match (&args.output, &args.relay) {
+                    (Some(dir), _) => format!("file drop to {}", dir),
+                    (None, Some(relay)) => format!("relay push via {}", relay),
+                    (None, None) => "wormhole (identity mode)".to_string(),
+                };
+                display::info("Route:", &route);
+            }
+        }
+    }
+
+    for key in var_names(envelope, content) {
+        println!("{}", key);
+    }
+
+    if !args.quiet {
+        display::ok("dry run: nothing was sent");
+    }
+    Ok(())
+}
+
+async fn send_anonymous_mode(args: &ShareArgs, envelope: &Envelope) -> Result<Sent> {
+    if let (true, Some(relay_url)) = (args.relay_code, &args.relay) {
+        return send_relay_code(args, envelope, relay_url).await;
+    }
+
     let (code, mailbox) =
-        transfer::wormhole::create_mailbox(args.relay.as_deref(), args.words.into()).await?;
+        transfer::wormhole::create_mailbox(args.relay.as_deref(), args.words.into(), args.retries)
+            .await?;
 
     if !args.quiet {
         display::info("Share code:", &code);
@@ -163,32 +613,124 @@ async fn send_anonymous_mode(args: &ShareArgs, envelope: &Envelope) -> Result<()
         println!("{}", code);
     }
 
-    transfer::wormhole::send(envelope, mailbox).await?;
+    transfer::wormhole::send(
+        envelope,
+        mailbox,
+        std::time::Duration::from_secs(args.timeout),
+    )
+    .await?;
 
     if !args.quiet {
         display::ok("sent");
     }
-    Ok(())
+    Ok(Sent {
+        mode: MODE_WORMHOLE,
+        recipients: Vec::new(),
+        code: Some(code),
+    })
+}
+
+/// Anonymous mode over the enseal relay: show a fresh code (or reuse the
+/// `--resume` one) and send under it. The relay burns the code once the
+/// receiver has acked.
+async fn send_relay_code(args: &ShareArgs, envelope: &Envelope, relay_url: &str) -> Result<Sent> {
+    let code = match args.resume {
+        Some(ref code) => code.clone(),
+        None => {
+            let words = transfer::relay::CodeWords::configured()?;
+            if let Some(warning) = words.warning() {
+                display::warning(&warning);
+            }
+            transfer::relay::generate_code(&words, args.words.into())?
+        }
+    };
+    let data = envelope.to_bytes()?;
+    let timeout = std::time::Duration::from_secs(args.timeout);
+
+    if !args.quiet {
+        display::info("Share code:", &code);
+        display::info("Expires:", "on first receive (relay channel TTL)");
+    } else {
+        println!("{}", code);
+    }
+
+    let chunk_size = match args.chunk_size {
+        Some(size) => Some(size as usize),
+        None if data.len() > AUTO_CHUNK_THRESHOLD => {
+            transfer::relay::frame_limit(relay_url, timeout).await
+        }
+        None => None,
+    };
+    match chunk_size {
+        Some(size) => {
+            transfer::relay::send_chunked(&data, relay_url, &code, size, args.retries, timeout)
+                .await?
+        }
+        None => transfer::relay::send(&data, relay_url, &code, args.retries, timeout).await?,
+    }
+
+    if !args.quiet {
+        display::ok("sent");
+    }
+    Ok(Sent {
+        mode: transfer::audit::MODE_RELAY,
+        recipients: Vec::new(),
+        code: Some(code),
+    })
 }
 
 async fn send_identity_mode(
     args: &ShareArgs,
+    store: &keys::store::KeyStore,
     envelope: &Envelope,
-    recipient_name: &str,
-) -> Result<()> {
-    // Resolve recipient (may be alias, group, or literal identity)
-    let identities = keys::resolve_to_identities(recipient_name)?;
+    recipients: &[String],
+) -> Result<Sent> {
+    let recipient_name = recipients.join(", ");
+
+    // A bare `age1...` recipient bypasses the trusted-key lookup. It has no
+    // verifying key, hence no relay channel to push to.
+    let raw_recipient = raw_recipient(recipients);
+    if raw_recipient.is_some() && args.output.is_none() && args.relay.is_some() {
+        anyhow::bail!("relay push needs an imported key; a raw age recipient has no relay channel");
+    }
+
+    // Resolve recipient (may be alias, group, literal identity, or @file list)
+    let identities = if raw_recipient.is_some() {
+        vec![recipient_name.clone()]
+    } else {
+        keys::resolve_recipient_args(store, recipients, args.literal)?
+    };
 
-    let store = keys::store::KeyStore::open()?;
-    let sender = keys::identity::EnsealIdentity::load(&store)?;
+    let sender = keys::identity::EnsealIdentity::load(store)?;
 
     // Load all trusted keys and collect age recipients
-    let trusted_keys: Vec<keys::identity::TrustedKey> = identities
-        .iter()
-        .map(|id| keys::identity::TrustedKey::load(&store, id))
-        .collect::<Result<Vec<_>>>()?;
-    let age_recipients: Vec<&age::x25519::Recipient> =
-        trusted_keys.iter().map(|k| &k.age_recipient).collect();
+    let trusted_keys: Vec<keys::identity::TrustedKey> = if raw_recipient.is_some() {
+        Vec::new()
+    } else {
+        keys::pin::check(store, &identities, args.accept_key_change)?;
+        identities
+            .iter()
+            .map(|id| keys::identity::TrustedKey::load(store, id))
+            .collect::<Result<Vec<_>>>()?
+    };
+    if let Some(ref prefix) = args.confirm_fingerprint {
+        if raw_recipient.is_some() {
+            anyhow::bail!("--confirm-fingerprint needs an imported key; a raw age recipient has no fingerprint");
+        }
+        check_fingerprint_prefix(&trusted_keys, prefix)?;
+    }
+    let age_recipients: Vec<&age::x25519::Recipient> = match raw_recipient {
+        Some(ref recipient) => vec![recipient],
+        None => trusted_keys.iter().map(|k| &k.age_recipient).collect(),
+    };
+    let audit_log = transfer::audit::log_path(store)?;
+    let audit_recipients: Vec<transfer::audit::AuditRecipient> = match raw_recipient {
+        Some(_) => vec![transfer::audit::AuditRecipient::raw(&recipient_name)],
+        None => trusted_keys
+            .iter()
+            .map(transfer::audit::AuditRecipient::trusted)
+            .collect(),
+    };
 
     let display_name = if identities.len() == 1 {
         identities[0].clone()
@@ -196,17 +738,28 @@ async fn send_identity_mode(
         format!("{} ({} recipients)", recipient_name, identities.len())
     };
 
+    if raw_recipient.is_some() {
+        display::warning(&keys::identity::raw_recipient_warning(&recipient_name));
+    }
     if !args.quiet {
         display::info("To:", &display_name);
-        if identities.len() == 1 {
-            display::info("Fingerprint:", &trusted_keys[0].fingerprint());
+        if let [key] = trusted_keys.as_slice() {
+            display::info("Fingerprint:", &key.fingerprint());
         }
     }
 
-    if let Some(ref output_dir) = args.output {
+    let (mode, code) = if let Some(ref output_dir) = args.output {
         // File drop mode — use group name or identity for filename
         let filename = if identities.len() > 1 {
-            recipient_name.to_string()
+            match recipients {
+                [list] if list.starts_with('@') => std::path::Path::new(&list[1..])
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("recipients")
+                    .to_string(),
+                [group] => group.clone(),
+                _ => "recipients".to_string(),
+            }
         } else {
             identities[0].clone()
         };
@@ -216,6 +769,10 @@ async fn send_identity_mode(
             &sender,
             std::path::Path::new(output_dir),
             &filename,
+            audit_log.as_deref().map(|log| transfer::filedrop::Audit {
+                log,
+                recipients: &audit_recipients,
+            }),
         )?;
         if !args.quiet {
             display::ok(&format!(
@@ -224,21 +781,55 @@ async fn send_identity_mode(
                 dest.display()
             ));
         }
+        (transfer::audit::MODE_FILE_DROP, None)
     } else if let Some(ref relay_url) = args.relay {
         // Enseal relay push mode — no code needed
-        let inner_bytes = envelope.to_bytes()?;
-        let signed = SignedEnvelope::seal(&inner_bytes, &age_recipients, &sender)?;
-        let wire_bytes = signed.to_bytes()?;
+        let pushes = seal_for_channels(envelope, &trusted_keys, &sender, args.per_recipient)?;
+        let timeout = std::time::Duration::from_secs(args.timeout);
+
+        // Large payloads go out in chunks the relay will forward
+        let largest = pushes.iter().map(|(_, bytes)| bytes.len()).max();
+        let chunk_size = match args.chunk_size {
+            Some(size) => Some(size as usize),
+            None if largest.is_some_and(|len| len > AUTO_CHUNK_THRESHOLD) => {
+                transfer::relay::frame_limit(relay_url, timeout).await
+            }
+            None => None,
+        };
 
         // Push to all recipients' channels (important for groups)
-        for tk in &trusted_keys {
-            let channel_id = tk.channel_id();
-            transfer::relay::push(&wire_bytes, relay_url, &channel_id).await?;
+        for (channel_id, wire_bytes) in &pushes {
+            match chunk_size {
+                Some(size) => {
+                    transfer::relay::push_chunked(
+                        wire_bytes,
+                        relay_url,
+                        channel_id,
+                        size,
+                        args.retries,
+                        timeout,
+                    )
+                    .await?
+                }
+                None => {
+                    transfer::relay::push(wire_bytes, relay_url, channel_id, args.retries, timeout)
+                        .await?
+                }
+            }
+        }
+        if let Some(ref log) = audit_log {
+            let entry = transfer::audit::AuditEntry::new(
+                transfer::audit::MODE_RELAY,
+                envelope,
+                &audit_recipients,
+            );
+            transfer::audit::append(log, &entry)?;
         }
 
         if !args.quiet {
             display::ok(&format!("pushed to {}", display_name));
         }
+        (transfer::audit::MODE_RELAY, None)
     } else {
         // Wormhole mode (default) — display code before sending
         let (code, wire_bytes, mailbox) = transfer::identity::create_mailbox(
@@ -247,6 +838,7 @@ async fn send_identity_mode(
             &sender,
             None,
             args.words.into(),
+            args.retries,
         )
         .await?;
 
@@ -257,12 +849,243 @@ async fn send_identity_mode(
             println!("{}", code);
         }
 
-        transfer::identity::send(wire_bytes, mailbox).await?;
+        transfer::identity::send(
+            wire_bytes,
+            mailbox,
+            std::time::Duration::from_secs(args.timeout),
+        )
+        .await?;
 
         if !args.quiet {
             display::ok(&format!("encrypted to {}, signed by you", display_name));
         }
+        (MODE_WORMHOLE, Some(code))
+    };
+
+    Ok(Sent {
+        mode,
+        recipients: audit_recipients,
+        code,
+    })
+}
+
+/// Seal the envelope for relay push, returning each recipient's channel and wire bytes.
+/// By default every channel gets the same ciphertext encrypted to the whole group.
+/// With `per_recipient`, each channel gets its own ciphertext for that recipient only,
+/// so the age header no longer reveals how many people received the secret.
+fn seal_for_channels(
+    envelope: &Envelope,
+    trusted_keys: &[keys::identity::TrustedKey],
+    sender: &keys::identity::EnsealIdentity,
+    per_recipient: bool,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    if per_recipient {
+        return trusted_keys
+            .iter()
+            .map(|tk| {
+                let wire_bytes =
+                    crate::crypto::signing::seal_signed(envelope, &[&tk.age_recipient], sender)?;
+                Ok((tk.channel_id(), wire_bytes))
+            })
+            .collect();
+    }
+
+    let age_recipients: Vec<&age::x25519::Recipient> =
+        trusted_keys.iter().map(|k| &k.age_recipient).collect();
+    let wire_bytes = crate::crypto::signing::seal_signed(envelope, &age_recipients, sender)?;
+    Ok(trusted_keys
+        .iter()
+        .map(|tk| (tk.channel_id(), wire_bytes.clone()))
+        .collect())
+}
+
+/// Require every recipient's fingerprint to start with `prefix`, listing
+/// the ones that don't. The `SHA256:` prefix is optional on both sides.
+fn check_fingerprint_prefix(recipients: &[keys::identity::TrustedKey], prefix: &str) -> Result<()> {
+    let prefix = prefix.trim();
+    let prefix = prefix.strip_prefix("SHA256:").unwrap_or(prefix);
+    if prefix.is_empty() {
+        anyhow::bail!("--confirm-fingerprint needs a non-empty fingerprint prefix");
     }
 
+    let mismatches: Vec<String> = recipients
+        .iter()
+        .filter_map(|key| {
+            let fingerprint = key.fingerprint();
+            let bare = fingerprint.strip_prefix("SHA256:").unwrap_or(&fingerprint);
+            (!bare.starts_with(prefix)).then(|| format!("  {}  {}", key.identity, fingerprint))
+        })
+        .collect();
+    if !mismatches.is_empty() {
+        anyhow::bail!(
+            "{} of {} recipient(s) don't match fingerprint prefix '{}':\n{}\nNothing was sent",
+            mismatches.len(),
+            recipients.len(),
+            prefix,
+            mismatches.join("\n")
+        );
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::signing::SignedEnvelope;
+    use keys::identity::{EnsealIdentity, TrustedKey};
+
+    fn trusted(name: &str, id: &EnsealIdentity) -> TrustedKey {
+        TrustedKey::parse(name, &id.pubkey_file(name)).unwrap()
+    }
+
+    #[test]
+    fn manifest_lists_names_and_fingerprints_without_values() {
+        let content = "API_KEY=abc123\nDB_PASS=hunter2\n";
+        let envelope = Envelope::seal(content, input::PayloadFormat::Env, None).unwrap();
+        let alice = EnsealIdentity::generate();
+        let recipients = vec![transfer::audit::AuditRecipient::trusted(&trusted(
+            "alice", &alice,
+        ))];
+        let manifest = ShareManifest::new(
+            &envelope,
+            transfer::audit::MODE_RELAY,
+            recipients,
+            var_names(&envelope, content),
+            None,
+        );
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(manifest["mode"], "relay");
+        assert_eq!(manifest["vars"], serde_json::json!(["API_KEY", "DB_PASS"]));
+        assert_eq!(manifest["recipients"][0]["identity"], "alice");
+        assert_eq!(
+            manifest["recipients"][0]["fingerprint"],
+            alice.fingerprint().as_str()
+        );
+        assert_eq!(manifest["sha256"], envelope.metadata.sha256.as_str());
+        assert_eq!(manifest["timestamp"], envelope.metadata.created_at);
+        assert!(manifest.get("code").is_none());
+        for secret in ["abc123", "hunter2"] {
+            assert!(!json.contains(secret), "manifest leaked {secret}");
+        }
+    }
+
+    #[test]
+    fn recipient_choices_list_keys_aliases_and_groups() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = keys::store::KeyStore::open_at(dir.path().to_path_buf());
+        assert!(recipient_choices(&store).is_err());
+
+        for name in ["bob", "alice"] {
+            keys::trust_new_identity(&store, name);
+        }
+        keys::alias::set(&store, "al", "alice").unwrap();
+        keys::group::create(&store, "team").unwrap();
+        keys::group::add_member(&store, "team", "bob").unwrap();
+
+        assert_eq!(
+            recipient_choices(&store).unwrap(),
+            ["al", "alice", "bob", "team"]
+        );
+    }
+
+    fn payload() -> Envelope {
+        Envelope::seal("KEY=value\n", input::PayloadFormat::Env, None).unwrap()
+    }
+
+    fn opens(wire: &[u8], id: &EnsealIdentity) -> bool {
+        SignedEnvelope::from_bytes(wire)
+            .unwrap()
+            .open(id, None)
+            .is_ok()
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("30s"), Ok(30));
+        assert_eq!(parse_duration("10m"), Ok(600));
+        assert_eq!(parse_duration("2h"), Ok(7200));
+        assert_eq!(parse_duration("1d"), Ok(86400));
+        assert_eq!(parse_duration("45"), Ok(45));
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("5w").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+
+    #[test]
+    fn per_recipient_channels_decrypt_for_exactly_one() {
+        let sender = EnsealIdentity::generate();
+        let members: Vec<EnsealIdentity> = (0..3).map(|_| EnsealIdentity::generate()).collect();
+        let trusted_keys: Vec<TrustedKey> = members
+            .iter()
+            .enumerate()
+            .map(|(i, id)| trusted(&format!("member{i}"), id))
+            .collect();
+
+        let pushes = seal_for_channels(&payload(), &trusted_keys, &sender, true).unwrap();
+        assert_eq!(pushes.len(), 3);
+
+        for (i, (channel_id, wire)) in pushes.iter().enumerate() {
+            assert_eq!(channel_id, &trusted_keys[i].channel_id());
+            let openers: Vec<usize> = (0..members.len())
+                .filter(|&j| opens(wire, &members[j]))
+                .collect();
+            assert_eq!(openers, vec![i]);
+        }
+    }
+
+    #[test]
+    fn group_channels_share_one_ciphertext_by_default() {
+        let sender = EnsealIdentity::generate();
+        let a = EnsealIdentity::generate();
+        let b = EnsealIdentity::generate();
+        let trusted_keys = vec![trusted("a", &a), trusted("b", &b)];
+
+        let pushes = seal_for_channels(&payload(), &trusted_keys, &sender, false).unwrap();
+        assert_eq!(pushes[0].1, pushes[1].1);
+        assert!(opens(&pushes[0].1, &a) && opens(&pushes[0].1, &b));
+    }
+
+    #[test]
+    fn fingerprint_prefix_matches() {
+        let alice = trusted("alice", &EnsealIdentity::generate());
+        let fingerprint = alice.fingerprint();
+        let bare = fingerprint.strip_prefix("SHA256:").unwrap();
+
+        check_fingerprint_prefix(std::slice::from_ref(&alice), &fingerprint[..14]).unwrap();
+        check_fingerprint_prefix(std::slice::from_ref(&alice), &bare[..6]).unwrap();
+        assert!(check_fingerprint_prefix(std::slice::from_ref(&alice), "SHA256:").is_err());
+    }
+
+    #[test]
+    fn fingerprint_prefix_rejects_wrong_recipient() {
+        let alice = trusted("alice", &EnsealIdentity::generate());
+        let mallory = trusted("mallory", &EnsealIdentity::generate());
+        let prefix = &mallory.fingerprint()[..20];
+
+        let err = check_fingerprint_prefix(&[alice], prefix)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("1 of 1") && err.contains("alice"));
+    }
+
+    #[test]
+    fn fingerprint_prefix_lists_bad_group_member() {
+        let id = EnsealIdentity::generate();
+        // The same key under two names stands in for a group whose members
+        // share a fingerprint prefix
+        let group = [
+            trusted("alice", &id),
+            trusted("alice-laptop", &id),
+            trusted("bob", &EnsealIdentity::generate()),
+        ];
+        let prefix = &group[0].fingerprint()[..20];
+
+        let err = check_fingerprint_prefix(&group, prefix)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("1 of 3") && err.contains("bob"));
+        assert!(!err.contains("alice"));
+    }
+}
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use clap::Args;
+
+use crate::cli::encrypt::check_overwrite;
+use crate::crypto::signing;
+use crate::keys::identity::EnsealIdentity;
+use crate::keys::store::KeyStore;
+use crate::ui::display;
+
+#[derive(Args)]
+pub struct SignArgs {
+    /// File to sign (contents stay readable)
+    pub file: String,
+
+    /// Output path (default: <file>.signed)
+    #[arg(long, short)]
+    pub output: Option<String>,
+
+    /// Overwrite existing files without prompting
+    #[arg(long)]
+    pub force: bool,
+}
+
+pub fn run(args: SignArgs, store: &KeyStore) -> Result<()> {
+    let content =
+        std::fs::read(&args.file).map_err(|e| crate::cli::exit::read_failed(&args.file, e))?;
+
+    let identity = EnsealIdentity::load(store)?;
+    let signed = signing::sign_plaintext(&content, &identity);
+
+    let output_path = args
+        .output
+        .clone()
+        .unwrap_or_else(|| format!("{}.signed", args.file));
+    check_overwrite(&output_path, args.force)?;
+
+    // Not secret: the point is for anyone to read and verify it
+    std::fs::write(&output_path, signed.to_bytes())
+        .map_err(|e| anyhow::anyhow!("failed to write '{}': {}", output_path, e))?;
+
+    display::ok(&format!("{} signed, written to {}", args.file, output_path));
+    display::info("Fingerprint:", &identity.fingerprint());
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use clap::Args;
+use clap::{Args, ValueEnum};
 
 use crate::env;
 use crate::ui::display;
@@ -17,25 +17,83 @@ pub struct TemplateArgs {
     /// Path to .enseal.toml manifest for schema descriptions
     #[arg(long)]
     pub config: Option<String>,
+
+    /// Output syntax
+    #[arg(long, value_enum, default_value_t = TemplateFormat::Dotenv)]
+    pub format: TemplateFormat,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum TemplateFormat {
+    /// `KEY=<hint>` lines, for .env.example
+    Dotenv,
+    /// docker-compose `environment:` list, hints as comments
+    Compose,
+    /// GitHub Actions `env:` map reading `${{ secrets.KEY }}`, hints as comments
+    GithubActions,
 }
 
 pub fn run(args: TemplateArgs) -> Result<()> {
     let content = std::fs::read_to_string(&args.file)
-        .map_err(|e| anyhow::anyhow!("failed to read '{}': {}", args.file, e))?;
+        .map_err(|e| crate::cli::exit::read_failed(&args.file, e))?;
 
     let env_file = env::parser::parse(&content)?;
 
     // Load schema for descriptions
     let schema = env::schema::load_schema(args.config.as_deref())?;
 
-    let mut output = String::new();
+    let output = render(&env_file, schema.as_ref(), args.format);
+
+    if let Some(ref path) = args.output {
+        if std::path::Path::new(path.as_str()).exists() {
+            anyhow::bail!(
+                "'{}' already exists. This command replaces values with type hints -- \
+                 overwriting a real .env would destroy secret values. \
+                 Delete the file first if this is intentional",
+                path
+            );
+        }
+        std::fs::write(path, &output)?;
+        display::ok(&format!(
+            "template written to {} ({} variables)",
+            path,
+            env_file.var_count()
+        ));
+    } else {
+        print!("{}", output);
+    }
+
+    Ok(())
+}
+
+/// Render the template in `format`. Each variable's hint is its schema
+/// description, else a type inferred from its value.
+fn render(
+    env_file: &env::EnvFile,
+    schema: Option<&env::schema::Schema>,
+    format: TemplateFormat,
+) -> String {
+    // YAML formats nest everything under one key, indented two spaces
+    let (mut output, indent) = match format {
+        TemplateFormat::Dotenv => (String::new(), ""),
+        TemplateFormat::Compose => ("environment:\n".to_string(), "  "),
+        TemplateFormat::GithubActions => ("env:\n".to_string(), "  "),
+    };
 
     for entry in &env_file.entries {
         match entry {
-            env::Entry::KeyValue { key, value } => {
+            env::Entry::KeyValue {
+                key,
+                value,
+                comment,
+            } => {
+                // A comment block may span several lines
+                for line in comment.iter().flat_map(|c| c.lines()) {
+                    output.push_str(&format!("{}{}\n", indent, line));
+                }
+
                 // Try to get description from schema
                 let description = schema
-                    .as_ref()
                     .and_then(|s| s.rules.get(key.as_str()))
                     .and_then(|r| r.description.as_deref());
 
@@ -45,38 +103,26 @@ pub fn run(args: TemplateArgs) -> Result<()> {
                     infer_type_hint(value)
                 };
 
-                output.push_str(&format!("{}=<{}>\n", key, hint));
+                let line = match format {
+                    TemplateFormat::Dotenv => format!("{}=<{}>", key, hint),
+                    TemplateFormat::Compose => {
+                        format!("# {}\n{}- {}=${{{}}}", hint, indent, key, key)
+                    }
+                    TemplateFormat::GithubActions => {
+                        format!("# {}\n{}{}: ${{{{ secrets.{} }}}}", hint, indent, key, key)
+                    }
+                };
+                output.push_str(&format!("{}{}\n", indent, line));
             }
             env::Entry::Comment(text) => {
-                output.push_str(text);
-                output.push('\n');
+                output.push_str(&format!("{}{}\n", indent, text));
             }
             env::Entry::Blank => {
                 output.push('\n');
             }
         }
     }
-
-    if let Some(ref path) = args.output {
-        if std::path::Path::new(path.as_str()).exists() {
-            anyhow::bail!(
-                "'{}' already exists. This command replaces values with type hints -- \
-                 overwriting a real .env would destroy secret values. \
-                 Delete the file first if this is intentional",
-                path
-            );
-        }
-        std::fs::write(path, &output)?;
-        display::ok(&format!(
-            "template written to {} ({} variables)",
-            path,
-            env_file.var_count()
-        ));
-    } else {
-        print!("{}", output);
-    }
-
-    Ok(())
+    output
 }
 
 /// Infer a human-readable type hint from a value.
@@ -130,6 +176,53 @@ fn infer_type_hint(value: &str) -> String {
 mod tests {
     use super::*;
 
+    const FIXTURE: &str =
+        "# Database\n# primary\nDATABASE_URL=postgres://localhost/db\n\nPORT=3000\n";
+
+    fn render_fixture(format: TemplateFormat) -> String {
+        render(&env::parser::parse(FIXTURE).unwrap(), None, format)
+    }
+
+    #[test]
+    fn dotenv_format() {
+        assert_eq!(
+            render_fixture(TemplateFormat::Dotenv),
+            "# Database\n# primary\nDATABASE_URL=<postgres connection string>\n\nPORT=<integer, port>\n"
+        );
+    }
+
+    #[test]
+    fn compose_format() {
+        let output = render_fixture(TemplateFormat::Compose);
+        assert_eq!(
+            output,
+            "environment:\n  \
+             # Database\n  \
+             # primary\n  \
+             # postgres connection string\n  \
+             - DATABASE_URL=${DATABASE_URL}\n\n  \
+             # integer, port\n  \
+             - PORT=${PORT}\n"
+        );
+        let yaml = &yaml_rust2::YamlLoader::load_from_str(&output).unwrap()[0];
+        assert_eq!(yaml["environment"][1].as_str(), Some("PORT=${PORT}"));
+    }
+
+    #[test]
+    fn github_actions_format() {
+        let output = render_fixture(TemplateFormat::GithubActions);
+        assert!(
+            output.starts_with("env:\n  # Database\n  # primary\n  # postgres connection string\n")
+        );
+        assert!(!output.contains("postgres://localhost"));
+        let yaml = &yaml_rust2::YamlLoader::load_from_str(&output).unwrap()[0];
+        assert_eq!(
+            yaml["env"]["DATABASE_URL"].as_str(),
+            Some("${{ secrets.DATABASE_URL }}")
+        );
+        assert_eq!(yaml["env"]["PORT"].as_str(), Some("${{ secrets.PORT }}"));
+    }
+
     #[test]
     fn infer_boolean() {
         assert_eq!(infer_type_hint("true"), "boolean");
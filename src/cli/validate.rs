@@ -1,7 +1,8 @@
-use anyhow::{bail, Result};
+use anyhow::Result;
 use clap::Args;
 
 use crate::env;
+use crate::env::validator::Severity;
 use crate::ui::display;
 
 #[derive(Args)]
@@ -17,10 +18,17 @@ pub struct ValidateArgs {
 
 pub fn run(args: ValidateArgs) -> Result<()> {
     let content = std::fs::read_to_string(&args.file)
-        .map_err(|e| anyhow::anyhow!("failed to read '{}': {}", args.file, e))?;
+        .map_err(|e| crate::cli::exit::read_failed(&args.file, e))?;
 
     let env_file = env::parser::parse(&content)?;
 
+    // Advisory only: weak secrets never fail validation
+    let mut advisories = env::validator::weak_secrets(&env_file);
+    advisories.extend(env::validator::empty_secrets(&env_file));
+    for issue in advisories {
+        display::warning(&issue.message);
+    }
+
     let schema = env::schema::load_schema(args.config.as_deref())?;
     let schema = match schema {
         Some(s) => s,
@@ -30,7 +38,14 @@ pub fn run(args: ValidateArgs) -> Result<()> {
         }
     };
 
-    let errors = env::schema::validate(&env_file, &schema);
+    let (errors, warnings): (Vec<_>, Vec<_>) = env::schema::validate(&env_file, &schema)
+        .into_iter()
+        .partition(|e| e.severity == Severity::Error);
+
+    // severity = "warn" rules are reported but never fail validation
+    for warn in &warnings {
+        display::warning(&format!("{}", warn));
+    }
 
     if errors.is_empty() {
         let count = env_file.var_count();
@@ -52,5 +67,5 @@ pub fn run(args: ValidateArgs) -> Result<()> {
 
     eprintln!();
     display::error(&format!("{}/{} variables passed validation", passed, total));
-    bail!("validation failed")
+    Err(crate::cli::exit::validation("validation failed"))
 }
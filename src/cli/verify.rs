@@ -0,0 +1,68 @@
+use anyhow::Result;
+use clap::Args;
+
+use crate::cli::encrypt::check_overwrite;
+use crate::crypto::signing::{self, SignedPlaintext};
+use crate::error::EnsealError;
+use crate::keys;
+use crate::keys::store::KeyStore;
+use crate::ui::display;
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// File produced by `enseal sign`
+    pub file: String,
+
+    /// Write the verified content to this path
+    #[arg(long, short)]
+    pub output: Option<String>,
+
+    /// Overwrite existing files without prompting
+    #[arg(long)]
+    pub force: bool,
+
+    /// Accept a valid signature from a key that isn't in your trusted keys
+    /// (authorship is then unverified)
+    #[arg(long)]
+    pub allow_unknown_signer: bool,
+}
+
+pub fn run(args: VerifyArgs, store: &KeyStore) -> Result<()> {
+    let data =
+        std::fs::read(&args.file).map_err(|e| crate::cli::exit::read_failed(&args.file, e))?;
+    let signed = SignedPlaintext::from_bytes(&data)?;
+
+    let trusted = keys::TrustedIndex::load(store);
+    let trusted_signer = trusted.find_key(&signed.sender_sign_pubkey);
+    signing::verify_plaintext(&signed, trusted_signer)?;
+
+    let key_prefix = &signed.sender_sign_pubkey[..20.min(signed.sender_sign_pubkey.len())];
+    match trusted_signer {
+        Some(signer) => {
+            display::ok("signature verified");
+            display::info("Signed by:", &signer.identity);
+        }
+        None if args.allow_unknown_signer => {
+            display::ok("signature is valid");
+            display::warning(&format!(
+                "signed by an unknown key (signing key: {}...); import the signer's key to verify authorship",
+                key_prefix
+            ));
+        }
+        None => {
+            return Err(EnsealError::SignatureVerification(format!(
+                "signed by an unknown key (signing key: {}...); import the signer's key, or pass --allow-unknown-signer to accept it",
+                key_prefix
+            ))
+            .into());
+        }
+    }
+
+    if let Some(ref output_path) = args.output {
+        check_overwrite(output_path, args.force)?;
+        std::fs::write(output_path, &signed.content)
+            .map_err(|e| anyhow::anyhow!("failed to write '{}': {}", output_path, e))?;
+        display::ok(&format!("content written to {}", output_path));
+    }
+    Ok(())
+}
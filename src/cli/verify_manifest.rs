@@ -0,0 +1,48 @@
+use anyhow::Result;
+use clap::Args;
+
+use crate::transfer::manifest::{self, ShareManifest};
+use crate::ui::display;
+
+#[derive(Args)]
+pub struct VerifyManifestArgs {
+    /// Manifest written by `enseal share --manifest`
+    pub manifest: String,
+
+    /// The received .env file
+    #[arg(default_value = ".env")]
+    pub file: String,
+}
+
+pub fn run(args: VerifyManifestArgs) -> Result<()> {
+    let manifest = load(&args.manifest)?;
+    let content =
+        std::fs::read(&args.file).map_err(|e| crate::cli::exit::read_failed(&args.file, e))?;
+
+    let discrepancies = manifest::verify(&manifest, &content)?;
+    if discrepancies.is_empty() {
+        display::ok(&format!(
+            "{} matches {} ({} vars)",
+            args.file,
+            args.manifest,
+            manifest.vars.len()
+        ));
+        return Ok(());
+    }
+    for discrepancy in &discrepancies {
+        display::error(&discrepancy.to_string());
+    }
+    Err(crate::cli::exit::validation(format!(
+        "{} does not match {} ({} discrepancies)",
+        args.file,
+        args.manifest,
+        discrepancies.len()
+    )))
+}
+
+/// Read a manifest written by `share --manifest`.
+pub fn load(path: &str) -> Result<ShareManifest> {
+    let json = std::fs::read_to_string(path).map_err(|e| crate::cli::exit::read_failed(path, e))?;
+    serde_json::from_str(&json)
+        .map_err(|e| crate::cli::exit::validation(format!("invalid manifest '{}': {}", path, e)))
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+
+/// Cargo features and whether each was compiled in.
+const FEATURES: &[(&str, bool)] = &[
+    ("client", cfg!(feature = "client")),
+    ("server", cfg!(feature = "server")),
+];
+
+pub fn run() -> Result<()> {
+    print!("{}", report());
+    Ok(())
+}
+
+/// Build report for bug reports: version, target, features, and the
+/// versions of the crates that define wire compatibility.
+fn report() -> String {
+    let mut out = format!("enseal {}\n", env!("CARGO_PKG_VERSION"));
+    out.push_str(&format!(
+        "target:         {}\n",
+        env!("ENSEAL_BUILD_TARGET")
+    ));
+    out.push_str(&format!("age:            {}\n", env!("ENSEAL_AGE_VERSION")));
+    out.push_str(&format!(
+        "magic-wormhole: {}\n",
+        env!("ENSEAL_WORMHOLE_VERSION")
+    ));
+    out.push_str("features:\n");
+    for (name, enabled) in FEATURES {
+        let state = if *enabled { "enabled" } else { "disabled" };
+        out.push_str(&format!("  {name}: {state}\n"));
+    }
+    out
+}
@@ -1,6 +1,7 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use serde::Deserialize;
 
 /// Project-level configuration from `.enseal.toml`.
@@ -12,6 +13,9 @@ pub struct Manifest {
     pub filter: FilterConfig,
     pub metadata: MetadataConfig,
     pub schema: Option<crate::env::schema::Schema>,
+    /// Named recipient lists (`[roles] prod = ["alice@...", "ops-group"]`)
+    /// for `--to-role`. Members are identities, aliases or groups.
+    pub roles: BTreeMap<String, Vec<String>>,
 }
 
 #[allow(dead_code)]
@@ -41,7 +45,6 @@ pub struct MetadataConfig {
 impl Manifest {
     /// Try to load `.enseal.toml` from the given directory or current dir.
     /// Returns default config if file doesn't exist.
-    #[allow(dead_code)]
     pub fn load(config_path: Option<&str>) -> Result<Self> {
         let path = if let Some(p) = config_path {
             std::path::PathBuf::from(p)
@@ -56,10 +59,49 @@ impl Manifest {
         Self::from_file(&path)
     }
 
-    #[allow(dead_code)]
+    /// The members listed for `role`, or an error naming the defined roles.
+    /// Members are `--to` names (alias, group or trusted key) and are
+    /// resolved by the caller like any other recipient argument.
+    pub fn role_members(&self, role: &str) -> Result<&[String]> {
+        let Some(members) = self.roles.get(role) else {
+            if self.roles.is_empty() {
+                bail!("unknown role '{}': .enseal.toml defines no [roles]", role);
+            }
+            let available: Vec<&str> = self.roles.keys().map(String::as_str).collect();
+            bail!(
+                "unknown role '{}' (available: {})",
+                role,
+                available.join(", ")
+            );
+        };
+        if members.is_empty() {
+            bail!("role '{}' has no members", role);
+        }
+        Ok(members)
+    }
+
     fn from_file(path: &Path) -> Result<Self> {
-        let content = std::fs::read_to_string(path)?;
-        let manifest: Manifest = toml::from_str(&content)?;
-        Ok(manifest)
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_role_lists_available_roles() {
+        let manifest: Manifest =
+            toml::from_str("[roles]\nprod = [\"alice\", \"ops\"]\nstaging = [\"bob\"]\n").unwrap();
+        assert_eq!(manifest.role_members("prod").unwrap(), ["alice", "ops"]);
+
+        let err = manifest.role_members("qa").unwrap_err().to_string();
+        assert!(err.contains("unknown role 'qa'"), "{err}");
+        assert!(err.contains("available: prod, staging"), "{err}");
+
+        let err = Manifest::default().role_members("prod").unwrap_err();
+        assert!(err.to_string().contains("no [roles]"));
     }
 }
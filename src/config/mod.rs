@@ -1,4 +1,5 @@
 pub mod manifest;
+pub mod user;
 
 #[allow(unused_imports)]
 pub use manifest::Manifest;
@@ -0,0 +1,155 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::Deserialize;
+
+/// Per-user defaults from `~/.config/enseal/config.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UserConfig {
+    /// Wormhole rendezvous server used when `--relay` isn't given.
+    pub rendezvous_url: Option<String>,
+    /// Enseal relay used for identity-mode push/listen when `--relay` isn't given.
+    pub relay_url: Option<String>,
+    /// Append file drops and relay pushes to the audit log (like `ENSEAL_AUDIT=1`).
+    pub audit: bool,
+    /// Word list for relay channel codes (`[codes]`).
+    pub codes: CodesConfig,
+    /// How `keys fingerprint` displays fingerprints when `--format` isn't given.
+    pub fingerprint_format: crate::keys::identity::FingerprintFormat,
+}
+
+/// `[codes]`: which words relay channel codes are made of.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CodesConfig {
+    /// File with one word per line (blank lines and `#` comments skipped).
+    pub wordlist: Option<std::path::PathBuf>,
+    /// Use the NATO phonetic alphabet instead of the built-in list.
+    pub nato: bool,
+}
+
+static USER_CONFIG: OnceLock<UserConfig> = OnceLock::new();
+
+impl UserConfig {
+    /// Load from the platform config directory. A missing file is an empty config.
+    pub fn load() -> Result<Self> {
+        let dirs = ProjectDirs::from("dev", "enseal", "enseal")
+            .context("could not determine config directory")?;
+        Self::load_from(&dirs.config_dir().join("config.toml"))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+    }
+}
+
+/// The user config, loaded on first use and kept for the rest of the
+/// process. Only code paths that read a setting load it, so a broken config
+/// file doesn't fail unrelated commands.
+pub fn get() -> Result<&'static UserConfig> {
+    if let Some(config) = USER_CONFIG.get() {
+        return Ok(config);
+    }
+    let config = UserConfig::load()?;
+    Ok(USER_CONFIG.get_or_init(|| config))
+}
+
+/// An explicit `--relay`/`ENSEAL_RELAY` wins over the configured relay.
+pub fn relay_url(explicit: Option<String>) -> Result<Option<String>> {
+    // Don't load the config when it can't change the answer
+    if explicit.is_some() {
+        return Ok(explicit);
+    }
+    Ok(pick(explicit, &get()?.relay_url))
+}
+
+fn pick(explicit: Option<String>, configured: &Option<String>) -> Option<String> {
+    explicit.or_else(|| configured.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn loads_urls_from_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "rendezvous_url = \"ws://rv.internal:4000/v1\"\nrelay_url = \"wss://relay.internal\"\naudit = true\n",
+        )
+        .unwrap();
+
+        let config = UserConfig::load_from(&path).unwrap();
+        assert_eq!(
+            config.rendezvous_url.as_deref(),
+            Some("ws://rv.internal:4000/v1")
+        );
+        assert_eq!(config.relay_url.as_deref(), Some("wss://relay.internal"));
+        assert!(config.audit);
+    }
+
+    #[test]
+    fn loads_codes_section() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[codes]\nwordlist = \"/etc/enseal/words.txt\"\n").unwrap();
+        let config = UserConfig::load_from(&path).unwrap();
+        assert_eq!(
+            config.codes.wordlist.as_deref(),
+            Some(Path::new("/etc/enseal/words.txt"))
+        );
+        assert!(!config.codes.nato);
+    }
+
+    #[test]
+    fn loads_fingerprint_format() {
+        use crate::keys::identity::FingerprintFormat;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "fingerprint_format = \"colon\"\n").unwrap();
+        let config = UserConfig::load_from(&path).unwrap();
+        assert_eq!(config.fingerprint_format, FingerprintFormat::Colon);
+        assert_eq!(
+            UserConfig::default().fingerprint_format,
+            FingerprintFormat::Base64
+        );
+    }
+
+    #[test]
+    fn missing_file_is_empty_and_typos_are_errors() {
+        let dir = TempDir::new().unwrap();
+        let config = UserConfig::load_from(&dir.path().join("config.toml")).unwrap();
+        assert!(config.rendezvous_url.is_none() && config.relay_url.is_none());
+        assert!(!config.audit);
+
+        let path = dir.path().join("bad.toml");
+        std::fs::write(&path, "relay = \"wss://relay.internal\"\n").unwrap();
+        assert!(UserConfig::load_from(&path).is_err());
+    }
+
+    #[test]
+    fn explicit_relay_wins() {
+        let configured = Some("wss://relay.internal".to_string());
+        assert_eq!(
+            pick(Some("wss://cli".to_string()), &configured).as_deref(),
+            Some("wss://cli")
+        );
+        assert_eq!(
+            pick(None, &configured).as_deref(),
+            Some("wss://relay.internal")
+        );
+        assert_eq!(pick(None, &None), None);
+    }
+}
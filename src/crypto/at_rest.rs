@@ -4,9 +4,15 @@ use anyhow::{bail, Context, Result};
 use base64::Engine;
 
 use crate::env::{Entry, EnvFile};
+use crate::error::EnsealError;
 
 const PER_VAR_PREFIX: &str = "ENC[age:";
 const PER_VAR_SUFFIX: &str = "]";
+const ENC_TO_DIRECTIVE: &str = "enc-to:";
+const ARMOR_BEGIN: &[u8] = b"-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// An age recipient that can be shared across encryption threads.
+pub type SharedRecipient = dyn age::Recipient + Send + Sync;
 
 // ---------------------------------------------------------------------------
 // Whole-file encryption
@@ -14,15 +20,28 @@ const PER_VAR_SUFFIX: &str = "]";
 
 /// Encrypt an entire .env file to one or more age recipients.
 /// Returns the raw age ciphertext bytes.
-pub fn encrypt_whole_file(
+pub fn encrypt_whole_file(plaintext: &[u8], recipients: &[&SharedRecipient]) -> Result<Vec<u8>> {
+    age_encrypt_multi(plaintext, recipients, age::armor::Format::Binary)
+}
+
+/// Like [`encrypt_whole_file`], but ASCII-armored
+/// (`-----BEGIN AGE ENCRYPTED FILE-----`) for pasting into text configs.
+pub fn encrypt_whole_file_armored(
     plaintext: &[u8],
-    recipients: &[&age::x25519::Recipient],
+    recipients: &[&SharedRecipient],
 ) -> Result<Vec<u8>> {
-    age_encrypt_multi(plaintext, recipients)
+    age_encrypt_multi(plaintext, recipients, age::armor::Format::AsciiArmor)
 }
 
-/// Decrypt a whole-file age ciphertext with the given identity.
-pub fn decrypt_whole_file(ciphertext: &[u8], identity: &age::x25519::Identity) -> Result<Vec<u8>> {
+/// Decrypt a whole-file ciphertext (binary or armored age, or a
+/// `--format compat` sealed box) with the given identity.
+pub fn decrypt_whole_file(
+    ciphertext: &[u8],
+    identity: &age::x25519::Identity,
+) -> Result<Vec<u8>, EnsealError> {
+    if super::sealed_box::is_sealed_box(ciphertext) {
+        return super::sealed_box::open(ciphertext, identity);
+    }
     age_decrypt(ciphertext, identity)
 }
 
@@ -32,53 +51,182 @@ pub fn decrypt_whole_file(ciphertext: &[u8], identity: &age::x25519::Identity) -
 
 /// Encrypt an EnvFile per-variable: keys stay visible, values become `ENC[age:...]`.
 /// Returns a new EnvFile where each value is individually encrypted.
-pub fn encrypt_per_var(env: &EnvFile, recipients: &[&age::x25519::Recipient]) -> Result<EnvFile> {
-    let mut result = EnvFile::new();
+/// Fails if the file contains `# enc-to:` directives; use [`encrypt_per_var_routed`].
+#[allow(dead_code)]
+pub fn encrypt_per_var(env: &EnvFile, recipients: &[&SharedRecipient]) -> Result<EnvFile> {
+    encrypt_per_var_routed(env, recipients, |name| {
+        bail!("'# enc-to: {}' directive cannot be resolved here", name)
+    })
+}
 
-    for entry in &env.entries {
+/// Like [`encrypt_per_var`], but a `# enc-to: <name>[, <name>...]` comment
+/// routes the next variable to the named recipients instead of the defaults.
+/// `resolve` maps each name to its age recipients (alias, group, or trusted key).
+pub fn encrypt_per_var_routed<F>(
+    env: &EnvFile,
+    recipients: &[&SharedRecipient],
+    resolve: F,
+) -> Result<EnvFile>
+where
+    F: Fn(&str) -> Result<Vec<Box<SharedRecipient>>>,
+{
+    encrypt_per_var_with(env, recipients, resolve, PARALLEL_THRESHOLD)
+}
+
+/// Files with fewer variables than this are encrypted on the calling
+/// thread; for them the thread pool costs more than it saves.
+const PARALLEL_THRESHOLD: usize = 16;
+
+fn encrypt_per_var_with<F>(
+    env: &EnvFile,
+    recipients: &[&SharedRecipient],
+    resolve: F,
+    parallel_threshold: usize,
+) -> Result<EnvFile>
+where
+    F: Fn(&str) -> Result<Vec<Box<SharedRecipient>>>,
+{
+    // Resolve `enc-to` routing in file order first; only the encryption
+    // itself runs in parallel. Each job is (entry index, value, index into
+    // `scoped` or None for the default recipients).
+    let mut scoped: Vec<Vec<Box<SharedRecipient>>> = Vec::new();
+    let mut jobs: Vec<(usize, &str, Option<usize>)> = Vec::new();
+    let mut directive: Option<Vec<String>> = None;
+
+    for (index, entry) in env.entries.iter().enumerate() {
         match entry {
-            Entry::KeyValue { key, value } => {
-                let ciphertext = age_encrypt_multi(value.as_bytes(), recipients)?;
-                let encoded = base64::engine::general_purpose::STANDARD.encode(&ciphertext);
-                result.entries.push(Entry::KeyValue {
-                    key: key.clone(),
-                    value: format!("{}{}{}", PER_VAR_PREFIX, encoded, PER_VAR_SUFFIX),
-                });
+            Entry::KeyValue {
+                key,
+                value,
+                comment,
+            } => {
+                // A directive can also sit in the comment block attached to the key
+                for line in comment.iter().flat_map(|c| c.lines()) {
+                    if let Some(names) = parse_enc_to(line)? {
+                        directive = Some(names);
+                    }
+                }
+                let route = match directive.take() {
+                    Some(names) => {
+                        let mut routed = Vec::new();
+                        for name in &names {
+                            routed.extend(resolve(name).with_context(|| {
+                                format!("failed to resolve enc-to '{}' for '{}'", name, key)
+                            })?);
+                        }
+                        scoped.push(routed);
+                        Some(scoped.len() - 1)
+                    }
+                    None => None,
+                };
+                jobs.push((index, value, route));
             }
-            other => {
-                result.entries.push(other.clone());
+            Entry::Comment(text) => {
+                if let Some(names) = parse_enc_to(text)? {
+                    directive = Some(names);
+                }
             }
+            Entry::Blank => {}
         }
     }
 
+    if directive.is_some() {
+        bail!("'# enc-to:' directive at end of file is not followed by a variable");
+    }
+
+    let encrypt = |&(_, value, route): &(usize, &str, Option<usize>)| -> Result<String> {
+        let ciphertext = match route {
+            Some(set) => {
+                let routed: Vec<&SharedRecipient> =
+                    scoped[set].iter().map(|r| r.as_ref()).collect();
+                age_encrypt_multi(value.as_bytes(), &routed, age::armor::Format::Binary)?
+            }
+            None => age_encrypt_multi(value.as_bytes(), recipients, age::armor::Format::Binary)?,
+        };
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&ciphertext);
+        Ok(format!("{}{}{}", PER_VAR_PREFIX, encoded, PER_VAR_SUFFIX))
+    };
+    let encrypted: Vec<String> = if jobs.len() >= parallel_threshold {
+        use rayon::prelude::*;
+        jobs.par_iter().map(encrypt).collect::<Result<_>>()?
+    } else {
+        jobs.iter().map(encrypt).collect::<Result<_>>()?
+    };
+
+    // Comments and blanks stay where they were; only values change
+    let mut result = EnvFile::new();
+    result.entries = env.entries.clone();
+    for ((index, _, _), ciphertext) in jobs.iter().zip(encrypted) {
+        if let Entry::KeyValue { value, .. } = &mut result.entries[*index] {
+            *value = ciphertext;
+        }
+    }
     Ok(result)
 }
 
+/// Parse an `# enc-to: a, b` comment into its recipient names.
+/// Returns `None` for ordinary comments.
+fn parse_enc_to(comment: &str) -> Result<Option<Vec<String>>> {
+    let Some(body) = comment.trim().strip_prefix('#') else {
+        return Ok(None);
+    };
+    let Some(list) = body.trim().strip_prefix(ENC_TO_DIRECTIVE) else {
+        return Ok(None);
+    };
+    let names: Vec<String> = list
+        .split(',')
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(String::from)
+        .collect();
+    if names.is_empty() {
+        bail!("'# enc-to:' directive has no recipients");
+    }
+    Ok(Some(names))
+}
+
 /// Decrypt an EnvFile where values are `ENC[age:...]`.
 /// Returns a new EnvFile with decrypted plaintext values.
-pub fn decrypt_per_var(env: &EnvFile, identity: &age::x25519::Identity) -> Result<EnvFile> {
+pub fn decrypt_per_var(
+    env: &EnvFile,
+    identity: &age::x25519::Identity,
+) -> Result<EnvFile, EnsealError> {
     let mut result = EnvFile::new();
 
     for entry in &env.entries {
         match entry {
-            Entry::KeyValue { key, value } => {
+            Entry::KeyValue {
+                key,
+                value,
+                comment,
+            } => {
                 let decrypted_value = if is_encrypted_value(value) {
                     let encoded = &value[PER_VAR_PREFIX.len()..value.len() - PER_VAR_SUFFIX.len()];
                     if encoded.len() > 1024 * 1024 {
-                        bail!(
+                        return Err(EnsealError::Decrypt(format!(
                             "encrypted value for '{}' exceeds maximum size (1 MB encoded)",
                             key
-                        );
+                        )));
                     }
                     let ciphertext = base64::engine::general_purpose::STANDARD
                         .decode(encoded)
-                        .with_context(|| {
-                            format!("invalid base64 in encrypted value for '{}'", key)
+                        .map_err(|e| {
+                            EnsealError::Parse(format!(
+                                "invalid base64 in encrypted value for '{}': {}",
+                                key, e
+                            ))
                         })?;
-                    let plaintext = age_decrypt(&ciphertext, identity)
-                        .with_context(|| format!("failed to decrypt value for '{}'", key))?;
-                    String::from_utf8(plaintext).with_context(|| {
-                        format!("decrypted value for '{}' is not valid UTF-8", key)
+                    let plaintext = age_decrypt(&ciphertext, identity).map_err(|e| {
+                        EnsealError::Decrypt(format!(
+                            "failed to decrypt value for '{}': {}",
+                            key, e
+                        ))
+                    })?;
+                    String::from_utf8(plaintext).map_err(|_| {
+                        EnsealError::Decrypt(format!(
+                            "decrypted value for '{}' is not valid UTF-8",
+                            key
+                        ))
                     })?
                 } else {
                     value.clone()
@@ -87,6 +235,7 @@ pub fn decrypt_per_var(env: &EnvFile, identity: &age::x25519::Identity) -> Resul
                 result.entries.push(Entry::KeyValue {
                     key: key.clone(),
                     value: decrypted_value,
+                    comment: comment.clone(),
                 });
             }
             other => {
@@ -125,16 +274,28 @@ pub fn is_per_var_encrypted(content: &str) -> bool {
     })
 }
 
-/// Detect whether content is an age-encrypted file (binary header check).
+/// Detect whether content is an age-encrypted file, binary or armored
+/// (header check).
 pub fn is_age_encrypted(content: &[u8]) -> bool {
     content.starts_with(b"age-encryption.org/v1")
+        || content.trim_ascii_start().starts_with(ARMOR_BEGIN)
+}
+
+/// Detect whole-file ciphertext [`decrypt_whole_file`] can open: age or a
+/// compat sealed box.
+pub fn is_whole_file_encrypted(content: &[u8]) -> bool {
+    is_age_encrypted(content) || super::sealed_box::is_sealed_box(content)
 }
 
 // ---------------------------------------------------------------------------
 // Age helpers (multi-recipient)
 // ---------------------------------------------------------------------------
 
-fn age_encrypt_multi(data: &[u8], recipients: &[&age::x25519::Recipient]) -> Result<Vec<u8>> {
+fn age_encrypt_multi(
+    data: &[u8],
+    recipients: &[&SharedRecipient],
+    format: age::armor::Format,
+) -> Result<Vec<u8>> {
     if recipients.is_empty() {
         bail!("at least one recipient is required for encryption");
     }
@@ -145,8 +306,10 @@ fn age_encrypt_multi(data: &[u8], recipients: &[&age::x25519::Recipient]) -> Res
         .map_err(|e| anyhow::anyhow!("failed to create encryptor: {}", e))?;
 
     let mut encrypted = vec![];
+    let armored = age::armor::ArmoredWriter::wrap_output(&mut encrypted, format)
+        .context("failed to create age armor writer")?;
     let mut writer = encryptor
-        .wrap_output(&mut encrypted)
+        .wrap_output(armored)
         .context("failed to create age encryptor")?;
 
     writer
@@ -154,22 +317,28 @@ fn age_encrypt_multi(data: &[u8], recipients: &[&age::x25519::Recipient]) -> Res
         .context("failed to write age ciphertext")?;
     writer
         .finish()
+        .and_then(|armored| armored.finish())
         .context("failed to finalize age encryption")?;
 
     Ok(encrypted)
 }
 
-fn age_decrypt(ciphertext: &[u8], identity: &age::x25519::Identity) -> Result<Vec<u8>> {
-    let decryptor = age::Decryptor::new(ciphertext).context("failed to read age header")?;
+fn age_decrypt(
+    ciphertext: &[u8],
+    identity: &age::x25519::Identity,
+) -> Result<Vec<u8>, EnsealError> {
+    // ArmoredReader passes binary input through unchanged
+    let decryptor = age::Decryptor::new(age::armor::ArmoredReader::new(ciphertext))
+        .map_err(|e| EnsealError::Decrypt(format!("failed to read age header: {}", e)))?;
 
     let mut reader = decryptor
         .decrypt(std::iter::once(identity as &dyn age::Identity))
-        .map_err(|e| anyhow::anyhow!("age decryption failed: {}", e))?;
+        .map_err(|e| EnsealError::Decrypt(format!("age decryption failed: {}", e)))?;
 
     let mut plaintext = vec![];
     reader
         .read_to_end(&mut plaintext)
-        .context("failed to read decrypted data")?;
+        .map_err(|e| EnsealError::Decrypt(format!("failed to read decrypted data: {}", e)))?;
 
     Ok(plaintext)
 }
@@ -204,6 +373,78 @@ mod tests {
         assert!(is_age_encrypted(&ciphertext));
     }
 
+    #[test]
+    fn parallel_per_var_matches_sequential() {
+        let ops = EnsealIdentity::generate();
+        let id = EnsealIdentity::generate();
+        let mut content = String::from("# header\n\n");
+        for i in 0..40 {
+            if i % 10 == 0 {
+                content.push_str("# enc-to: ops\n");
+            }
+            content.push_str(&format!("VAR_{}=value-{}\n", i, i));
+            if i % 7 == 0 {
+                content.push('\n');
+            }
+        }
+        let env = parser::parse(&content).unwrap();
+        let resolve = |_: &str| -> Result<Vec<Box<SharedRecipient>>> {
+            Ok(vec![Box::new(ops.age_recipient.clone())])
+        };
+
+        let parallel = encrypt_per_var_with(&env, &[&id.age_recipient], resolve, 0).unwrap();
+        let sequential =
+            encrypt_per_var_with(&env, &[&id.age_recipient], resolve, usize::MAX).unwrap();
+
+        // Same layout: comments and blanks in place, keys in order
+        assert_eq!(parallel.entries.len(), env.entries.len());
+        for (out, orig) in parallel.entries.iter().zip(&env.entries) {
+            match (out, orig) {
+                (
+                    Entry::KeyValue { key, comment, .. },
+                    Entry::KeyValue {
+                        key: k, comment: c, ..
+                    },
+                ) => assert!(key == k && comment == c),
+                (Entry::Comment(a), Entry::Comment(b)) => assert_eq!(a, b),
+                (Entry::Blank, Entry::Blank) => {}
+                _ => panic!("entry kinds differ"),
+            }
+        }
+
+        // Both decrypt to the original; routed values only open for ops
+        let routed: Vec<String> = (0..40).step_by(10).map(|i| format!("VAR_{}", i)).collect();
+        for encrypted in [&parallel, &sequential] {
+            let ops_view = decrypt_per_var(&keep(encrypted, &routed), &ops.age_identity).unwrap();
+            let id_view = decrypt_per_var(&without(encrypted, &routed), &id.age_identity).unwrap();
+            for (key, value) in ops_view.vars().into_iter().chain(id_view.vars()) {
+                assert_eq!(value, format!("value-{}", &key[4..]));
+            }
+            assert_eq!(ops_view.var_count() + id_view.var_count(), 40);
+        }
+    }
+
+    /// Only the variables named in `keys`.
+    fn keep(env: &EnvFile, keys: &[String]) -> EnvFile {
+        filter(env, |k| keys.iter().any(|x| x == k))
+    }
+
+    /// Every variable except those named in `keys`.
+    fn without(env: &EnvFile, keys: &[String]) -> EnvFile {
+        filter(env, |k| !keys.iter().any(|x| x == k))
+    }
+
+    fn filter(env: &EnvFile, pred: impl Fn(&str) -> bool) -> EnvFile {
+        let mut out = EnvFile::new();
+        out.entries = env
+            .entries
+            .iter()
+            .filter(|e| matches!(e, Entry::KeyValue { key, .. } if pred(key)))
+            .cloned()
+            .collect();
+        out
+    }
+
     #[test]
     fn per_var_round_trip() {
         let id = EnsealIdentity::generate();
@@ -240,9 +481,12 @@ mod tests {
         let env = parser::parse("# comment\nKEY=value\n\nOTHER=stuff\n").unwrap();
 
         let encrypted = encrypt_per_var(&env, &[&id.age_recipient]).unwrap();
-        assert_eq!(encrypted.entries.len(), 4); // comment, kv, blank, kv
-        assert!(matches!(encrypted.entries[0], Entry::Comment(_)));
-        assert!(matches!(encrypted.entries[2], Entry::Blank));
+        assert_eq!(encrypted.entries.len(), 3); // kv (with comment), blank, kv
+        assert!(matches!(
+            &encrypted.entries[0],
+            Entry::KeyValue { comment: Some(c), .. } if c == "# comment"
+        ));
+        assert!(matches!(encrypted.entries[1], Entry::Blank));
     }
 
     #[test]
@@ -296,7 +540,10 @@ mod tests {
         let wrong = EnsealIdentity::generate();
 
         let ciphertext = encrypt_whole_file(b"secret", &[&id.age_recipient]).unwrap();
-        assert!(decrypt_whole_file(&ciphertext, &wrong.age_identity).is_err());
+        assert!(matches!(
+            decrypt_whole_file(&ciphertext, &wrong.age_identity),
+            Err(EnsealError::Decrypt(_))
+        ));
     }
 
     #[test]
@@ -306,7 +553,76 @@ mod tests {
         let env = parser::parse("SECRET=value\n").unwrap();
 
         let encrypted = encrypt_per_var(&env, &[&id.age_recipient]).unwrap();
-        assert!(decrypt_per_var(&encrypted, &wrong.age_identity).is_err());
+        assert!(matches!(
+            decrypt_per_var(&encrypted, &wrong.age_identity),
+            Err(EnsealError::Decrypt(_))
+        ));
+    }
+
+    /// Decrypt a single variable out of a per-var encrypted file.
+    fn decrypt_one(env: &EnvFile, key: &str, identity: &age::x25519::Identity) -> Result<String> {
+        let mut single = EnvFile::new();
+        single.entries.extend(
+            env.entries
+                .iter()
+                .filter(|e| matches!(e, Entry::KeyValue { key: k, .. } if k == key))
+                .cloned(),
+        );
+        let decrypted = decrypt_per_var(&single, identity)?;
+        Ok(decrypted.vars()[0].1.to_string())
+    }
+
+    #[test]
+    fn enc_to_directive_scopes_single_value() {
+        let default = EnsealIdentity::generate();
+        let ops = EnsealIdentity::generate();
+        let env =
+            parser::parse("APP=demo\n# enc-to: ops\nDB_PASSWORD=hunter2\nPORT=8080\n").unwrap();
+
+        let encrypted = encrypt_per_var_routed(&env, &[&default.age_recipient], |name| {
+            assert_eq!(name, "ops");
+            Ok(vec![Box::new(ops.age_recipient.clone())])
+        })
+        .unwrap();
+
+        // The directive comment is kept so re-encryption routes the same way
+        assert!(encrypted.to_string().contains("# enc-to: ops"));
+
+        // Only ops can decrypt the scoped value
+        assert_eq!(
+            decrypt_one(&encrypted, "DB_PASSWORD", &ops.age_identity).unwrap(),
+            "hunter2"
+        );
+        assert!(decrypt_one(&encrypted, "DB_PASSWORD", &default.age_identity).is_err());
+
+        // The directive does not carry over to later variables
+        assert_eq!(
+            decrypt_one(&encrypted, "PORT", &default.age_identity).unwrap(),
+            "8080"
+        );
+        assert!(decrypt_one(&encrypted, "PORT", &ops.age_identity).is_err());
+        assert!(decrypt_one(&encrypted, "APP", &ops.age_identity).is_err());
+    }
+
+    #[test]
+    fn enc_to_directive_parsing() {
+        assert_eq!(
+            parse_enc_to("# enc-to: ops, alice").unwrap(),
+            Some(vec!["ops".to_string(), "alice".to_string()])
+        );
+        assert_eq!(
+            parse_enc_to("#enc-to:ops").unwrap(),
+            Some(vec!["ops".into()])
+        );
+        assert_eq!(parse_enc_to("# regular comment").unwrap(), None);
+        assert!(parse_enc_to("# enc-to:").is_err());
+    }
+
+    #[test]
+    fn dangling_enc_to_directive_rejected() {
+        let id = EnsealIdentity::generate();
+        let env = parser::parse("KEY=value\n# enc-to: ops\n").unwrap();
+        assert!(encrypt_per_var(&env, &[&id.age_recipient]).is_err());
     }
 
     #[test]
@@ -330,6 +646,33 @@ mod tests {
     #[test]
     fn detection_age_format() {
         assert!(is_age_encrypted(b"age-encryption.org/v1\nsomething"));
+        assert!(is_age_encrypted(
+            b"\n-----BEGIN AGE ENCRYPTED FILE-----\nYWdl\n"
+        ));
         assert!(!is_age_encrypted(b"KEY=value\n"));
     }
+
+    #[test]
+    fn armored_round_trip() {
+        let id = EnsealIdentity::generate();
+        let plaintext = b"SECRET=hunter2\nAPI_KEY=abc123\n";
+
+        let armored = encrypt_whole_file_armored(plaintext, &[&id.age_recipient]).unwrap();
+        let text = std::str::from_utf8(&armored).unwrap();
+        assert!(text.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+        assert!(text
+            .trim_end()
+            .ends_with("-----END AGE ENCRYPTED FILE-----"));
+        assert!(!text.contains("hunter2"));
+
+        let binary = encrypt_whole_file(plaintext, &[&id.age_recipient]).unwrap();
+        for ciphertext in [&armored, &binary] {
+            assert!(is_age_encrypted(ciphertext));
+            assert!(is_whole_file_encrypted(ciphertext));
+            assert_eq!(
+                decrypt_whole_file(ciphertext, &id.age_identity).unwrap(),
+                plaintext
+            );
+        }
+    }
 }
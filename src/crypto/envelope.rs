@@ -1,6 +1,8 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use anyhow::{bail, Context, Result};
+use crate::error::{EnsealError, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
@@ -35,6 +37,14 @@ pub struct Metadata {
     /// Unix epoch seconds when the envelope was created.
     #[serde(default)]
     pub created_at: u64,
+    /// Unix epoch seconds after which the sender wants the envelope
+    /// rejected. Overrides the receiver's max age when present.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    /// Random per-transfer value (hex), so receivers can reject a replayed
+    /// copy of an envelope they already accepted. Absent from older senders.
+    #[serde(default)]
+    pub nonce: Option<String>,
 }
 
 impl Envelope {
@@ -48,7 +58,7 @@ impl Envelope {
                 Some(env.var_count())
             }
             PayloadFormat::Kv => Some(content.lines().filter(|l| l.contains('=')).count()),
-            PayloadFormat::Raw => None,
+            PayloadFormat::Raw | PayloadFormat::Binary => None,
         };
 
         let created_at = SystemTime::now()
@@ -65,68 +75,146 @@ impl Envelope {
                 sha256,
                 project: None,
                 created_at,
+                expires_at: None,
+                nonce: Some(hex::encode(rand::random::<[u8; 16]>())),
             },
             payload: content.to_string(),
         })
     }
 
-    /// Check that the envelope is not older than `max_age_secs`.
+    /// Tag the envelope with the project its secrets belong to.
+    pub fn with_project(mut self, project: Option<String>) -> Self {
+        self.metadata.project = project;
+        self
+    }
+
+    /// Have receivers reject the envelope `secs` after its creation.
+    pub fn with_expiry(mut self, secs: Option<u64>) -> Self {
+        self.metadata.expires_at = secs.map(|secs| self.metadata.created_at.saturating_add(secs));
+        self
+    }
+
+    /// Seal arbitrary bytes. The payload is stored base64-encoded; the
+    /// integrity hash covers the raw bytes.
+    pub fn seal_binary(bytes: &[u8], label: Option<String>) -> Result<Self> {
+        let mut envelope = Self::seal(&BASE64.encode(bytes), PayloadFormat::Binary, label)?;
+        envelope.metadata.sha256 = hex_sha256(bytes);
+        Ok(envelope)
+    }
+
+    /// Whether the payload carries base64-encoded binary bytes.
+    pub fn is_binary(&self) -> bool {
+        self.format == PayloadFormat::Binary
+    }
+
+    /// The payload as bytes, decoding binary payloads.
+    pub fn payload_bytes(&self) -> Result<Vec<u8>> {
+        if !self.is_binary() {
+            return Ok(self.payload.as_bytes().to_vec());
+        }
+        BASE64
+            .decode(&self.payload)
+            .map_err(|e| EnsealError::Parse(format!("invalid binary payload: {}", e)))
+    }
+
+    /// Creation time with relative age, for display (e.g. "... (3m ago)").
+    pub fn created_display(&self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        crate::ui::display::timestamp_with_age(self.metadata.created_at, now)
+    }
+
+    /// Default freshness window for envelopes received over the network.
+    pub const NETWORK_MAX_AGE: u64 = 300;
+
+    /// Default freshness window for file drops, which may sit on disk longer.
+    pub const FILEDROP_MAX_AGE: u64 = 86400;
+
+    /// How far in the future a timestamp may be before it is rejected.
+    pub const CLOCK_SKEW_TOLERANCE: u64 = 60;
+
+    /// Check that the envelope is not older than `max_age_secs`, or past
+    /// the sender's `expires_at` when one is set.
     /// Returns an error if the envelope is too old (replay protection).
     pub fn check_age(&self, max_age_secs: u64) -> Result<()> {
         if self.metadata.created_at == 0 {
-            bail!("envelope has no timestamp (created_at is 0). This may indicate tampering or a replay attempt");
+            return Err(EnsealError::Expired(
+                "envelope has no timestamp (created_at is 0). This may indicate tampering or a replay attempt".to_string(),
+            ));
         }
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
-        // Reject future timestamps (clock skew tolerance: 60 seconds)
-        if self.metadata.created_at > now + 60 {
-            bail!("envelope timestamp is in the future. Clock skew or tampering suspected");
+        // Reject future timestamps, allowing for some clock skew
+        if self.metadata.created_at > now + Self::CLOCK_SKEW_TOLERANCE {
+            return Err(EnsealError::Expired(
+                "envelope timestamp is in the future. Clock skew or tampering suspected"
+                    .to_string(),
+            ));
+        }
+        if let Some(expires_at) = self.metadata.expires_at {
+            if now > expires_at {
+                return Err(EnsealError::Expired(format!(
+                    "envelope expired {} seconds ago (sender set an expiry of {} seconds)",
+                    now - expires_at,
+                    expires_at.saturating_sub(self.metadata.created_at)
+                )));
+            }
+            return Ok(());
         }
         let age = now.saturating_sub(self.metadata.created_at);
         if age > max_age_secs {
-            bail!(
+            return Err(EnsealError::Expired(format!(
                 "envelope expired: created {} seconds ago (max {})",
-                age,
-                max_age_secs
-            );
+                age, max_age_secs
+            )));
         }
         Ok(())
     }
 
     /// Serialize the envelope to JSON bytes for transfer.
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
-        serde_json::to_vec(self).context("failed to serialize envelope")
+        serde_json::to_vec(self)
+            .map_err(|e| EnsealError::Parse(format!("failed to serialize envelope: {}", e)))
     }
 
     /// Deserialize an envelope from JSON bytes.
     pub fn from_bytes(data: &[u8]) -> Result<Self> {
         if data.len() > 16 * 1024 * 1024 {
-            bail!("envelope data exceeds maximum size (16 MiB)");
+            return Err(EnsealError::Parse(
+                "envelope data exceeds maximum size (16 MiB)".to_string(),
+            ));
         }
 
-        let envelope: Self =
-            serde_json::from_slice(data).context("failed to deserialize envelope")?;
+        let envelope: Self = serde_json::from_slice(data)
+            .map_err(|e| EnsealError::Parse(format!("failed to deserialize envelope: {}", e)))?;
 
         // Validate version
         if envelope.version != 1 {
-            bail!("unsupported envelope version: {}", envelope.version);
+            return Err(EnsealError::Parse(format!(
+                "unsupported envelope version: {}",
+                envelope.version
+            )));
         }
 
         // Verify integrity
-        let expected_hash = hex_sha256(&envelope.payload);
+        let expected_hash = hex_sha256(&envelope.payload_bytes()?);
         if envelope.metadata.sha256 != expected_hash {
-            bail!("integrity check failed: payload hash mismatch");
+            return Err(EnsealError::SignatureVerification(
+                "integrity check failed: payload hash mismatch".to_string(),
+            ));
         }
 
         Ok(envelope)
     }
 }
 
-fn hex_sha256(data: &str) -> String {
+fn hex_sha256(data: impl AsRef<[u8]>) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(data.as_bytes());
+    hasher.update(data.as_ref());
     format!("{:x}", hasher.finalize())
 }
 
@@ -167,6 +255,105 @@ mod tests {
         envelope.payload = "SECRET=tampered".to_string();
 
         let bytes = envelope.to_bytes().unwrap();
-        assert!(Envelope::from_bytes(&bytes).is_err());
+        assert!(matches!(
+            Envelope::from_bytes(&bytes),
+            Err(EnsealError::SignatureVerification(_))
+        ));
+    }
+
+    #[test]
+    fn project_survives_round_trip() {
+        let envelope = Envelope::seal("KEY=value\n", PayloadFormat::Env, None)
+            .unwrap()
+            .with_project(Some("myapp".to_string()));
+
+        let restored = Envelope::from_bytes(&envelope.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.metadata.project.as_deref(), Some("myapp"));
+    }
+
+    #[test]
+    fn binary_payload_round_trip() {
+        use rand::RngCore;
+        let mut bytes = vec![0u8; 4096];
+        rand::thread_rng().fill_bytes(&mut bytes);
+
+        let envelope = Envelope::seal_binary(&bytes, Some("keystore".to_string())).unwrap();
+        assert_eq!(envelope.format, PayloadFormat::Binary);
+        assert_eq!(envelope.metadata.var_count, None);
+        assert_eq!(envelope.metadata.sha256, hex_sha256(&bytes));
+
+        let restored = Envelope::from_bytes(&envelope.to_bytes().unwrap()).unwrap();
+        assert!(restored.is_binary());
+        assert_eq!(restored.metadata.label.as_deref(), Some("keystore"));
+        assert_eq!(restored.payload_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn tampered_binary_payload_rejected() {
+        let mut envelope = Envelope::seal_binary(&[0u8, 1, 2, 255], None).unwrap();
+        envelope.payload = BASE64.encode([0u8, 1, 2, 254]);
+        let err = Envelope::from_bytes(&envelope.to_bytes().unwrap()).unwrap_err();
+        assert!(matches!(err, EnsealError::SignatureVerification(_)));
+    }
+
+    #[test]
+    fn envelope_without_project_field_still_parses() {
+        let content = "KEY=value\n";
+        let legacy = serde_json::json!({
+            "version": 1,
+            "format": "env",
+            "metadata": {
+                "var_count": 1,
+                "label": null,
+                "sha256": hex_sha256(content),
+                "created_at": 1,
+            },
+            "payload": content,
+        });
+
+        let restored = Envelope::from_bytes(legacy.to_string().as_bytes()).unwrap();
+        assert_eq!(restored.metadata.project, None);
+    }
+
+    #[test]
+    fn short_sender_expiry_is_rejected() {
+        let mut envelope = Envelope::seal("KEY=value\n", PayloadFormat::Env, None)
+            .unwrap()
+            .with_expiry(Some(30));
+        envelope.metadata.created_at -= 60;
+        envelope.metadata.expires_at = envelope.metadata.expires_at.map(|t| t - 60);
+
+        // Well inside the receiver's default window, but past the sender's expiry
+        let err = envelope.check_age(Envelope::NETWORK_MAX_AGE).unwrap_err();
+        assert!(matches!(err, EnsealError::Expired(_)));
+        assert!(err.to_string().contains("expiry of 30 seconds"));
+    }
+
+    #[test]
+    fn long_sender_expiry_is_accepted() {
+        let mut envelope = Envelope::seal("KEY=value\n", PayloadFormat::Env, None)
+            .unwrap()
+            .with_expiry(Some(3600));
+        envelope.metadata.created_at -= 600;
+        envelope.metadata.expires_at = envelope.metadata.expires_at.map(|t| t - 600);
+
+        let restored = Envelope::from_bytes(&envelope.to_bytes().unwrap()).unwrap();
+        assert!(restored.metadata.expires_at.is_some());
+        restored.check_age(Envelope::NETWORK_MAX_AGE).unwrap();
+    }
+
+    #[test]
+    fn malformed_and_stale_envelopes_have_distinct_errors() {
+        assert!(matches!(
+            Envelope::from_bytes(b"{\"version\": 1}"),
+            Err(EnsealError::Parse(_))
+        ));
+
+        let mut envelope = Envelope::seal("KEY=value\n", PayloadFormat::Env, None).unwrap();
+        envelope.metadata.created_at -= 600;
+        assert!(matches!(
+            envelope.check_age(Envelope::NETWORK_MAX_AGE),
+            Err(EnsealError::Expired(_))
+        ));
     }
 }
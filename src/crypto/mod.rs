@@ -1,3 +1,4 @@
 pub mod at_rest;
 pub mod envelope;
+pub mod sealed_box;
 pub mod signing;
@@ -0,0 +1,290 @@
+//! Sealed boxes: an encryption format independent of age, for systems that
+//! can't parse age files (`enseal encrypt --format compat`).
+//!
+//! The payload is encrypted once with XChaCha20-Poly1305 under a random
+//! 256-bit content key. For each recipient, the content key is wrapped with a
+//! key derived from X25519 between a per-message ephemeral key and the
+//! recipient's age X25519 public key (the raw 32 bytes behind `age1...`):
+//!
+//! ```text
+//! shared   = X25519(ephemeral_secret, recipient_public)
+//! wrap_key = HKDF-SHA256(ikm = shared, salt = epk || recipient_public, info = "enseal-sealed-box/v1")
+//! wrapped  = XChaCha20-Poly1305(wrap_key, key_nonce, content_key)
+//! body     = XChaCha20-Poly1305(content_key, nonce, plaintext)
+//! ```
+//!
+//! Serialized as a JSON object, binary fields in standard base64:
+//!
+//! ```text
+//! {
+//!   "format": "enseal-sealed-box/v1",
+//!   "alg": "X25519-HKDF-SHA256-XChaCha20Poly1305",
+//!   "epk": "<ephemeral X25519 public key, 32 bytes>",
+//!   "recipients": [{ "nonce": "<24 bytes>", "key": "<wrapped content key, 48 bytes>" }],
+//!   "nonce": "<24 bytes>",
+//!   "ciphertext": "<body, with 16-byte tag>"
+//! }
+//! ```
+//!
+//! Recipient stanzas carry no key identifier; a reader tries each in turn.
+
+use age::secrecy::ExposeSecret;
+use base64::Engine;
+use bech32::FromBase32;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::error::{EnsealError, Result};
+
+/// Value of the `format` field.
+pub const FORMAT: &str = "enseal-sealed-box/v1";
+/// Value of the `alg` field.
+pub const ALG: &str = "X25519-HKDF-SHA256-XChaCha20Poly1305";
+
+#[derive(Serialize, Deserialize)]
+struct SealedBox {
+    format: String,
+    alg: String,
+    epk: String,
+    recipients: Vec<Stanza>,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Stanza {
+    nonce: String,
+    key: String,
+}
+
+/// Encrypt `plaintext` to one or more age X25519 recipients as a sealed box.
+pub fn seal(plaintext: &[u8], recipients: &[&age::x25519::Recipient]) -> Result<Vec<u8>> {
+    if recipients.is_empty() {
+        return Err(EnsealError::Encrypt(
+            "at least one recipient is required for encryption".to_string(),
+        ));
+    }
+
+    let ephemeral = x25519_dalek::StaticSecret::random_from_rng(OsRng);
+    let epk = x25519_dalek::PublicKey::from(&ephemeral);
+
+    let mut content_key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(content_key.as_mut_slice());
+    let stanzas = recipients
+        .iter()
+        .map(|recipient| {
+            let recipient_pk = recipient_bytes(recipient)?;
+            let shared = ephemeral.diffie_hellman(&x25519_dalek::PublicKey::from(recipient_pk));
+            if !shared.was_contributory() {
+                return Err(EnsealError::Encrypt(format!(
+                    "recipient {} is not a usable X25519 key (low-order point)",
+                    recipient
+                )));
+            }
+            let wrap = wrap_cipher(shared.as_bytes(), epk.as_bytes(), &recipient_pk);
+            let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+            let key = wrap
+                .encrypt(&nonce, content_key.as_slice())
+                .map_err(|_| EnsealError::Encrypt("failed to wrap content key".to_string()))?;
+            Ok(Stanza {
+                nonce: b64(&nonce),
+                key: b64(&key),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(content_key.as_slice()))
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| EnsealError::Encrypt("sealed box encryption failed".to_string()))?;
+
+    let sealed = SealedBox {
+        format: FORMAT.to_string(),
+        alg: ALG.to_string(),
+        epk: b64(epk.as_bytes()),
+        recipients: stanzas,
+        nonce: b64(&nonce),
+        ciphertext: b64(&ciphertext),
+    };
+    let mut out = serde_json::to_vec_pretty(&sealed)
+        .map_err(|e| EnsealError::Encrypt(format!("failed to serialize sealed box: {}", e)))?;
+    out.push(b'\n');
+    Ok(out)
+}
+
+/// Decrypt a sealed box with an age X25519 identity.
+pub fn open(data: &[u8], identity: &age::x25519::Identity) -> Result<Vec<u8>> {
+    let sealed: SealedBox = serde_json::from_slice(data)
+        .map_err(|e| EnsealError::Parse(format!("invalid sealed box: {}", e)))?;
+    if sealed.format != FORMAT || sealed.alg != ALG {
+        return Err(EnsealError::Parse(format!(
+            "unsupported sealed box format '{}' ({})",
+            sealed.format, sealed.alg
+        )));
+    }
+
+    let epk: [u8; 32] = decode_array(&sealed.epk, "epk")?;
+    let secret = x25519_dalek::StaticSecret::from(*identity_bytes(identity)?);
+    let own_pk = x25519_dalek::PublicKey::from(&secret);
+    let shared = secret.diffie_hellman(&x25519_dalek::PublicKey::from(epk));
+    if !shared.was_contributory() {
+        return Err(EnsealError::Decrypt(
+            "sealed box ephemeral key is a low-order point".to_string(),
+        ));
+    }
+    let wrap = wrap_cipher(shared.as_bytes(), &epk, own_pk.as_bytes());
+
+    let content_key = sealed
+        .recipients
+        .iter()
+        .find_map(|stanza| {
+            let nonce: [u8; 24] = decode_array(&stanza.nonce, "nonce").ok()?;
+            let key = decode(&stanza.key, "key").ok()?;
+            wrap.decrypt(XNonce::from_slice(&nonce), key.as_slice())
+                .ok()
+                .map(Zeroizing::new)
+        })
+        .ok_or_else(|| {
+            EnsealError::Decrypt("no sealed box recipient matches your key".to_string())
+        })?;
+    let cipher = XChaCha20Poly1305::new_from_slice(content_key.as_slice())
+        .map_err(|_| EnsealError::Decrypt("invalid content key length".to_string()))?;
+
+    let nonce: [u8; 24] = decode_array(&sealed.nonce, "nonce")?;
+    let ciphertext = decode(&sealed.ciphertext, "ciphertext")?;
+    cipher
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| EnsealError::Decrypt("sealed box ciphertext is corrupt".to_string()))
+}
+
+/// Whether `data` looks like a sealed box (a JSON object with our `format`).
+pub fn is_sealed_box(data: &[u8]) -> bool {
+    #[derive(Deserialize)]
+    struct Header {
+        format: String,
+    }
+    serde_json::from_slice::<Header>(data).is_ok_and(|h| h.format == FORMAT)
+}
+
+fn wrap_cipher(shared: &[u8; 32], epk: &[u8; 32], recipient_pk: &[u8; 32]) -> XChaCha20Poly1305 {
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(epk);
+    salt[32..].copy_from_slice(recipient_pk);
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(FORMAT.as_bytes(), key.as_mut_slice())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    XChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+}
+
+/// The raw X25519 public key behind an `age1...` recipient.
+fn recipient_bytes(recipient: &age::x25519::Recipient) -> Result<[u8; 32]> {
+    bech32_bytes(&recipient.to_string())
+}
+
+/// The raw X25519 secret behind an `AGE-SECRET-KEY-1...` identity.
+fn identity_bytes(identity: &age::x25519::Identity) -> Result<Zeroizing<[u8; 32]>> {
+    bech32_bytes(identity.to_string().expose_secret()).map(Zeroizing::new)
+}
+
+fn bech32_bytes(encoded: &str) -> Result<[u8; 32]> {
+    let invalid = || EnsealError::Parse("invalid age X25519 key encoding".to_string());
+    let (_, data, _) = bech32::decode(encoded).map_err(|_| invalid())?;
+    let bytes = Zeroizing::new(Vec::<u8>::from_base32(&data).map_err(|_| invalid())?);
+    bytes.as_slice().try_into().map_err(|_| invalid())
+}
+
+fn b64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+fn decode(value: &str, field: &str) -> Result<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(value)
+        .map_err(|_| EnsealError::Parse(format!("invalid base64 in sealed box '{}'", field)))
+}
+
+fn decode_array<const N: usize>(value: &str, field: &str) -> Result<[u8; N]> {
+    decode(value, field)?
+        .try_into()
+        .map_err(|_| EnsealError::Parse(format!("sealed box '{}' must be {} bytes", field, N)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::identity::EnsealIdentity;
+
+    #[test]
+    fn seal_open_round_trip() {
+        let alice = EnsealIdentity::generate();
+        let bob = EnsealIdentity::generate();
+        let plaintext = b"API_KEY=abc123\nDB_PASS=hunter2\n";
+
+        let sealed = seal(plaintext, &[&alice.age_recipient, &bob.age_recipient]).unwrap();
+        assert!(is_sealed_box(&sealed));
+        assert!(!String::from_utf8_lossy(&sealed).contains("abc123"));
+
+        assert_eq!(open(&sealed, &alice.age_identity).unwrap(), plaintext);
+        assert_eq!(open(&sealed, &bob.age_identity).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn wrong_key_fails() {
+        let alice = EnsealIdentity::generate();
+        let mallory = EnsealIdentity::generate();
+        let sealed = seal(b"SECRET=1\n", &[&alice.age_recipient]).unwrap();
+
+        let err = open(&sealed, &mallory.age_identity).unwrap_err();
+        assert!(matches!(err, EnsealError::Decrypt(_)));
+    }
+
+    #[test]
+    fn tampered_ciphertext_fails() {
+        let alice = EnsealIdentity::generate();
+        let sealed = seal(b"SECRET=1\n", &[&alice.age_recipient]).unwrap();
+        let mut parsed: SealedBox = serde_json::from_slice(&sealed).unwrap();
+        let mut body = decode(&parsed.ciphertext, "ciphertext").unwrap();
+        body[0] ^= 1;
+        parsed.ciphertext = b64(&body);
+        let tampered = serde_json::to_vec(&parsed).unwrap();
+
+        assert!(matches!(
+            open(&tampered, &alice.age_identity),
+            Err(EnsealError::Decrypt(_))
+        ));
+    }
+
+    #[test]
+    fn low_order_points_are_rejected() {
+        use bech32::ToBase32;
+        use std::str::FromStr;
+
+        // The all-zero point yields an all-zero shared secret for any key
+        let zero = bech32::encode("age", [0u8; 32].to_base32(), bech32::Variant::Bech32).unwrap();
+        let recipient = age::x25519::Recipient::from_str(&zero).unwrap();
+        assert!(matches!(
+            seal(b"SECRET=1\n", &[&recipient]),
+            Err(EnsealError::Encrypt(_))
+        ));
+
+        let alice = EnsealIdentity::generate();
+        let sealed = seal(b"SECRET=1\n", &[&alice.age_recipient]).unwrap();
+        let mut parsed: SealedBox = serde_json::from_slice(&sealed).unwrap();
+        parsed.epk = b64(&[0u8; 32]);
+        let forged = serde_json::to_vec(&parsed).unwrap();
+        let err = open(&forged, &alice.age_identity).unwrap_err();
+        assert!(err.to_string().contains("low-order"), "{err}");
+    }
+
+    #[test]
+    fn age_files_are_not_sealed_boxes() {
+        assert!(!is_sealed_box(b"age-encryption.org/v1\n"));
+        assert!(!is_sealed_box(br#"{"format":"something-else"}"#));
+    }
+}
@@ -1,7 +1,8 @@
-use anyhow::{bail, Context, Result};
 use base64::Engine;
 use ed25519_dalek::{Signature, Signer, Verifier, VerifyingKey};
 
+use crate::crypto::envelope::Envelope;
+use crate::error::{EnsealError, Result};
 use crate::keys::identity::{EnsealIdentity, TrustedKey};
 
 /// A signed and encrypted identity-mode payload.
@@ -33,8 +34,7 @@ impl SignedEnvelope {
         // Sign the ciphertext
         let signature = sender.signing_key.sign(&ciphertext);
 
-        let sender_sign_pubkey = base64::engine::general_purpose::STANDARD
-            .encode(sender.signing_key.verifying_key().to_bytes());
+        let sender_sign_pubkey = sender.sign_pubkey_b64();
         let sender_age_pubkey = sender.age_recipient.to_string();
 
         Ok(Self {
@@ -53,38 +53,27 @@ impl SignedEnvelope {
         expected_sender: Option<&TrustedKey>,
     ) -> Result<Vec<u8>> {
         // Decode and verify the sender's signing key
-        let sign_bytes = base64::engine::general_purpose::STANDARD
-            .decode(&self.sender_sign_pubkey)
-            .context("invalid sender signing key encoding")?;
-        let sign_array: [u8; 32] = sign_bytes
-            .try_into()
-            .map_err(|_| anyhow::anyhow!("invalid sender signing key length"))?;
-        let verifying_key =
-            VerifyingKey::from_bytes(&sign_array).context("invalid sender signing key")?;
+        let verifying_key = decode_verifying_key(&self.sender_sign_pubkey)?;
 
         // If we have an expected sender, verify it matches
         if let Some(trusted) = expected_sender {
             if verifying_key != trusted.verifying_key {
-                bail!(
+                return Err(EnsealError::SignatureVerification(format!(
                     "sender key mismatch: expected {}, got a different key",
                     trusted.identity
-                );
+                )));
             }
         }
 
         // Verify signature over ciphertext
-        let sig_bytes = base64::engine::general_purpose::STANDARD
-            .decode(&self.signature)
-            .context("invalid signature encoding")?;
-        let sig_array: [u8; 64] = sig_bytes
-            .try_into()
-            .map_err(|_| anyhow::anyhow!("invalid signature length"))?;
-        let signature = Signature::from_bytes(&sig_array);
+        let signature = decode_signature(&self.signature)?;
 
         verifying_key
             .verify(&self.ciphertext, &signature)
             .map_err(|_| {
-                anyhow::anyhow!("signature verification failed: payload may be tampered")
+                EnsealError::SignatureVerification(
+                    "signature verification failed: payload may be tampered".to_string(),
+                )
             })?;
 
         // Decrypt with own age key
@@ -95,77 +84,240 @@ impl SignedEnvelope {
 
     /// Serialize to JSON bytes for wire transfer.
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
-        serde_json::to_vec(self).context("failed to serialize signed envelope")
+        serde_json::to_vec(self)
+            .map_err(|e| EnsealError::Parse(format!("failed to serialize signed envelope: {}", e)))
     }
 
     /// Deserialize from JSON bytes.
     pub fn from_bytes(data: &[u8]) -> Result<Self> {
         if data.len() > 16 * 1024 * 1024 {
-            bail!("signed envelope data exceeds maximum size (16 MiB)");
+            return Err(parse_error(
+                "signed envelope data exceeds maximum size (16 MiB)",
+            ));
         }
 
-        let envelope: Self =
-            serde_json::from_slice(data).context("failed to deserialize signed envelope")?;
+        let envelope: Self = serde_json::from_slice(data).map_err(|e| {
+            EnsealError::Parse(format!("failed to deserialize signed envelope: {}", e))
+        })?;
 
         // Validate field lengths to prevent memory exhaustion from crafted inputs.
         // Base64-encoded 32-byte key = ~44 chars; 64-byte signature = ~88 chars.
         if envelope.sender_sign_pubkey.len() > 100 {
-            bail!("sender signing key field too long");
+            return Err(parse_error("sender signing key field too long"));
         }
         if envelope.sender_age_pubkey.len() > 100 {
-            bail!("sender age key field too long");
+            return Err(parse_error("sender age key field too long"));
         }
         if envelope.signature.len() > 200 {
-            bail!("signature field too long");
+            return Err(parse_error("signature field too long"));
         }
         if envelope.ciphertext.len() > 16 * 1024 * 1024 {
-            bail!("ciphertext field too large (max 16 MiB)");
+            return Err(parse_error("ciphertext field too large (max 16 MiB)"));
         }
 
         Ok(envelope)
     }
 }
 
+/// Encrypt an envelope to `recipients`, sign it, and serialize for the wire.
+pub fn seal_signed(
+    envelope: &Envelope,
+    recipients: &[&age::x25519::Recipient],
+    sender: &EnsealIdentity,
+) -> Result<Vec<u8>> {
+    let inner_bytes = envelope.to_bytes()?;
+    SignedEnvelope::seal(&inner_bytes, recipients, sender)?.to_bytes()
+}
+
+/// Verify, decrypt and parse signed wire bytes.
+///
+/// If `expected_sender` is given, the payload must be signed by that key.
+/// `max_age` bounds the envelope's age in seconds; `None` skips the check.
+/// Returns the envelope and the sender's signing key (base64).
+pub fn open_signed(
+    bytes: &[u8],
+    identity: &EnsealIdentity,
+    expected_sender: Option<&TrustedKey>,
+    max_age: Option<u64>,
+) -> Result<(Envelope, String)> {
+    let signed = SignedEnvelope::from_bytes(bytes)?;
+    let sender_pubkey = signed.sender_sign_pubkey.clone();
+
+    let inner_bytes = signed.open(identity, expected_sender)?;
+    let envelope = Envelope::from_bytes(&inner_bytes)?;
+    if let Some(max_age) = max_age {
+        envelope.check_age(max_age)?;
+    }
+
+    Ok((envelope, sender_pubkey))
+}
+
+/// Domain separator for plaintext signatures, so a signature made by
+/// `enseal sign` can never be passed off as a [`SignedEnvelope`] signature.
+const PLAINTEXT_DOMAIN: &[u8] = b"enseal-signed-plaintext-v1\0";
+
+/// First line of a signed plaintext file.
+const PLAINTEXT_HEADER: &str = "# enseal signed file v1";
+
+/// A signed but unencrypted file, for integrity-critical config that isn't secret.
+///
+/// Text format: a header, `signer:` and `signature:` lines, a blank line, then
+/// the content verbatim.
+#[derive(Debug)]
+pub struct SignedPlaintext {
+    pub content: Vec<u8>,
+    /// Signer's ed25519 public key (base64).
+    pub sender_sign_pubkey: String,
+    /// Ed25519 signature over the domain separator and content (base64).
+    pub signature: String,
+}
+
+/// Sign `content` with the signer's ed25519 key, without encrypting it.
+pub fn sign_plaintext(content: &[u8], signer: &EnsealIdentity) -> SignedPlaintext {
+    let signature = signer.signing_key.sign(&plaintext_message(content));
+    SignedPlaintext {
+        content: content.to_vec(),
+        sender_sign_pubkey: signer.sign_pubkey_b64(),
+        signature: base64::engine::general_purpose::STANDARD.encode(signature.to_bytes()),
+    }
+}
+
+/// Verify a plaintext signature against the key embedded in it.
+/// If `expected_sender` is Some, the signer must also be that trusted key.
+pub fn verify_plaintext(
+    signed: &SignedPlaintext,
+    expected_sender: Option<&TrustedKey>,
+) -> Result<()> {
+    let verifying_key = decode_verifying_key(&signed.sender_sign_pubkey)?;
+    if let Some(trusted) = expected_sender {
+        if verifying_key != trusted.verifying_key {
+            return Err(EnsealError::SignatureVerification(format!(
+                "signer mismatch: expected {}, got a different key",
+                trusted.identity
+            )));
+        }
+    }
+    let signature = decode_signature(&signed.signature)?;
+    verifying_key
+        .verify(&plaintext_message(&signed.content), &signature)
+        .map_err(|_| {
+            EnsealError::SignatureVerification(
+                "signature verification failed: file may be tampered".to_string(),
+            )
+        })
+}
+
+impl SignedPlaintext {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = format!(
+            "{}\nsigner: {}\nsignature: {}\n\n",
+            PLAINTEXT_HEADER, self.sender_sign_pubkey, self.signature
+        )
+        .into_bytes();
+        out.extend_from_slice(&self.content);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let split = data
+            .windows(2)
+            .position(|w| w == b"\n\n")
+            .ok_or_else(|| parse_error("not an enseal signed file (no header)"))?;
+        let header = std::str::from_utf8(&data[..split])
+            .map_err(|_| parse_error("signed file header is not valid UTF-8"))?;
+        let mut lines = header.lines();
+        if lines.next() != Some(PLAINTEXT_HEADER) {
+            return Err(parse_error("not an enseal signed file (bad header)"));
+        }
+        let mut field = |name: &str| {
+            lines
+                .next()
+                .and_then(|l| l.strip_prefix(name))
+                .map(|v| v.trim().to_string())
+                .ok_or_else(|| EnsealError::Parse(format!("signed file is missing '{}'", name)))
+        };
+        let sender_sign_pubkey = field("signer:")?;
+        let signature = field("signature:")?;
+        Ok(Self {
+            content: data[split + 2..].to_vec(),
+            sender_sign_pubkey,
+            signature,
+        })
+    }
+}
+
+fn plaintext_message(content: &[u8]) -> Vec<u8> {
+    [PLAINTEXT_DOMAIN, content].concat()
+}
+
+fn decode_verifying_key(b64: &str) -> Result<VerifyingKey> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(b64)
+        .map_err(|e| EnsealError::Parse(format!("invalid sender signing key encoding: {}", e)))?;
+    let array: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| parse_error("invalid sender signing key length"))?;
+    VerifyingKey::from_bytes(&array)
+        .map_err(|e| EnsealError::Parse(format!("invalid sender signing key: {}", e)))
+}
+
+fn decode_signature(b64: &str) -> Result<Signature> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(b64)
+        .map_err(|e| EnsealError::Parse(format!("invalid signature encoding: {}", e)))?;
+    let array: [u8; 64] = bytes
+        .try_into()
+        .map_err(|_| parse_error("invalid signature length"))?;
+    Ok(Signature::from_bytes(&array))
+}
+
+fn parse_error(message: &str) -> EnsealError {
+    EnsealError::Parse(message.to_string())
+}
+
 /// Encrypt data with age to one or more recipients.
 fn age_encrypt_multi(data: &[u8], recipients: &[&age::x25519::Recipient]) -> Result<Vec<u8>> {
     if recipients.is_empty() {
-        bail!("at least one recipient is required for encryption");
+        return Err(EnsealError::Encrypt(
+            "at least one recipient is required for encryption".to_string(),
+        ));
     }
 
     let recipients_iter = recipients.iter().map(|r| *r as &dyn age::Recipient);
 
     let encryptor = age::Encryptor::with_recipients(recipients_iter)
-        .map_err(|e| anyhow::anyhow!("failed to create encryptor: {}", e))?;
+        .map_err(|e| EnsealError::Encrypt(format!("failed to create encryptor: {}", e)))?;
 
     let mut encrypted = vec![];
     let mut writer = encryptor
         .wrap_output(&mut encrypted)
-        .context("failed to create age encryptor")?;
+        .map_err(|e| EnsealError::Encrypt(format!("failed to create age encryptor: {}", e)))?;
 
     use std::io::Write;
     writer
         .write_all(data)
-        .context("failed to write age ciphertext")?;
+        .map_err(|e| EnsealError::Encrypt(format!("failed to write age ciphertext: {}", e)))?;
     writer
         .finish()
-        .context("failed to finalize age encryption")?;
+        .map_err(|e| EnsealError::Encrypt(format!("failed to finalize age encryption: {}", e)))?;
 
     Ok(encrypted)
 }
 
 /// Decrypt age-encrypted data with own identity.
 fn age_decrypt(ciphertext: &[u8], identity: &age::x25519::Identity) -> Result<Vec<u8>> {
-    let decryptor = age::Decryptor::new(ciphertext).context("failed to read age header")?;
+    let decryptor = age::Decryptor::new(ciphertext)
+        .map_err(|e| EnsealError::Decrypt(format!("failed to read age header: {}", e)))?;
 
     let mut reader = decryptor
         .decrypt(std::iter::once(identity as &dyn age::Identity))
-        .map_err(|e| anyhow::anyhow!("age decryption failed: {}", e))?;
+        .map_err(|e| EnsealError::Decrypt(format!("age decryption failed: {}", e)))?;
 
     let mut plaintext = vec![];
     use std::io::Read;
     reader
         .read_to_end(&mut plaintext)
-        .context("failed to read decrypted data")?;
+        .map_err(|e| EnsealError::Decrypt(format!("failed to read decrypted data: {}", e)))?;
 
     Ok(plaintext)
 }
@@ -204,7 +356,7 @@ mod tests {
         }
 
         let result = signed.open(&receiver, None);
-        assert!(result.is_err());
+        assert!(matches!(result, Err(EnsealError::SignatureVerification(_))));
     }
 
     #[test]
@@ -217,7 +369,7 @@ mod tests {
         let signed = SignedEnvelope::seal(plaintext, &[&receiver.age_recipient], &sender).unwrap();
 
         let result = signed.open(&wrong_receiver, None);
-        assert!(result.is_err());
+        assert!(matches!(result, Err(EnsealError::Decrypt(_))));
     }
 
     #[test]
@@ -237,10 +389,56 @@ mod tests {
         };
 
         let result = signed.open(&receiver, Some(&trusted));
-        assert!(result.is_err());
+        assert!(matches!(result, Err(EnsealError::SignatureVerification(_))));
         assert!(result
             .unwrap_err()
             .to_string()
             .contains("sender key mismatch"));
     }
+
+    #[test]
+    fn malformed_signed_envelope_is_parse_error() {
+        assert!(matches!(
+            SignedEnvelope::from_bytes(b"not json"),
+            Err(EnsealError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn plaintext_signature_round_trip() {
+        let signer = EnsealIdentity::generate();
+        let signed = sign_plaintext(b"LOG_LEVEL=info\n\nFEATURE_X=on\n", &signer);
+
+        let restored = SignedPlaintext::from_bytes(&signed.to_bytes()).unwrap();
+        assert_eq!(restored.content, b"LOG_LEVEL=info\n\nFEATURE_X=on\n");
+        verify_plaintext(&restored, None).unwrap();
+    }
+
+    #[test]
+    fn tampered_plaintext_rejected() {
+        let signer = EnsealIdentity::generate();
+        let mut bytes = sign_plaintext(b"REPLICAS=3\n", &signer).to_bytes();
+        let last = bytes.len() - 2;
+        bytes[last] = b'9';
+
+        let tampered = SignedPlaintext::from_bytes(&bytes).unwrap();
+        assert!(matches!(
+            verify_plaintext(&tampered, None),
+            Err(EnsealError::SignatureVerification(_))
+        ));
+    }
+
+    #[test]
+    fn plaintext_signature_is_not_an_envelope_signature() {
+        // The same bytes signed as plaintext must not verify as ciphertext
+        let signer = EnsealIdentity::generate();
+        let receiver = EnsealIdentity::generate();
+        let envelope =
+            SignedEnvelope::seal(b"SECRET=value", &[&receiver.age_recipient], &signer).unwrap();
+        let forged = SignedEnvelope {
+            signature: sign_plaintext(&envelope.ciphertext, &signer).signature,
+            ..envelope
+        };
+        assert!(forged.open(&receiver, None).is_err());
+    }
 }
@@ -1,5 +1,6 @@
 use std::collections::BTreeSet;
 
+use super::transform::{self, Collision};
 use super::EnvFile;
 
 /// Result of diffing two .env files by keys only (never compares values).
@@ -30,6 +31,70 @@ pub fn diff(left: &EnvFile, right: &EnvFile) -> EnvDiff {
     }
 }
 
+/// How keys are canonicalized before diffing, so files from different
+/// tools can be compared (`diff --ignore-case`, `--strip-prefix`).
+#[derive(Debug, Default)]
+pub struct Normalize {
+    /// Compare keys case-insensitively (`api_key` matches `API_KEY`).
+    pub ignore_case: bool,
+    /// Drop this prefix from keys that have it (`APP_FOO` matches `FOO`).
+    pub strip_prefix: Option<String>,
+}
+
+impl Normalize {
+    /// Whether this changes any key at all.
+    pub fn is_noop(&self) -> bool {
+        !self.ignore_case && self.strip_prefix.is_none()
+    }
+
+    /// The canonical form of `key`. With `ignore_case`, the prefix also
+    /// matches case-insensitively and the result is uppercased. A key that
+    /// is nothing but the prefix is kept as is.
+    pub fn key(&self, key: &str) -> String {
+        let stripped = match self.strip_prefix.as_deref() {
+            Some(prefix) if key.len() > prefix.len() => {
+                let matches = key.get(..prefix.len()).is_some_and(|head| {
+                    if self.ignore_case {
+                        head.eq_ignore_ascii_case(prefix)
+                    } else {
+                        head == prefix
+                    }
+                });
+                if matches {
+                    &key[prefix.len()..]
+                } else {
+                    key
+                }
+            }
+            _ => key,
+        };
+        if self.ignore_case {
+            stripped.to_ascii_uppercase()
+        } else {
+            stripped.to_string()
+        }
+    }
+}
+
+/// Rewrite `env`'s keys to their canonical form. Distinct keys that end up
+/// the same are reported; the last one wins, as for any duplicate key.
+pub fn normalize(env: &EnvFile, normalize: &Normalize) -> (EnvFile, Vec<Collision>) {
+    transform::rename(env, |key| normalize.key(key))
+}
+
+/// Keys present in both files whose values differ. Only key names are
+/// returned; callers must not print the values themselves.
+pub fn changed_values(left: &EnvFile, right: &EnvFile) -> Vec<String> {
+    let right_vars: std::collections::HashMap<&str, &str> = right.vars().into_iter().collect();
+    let changed: BTreeSet<String> = left
+        .vars()
+        .into_iter()
+        .filter(|(k, v)| right_vars.get(k).is_some_and(|r| r != v))
+        .map(|(k, _)| k.to_string())
+        .collect();
+    changed.into_iter().collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,4 +129,53 @@ mod tests {
         assert!(d.only_right.is_empty());
         assert!(d.common.is_empty());
     }
+
+    #[test]
+    fn ignore_case_matches_keys() {
+        let a = parser::parse("API_KEY=1\nDb_Url=2\n").unwrap();
+        let b = parser::parse("api_key=1\nDB_URL=2\nPORT=3\n").unwrap();
+        let opts = Normalize {
+            ignore_case: true,
+            strip_prefix: None,
+        };
+        let (a, collisions) = normalize(&a, &opts);
+        assert!(collisions.is_empty());
+        let (b, _) = normalize(&b, &opts);
+        let d = diff(&a, &b);
+        assert!(d.only_left.is_empty());
+        assert_eq!(d.only_right, vec!["PORT"]);
+        assert_eq!(d.common, vec!["API_KEY", "DB_URL"]);
+    }
+
+    #[test]
+    fn strip_prefix_matches_and_reports_collisions() {
+        let a = parser::parse("APP_FOO=1\nFOO=2\nAPP_BAR=3\nAPP_=4\n").unwrap();
+        let b = parser::parse("FOO=1\nBAR=3\nAPP_=4\n").unwrap();
+        let opts = Normalize {
+            ignore_case: false,
+            strip_prefix: Some("APP_".to_string()),
+        };
+        let (a, collisions) = normalize(&a, &opts);
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].name, "FOO");
+        assert_eq!(collisions[0].sources, vec!["APP_FOO", "FOO"]);
+
+        let (b, _) = normalize(&b, &opts);
+        let d = diff(&a, &b);
+        assert!(d.only_left.is_empty() && d.only_right.is_empty());
+        assert_eq!(d.common, vec!["APP_", "BAR", "FOO"]);
+
+        let both = Normalize {
+            ignore_case: true,
+            strip_prefix: Some("APP_".to_string()),
+        };
+        assert_eq!(both.key("app_foo"), "FOO");
+    }
+
+    #[test]
+    fn changed_values_lists_keys_only() {
+        let a = parser::parse("A=1\nB=2\nC=3\n").unwrap();
+        let b = parser::parse("A=1\nB=two\nD=4\n").unwrap();
+        assert_eq!(changed_values(&a, &b), vec!["B"]);
+    }
 }
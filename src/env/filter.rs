@@ -1,25 +1,34 @@
 use anyhow::Result;
-use regex::RegexBuilder;
+use regex::{Regex, RegexBuilder};
 
 use super::{Entry, EnvFile};
 
-/// Filter an EnvFile by include/exclude regex patterns on variable names.
-/// - `include`: if Some, only keep vars matching this pattern
-/// - `exclude`: if Some, remove vars matching this pattern
-///   Include is applied first, then exclude.
-pub fn filter(env: &EnvFile, include: Option<&str>, exclude: Option<&str>) -> Result<EnvFile> {
-    let include_re = include
-        .map(|p| RegexBuilder::new(p).size_limit(100 * 1024).build())
-        .transpose()?;
-    let exclude_re = exclude
-        .map(|p| RegexBuilder::new(p).size_limit(100 * 1024).build())
-        .transpose()?;
+/// Filter an EnvFile by include/exclude regex patterns.
+/// - `include`: if Some, only keep vars whose name matches this pattern
+/// - `exclude`: if Some, remove vars whose name matches this pattern
+/// - `include_value`: if Some, only keep vars whose value matches this pattern
+/// - `exclude_value`: if Some, remove vars whose value matches this pattern
+///
+/// Key filters run first (include, then exclude), then value filters
+/// (include, then exclude). Comments and blank lines are always kept.
+/// Matched values are never logged.
+pub fn filter(
+    env: &EnvFile,
+    include: Option<&str>,
+    exclude: Option<&str>,
+    include_value: Option<&str>,
+    exclude_value: Option<&str>,
+) -> Result<EnvFile> {
+    let include_re = compile(include)?;
+    let exclude_re = compile(exclude)?;
+    let include_value_re = compile(include_value)?;
+    let exclude_value_re = compile(exclude_value)?;
 
     let entries = env
         .entries
         .iter()
         .filter(|entry| match entry {
-            Entry::KeyValue { key, .. } => {
+            Entry::KeyValue { key, value, .. } => {
                 if let Some(ref re) = include_re {
                     if !re.is_match(key) {
                         return false;
@@ -30,6 +39,16 @@ pub fn filter(env: &EnvFile, include: Option<&str>, exclude: Option<&str>) -> Re
                         return false;
                     }
                 }
+                if let Some(ref re) = include_value_re {
+                    if !re.is_match(value) {
+                        return false;
+                    }
+                }
+                if let Some(ref re) = exclude_value_re {
+                    if re.is_match(value) {
+                        return false;
+                    }
+                }
                 true
             }
             // Keep comments and blank lines
@@ -41,6 +60,12 @@ pub fn filter(env: &EnvFile, include: Option<&str>, exclude: Option<&str>) -> Re
     Ok(EnvFile { entries })
 }
 
+pub(super) fn compile(pattern: Option<&str>) -> Result<Option<Regex>> {
+    Ok(pattern
+        .map(|p| RegexBuilder::new(p).size_limit(100 * 1024).build())
+        .transpose()?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -49,7 +74,7 @@ mod tests {
     #[test]
     fn include_filter() {
         let env = parser::parse("DB_HOST=h\nDB_PORT=p\nAPI_KEY=k\n").unwrap();
-        let filtered = filter(&env, Some("^DB_"), None).unwrap();
+        let filtered = filter(&env, Some("^DB_"), None, None, None).unwrap();
         assert_eq!(filtered.var_count(), 2);
         assert!(filtered.get("API_KEY").is_none());
     }
@@ -57,7 +82,7 @@ mod tests {
     #[test]
     fn exclude_filter() {
         let env = parser::parse("DB_HOST=h\nPUBLIC_URL=u\nAPI_KEY=k\n").unwrap();
-        let filtered = filter(&env, None, Some("^PUBLIC_")).unwrap();
+        let filtered = filter(&env, None, Some("^PUBLIC_"), None, None).unwrap();
         assert_eq!(filtered.var_count(), 2);
         assert!(filtered.get("PUBLIC_URL").is_none());
     }
@@ -65,21 +90,59 @@ mod tests {
     #[test]
     fn include_and_exclude() {
         let env = parser::parse("DB_HOST=h\nDB_DEBUG=d\nAPI_KEY=k\n").unwrap();
-        let filtered = filter(&env, Some("^DB_"), Some("DEBUG")).unwrap();
+        let filtered = filter(&env, Some("^DB_"), Some("DEBUG"), None, None).unwrap();
         assert_eq!(filtered.var_count(), 1);
         assert_eq!(filtered.get("DB_HOST"), Some("h"));
     }
 
+    #[test]
+    fn attached_comments_follow_their_key() {
+        let env = parser::parse("# db host\nDB_HOST=h\n# api key\nAPI_KEY=k\n").unwrap();
+        let filtered = filter(&env, Some("^DB_"), None, None, None).unwrap();
+        assert_eq!(filtered.to_string(), "# db host\nDB_HOST=h\n");
+    }
+
     #[test]
     fn no_filters() {
         let env = parser::parse("A=1\nB=2\n").unwrap();
-        let filtered = filter(&env, None, None).unwrap();
+        let filtered = filter(&env, None, None, None, None).unwrap();
         assert_eq!(filtered.var_count(), 2);
     }
 
     #[test]
     fn invalid_regex() {
         let env = parser::parse("A=1\n").unwrap();
-        assert!(filter(&env, Some("[invalid"), None).is_err());
+        assert!(filter(&env, Some("[invalid"), None, None, None).is_err());
+    }
+
+    #[test]
+    fn key_and_value_filters_combined() {
+        let env = parser::parse(
+            "DB_URL=postgres://localhost/dev\nDB_REPLICA=postgres://db.internal/prod\nAPI_KEY=k\n",
+        )
+        .unwrap();
+        let filtered = filter(&env, Some("^DB_"), None, None, Some("localhost")).unwrap();
+        assert_eq!(filtered.var_count(), 1);
+        assert!(filtered.get("DB_REPLICA").is_some());
+
+        let filtered = filter(&env, None, Some("^API_"), Some("^postgres://"), None).unwrap();
+        assert_eq!(filtered.keys(), vec!["DB_URL", "DB_REPLICA"]);
+    }
+
+    #[test]
+    fn value_filters_keep_comments_and_blanks() {
+        let env = parser::parse("# header\n\nA=http://localhost:3000\n\nB=https://example.com\n")
+            .unwrap();
+        let filtered = filter(&env, None, None, None, Some("localhost")).unwrap();
+        assert_eq!(filtered.var_count(), 1);
+        assert!(matches!(filtered.entries[0], Entry::Comment(_)));
+        assert_eq!(
+            filtered
+                .entries
+                .iter()
+                .filter(|e| matches!(e, Entry::Blank))
+                .count(),
+            2
+        );
     }
 }
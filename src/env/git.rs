@@ -0,0 +1,70 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Check whether a file is tracked by git.
+///
+/// Returns false when git is not installed, the file is outside a work
+/// tree, or the file is untracked/ignored. Never fails: this only feeds
+/// a warning.
+pub fn is_tracked(path: &Path) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["ls-files", "--error-unmatch", "--"])
+        .arg(name)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) -> bool {
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn tracked_and_ignored_env_files() {
+        let dir = TempDir::new().unwrap();
+        if !git(dir.path(), &["init", "-q"]) {
+            // git not installed; nothing to check
+            return;
+        }
+        std::fs::write(dir.path().join(".gitignore"), ".env.local\n").unwrap();
+        std::fs::write(dir.path().join(".env"), "SECRET=oops\n").unwrap();
+        std::fs::write(dir.path().join(".env.local"), "SECRET=fine\n").unwrap();
+        assert!(git(dir.path(), &["add", ".env", ".gitignore"]));
+
+        assert!(is_tracked(&dir.path().join(".env")));
+        assert!(!is_tracked(&dir.path().join(".env.local")));
+    }
+
+    #[test]
+    fn outside_repo_is_not_tracked() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(".env"), "KEY=value\n").unwrap();
+        assert!(!is_tracked(&dir.path().join(".env")));
+    }
+}
@@ -13,12 +13,17 @@ pub fn interpolate(env: &EnvFile) -> Result<EnvFile> {
 
     for entry in &env.entries {
         match entry {
-            super::Entry::KeyValue { key, value } => {
+            super::Entry::KeyValue {
+                key,
+                value,
+                comment,
+            } => {
                 let new_value = resolve_value(value, key, &resolved)?;
                 resolved.insert(key.clone(), new_value.clone());
                 result.entries.push(super::Entry::KeyValue {
                     key: key.clone(),
                     value: new_value,
+                    comment: comment.clone(),
                 });
             }
             other => {
@@ -181,7 +186,11 @@ mod tests {
         let input = "# comment\nKEY=value\n\nOTHER=${KEY}\n";
         let env = parser::parse(input).unwrap();
         let resolved = interpolate(&env).unwrap();
-        assert_eq!(resolved.entries.len(), 4); // comment, kv, blank, kv
+        assert_eq!(resolved.entries.len(), 3); // kv (with comment), blank, kv
+        assert_eq!(
+            resolved.to_string(),
+            "# comment\nKEY=value\n\nOTHER=value\n"
+        );
     }
 
     #[test]
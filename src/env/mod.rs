@@ -1,10 +1,12 @@
 pub mod diff;
 pub mod filter;
+pub mod git;
 pub mod interpolation;
 pub mod parser;
 pub mod profile;
 pub mod redact;
 pub mod schema;
+pub mod transform;
 pub mod validator;
 
 use std::fmt;
@@ -18,9 +20,15 @@ pub struct EnvFile {
 /// A single line/entry in a .env file.
 #[derive(Debug, Clone)]
 pub enum Entry {
-    /// A key-value pair.
-    KeyValue { key: String, value: String },
-    /// A comment line (including the leading `#`).
+    /// A key-value pair, with the comment lines directly above it (if any).
+    KeyValue {
+        key: String,
+        value: String,
+        /// Leading comment block (lines including `#`, joined by `\n`).
+        comment: Option<String>,
+    },
+    /// A standalone comment line (including the leading `#`), i.e. one not
+    /// directly followed by a key-value pair.
     Comment(String),
     /// A blank line.
     Blank,
@@ -39,7 +47,7 @@ impl EnvFile {
         self.entries
             .iter()
             .filter_map(|e| match e {
-                Entry::KeyValue { key, value } => Some((key.as_str(), value.as_str())),
+                Entry::KeyValue { key, value, .. } => Some((key.as_str(), value.as_str())),
                 _ => None,
             })
             .collect()
@@ -54,7 +62,7 @@ impl EnvFile {
     #[allow(dead_code)]
     pub fn get(&self, key: &str) -> Option<&str> {
         self.entries.iter().rev().find_map(|e| match e {
-            Entry::KeyValue { key: k, value } if k == key => Some(value.as_str()),
+            Entry::KeyValue { key: k, value, .. } if k == key => Some(value.as_str()),
             _ => None,
         })
     }
@@ -78,7 +86,14 @@ impl fmt::Display for EnvFile {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for entry in &self.entries {
             match entry {
-                Entry::KeyValue { key, value } => {
+                Entry::KeyValue {
+                    key,
+                    value,
+                    comment,
+                } => {
+                    if let Some(comment) = comment {
+                        writeln!(f, "{comment}")?;
+                    }
                     if value.contains(' ')
                         || value.contains('"')
                         || value.contains('\'')
@@ -1,4 +1,4 @@
-use anyhow::{bail, Result};
+use crate::error::{EnsealError, Result};
 
 use super::{Entry, EnvFile};
 
@@ -7,20 +7,26 @@ use super::{Entry, EnvFile};
 /// Handles: KEY=value, KEY="quoted value", KEY='single quoted',
 /// comments (#), blank lines. Warns on duplicates (keeps last).
 /// Rejects multi-line values.
+///
+/// Comment lines directly above a key-value pair are attached to it;
+/// comments followed by a blank line (or end of input) stay standalone.
 pub fn parse(input: &str) -> Result<EnvFile> {
     let mut entries = Vec::new();
     let mut seen_keys: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    // Comment lines waiting to see whether a key-value pair follows
+    let mut pending_comments: Vec<&str> = Vec::new();
 
     for (line_num, line) in input.lines().enumerate() {
         let trimmed = line.trim();
 
         if trimmed.is_empty() {
+            flush_comments(&mut entries, &mut pending_comments);
             entries.push(Entry::Blank);
             continue;
         }
 
         if trimmed.starts_with('#') {
-            entries.push(Entry::Comment(line.to_string()));
+            pending_comments.push(line);
             continue;
         }
 
@@ -35,18 +41,21 @@ pub fn parse(input: &str) -> Result<EnvFile> {
             } else {
                 trimmed.to_string()
             };
-            bail!(
+            return Err(EnsealError::Parse(format!(
                 "line {}: invalid syntax (no '=' found): {}",
                 line_num + 1,
                 preview
-            );
+            )));
         };
 
         let key = trimmed[..eq_pos].trim();
 
         // Validate key: uppercase alphanumeric + underscore
         if key.is_empty() {
-            bail!("line {}: empty key", line_num + 1);
+            return Err(EnsealError::Parse(format!(
+                "line {}: empty key",
+                line_num + 1
+            )));
         }
         if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
             tracing::warn!(
@@ -70,15 +79,31 @@ pub fn parse(input: &str) -> Result<EnvFile> {
         }
         seen_keys.insert(key, line_num + 1);
 
+        let comment = if pending_comments.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut pending_comments).join("\n"))
+        };
         entries.push(Entry::KeyValue {
             key: key.to_string(),
             value,
+            comment,
         });
     }
+    flush_comments(&mut entries, &mut pending_comments);
 
     Ok(EnvFile { entries })
 }
 
+/// Emit pending comment lines as standalone comments.
+fn flush_comments(entries: &mut Vec<Entry>, pending: &mut Vec<&str>) {
+    entries.extend(
+        pending
+            .drain(..)
+            .map(|line| Entry::Comment(line.to_string())),
+    );
+}
+
 /// Parse the value portion of a KEY=VALUE line.
 fn parse_value(raw: &str, line_num: usize) -> Result<String> {
     if raw.is_empty() {
@@ -128,19 +153,32 @@ fn strip_quotes(raw: &str, quote: char, line_num: usize) -> Result<String> {
                         result.push('\\');
                         result.push(c);
                     }
-                    None => bail!("line {}: unterminated escape sequence", line_num),
+                    None => {
+                        return Err(EnsealError::Parse(format!(
+                            "line {}: unterminated escape sequence",
+                            line_num
+                        )))
+                    }
                 },
                 Some(c) if c == quote => {
                     // Closing quote found; rest should be empty or a comment
                     let rest: String = chars.collect();
                     let rest = rest.trim();
                     if !rest.is_empty() && !rest.starts_with('#') {
-                        bail!("line {}: unexpected content after closing quote", line_num);
+                        return Err(EnsealError::Parse(format!(
+                            "line {}: unexpected content after closing quote",
+                            line_num
+                        )));
                     }
                     return Ok(result);
                 }
                 Some(c) => result.push(c),
-                None => bail!("line {}: unterminated double quote", line_num),
+                None => {
+                    return Err(EnsealError::Parse(format!(
+                        "line {}: unterminated double quote",
+                        line_num
+                    )))
+                }
             }
         }
     } else {
@@ -148,11 +186,17 @@ fn strip_quotes(raw: &str, quote: char, line_num: usize) -> Result<String> {
         if let Some(end) = inner.find(quote) {
             let rest = inner[end + 1..].trim();
             if !rest.is_empty() && !rest.starts_with('#') {
-                bail!("line {}: unexpected content after closing quote", line_num);
+                return Err(EnsealError::Parse(format!(
+                    "line {}: unexpected content after closing quote",
+                    line_num
+                )));
             }
             Ok(inner[..end].to_string())
         } else {
-            bail!("line {}: unterminated single quote", line_num)
+            Err(EnsealError::Parse(format!(
+                "line {}: unterminated single quote",
+                line_num
+            )))
         }
     }
 }
@@ -227,6 +271,43 @@ mod tests {
         assert_eq!(env.entries.len(), 4);
     }
 
+    #[test]
+    fn comment_block_attaches_to_following_key() {
+        let input = "# must be 32 chars\n# rotate quarterly\nAPI_KEY=abc\n";
+        let env = parse(input).unwrap();
+        assert_eq!(env.entries.len(), 1);
+        match &env.entries[0] {
+            Entry::KeyValue { key, comment, .. } => {
+                assert_eq!(key, "API_KEY");
+                assert_eq!(
+                    comment.as_deref(),
+                    Some("# must be 32 chars\n# rotate quarterly")
+                );
+            }
+            other => panic!("expected key-value, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn comment_before_blank_line_stays_standalone() {
+        let input = "# Database settings\n\nDB_HOST=localhost\n# trailing\n";
+        let env = parse(input).unwrap();
+        assert!(matches!(&env.entries[0], Entry::Comment(c) if c == "# Database settings"));
+        assert!(matches!(&env.entries[1], Entry::Blank));
+        assert!(matches!(
+            &env.entries[2],
+            Entry::KeyValue { comment: None, .. }
+        ));
+        assert!(matches!(&env.entries[3], Entry::Comment(c) if c == "# trailing"));
+    }
+
+    #[test]
+    fn attached_comments_round_trip() {
+        let input = "# header\n\n# must be 32 chars\nAPI_KEY=abc\nPORT=3000\n";
+        let env = parse(input).unwrap();
+        assert_eq!(env.to_string(), input);
+    }
+
     #[test]
     fn inline_comment() {
         let env = parse("KEY=value # this is a comment").unwrap();
@@ -292,7 +373,7 @@ mod tests {
 
     #[test]
     fn no_equals_sign() {
-        assert!(parse("INVALID_LINE").is_err());
+        assert!(matches!(parse("INVALID_LINE"), Err(EnsealError::Parse(_))));
     }
 
     #[test]
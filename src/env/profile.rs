@@ -1,6 +1,10 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+
+/// Top-level key marking a multi-profile payload: `{"profiles": {"<name>": "<.env content>"}}`.
+const BUNDLE_KEY: &str = "profiles";
 
 /// Resolve an environment profile name to a file path.
 ///
@@ -10,6 +14,28 @@ use anyhow::{bail, Result};
 ///
 /// Returns the path if found, or an error if neither exists.
 pub fn resolve(profile: &str, dir: &Path) -> Result<PathBuf> {
+    validate_name(profile)?;
+
+    let primary = dir.join(format!(".env.{}", profile));
+    if primary.exists() {
+        return Ok(primary);
+    }
+
+    let local = dir.join(format!(".env.{}.local", profile));
+    if local.exists() {
+        return Ok(local);
+    }
+
+    bail!(
+        "no .env file found for profile '{}'. Expected {} or {}",
+        profile,
+        primary.display(),
+        local.display()
+    );
+}
+
+/// Check that a profile name is safe to use in a `.env.<profile>` filename.
+pub fn validate_name(profile: &str) -> Result<()> {
     if profile.is_empty() {
         bail!("profile name cannot be empty");
     }
@@ -25,23 +51,27 @@ pub fn resolve(profile: &str, dir: &Path) -> Result<PathBuf> {
             profile
         );
     }
+    Ok(())
+}
 
-    let primary = dir.join(format!(".env.{}", profile));
-    if primary.exists() {
-        return Ok(primary);
+/// Parse a multi-profile payload into profile name -> .env content.
+/// Returns `None` if the content is not a profile bundle.
+pub fn unpack_bundle(content: &str) -> Result<Option<BTreeMap<String, String>>> {
+    let Ok(serde_json::Value::Object(mut root)) = serde_json::from_str(content) else {
+        return Ok(None);
+    };
+    let Some(profiles) = root.remove(BUNDLE_KEY) else {
+        return Ok(None);
+    };
+    let profiles: BTreeMap<String, String> =
+        serde_json::from_value(profiles).context("invalid multi-profile payload")?;
+    if profiles.is_empty() {
+        bail!("multi-profile payload contains no profiles");
     }
-
-    let local = dir.join(format!(".env.{}.local", profile));
-    if local.exists() {
-        return Ok(local);
+    for name in profiles.keys() {
+        validate_name(name)?;
     }
-
-    bail!(
-        "no .env file found for profile '{}'. Expected {} or {}",
-        profile,
-        primary.display(),
-        local.display()
-    );
+    Ok(Some(profiles))
 }
 
 /// Resolve a file argument that might be a profile name or a path.
@@ -115,6 +145,27 @@ mod tests {
         assert_eq!(path, PathBuf::from("custom.env"));
     }
 
+    #[test]
+    fn unpack_bundle_profiles() {
+        let bundle = r#"{"profiles":{"staging":"A=1\n","production":"A=2\n"}}"#;
+        let profiles = unpack_bundle(bundle).unwrap().unwrap();
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles["staging"], "A=1\n");
+        assert_eq!(profiles["production"], "A=2\n");
+    }
+
+    #[test]
+    fn unpack_bundle_ignores_other_payloads() {
+        assert!(unpack_bundle("KEY=value\n").unwrap().is_none());
+        assert!(unpack_bundle(r#"{"token":"abc"}"#).unwrap().is_none());
+    }
+
+    #[test]
+    fn unpack_bundle_rejects_unsafe_profile_names() {
+        let bundle = r#"{"profiles":{"../evil":"A=1\n"}}"#;
+        assert!(unpack_bundle(bundle).is_err());
+    }
+
     #[test]
     fn resolve_file_defaults() {
         let dir = TempDir::new().unwrap();
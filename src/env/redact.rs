@@ -1,15 +1,46 @@
+use anyhow::Result;
+
+use super::filter::compile;
+use super::validator::looks_like_secret_name;
 use super::{Entry, EnvFile};
 
 /// Produce a copy of an EnvFile with all values replaced by `<REDACTED>`.
 /// Preserves keys, comments, and structure.
 pub fn redact(env: &EnvFile) -> EnvFile {
+    redact_where(env, |_| true)
+}
+
+/// Like [`redact`], but only for some keys:
+/// - `keep`: if Some, vars whose name matches keep their real value
+/// - `only`: if Some, only vars whose name matches are redacted
+/// - `secrets_only`: only vars whose name looks like a secret are redacted
+///
+/// A var is redacted when it passes every condition given; `keep` wins
+/// over `only`.
+pub fn redact_matching(
+    env: &EnvFile,
+    keep: Option<&str>,
+    only: Option<&str>,
+    secrets_only: bool,
+) -> Result<EnvFile> {
+    let keep_re = compile(keep)?;
+    let only_re = compile(only)?;
+    Ok(redact_where(env, |key| {
+        !keep_re.as_ref().is_some_and(|re| re.is_match(key))
+            && only_re.as_ref().is_none_or(|re| re.is_match(key))
+            && (!secrets_only || looks_like_secret_name(key))
+    }))
+}
+
+fn redact_where(env: &EnvFile, should_redact: impl Fn(&str) -> bool) -> EnvFile {
     let entries = env
         .entries
         .iter()
         .map(|entry| match entry {
-            Entry::KeyValue { key, .. } => Entry::KeyValue {
+            Entry::KeyValue { key, comment, .. } if should_redact(key) => Entry::KeyValue {
                 key: key.clone(),
                 value: "<REDACTED>".to_string(),
+                comment: comment.clone(),
             },
             other => other.clone(),
         })
@@ -40,6 +71,51 @@ mod tests {
         assert!(matches!(&redacted.entries[1], Entry::Blank));
     }
 
+    #[test]
+    fn keep_leaves_matching_keys_intact() {
+        let env = parser::parse("API_KEY=abc\nPORT=3000\nHOST=db\n").unwrap();
+        let redacted = redact_matching(&env, Some("^(PORT|HOST)$"), None, false).unwrap();
+        assert_eq!(redacted.get("API_KEY"), Some("<REDACTED>"));
+        assert_eq!(redacted.get("PORT"), Some("3000"));
+        assert_eq!(redacted.get("HOST"), Some("db"));
+    }
+
+    #[test]
+    fn only_redacts_matching_keys() {
+        let env = parser::parse("STRIPE_KEY=sk\nSTRIPE_MODE=test\nPORT=3000\n").unwrap();
+        let redacted = redact_matching(&env, None, Some("^STRIPE_"), false).unwrap();
+        assert_eq!(redacted.get("STRIPE_KEY"), Some("<REDACTED>"));
+        assert_eq!(redacted.get("STRIPE_MODE"), Some("<REDACTED>"));
+        assert_eq!(redacted.get("PORT"), Some("3000"));
+
+        let redacted = redact_matching(&env, Some("MODE"), Some("^STRIPE_"), false).unwrap();
+        assert_eq!(redacted.get("STRIPE_MODE"), Some("test"));
+    }
+
+    #[test]
+    fn secrets_only_composes_with_keep_and_only() {
+        let env = parser::parse("DB_PASSWORD=pw\nAPI_TOKEN=t\nPUBLIC_KEY=pk\nPORT=3000\n").unwrap();
+        let redacted = redact_matching(&env, None, None, true).unwrap();
+        assert_eq!(redacted.get("DB_PASSWORD"), Some("<REDACTED>"));
+        assert_eq!(redacted.get("PUBLIC_KEY"), Some("<REDACTED>"));
+        assert_eq!(redacted.get("PORT"), Some("3000"));
+
+        let redacted = redact_matching(&env, Some("^PUBLIC_"), None, true).unwrap();
+        assert_eq!(redacted.get("PUBLIC_KEY"), Some("pk"));
+        assert_eq!(redacted.get("API_TOKEN"), Some("<REDACTED>"));
+
+        let redacted = redact_matching(&env, None, Some("^(DB_|PORT)"), true).unwrap();
+        assert_eq!(redacted.get("DB_PASSWORD"), Some("<REDACTED>"));
+        assert_eq!(redacted.get("API_TOKEN"), Some("t"));
+        assert_eq!(redacted.get("PORT"), Some("3000"));
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        let env = parser::parse("KEY=value\n").unwrap();
+        assert!(redact_matching(&env, Some("("), None, false).is_err());
+    }
+
     #[test]
     fn no_values_leak() {
         let env = parser::parse("SECRET=super_secret_password_123\n").unwrap();
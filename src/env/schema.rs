@@ -3,7 +3,8 @@ use std::collections::HashMap;
 use anyhow::{Context, Result};
 use serde::Deserialize;
 
-use super::EnvFile;
+use super::validator::Severity;
+use super::{Entry, EnvFile};
 
 /// Schema definition from `.enseal.toml` `[schema]` section.
 #[derive(Debug, Default, Deserialize, Clone)]
@@ -13,13 +14,35 @@ pub struct Schema {
     pub required: Vec<String>,
     /// Per-variable validation rules.
     pub rules: HashMap<String, Rule>,
+    /// Relations between variables (`[[schema.constraints]]`).
+    pub constraints: Vec<Constraint>,
+}
+
+/// A cross-variable constraint. A variable counts as set when it is present
+/// with a non-empty value.
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Constraint {
+    /// Primary variable for `requires` and `equals`.
+    pub key: Option<String>,
+    /// Only apply `requires` when `key` has this value (e.g. "true").
+    pub when: Option<String>,
+    /// Variables that must be set when `key` is set.
+    pub requires: Vec<String>,
+    /// Variable whose value `key` must equal.
+    pub equals: Option<String>,
+    /// At most one of these variables may be set.
+    pub mutually_exclusive: Vec<String>,
+    /// With `mutually_exclusive`: exactly one must be set.
+    pub require_one: bool,
 }
 
 /// Validation rule for a single variable.
 #[derive(Debug, Default, Deserialize, Clone)]
 #[serde(default)]
 pub struct Rule {
-    /// Expected type: "string", "integer", "boolean", "url", "email".
+    /// Expected type: "string", "integer", "boolean", "url", "email", "semver",
+    /// "ip", "port", "hostname".
     #[serde(rename = "type")]
     pub var_type: Option<String>,
     /// Regex the value must match.
@@ -30,11 +53,36 @@ pub struct Rule {
     pub max_length: Option<usize>,
     /// Allowed integer range [min, max].
     pub range: Option<[i64; 2]>,
+    /// Allowed URL schemes for the "url" type, e.g. ["https", "postgres"].
+    pub schemes: Option<Vec<String>>,
+    /// Semantic version requirement, e.g. ">=1.2, <2".
+    pub semver_req: Option<String>,
     /// List of allowed values.
     #[serde(rename = "enum")]
     pub allowed_values: Option<Vec<String>>,
     /// Human-readable description (used by template command).
     pub description: Option<String>,
+    /// Value filled in by `--apply-defaults` when the variable is absent.
+    pub default: Option<String>,
+    /// "error" (default) fails validation; "warn" only reports. Unknown
+    /// values are rejected when the schema is loaded.
+    #[serde(deserialize_with = "deserialize_severity")]
+    pub severity: Option<Severity>,
+}
+
+fn deserialize_severity<'de, D>(deserializer: D) -> std::result::Result<Option<Severity>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None => Ok(None),
+        Some("error") => Ok(Some(Severity::Error)),
+        Some("warn") => Ok(Some(Severity::Warning)),
+        Some(other) => Err(serde::de::Error::custom(format!(
+            "unknown severity '{}' (expected: error, warn)",
+            other
+        ))),
+    }
 }
 
 /// A single validation error.
@@ -42,6 +90,7 @@ pub struct Rule {
 pub struct SchemaError {
     pub key: String,
     pub message: String,
+    pub severity: Severity,
 }
 
 impl std::fmt::Display for SchemaError {
@@ -61,6 +110,7 @@ pub fn validate(env: &EnvFile, schema: &Schema) -> Vec<SchemaError> {
             errors.push(SchemaError {
                 key: key.clone(),
                 message: "missing required variable".to_string(),
+                severity: Severity::Error,
             });
         }
     }
@@ -68,16 +118,122 @@ pub fn validate(env: &EnvFile, schema: &Schema) -> Vec<SchemaError> {
     // Check rules
     for (key, rule) in &schema.rules {
         if let Some(&value) = vars.get(key.as_str()) {
-            errors.extend(validate_rule(key, value, rule));
+            let severity = rule.severity.unwrap_or(Severity::Error);
+            errors.extend(
+                validate_rule(value, rule)
+                    .into_iter()
+                    .map(|message| SchemaError {
+                        key: key.clone(),
+                        message,
+                        severity,
+                    }),
+            );
         }
         // If key is missing and it's in required, that's already caught above.
         // If key is missing and not required, no error — the rule just doesn't apply.
     }
 
+    // Cross-variable constraints
+    for constraint in &schema.constraints {
+        errors.extend(validate_constraint(&vars, constraint));
+    }
+
     errors
 }
 
-fn validate_rule(key: &str, value: &str, rule: &Rule) -> Vec<SchemaError> {
+fn validate_constraint(vars: &HashMap<&str, &str>, constraint: &Constraint) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    let is_set = |key: &str| vars.get(key).is_some_and(|v| !v.is_empty());
+    let error = |key: &str, message: String| SchemaError {
+        key: key.to_string(),
+        message,
+        severity: Severity::Error,
+    };
+
+    if !constraint.requires.is_empty() || constraint.equals.is_some() {
+        let Some(key) = constraint.key.as_deref() else {
+            return vec![error(
+                "constraint",
+                "`requires` and `equals` need a `key`".to_string(),
+            )];
+        };
+        let value = vars.get(key).copied().filter(|v| !v.is_empty());
+        let applies = match (&constraint.when, value) {
+            (Some(when), Some(value)) => value == when,
+            (None, Some(_)) => true,
+            (_, None) => false,
+        };
+        if applies {
+            let condition = match &constraint.when {
+                Some(when) => format!("{}={}", key, when),
+                None => format!("{} is set", key),
+            };
+            for required in &constraint.requires {
+                if !is_set(required) {
+                    errors.push(error(
+                        key,
+                        format!("{} is required when {}", required, condition),
+                    ));
+                }
+            }
+        }
+        if let (Some(other), Some(value)) = (&constraint.equals, value) {
+            if vars.get(other.as_str()).copied() != Some(value) {
+                errors.push(error(key, format!("must equal {}", other)));
+            }
+        }
+    }
+
+    if !constraint.mutually_exclusive.is_empty() {
+        let group = &constraint.mutually_exclusive;
+        let set: Vec<&str> = group
+            .iter()
+            .map(String::as_str)
+            .filter(|k| is_set(k))
+            .collect();
+        if set.len() > 1 {
+            errors.push(error(
+                set[0],
+                format!(
+                    "only one of {} may be set (found {})",
+                    group.join(", "),
+                    set.join(", ")
+                ),
+            ));
+        } else if set.is_empty() && constraint.require_one {
+            errors.push(error(
+                &group[0],
+                format!("one of {} must be set", group.join(", ")),
+            ));
+        }
+    }
+
+    errors
+}
+
+/// A URL must parse and name a host; `schemes` optionally restricts the scheme.
+fn check_url(value: &str, schemes: Option<&[String]>) -> Result<(), String> {
+    let parsed = url::Url::parse(value).map_err(|e| format!("value is not a valid URL: {}", e))?;
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err("URL has no host".to_string());
+    }
+    if let Some(schemes) = schemes {
+        if !schemes
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(parsed.scheme()))
+        {
+            return Err(format!(
+                "URL scheme '{}' not allowed (expected: {})",
+                parsed.scheme(),
+                schemes.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check `value` against `rule`, returning one message per violation.
+fn validate_rule(value: &str, rule: &Rule) -> Vec<String> {
     let mut errors = Vec::new();
 
     // Type check
@@ -85,34 +241,18 @@ fn validate_rule(key: &str, value: &str, rule: &Rule) -> Vec<SchemaError> {
         match var_type.as_str() {
             "integer" => {
                 if value.parse::<i64>().is_err() {
-                    errors.push(SchemaError {
-                        key: key.to_string(),
-                        message: "value is not an integer".to_string(),
-                    });
+                    errors.push("value is not an integer".to_string());
                 }
             }
             "boolean" => {
                 let lower = value.to_lowercase();
                 if !["true", "false", "1", "0", "yes", "no"].contains(&lower.as_str()) {
-                    errors.push(SchemaError {
-                        key: key.to_string(),
-                        message: "value is not a boolean".to_string(),
-                    });
+                    errors.push("value is not a boolean".to_string());
                 }
             }
             "url" => {
-                if !value.starts_with("http://")
-                    && !value.starts_with("https://")
-                    && !value.starts_with("postgres://")
-                    && !value.starts_with("mysql://")
-                    && !value.starts_with("redis://")
-                    && !value.starts_with("amqp://")
-                    && !value.starts_with("mongodb://")
-                {
-                    errors.push(SchemaError {
-                        key: key.to_string(),
-                        message: "value doesn't look like a URL".to_string(),
-                    });
+                if let Err(message) = check_url(value, rule.schemes.as_deref()) {
+                    errors.push(message);
                 }
             }
             "email" => {
@@ -122,21 +262,35 @@ fn validate_rule(key: &str, value: &str, rule: &Rule) -> Vec<SchemaError> {
                     false
                 };
                 if !valid {
-                    errors.push(SchemaError {
-                        key: key.to_string(),
-                        message: "value doesn't look like an email".to_string(),
-                    });
+                    errors.push("value doesn't look like an email".to_string());
+                }
+            }
+            "ip" => {
+                if value.parse::<std::net::IpAddr>().is_err() {
+                    errors.push("value is not a valid IPv4/IPv6 address".to_string());
+                }
+            }
+            "port" => {
+                if !matches!(value.parse::<u16>(), Ok(port) if port > 0) {
+                    errors.push("value is not a valid port (1-65535)".to_string());
+                }
+            }
+            "hostname" => {
+                if !is_hostname(value) {
+                    errors.push("value is not a valid hostname".to_string());
+                }
+            }
+            "semver" => {
+                if let Err(e) = semver::Version::parse(value) {
+                    errors.push(format!("value is not a semantic version: {}", e));
                 }
             }
             "string" => {} // any value is a valid string
             unknown => {
-                errors.push(SchemaError {
-                    key: key.to_string(),
-                    message: format!(
-                        "unknown type '{}' (expected: string, integer, boolean, url, email)",
+                errors.push(format!(
+                        "unknown type '{}' (expected: string, integer, boolean, url, email, semver, ip, port, hostname)",
                         unknown
-                    ),
-                });
+                    ));
             }
         }
     }
@@ -149,17 +303,11 @@ fn validate_rule(key: &str, value: &str, rule: &Rule) -> Vec<SchemaError> {
         {
             Ok(re) => {
                 if !re.is_match(value) {
-                    errors.push(SchemaError {
-                        key: key.to_string(),
-                        message: format!("doesn't match pattern {}", pattern),
-                    });
+                    errors.push(format!("doesn't match pattern {}", pattern));
                 }
             }
             Err(e) => {
-                errors.push(SchemaError {
-                    key: key.to_string(),
-                    message: format!("invalid pattern '{}': {}", pattern, e),
-                });
+                errors.push(format!("invalid pattern '{}': {}", pattern, e));
             }
         }
     }
@@ -168,19 +316,13 @@ fn validate_rule(key: &str, value: &str, rule: &Rule) -> Vec<SchemaError> {
     if let Some(min) = rule.min_length {
         let char_count = value.chars().count();
         if char_count < min {
-            errors.push(SchemaError {
-                key: key.to_string(),
-                message: format!("length {} is below minimum {}", char_count, min),
-            });
+            errors.push(format!("length {} is below minimum {}", char_count, min));
         }
     }
     if let Some(max) = rule.max_length {
         let char_count = value.chars().count();
         if char_count > max {
-            errors.push(SchemaError {
-                key: key.to_string(),
-                message: format!("length {} exceeds maximum {}", char_count, max),
-            });
+            errors.push(format!("length {} exceeds maximum {}", char_count, max));
         }
     }
 
@@ -189,37 +331,105 @@ fn validate_rule(key: &str, value: &str, rule: &Rule) -> Vec<SchemaError> {
         match value.parse::<i64>() {
             Ok(n) => {
                 if n < min || n > max {
-                    errors.push(SchemaError {
-                        key: key.to_string(),
-                        message: format!("value is outside range [{}, {}]", min, max),
-                    });
+                    errors.push(format!("value is outside range [{}, {}]", min, max));
                 }
             }
             Err(_) => {
                 // Only flag if type check didn't already catch this
-                if rule.var_type.as_deref() != Some("integer") {
-                    errors.push(SchemaError {
-                        key: key.to_string(),
-                        message: "range check requires an integer value".to_string(),
-                    });
+                if !matches!(rule.var_type.as_deref(), Some("integer" | "port")) {
+                    errors.push("range check requires an integer value".to_string());
                 }
             }
         }
     }
 
+    // Semver requirement check
+    if let Some(ref req) = rule.semver_req {
+        match semver::VersionReq::parse(req) {
+            Ok(version_req) => match semver::Version::parse(value) {
+                Ok(version) => {
+                    if !version_req.matches(&version) {
+                        errors.push(format!("version {} does not satisfy {}", version, req));
+                    }
+                }
+                Err(_) => {
+                    // Only flag if type check didn't already catch this
+                    if rule.var_type.as_deref() != Some("semver") {
+                        errors.push("semver_req check requires a semantic version".to_string());
+                    }
+                }
+            },
+            Err(e) => {
+                errors.push(format!("invalid semver_req '{}': {}", req, e));
+            }
+        }
+    }
+
     // Enum check
     if let Some(ref allowed) = rule.allowed_values {
         if !allowed.iter().any(|a| a == value) {
-            errors.push(SchemaError {
-                key: key.to_string(),
-                message: format!("value not in allowed values: {}", allowed.join(", ")),
-            });
+            errors.push(format!(
+                "value not in allowed values: {}",
+                allowed.join(", ")
+            ));
         }
     }
 
     errors
 }
 
+/// Defaults for rules whose key `is_present` says is missing, sorted by key.
+/// Each default must pass its own rule.
+pub fn missing_defaults(
+    schema: &Schema,
+    is_present: impl Fn(&str) -> bool,
+) -> Result<Vec<(String, String)>> {
+    let mut defaults: Vec<(String, String)> = Vec::new();
+    for (key, rule) in &schema.rules {
+        let Some(default) = &rule.default else {
+            continue;
+        };
+        if is_present(key) {
+            continue;
+        }
+        if let Some(message) = validate_rule(default, rule).into_iter().next() {
+            anyhow::bail!("schema default for {} is invalid: {}", key, message);
+        }
+        defaults.push((key.clone(), default.clone()));
+    }
+    defaults.sort();
+    Ok(defaults)
+}
+
+/// Append schema defaults for any missing keys. Keys already present,
+/// even with an empty value, are left untouched.
+pub fn apply_defaults(env: &EnvFile, schema: &Schema) -> Result<EnvFile> {
+    let mut filled = env.clone();
+    let present = env.keys();
+    for (key, value) in missing_defaults(schema, |k| present.contains(&k))? {
+        filled.entries.push(Entry::KeyValue {
+            key,
+            value,
+            comment: None,
+        });
+    }
+    Ok(filled)
+}
+
+/// RFC 1123 hostname: dot-separated labels of 1-63 letters, digits, and
+/// hyphens, not starting or ending with a hyphen; 253 characters at most.
+fn is_hostname(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 253
+        && value.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
 /// Load a Schema from a .enseal.toml file, if one exists.
 pub fn load_schema(config_path: Option<&str>) -> Result<Option<Schema>> {
     let path = config_path.unwrap_or(".enseal.toml");
@@ -304,6 +514,7 @@ mod tests {
                 "PORT".to_string(),
             ],
             rules,
+            ..Default::default()
         }
     }
 
@@ -427,4 +638,326 @@ enum = ["debug", "info", "warn", "error"]
         assert!(schema.rules.contains_key("LOG_LEVEL"));
         assert_eq!(schema.rules["PORT"].var_type.as_deref(), Some("integer"));
     }
+
+    fn semver_rule() -> Schema {
+        let mut rules = HashMap::new();
+        rules.insert(
+            "MIN_CLIENT_VERSION".to_string(),
+            Rule {
+                var_type: Some("semver".to_string()),
+                semver_req: Some(">=1.2, <2".to_string()),
+                ..Default::default()
+            },
+        );
+        Schema {
+            required: vec![],
+            rules,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn semver_valid_version_passes() {
+        let env = parser::parse("MIN_CLIENT_VERSION=1.4.2\n").unwrap();
+        assert!(validate(&env, &semver_rule()).is_empty());
+    }
+
+    #[test]
+    fn semver_invalid_string_fails() {
+        let env = parser::parse("MIN_CLIENT_VERSION=1.4\n").unwrap();
+        let errors = validate(&env, &semver_rule());
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("not a semantic version"));
+    }
+
+    #[test]
+    fn semver_out_of_range_fails() {
+        let env = parser::parse("MIN_CLIENT_VERSION=2.0.1\n").unwrap();
+        let errors = validate(&env, &semver_rule());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "version 2.0.1 does not satisfy >=1.2, <2"
+        );
+    }
+
+    fn type_errors(var_type: &str, value: &str) -> Vec<String> {
+        validate_rule(
+            value,
+            &Rule {
+                var_type: Some(var_type.to_string()),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn ip_type() {
+        assert!(type_errors("ip", "10.0.0.1").is_empty());
+        assert!(type_errors("ip", "::1").is_empty());
+        let errors = type_errors("ip", "10.0.0.256");
+        assert_eq!(errors[0], "value is not a valid IPv4/IPv6 address");
+    }
+
+    #[test]
+    fn port_type() {
+        assert!(type_errors("port", "9090").is_empty());
+        for bad in ["0", "70000", "http"] {
+            let errors = type_errors("port", bad);
+            assert_eq!(errors.len(), 1, "{}", bad);
+            assert_eq!(errors[0], "value is not a valid port (1-65535)");
+        }
+    }
+
+    #[test]
+    fn port_type_composes_with_range() {
+        let rule = Rule {
+            var_type: Some("port".to_string()),
+            range: Some([1024, 49151]),
+            ..Default::default()
+        };
+        assert!(validate_rule("9090", &rule).is_empty());
+        let errors = validate_rule("80", &rule);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("outside range"));
+        // An invalid port is reported once, by the type check
+        assert_eq!(validate_rule("http", &rule).len(), 1);
+    }
+
+    #[test]
+    fn hostname_type() {
+        assert!(type_errors("hostname", "redis-1.internal.example.com").is_empty());
+        assert!(type_errors("hostname", "localhost").is_empty());
+        for bad in ["-redis.internal", "redis_1", "redis..internal", ""] {
+            let errors = type_errors("hostname", bad);
+            assert_eq!(errors.len(), 1, "{}", bad);
+            assert_eq!(errors[0], "value is not a valid hostname");
+        }
+        assert!(!is_hostname(&"a".repeat(64)));
+    }
+
+    #[test]
+    fn url_type_requires_host() {
+        assert_eq!(
+            type_errors("url", "https://")[0],
+            "value is not a valid URL: empty host"
+        );
+        assert_eq!(
+            type_errors("url", "mailto:ops@example.com")[0],
+            "URL has no host"
+        );
+        assert!(type_errors("url", "postgres://user:pw@db.internal:5432/app").is_empty());
+        assert!(type_errors("url", "ftp://files.example.com").is_empty());
+    }
+
+    #[test]
+    fn url_schemes_restrict() {
+        let rule = Rule {
+            var_type: Some("url".to_string()),
+            schemes: Some(vec!["https".to_string(), "postgres".to_string()]),
+            ..Default::default()
+        };
+        assert!(validate_rule("postgres://db.internal/app", &rule).is_empty());
+        let errors = validate_rule("http://db.internal", &rule);
+        assert_eq!(
+            errors[0],
+            "URL scheme 'http' not allowed (expected: https, postgres)"
+        );
+    }
+
+    fn defaults_schema() -> Schema {
+        let mut rules = HashMap::new();
+        rules.insert(
+            "LOG_LEVEL".to_string(),
+            Rule {
+                default: Some("info".to_string()),
+                ..Default::default()
+            },
+        );
+        rules.insert(
+            "PORT".to_string(),
+            Rule {
+                var_type: Some("port".to_string()),
+                default: Some("8080".to_string()),
+                ..Default::default()
+            },
+        );
+        Schema {
+            required: vec![],
+            rules,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn defaults_fill_only_missing_keys() {
+        let env = parser::parse("PORT=3000\nDB_URL=postgres://x\n").unwrap();
+        let filled = apply_defaults(&env, &defaults_schema()).unwrap();
+        assert_eq!(filled.get("PORT"), Some("3000"));
+        assert_eq!(filled.get("LOG_LEVEL"), Some("info"));
+        assert_eq!(filled.var_count(), 3);
+
+        let empty = parser::parse("LOG_LEVEL=\n").unwrap();
+        let filled = apply_defaults(&empty, &defaults_schema()).unwrap();
+        assert_eq!(filled.get("LOG_LEVEL"), Some(""));
+    }
+
+    #[test]
+    fn invalid_default_is_an_error() {
+        let mut schema = defaults_schema();
+        schema.rules.get_mut("PORT").unwrap().default = Some("0".to_string());
+        let env = parser::parse("DB_URL=postgres://x\n").unwrap();
+        let err = apply_defaults(&env, &schema).unwrap_err();
+        assert!(err.to_string().contains("default for PORT"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_severity_rejected_at_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".enseal.toml");
+        std::fs::write(
+            &path,
+            "[schema.rules.API_KEY]\nmin_length = 32\nseverity = \"warning\"\n",
+        )
+        .unwrap();
+        // Reported even though no file sets API_KEY
+        let err = load_schema(Some(path.to_str().unwrap())).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("unknown severity 'warning' (expected: error, warn)"),
+            "{:#}",
+            err
+        );
+
+        std::fs::write(&path, "[schema.rules.API_KEY]\nseverity = \"warn\"\n").unwrap();
+        let schema = load_schema(Some(path.to_str().unwrap())).unwrap().unwrap();
+        assert_eq!(schema.rules["API_KEY"].severity, Some(Severity::Warning));
+    }
+
+    #[test]
+    fn warn_severity_tags_violations() {
+        let mut rules = HashMap::new();
+        rules.insert(
+            "API_KEY".to_string(),
+            Rule {
+                min_length: Some(32),
+                severity: Some(Severity::Warning),
+                ..Default::default()
+            },
+        );
+        rules.insert(
+            "PORT".to_string(),
+            Rule {
+                var_type: Some("port".to_string()),
+                ..Default::default()
+            },
+        );
+        let schema = Schema {
+            required: vec!["DB_URL".to_string()],
+            rules,
+            ..Default::default()
+        };
+        let env = parser::parse("API_KEY=short\nPORT=0\n").unwrap();
+
+        let mut errors = validate(&env, &schema);
+        errors.sort_by(|a, b| a.key.cmp(&b.key));
+        let tagged: Vec<_> = errors
+            .iter()
+            .map(|e| (e.key.as_str(), e.severity))
+            .collect();
+        assert_eq!(
+            tagged,
+            vec![
+                ("API_KEY", Severity::Warning),
+                ("DB_URL", Severity::Error),
+                ("PORT", Severity::Error),
+            ]
+        );
+    }
+
+    fn constraint_errors(content: &str, constraint: Constraint) -> Vec<String> {
+        let schema = Schema {
+            constraints: vec![constraint],
+            ..Default::default()
+        };
+        validate(&parser::parse(content).unwrap(), &schema)
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+
+    fn tls_requires() -> Constraint {
+        Constraint {
+            key: Some("USE_TLS".to_string()),
+            when: Some("true".to_string()),
+            requires: vec!["TLS_CERT_PATH".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn requires_satisfied() {
+        let ok = "USE_TLS=true\nTLS_CERT_PATH=/etc/tls/cert.pem\n";
+        assert!(constraint_errors(ok, tls_requires()).is_empty());
+        // Condition not met: nothing required
+        assert!(constraint_errors("USE_TLS=false\n", tls_requires()).is_empty());
+    }
+
+    #[test]
+    fn requires_violated() {
+        assert_eq!(
+            constraint_errors("USE_TLS=true\nTLS_CERT_PATH=\n", tls_requires()),
+            vec!["USE_TLS: TLS_CERT_PATH is required when USE_TLS=true"]
+        );
+    }
+
+    #[test]
+    fn mutually_exclusive_counts() {
+        let aws = || Constraint {
+            mutually_exclusive: vec!["AWS_PROFILE".to_string(), "AWS_ACCESS_KEY_ID".to_string()],
+            ..Default::default()
+        };
+        assert!(constraint_errors("REGION=us-east-1\n", aws()).is_empty());
+        assert!(constraint_errors("AWS_PROFILE=dev\n", aws()).is_empty());
+        assert_eq!(
+            constraint_errors("AWS_PROFILE=dev\nAWS_ACCESS_KEY_ID=AKIA123\n", aws()),
+            vec![
+                "AWS_PROFILE: only one of AWS_PROFILE, AWS_ACCESS_KEY_ID may be set \
+                 (found AWS_PROFILE, AWS_ACCESS_KEY_ID)"
+            ]
+        );
+
+        let exactly_one = Constraint {
+            require_one: true,
+            ..aws()
+        };
+        assert_eq!(
+            constraint_errors("REGION=us-east-1\n", exactly_one),
+            vec!["AWS_PROFILE: one of AWS_PROFILE, AWS_ACCESS_KEY_ID must be set"]
+        );
+    }
+
+    #[test]
+    fn equals_compares_values() {
+        let confirm = || Constraint {
+            key: Some("DB_PASSWORD_CONFIRM".to_string()),
+            equals: Some("DB_PASSWORD".to_string()),
+            ..Default::default()
+        };
+        let same = "DB_PASSWORD=s3cret\nDB_PASSWORD_CONFIRM=s3cret\n";
+        assert!(constraint_errors(same, confirm()).is_empty());
+        let differ = "DB_PASSWORD=s3cret\nDB_PASSWORD_CONFIRM=other\n";
+        assert_eq!(
+            constraint_errors(differ, confirm()),
+            vec!["DB_PASSWORD_CONFIRM: must equal DB_PASSWORD"]
+        );
+    }
+
+    #[test]
+    fn constraints_parse_from_toml() {
+        let schema: Schema = toml::from_str(
+            "[[constraints]]\nkey = \"USE_TLS\"\nwhen = \"true\"\nrequires = [\"TLS_CERT_PATH\"]\n",
+        )
+        .unwrap();
+        assert_eq!(schema.constraints[0].requires, vec!["TLS_CERT_PATH"]);
+    }
 }
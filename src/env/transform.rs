@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use super::{Entry, EnvFile};
+
+/// A rewrite applied to every key of an EnvFile (`receive --transform`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyTransform {
+    Uppercase,
+    Lowercase,
+    /// Prepend this string to every key.
+    Prefix(String),
+    /// Remove this string from the start of keys that have it.
+    StripPrefix(String),
+}
+
+/// Parse `uppercase-keys`, `lowercase-keys`, `prefix:X`, or `strip-prefix:X`.
+pub fn parse(value: &str) -> Result<KeyTransform, String> {
+    let transform = match value.split_once(':') {
+        None if value == "uppercase-keys" => KeyTransform::Uppercase,
+        None if value == "lowercase-keys" => KeyTransform::Lowercase,
+        Some(("prefix", prefix)) if !prefix.is_empty() => KeyTransform::Prefix(prefix.to_string()),
+        Some(("strip-prefix", prefix)) if !prefix.is_empty() => {
+            KeyTransform::StripPrefix(prefix.to_string())
+        }
+        _ => {
+            return Err(format!(
+                "unknown transform '{}' (use uppercase-keys, lowercase-keys, prefix:X, or strip-prefix:X)",
+                value
+            ))
+        }
+    };
+    Ok(transform)
+}
+
+impl KeyTransform {
+    /// The new name for `key`.
+    pub fn apply(&self, key: &str) -> String {
+        match self {
+            Self::Uppercase => key.to_ascii_uppercase(),
+            Self::Lowercase => key.to_ascii_lowercase(),
+            Self::Prefix(prefix) => format!("{}{}", prefix, key),
+            Self::StripPrefix(prefix) => {
+                key.strip_prefix(prefix.as_str()).unwrap_or(key).to_string()
+            }
+        }
+    }
+}
+
+/// Several distinct keys that a transform mapped to the same name.
+#[derive(Debug, PartialEq)]
+pub struct Collision {
+    pub name: String,
+    /// Original keys, in file order.
+    pub sources: Vec<String>,
+}
+
+/// Rename every key with `transform`. Values, comments and ordering are
+/// kept; keys that collide are all kept too, so the last one wins as usual.
+pub fn transform(env: &EnvFile, transform: &KeyTransform) -> (EnvFile, Vec<Collision>) {
+    rename(env, |key| transform.apply(key))
+}
+
+/// Rename every key with `rename`, reporting distinct keys that end up
+/// with the same name.
+pub(super) fn rename(env: &EnvFile, rename: impl Fn(&str) -> String) -> (EnvFile, Vec<Collision>) {
+    let mut sources: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let entries = env
+        .entries
+        .iter()
+        .map(|entry| match entry {
+            Entry::KeyValue {
+                key,
+                value,
+                comment,
+            } => {
+                let name = rename(key);
+                let seen = sources.entry(name.clone()).or_default();
+                if !seen.contains(key) {
+                    seen.push(key.clone());
+                }
+                Entry::KeyValue {
+                    key: name,
+                    value: value.clone(),
+                    comment: comment.clone(),
+                }
+            }
+            other => other.clone(),
+        })
+        .collect();
+
+    let collisions = sources
+        .into_iter()
+        .filter(|(_, sources)| sources.len() > 1)
+        .map(|(name, sources)| Collision { name, sources })
+        .collect();
+    (EnvFile { entries }, collisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::parser;
+
+    fn run(input: &str, spec: &str) -> (EnvFile, Vec<Collision>) {
+        transform(&parser::parse(input).unwrap(), &parse(spec).unwrap())
+    }
+
+    #[test]
+    fn uppercase_and_lowercase() {
+        let (env, collisions) = run("db_url=x\nApi_Key=y\n", "uppercase-keys");
+        assert_eq!(env.keys(), ["DB_URL", "API_KEY"]);
+        assert!(collisions.is_empty());
+
+        let (env, _) = run("DB_URL=x\nApi_Key=y\n", "lowercase-keys");
+        assert_eq!(env.keys(), ["db_url", "api_key"]);
+        assert_eq!(env.get("db_url"), Some("x"));
+    }
+
+    #[test]
+    fn prefix_and_strip_prefix() {
+        let (env, _) = run("# comment\nDB_URL=x\n", "prefix:APP_");
+        assert_eq!(env.keys(), ["APP_DB_URL"]);
+        assert!(env.to_string().starts_with("# comment\n"));
+
+        let (env, _) = run("VITE_API=x\nPORT=1\n", "strip-prefix:VITE_");
+        assert_eq!(env.keys(), ["API", "PORT"]);
+    }
+
+    #[test]
+    fn collisions_are_reported() {
+        let (env, collisions) = run("api_key=a\nAPI_KEY=b\nOTHER=c\n", "uppercase-keys");
+        assert_eq!(env.keys(), ["API_KEY", "API_KEY", "OTHER"]);
+        assert_eq!(env.get("API_KEY"), Some("b"));
+        assert_eq!(
+            collisions,
+            [Collision {
+                name: "API_KEY".to_string(),
+                sources: vec!["api_key".to_string(), "API_KEY".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_unknown_transforms() {
+        assert!(parse("titlecase-keys").is_err());
+        assert!(parse("prefix:").is_err());
+        assert!(parse("suffix:X").is_err());
+        assert_eq!(
+            parse("strip-prefix:A_"),
+            Ok(KeyTransform::StripPrefix("A_".to_string()))
+        );
+    }
+}
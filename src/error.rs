@@ -0,0 +1,38 @@
+//! Error type for the library boundary.
+//!
+//! Core parsing, envelope and decryption functions return [`EnsealError`] so
+//! embedders can match on what went wrong. The CLI converts to `anyhow` with `?`.
+
+/// Failure categories surfaced by the core library.
+#[derive(Debug, thiserror::Error)]
+pub enum EnsealError {
+    /// Malformed input: .env syntax, envelope JSON, key encodings.
+    #[error("{0}")]
+    Parse(String),
+    /// age encryption failed (no recipients, encryptor errors).
+    #[error("{0}")]
+    Encrypt(String),
+    /// age decryption failed (wrong key, corrupt ciphertext, bad plaintext).
+    #[error("{0}")]
+    Decrypt(String),
+    /// Signature, sender, or payload integrity check failed.
+    #[error("{0}")]
+    SignatureVerification(String),
+    /// No identity or key available for the operation.
+    #[error("{0}")]
+    KeyNotFound(String),
+    /// Envelope is outside its freshness window.
+    #[error("{0}")]
+    Expired(String),
+    /// Envelope was already received (its nonce is in the seen-nonce cache).
+    #[error("{0}")]
+    Replay(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Rendezvous/relay connection or transfer failure.
+    #[error("{0}")]
+    Network(String),
+}
+
+/// `Result` with [`EnsealError`] as the error type.
+pub type Result<T, E = EnsealError> = std::result::Result<T, E>;
@@ -0,0 +1,105 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use rand::Rng;
+
+/// Write a file containing secrets with restrictive permissions (0600 on Unix),
+/// atomically: the content goes to a temp file in the same directory, is
+/// fsynced, then renamed over `path`. A crash mid-write leaves the original intact.
+pub fn write_secret_file(path: impl AsRef<Path>, content: &[u8]) -> Result<()> {
+    write_atomic(path.as_ref(), content, |_| Ok(()))
+}
+
+/// [`write_secret_file`] with a hook that runs just before the rename
+/// (lets tests simulate a failure at the last moment).
+fn write_atomic<F>(path: &Path, content: &[u8], before_rename: F) -> Result<()>
+where
+    F: FnOnce(&Path) -> Result<()>,
+{
+    let tmp = temp_path(path);
+    let result = write_temp(&tmp, content)
+        .and_then(|()| before_rename(&tmp))
+        .and_then(|()| {
+            std::fs::rename(&tmp, path)
+                .with_context(|| format!("failed to replace '{}'", path.display()))
+        });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+fn write_temp(tmp: &Path, content: &[u8]) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(tmp)
+        .with_context(|| format!("failed to create '{}'", tmp.display()))?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Hidden, randomly suffixed sibling of `path` (same directory, so the
+/// rename stays on one filesystem).
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let suffix: u32 = rand::thread_rng().gen();
+    path.with_file_name(format!(".{}.{:08x}.tmp", name, suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn replaces_existing_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".env");
+        std::fs::write(&path, "OLD=1\n").unwrap();
+
+        write_secret_file(&path, b"NEW=2\n").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "NEW=2\n");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn failure_before_rename_keeps_original() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".env");
+        std::fs::write(&path, "OLD=1\n").unwrap();
+
+        let result = write_atomic(&path, b"NEW=2\n", |_| anyhow::bail!("simulated crash"));
+
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "OLD=1\n");
+        // The temp file is cleaned up
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn written_with_owner_only_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".env");
+        std::fs::write(&path, "OLD=1\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_secret_file(&path, b"NEW=2\n").unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+    }
+}
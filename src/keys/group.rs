@@ -60,6 +60,23 @@ pub fn add_member(store: &KeyStore, group: &str, identity: &str) -> Result<bool>
     Ok(true)
 }
 
+/// Check that a prospective member resolves to something: a trusted key,
+/// an alias, or another group.
+pub fn check_member(store: &KeyStore, identity: &str) -> Result<()> {
+    if store.trusted_key_path(identity)?.exists()
+        || super::alias::resolve(store, identity)?.is_some()
+        || load_groups(store)?.contains_key(identity)
+    {
+        return Ok(());
+    }
+    bail!(
+        "no trusted key, alias, or group named '{}'. Import their key with: enseal keys import <file>\n\
+         Or add it anyway with: enseal keys group add <group> {} --allow-missing",
+        identity,
+        identity
+    );
+}
+
 /// Remove a member from a group. Returns whether the member was found.
 pub fn remove_member(store: &KeyStore, group: &str, identity: &str) -> Result<bool> {
     let mut groups = load_groups(store)?;
@@ -93,6 +110,57 @@ pub fn get_members(store: &KeyStore, name: &str) -> Result<Option<Vec<String>>>
     Ok(groups.get(name).map(|e| e.members.clone()))
 }
 
+/// How deep `expand` follows groups nested inside groups.
+const MAX_NESTING_DEPTH: usize = 8;
+
+/// Expand a group into a flat, deduplicated identity list, following members
+/// that name other groups. A member that names a group is always treated as
+/// that group, even if a trusted key shares the name. Returns None if the
+/// group doesn't exist; errors on cycles, excessive nesting, or no members.
+pub fn expand(store: &KeyStore, name: &str) -> Result<Option<Vec<String>>> {
+    let groups = load_groups(store)?;
+    if !groups.contains_key(name) {
+        return Ok(None);
+    }
+    let mut identities = Vec::new();
+    expand_into(&groups, name, &mut vec![name.to_string()], &mut identities)?;
+    if identities.is_empty() {
+        bail!("group '{}' has no members", name);
+    }
+    Ok(Some(identities))
+}
+
+fn expand_into(
+    groups: &BTreeMap<String, GroupEntry>,
+    name: &str,
+    path: &mut Vec<String>,
+    identities: &mut Vec<String>,
+) -> Result<()> {
+    for member in &groups[name].members {
+        if !groups.contains_key(member) {
+            if !identities.contains(member) {
+                identities.push(member.clone());
+            }
+            continue;
+        }
+        if path.contains(member) {
+            bail!("group cycle: {} -> {}", path.join(" -> "), member);
+        }
+        if path.len() >= MAX_NESTING_DEPTH {
+            bail!(
+                "groups nested more than {} deep: {} -> {}",
+                MAX_NESTING_DEPTH,
+                path.join(" -> "),
+                member
+            );
+        }
+        path.push(member.clone());
+        expand_into(groups, member, path, identities)?;
+        path.pop();
+    }
+    Ok(())
+}
+
 /// List all groups as (name, entry) pairs.
 pub fn list_groups(store: &KeyStore) -> Result<Vec<(String, GroupEntry)>> {
     let groups = load_groups(store)?;
@@ -202,4 +270,81 @@ mod tests {
 
         assert!(get_members(&store, "nope").unwrap().is_none());
     }
+
+    #[test]
+    fn nested_groups_expand_flat() {
+        let dir = TempDir::new().unwrap();
+        let store = test_store(&dir);
+
+        create(&store, "backend").unwrap();
+        add_member(&store, "backend", "alice").unwrap();
+        add_member(&store, "backend", "bob").unwrap();
+        create(&store, "frontend").unwrap();
+        add_member(&store, "frontend", "carol").unwrap();
+        add_member(&store, "frontend", "bob").unwrap();
+        create(&store, "all-eng").unwrap();
+        add_member(&store, "all-eng", "backend").unwrap();
+        add_member(&store, "all-eng", "frontend").unwrap();
+        add_member(&store, "all-eng", "alice").unwrap();
+
+        let members = expand(&store, "all-eng").unwrap().unwrap();
+        assert_eq!(members, vec!["alice", "bob", "carol"]);
+        assert!(expand(&store, "nope").unwrap().is_none());
+    }
+
+    #[test]
+    fn group_cycle_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let store = test_store(&dir);
+
+        create(&store, "a").unwrap();
+        create(&store, "b").unwrap();
+        add_member(&store, "a", "alice").unwrap();
+        add_member(&store, "a", "b").unwrap();
+        add_member(&store, "b", "a").unwrap();
+
+        let err = expand(&store, "a").unwrap_err();
+        assert!(err.to_string().contains("a -> b -> a"), "{}", err);
+    }
+
+    #[test]
+    fn deep_nesting_is_limited() {
+        let dir = TempDir::new().unwrap();
+        let store = test_store(&dir);
+
+        for i in 0..=MAX_NESTING_DEPTH {
+            create(&store, &format!("g{}", i)).unwrap();
+        }
+        for i in 0..MAX_NESTING_DEPTH {
+            add_member(&store, &format!("g{}", i), &format!("g{}", i + 1)).unwrap();
+        }
+        add_member(&store, &format!("g{}", MAX_NESTING_DEPTH), "alice").unwrap();
+
+        let err = expand(&store, "g0").unwrap_err();
+        assert!(err.to_string().contains("nested more than"), "{}", err);
+        assert_eq!(expand(&store, "g1").unwrap().unwrap(), vec!["alice"]);
+    }
+
+    #[test]
+    fn check_member_accepts_known_names() {
+        let dir = TempDir::new().unwrap();
+        let store = test_store(&dir);
+        store.ensure_dirs().unwrap();
+        std::fs::write(store.trusted_key_path("alice").unwrap(), "").unwrap();
+        crate::keys::alias::set(&store, "bob", "bob@example.com").unwrap();
+        create(&store, "backend").unwrap();
+
+        check_member(&store, "alice").unwrap();
+        check_member(&store, "bob").unwrap();
+        check_member(&store, "backend").unwrap();
+    }
+
+    #[test]
+    fn check_member_rejects_unknown_names() {
+        let dir = TempDir::new().unwrap();
+        let store = test_store(&dir);
+
+        let err = check_member(&store, "ghost@example.com").unwrap_err();
+        assert!(err.to_string().contains("enseal keys import"), "{}", err);
+    }
 }
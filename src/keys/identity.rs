@@ -6,6 +6,8 @@ use sha2::{Digest, Sha256};
 use age::secrecy::ExposeSecret;
 
 use super::store::KeyStore;
+use crate::crypto::at_rest::SharedRecipient;
+use crate::error::EnsealError;
 
 /// A complete enseal identity: age keypair (encryption) + ed25519 keypair (signing).
 pub struct EnsealIdentity {
@@ -34,28 +36,82 @@ impl EnsealIdentity {
         }
     }
 
+    /// Derive a keypair deterministically from `seed` via HKDF-SHA256, so
+    /// test failures reproduce. Never use this for real keys.
+    #[cfg(any(test, feature = "test-util"))]
+    #[allow(dead_code)]
+    pub fn generate_from_seed(seed: [u8; 32]) -> Self {
+        use bech32::ToBase32;
+
+        let hkdf = hkdf::Hkdf::<Sha256>::new(None, &seed);
+        let mut age_secret = [0u8; 32];
+        let mut sign_secret = [0u8; 32];
+        hkdf.expand(b"enseal test identity: age", &mut age_secret)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        hkdf.expand(b"enseal test identity: ed25519", &mut sign_secret)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+
+        // age only parses identities from their bech32 string form
+        let encoded = bech32::encode(
+            "age-secret-key-",
+            age_secret.to_base32(),
+            bech32::Variant::Bech32,
+        )
+        .expect("valid bech32 prefix");
+        let age_identity: age::x25519::Identity = encoded
+            .to_uppercase()
+            .parse()
+            .expect("32 bytes form a valid age identity");
+        Self {
+            age_recipient: age_identity.to_public(),
+            age_identity,
+            signing_key: SigningKey::from_bytes(&sign_secret),
+        }
+    }
+
     /// Load own identity from the key store.
-    pub fn load(store: &KeyStore) -> Result<Self> {
+    pub fn load(store: &KeyStore) -> Result<Self, EnsealError> {
         if !store.is_initialized() {
-            bail!("no identity found. Run `enseal keys init` first.");
+            return Err(EnsealError::KeyNotFound(
+                "no identity found. Run `enseal keys init` first.".to_string(),
+            ));
         }
 
-        let age_key_str = std::fs::read_to_string(store.age_private_key_path())
-            .context("failed to read age private key")?;
+        Self::load_files(
+            &store.age_private_key_path(),
+            &store.sign_private_key_path(),
+        )
+    }
+
+    /// Load identities retired by `keys rotate-self`, newest first.
+    pub fn load_archived(store: &KeyStore) -> Result<Vec<Self>, EnsealError> {
+        store
+            .archived_key_dirs()?
+            .iter()
+            .map(|dir| Self::load_files(&dir.join("self.age.key"), &dir.join("self.sign.key")))
+            .collect()
+    }
+
+    fn load_files(
+        age_key_path: &std::path::Path,
+        sign_key_path: &std::path::Path,
+    ) -> Result<Self, EnsealError> {
+        let age_key_str = read_key_file(age_key_path, "age private key")?;
         let age_identity: age::x25519::Identity = age_key_str
             .trim()
             .parse()
-            .map_err(|e: &str| anyhow::anyhow!("{}", e))?;
+            .map_err(|e: &str| EnsealError::Parse(format!("invalid age private key: {}", e)))?;
         let age_recipient = age_identity.to_public();
 
-        let sign_key_b64 = std::fs::read_to_string(store.sign_private_key_path())
-            .context("failed to read signing private key")?;
+        let sign_key_b64 = read_key_file(sign_key_path, "signing private key")?;
         let sign_key_bytes = base64::engine::general_purpose::STANDARD
             .decode(sign_key_b64.trim())
-            .context("invalid base64 in signing private key")?;
+            .map_err(|e| {
+                EnsealError::Parse(format!("invalid base64 in signing private key: {}", e))
+            })?;
         let sign_key_array: [u8; 32] = sign_key_bytes
             .try_into()
-            .map_err(|_| anyhow::anyhow!("invalid signing key length"))?;
+            .map_err(|_| EnsealError::Parse("invalid signing key length".to_string()))?;
         let signing_key = SigningKey::from_bytes(&sign_key_array);
 
         Ok(Self {
@@ -83,35 +139,57 @@ impl EnsealIdentity {
         )?;
 
         // Ed25519 verifying key (raw 32 bytes)
-        std::fs::write(
-            store.sign_public_key_path(),
-            base64::engine::general_purpose::STANDARD
-                .encode(self.signing_key.verifying_key().to_bytes()),
-        )?;
+        std::fs::write(store.sign_public_key_path(), self.sign_pubkey_b64())?;
 
         Ok(())
     }
 
+    /// The Ed25519 verifying key, base64-encoded as it appears in `.pub` files.
+    pub fn sign_pubkey_b64(&self) -> String {
+        base64::engine::general_purpose::STANDARD
+            .encode(self.signing_key.verifying_key().to_bytes())
+    }
+
+    /// Render this identity's public key bundle under the label `identity`.
+    pub fn pubkey_file(&self, identity: &str) -> String {
+        format_pubkey_file(
+            identity,
+            &self.age_recipient.to_string(),
+            &self.sign_pubkey_b64(),
+        )
+    }
+
     /// Compute the fingerprint of the public keys (SHA256 of age pubkey + sign pubkey).
     pub fn fingerprint(&self) -> String {
-        fingerprint_from_keys(
+        self.fingerprint_as(FingerprintFormat::Base64)
+    }
+
+    /// The fingerprint rendered for display in `format`.
+    pub fn fingerprint_as(&self, format: FingerprintFormat) -> String {
+        format.render(&fingerprint_digest(
             &self.age_recipient.to_string(),
-            &base64::engine::general_purpose::STANDARD
-                .encode(self.signing_key.verifying_key().to_bytes()),
-        )
+            &self.sign_pubkey_b64(),
+        ))
     }
 
     /// Compute a URL-safe channel ID for relay listen mode.
     /// Hex-encoded SHA256 prefix of the public keys.
     pub fn channel_id(&self) -> String {
-        channel_id_from_keys(
-            &self.age_recipient.to_string(),
-            &base64::engine::general_purpose::STANDARD
-                .encode(self.signing_key.verifying_key().to_bytes()),
-        )
+        channel_id_from_keys(&self.age_recipient.to_string(), &self.sign_pubkey_b64())
     }
 }
 
+/// Read a private key file; the error names the key and its path but keeps
+/// the I/O error kind (for exit codes).
+fn read_key_file(path: &std::path::Path, what: &str) -> Result<String, EnsealError> {
+    std::fs::read_to_string(path).map_err(|e| {
+        EnsealError::Io(std::io::Error::new(
+            e.kind(),
+            format!("failed to read {} {}: {}", what, path.display(), e),
+        ))
+    })
+}
+
 impl TrustedKey {
     /// Parse a `.pub` file containing an enseal public key bundle.
     ///
@@ -122,6 +200,9 @@ impl TrustedKey {
     /// age: age1...
     /// sign: ed25519:<base64>
     /// ```
+    ///
+    /// Bundles holding only `ssh:` lines (see [`format_ssh_key_file`]) are
+    /// encryption-only and rejected here; use [`parse_recipients`] for those.
     pub fn parse(identity: &str, content: &str) -> Result<Self> {
         let mut age_pubkey: Option<String> = None;
         let mut sign_pubkey: Option<String> = None;
@@ -138,6 +219,14 @@ impl TrustedKey {
             }
         }
 
+        if age_pubkey.is_none() && !parse_ssh_keys(content).is_empty() {
+            bail!(
+                "'{}' is an SSH key entry: it can be used with 'enseal encrypt --to' \
+                 but cannot send or verify signed transfers",
+                identity
+            );
+        }
+
         let age_str = age_pubkey.context("missing 'age:' line in public key file")?;
         let sign_str = sign_pubkey.context("missing 'sign: ed25519:' line in public key file")?;
 
@@ -163,23 +252,20 @@ impl TrustedKey {
 
     /// Load a trusted key from the store by identity name.
     pub fn load(store: &KeyStore, identity: &str) -> Result<Self> {
-        let path = store.trusted_key_path(identity)?;
-        if !path.exists() {
-            bail!(
-                "no public key found for '{}'. Import with: enseal keys import <file>",
-                identity
-            );
-        }
-        let content = std::fs::read_to_string(&path)?;
-        Self::parse(identity, &content)
+        Self::parse(identity, &read_trusted(store, identity)?)
     }
 
     /// Compute the fingerprint of this key.
     pub fn fingerprint(&self) -> String {
-        fingerprint_from_keys(
+        self.fingerprint_as(FingerprintFormat::Base64)
+    }
+
+    /// The fingerprint rendered for display in `format`.
+    pub fn fingerprint_as(&self, format: FingerprintFormat) -> String {
+        format.render(&fingerprint_digest(
             &self.age_recipient.to_string(),
             &base64::engine::general_purpose::STANDARD.encode(self.verifying_key.to_bytes()),
-        )
+        ))
     }
 
     /// Compute a URL-safe channel ID for relay listen mode.
@@ -192,6 +278,254 @@ impl TrustedKey {
     }
 }
 
+/// Parse a `--to` value that is a bare `age1...` recipient string (as used by
+/// the `age` tool) rather than a trusted identity name.
+pub fn parse_raw_recipient(value: &str) -> Option<age::x25519::Recipient> {
+    if !value.starts_with("age1") {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// Warning for recipients used without a trusted-key bundle behind them.
+pub fn raw_recipient_warning(value: &str) -> String {
+    format!(
+        "'{}' is a raw age recipient: no fingerprint or identity is associated with it",
+        value
+    )
+}
+
+/// Read the raw `.pub` bundle stored for a trusted identity.
+pub fn read_trusted(store: &KeyStore, identity: &str) -> Result<String> {
+    let path = store.trusted_key_path(identity)?;
+    if !path.exists() {
+        bail!(
+            "no public key found for '{}'. Import with: enseal keys import <file>",
+            identity
+        );
+    }
+    Ok(std::fs::read_to_string(&path)?)
+}
+
+/// Outcome of one `keys verify-bundle` check.
+#[derive(Debug, PartialEq)]
+pub enum CheckOutcome {
+    Pass,
+    Fail(String),
+    /// The check doesn't apply to this bundle, with the reason.
+    Skipped(String),
+}
+
+/// One named check run on a `.pub` bundle.
+#[derive(Debug)]
+pub struct BundleCheck {
+    pub name: &'static str,
+    pub outcome: CheckOutcome,
+}
+
+/// Sanity-check a `.pub` bundle before trusting it: the age recipient
+/// parses, the signing key is a valid ed25519 point, the whole bundle
+/// parses, and the `# fingerprint:` comment matches the keys.
+pub fn verify_bundle(content: &str) -> Vec<BundleCheck> {
+    let line_value = |prefix: &str| {
+        content
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(prefix))
+            .map(str::trim)
+    };
+    let age_line = line_value("age: ");
+    let sign_line = line_value("sign: ed25519:");
+    let check = |name, result: Result<()>| BundleCheck {
+        name,
+        outcome: match result {
+            Ok(()) => CheckOutcome::Pass,
+            Err(e) => CheckOutcome::Fail(format!("{:#}", e)),
+        },
+    };
+
+    let age = check(
+        "age recipient",
+        age_line.context("missing 'age:' line").and_then(|value| {
+            value
+                .parse::<age::x25519::Recipient>()
+                .map(drop)
+                .map_err(|e| anyhow::anyhow!("invalid age public key: {}", e))
+        }),
+    );
+    let sign = check(
+        "signing key",
+        sign_line
+            .context("missing 'sign: ed25519:' line")
+            .and_then(|value| {
+                let bytes: [u8; 32] = base64::engine::general_purpose::STANDARD
+                    .decode(value)
+                    .context("invalid base64")?
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("not 32 bytes"))?;
+                ed25519_dalek::VerifyingKey::from_bytes(&bytes)
+                    .map(drop)
+                    .context("not a valid ed25519 point")
+            }),
+    );
+    let parse = check(
+        "bundle parses",
+        TrustedKey::parse("bundle", content).map(drop),
+    );
+
+    let embedded = line_value("# fingerprint:");
+    let fingerprint = match (embedded, age_line, sign_line) {
+        (None, ..) => BundleCheck {
+            name: "fingerprint comment",
+            outcome: CheckOutcome::Skipped("no '# fingerprint:' line".to_string()),
+        },
+        (Some(embedded), Some(age), Some(sign)) => {
+            let computed = fingerprint_from_keys(age, sign);
+            check(
+                "fingerprint comment",
+                if embedded == computed {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!(
+                        "comment says {} but the keys hash to {}",
+                        embedded,
+                        computed
+                    ))
+                },
+            )
+        }
+        (Some(_), ..) => check(
+            "fingerprint comment",
+            Err(anyhow::anyhow!("can't recompute without both key lines")),
+        ),
+    };
+
+    vec![age, sign, parse, fingerprint]
+}
+
+/// The date in a trusted bundle's optional `expires: YYYY-MM-DD` line.
+pub fn bundle_expiry(content: &str) -> Result<Option<time::Date>> {
+    let Some(value) = content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("expires:"))
+        .map(str::trim)
+    else {
+        return Ok(None);
+    };
+    let invalid = || anyhow::anyhow!("invalid expires date '{}' (use YYYY-MM-DD)", value);
+    let mut parts = value.splitn(3, '-');
+    let mut next = || -> Result<&str> { parts.next().ok_or_else(invalid) };
+    let (year, month, day) = (next()?, next()?, next()?);
+    let month: u8 = month.parse().map_err(|_| invalid())?;
+    let date = time::Date::from_calendar_date(
+        year.parse().map_err(|_| invalid())?,
+        time::Month::try_from(month).map_err(|_| invalid())?,
+        day.parse().map_err(|_| invalid())?,
+    )
+    .map_err(|_| invalid())?;
+    Ok(Some(date))
+}
+
+/// Whether a bundle's `expires:` date is before `today`. A key is still
+/// valid on its expiry date.
+pub fn is_expired(content: &str, today: time::Date) -> Result<bool> {
+    Ok(bundle_expiry(content)?.is_some_and(|expires| expires < today))
+}
+
+/// Parse the recipients for at-rest encryption from a `.pub` bundle: the
+/// `age:` key of an enseal bundle, or one recipient per `ssh:` line.
+pub fn parse_recipients(identity: &str, content: &str) -> Result<Vec<Box<SharedRecipient>>> {
+    let ssh_keys = parse_ssh_keys(content);
+    if ssh_keys.is_empty() {
+        let trusted = TrustedKey::parse(identity, content)?;
+        return Ok(vec![Box::new(trusted.age_recipient)]);
+    }
+    ssh_keys
+        .iter()
+        .map(|key| {
+            let recipient: age::ssh::Recipient = key
+                .parse()
+                .map_err(|e| anyhow::anyhow!("invalid SSH key for '{}': {:?}", identity, e))?;
+            Ok(Box::new(recipient) as Box<SharedRecipient>)
+        })
+        .collect()
+}
+
+/// Load the at-rest encryption recipients of a trusted identity.
+pub fn load_recipients(store: &KeyStore, identity: &str) -> Result<Vec<Box<SharedRecipient>>> {
+    parse_recipients(identity, &read_trusted(store, identity)?)
+}
+
+/// Extract the SSH public keys (`ssh: <type> <base64>`) from a `.pub` bundle.
+pub fn parse_ssh_keys(content: &str) -> Vec<&str> {
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("ssh: "))
+        .map(str::trim)
+        .collect()
+}
+
+/// OpenSSH-style fingerprint of an SSH public key line, as shown by `ssh-keygen -l`.
+pub fn ssh_fingerprint(key: &str) -> Result<String> {
+    let blob = key
+        .split_whitespace()
+        .nth(1)
+        .context("SSH public key is missing its key data")?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(blob)
+        .context("invalid base64 in SSH public key")?;
+    Ok(format!(
+        "SHA256:{}",
+        base64::engine::general_purpose::STANDARD_NO_PAD.encode(Sha256::digest(&bytes))
+    ))
+}
+
+/// Format an encryption-only bundle of SSH public keys, e.g. from `keys import --github`.
+pub fn format_ssh_key_file(identity: &str, source: &str, keys: &[&str]) -> Result<String> {
+    let mut out = format!("# enseal ssh keys for {} (from {})\n", identity, source);
+    for key in keys {
+        out.push_str(&format!(
+            "# fingerprint: {}\nssh: {}\n",
+            ssh_fingerprint(key)?,
+            key
+        ));
+    }
+    Ok(out)
+}
+
+/// A signing public key as an OpenSSH `authorized_keys` line
+/// (`ssh-ed25519 <base64> <comment>`).
+pub fn format_sign_key_openssh(key: &ed25519_dalek::VerifyingKey, comment: &str) -> String {
+    const KEY_TYPE: &[u8] = b"ssh-ed25519";
+    let mut blob = Vec::with_capacity(4 + KEY_TYPE.len() + 4 + 32);
+    blob.extend_from_slice(&(KEY_TYPE.len() as u32).to_be_bytes());
+    blob.extend_from_slice(KEY_TYPE);
+    blob.extend_from_slice(&32u32.to_be_bytes());
+    blob.extend_from_slice(key.as_bytes());
+    format!(
+        "ssh-ed25519 {} {}\n",
+        base64::engine::general_purpose::STANDARD.encode(blob),
+        comment
+    )
+}
+
+/// A signing public key as a minisign public key file. minisign key IDs are
+/// random; ours are the first 8 bytes of the key's SHA-256, so the same key
+/// always exports the same file.
+pub fn format_sign_key_minisign(key: &ed25519_dalek::VerifyingKey) -> String {
+    let key_id: [u8; 8] = Sha256::digest(key.as_bytes())[..8]
+        .try_into()
+        .expect("SHA-256 is longer than 8 bytes");
+    let mut blob = Vec::with_capacity(2 + 8 + 32);
+    blob.extend_from_slice(b"Ed");
+    blob.extend_from_slice(&key_id);
+    blob.extend_from_slice(key.as_bytes());
+    format!(
+        "untrusted comment: minisign public key {:016X}\n{}\n",
+        u64::from_le_bytes(key_id),
+        base64::engine::general_purpose::STANDARD.encode(blob)
+    )
+}
+
 /// Format a public key bundle for export as a `.pub` file.
 pub fn format_pubkey_file(identity: &str, age_pubkey: &str, sign_pubkey_b64: &str) -> String {
     let fingerprint = fingerprint_from_keys(age_pubkey, sign_pubkey_b64);
@@ -201,6 +535,52 @@ pub fn format_pubkey_file(identity: &str, age_pubkey: &str, sign_pubkey_b64: &st
     )
 }
 
+/// Fixed word list for spoken fingerprint comparison, one word per byte value.
+/// The order is part of the output format: never reorder or replace entries.
+const FINGERPRINT_WORDS: [&str; 256] = [
+    "acid", "acorn", "actor", "adobe", "agent", "alarm", "album", "alley", "amber", "angle",
+    "ankle", "apple", "apron", "arena", "armor", "arrow", "aspen", "atlas", "attic", "audio",
+    "award", "axis", "bacon", "badge", "bagel", "baker", "bamboo", "banjo", "barn", "basil",
+    "basin", "beach", "beacon", "bean", "bear", "beetle", "bell", "bench", "berry", "bison",
+    "blade", "blanket", "bloom", "board", "boat", "bolt", "bonus", "boots", "bottle", "bowl",
+    "brain", "brass", "bread", "brick", "bridge", "brook", "broom", "brush", "bucket", "buffalo",
+    "bugle", "cabin", "cable", "cactus", "camel", "camera", "candle", "canoe", "canyon", "cargo",
+    "carpet", "carrot", "castle", "cedar", "cello", "chair", "chalk", "cherry", "chess", "chimney",
+    "cider", "cinema", "circus", "citrus", "clock", "cloud", "clover", "cobalt", "cocoa", "comet",
+    "copper", "coral", "cotton", "cradle", "crane", "crayon", "cricket", "crown", "crystal",
+    "cube", "cup", "curtain", "daisy", "dancer", "delta", "denim", "desert", "diamond", "dingo",
+    "dollar", "dolphin", "domino", "donkey", "dragon", "drum", "duck", "dune", "eagle", "easel",
+    "eclipse", "elbow", "elder", "ember", "engine", "envoy", "falcon", "feather", "fern", "ferry",
+    "fiddle", "fig", "finch", "flame", "flask", "flute", "forest", "fossil", "fountain", "fox",
+    "frost", "gadget", "galaxy", "garden", "garlic", "gazelle", "geyser", "ginger", "giraffe",
+    "glacier", "glove", "goblet", "gold", "gondola", "gorilla", "granite", "grape", "gravel",
+    "guitar", "hammer", "harbor", "harp", "hawk", "hazel", "helmet", "heron", "hickory", "honey",
+    "hornet", "husky", "igloo", "iguana", "indigo", "iris", "island", "ivory", "jacket", "jaguar",
+    "jasmine", "jelly", "jewel", "jigsaw", "jungle", "kayak", "kernel", "kettle", "kiwi", "koala",
+    "ladder", "lagoon", "lantern", "laser", "lemon", "lentil", "leopard", "lilac", "linen",
+    "lizard", "llama", "lobster", "locket", "lotus", "magnet", "mango", "maple", "marble",
+    "meadow", "melon", "meteor", "mint", "mirror", "mitten", "monkey", "moose", "mosaic", "muffin",
+    "mural", "nectar", "needle", "nickel", "noodle", "nutmeg", "oak", "oasis", "ocean", "olive",
+    "onion", "orbit", "orchid", "otter", "owl", "paddle", "panda", "panther", "paper", "parrot",
+    "peach", "pebble", "pelican", "pepper", "piano", "pickle", "pigeon", "pillow", "pine",
+    "pirate", "planet", "plum", "pocket", "pony", "poppy", "potato", "pretzel", "prism", "puffin",
+    "pumpkin", "puzzle",
+];
+
+/// Render a `SHA256:<base64>` fingerprint as a sequence of words (one per byte).
+/// The mapping is stable across versions, so two parties can read words aloud
+/// to compare keys.
+pub fn fingerprint_words(fingerprint: &str) -> Result<Vec<&'static str>> {
+    let encoded = fingerprint.strip_prefix("SHA256:").unwrap_or(fingerprint);
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .context("invalid fingerprint encoding")?;
+    Ok(bytes
+        .iter()
+        .map(|b| FINGERPRINT_WORDS[*b as usize])
+        .collect())
+}
+
 /// Compute a URL-safe channel ID from public key strings.
 /// Returns hex-encoded SHA256 prefix (first 16 bytes = 32 hex chars).
 fn channel_id_from_keys(age_pubkey: &str, sign_pubkey_b64: &str) -> String {
@@ -213,14 +593,48 @@ fn channel_id_from_keys(age_pubkey: &str, sign_pubkey_b64: &str) -> String {
 
 /// Compute SHA256 fingerprint from age + sign public key strings.
 fn fingerprint_from_keys(age_pubkey: &str, sign_pubkey_b64: &str) -> String {
+    FingerprintFormat::Base64.render(&fingerprint_digest(age_pubkey, sign_pubkey_b64))
+}
+
+fn fingerprint_digest(age_pubkey: &str, sign_pubkey_b64: &str) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(age_pubkey.as_bytes());
     hasher.update(sign_pubkey_b64.as_bytes());
-    let hash = hasher.finalize();
-    format!(
-        "SHA256:{}",
-        base64::engine::general_purpose::STANDARD.encode(&hash[..16])
-    )
+    hasher.finalize().into()
+}
+
+/// How a key fingerprint is displayed (`keys fingerprint --format`, or
+/// `fingerprint_format` in the user config).
+///
+/// Only `base64` is canonical: it's what gets stored, pinned and compared,
+/// whatever the display setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum FingerprintFormat {
+    /// `SHA256:` and the first 16 bytes of the digest in base64 (canonical)
+    #[default]
+    Base64,
+    /// The full SHA-256 digest in lowercase hex
+    Hex,
+    /// The first 16 bytes of the digest as colon-separated hex, like SSH
+    Colon,
+}
+
+impl FingerprintFormat {
+    fn render(self, digest: &[u8; 32]) -> String {
+        match self {
+            Self::Base64 => format!(
+                "SHA256:{}",
+                base64::engine::general_purpose::STANDARD.encode(&digest[..16])
+            ),
+            Self::Hex => hex::encode(digest),
+            Self::Colon => digest[..16]
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(":"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -228,6 +642,137 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    fn sample_bundle() -> String {
+        EnsealIdentity::generate().pubkey_file("alice")
+    }
+
+    fn failed(checks: &[BundleCheck]) -> Vec<&'static str> {
+        checks
+            .iter()
+            .filter(|c| matches!(c.outcome, CheckOutcome::Fail(_)))
+            .map(|c| c.name)
+            .collect()
+    }
+
+    #[test]
+    fn verify_bundle_passes_valid_bundle() {
+        let checks = verify_bundle(&sample_bundle());
+        assert!(checks.iter().all(|c| c.outcome == CheckOutcome::Pass));
+    }
+
+    #[test]
+    fn verify_bundle_catches_tampered_fingerprint() {
+        let bundle = sample_bundle();
+        let other = sample_bundle();
+        let fp_line = |b: &str| b.lines().nth(1).unwrap().to_string();
+        let tampered = bundle.replace(&fp_line(&bundle), &fp_line(&other));
+
+        // The keys themselves are fine; only the comment is wrong
+        assert_eq!(failed(&verify_bundle(&tampered)), ["fingerprint comment"]);
+    }
+
+    #[test]
+    fn verify_bundle_catches_malformed_age_line() {
+        let bundle = sample_bundle().replace("age: age1", "age: age2");
+        assert_eq!(
+            failed(&verify_bundle(&bundle)),
+            ["age recipient", "bundle parses", "fingerprint comment"]
+        );
+    }
+
+    #[test]
+    fn bundle_expiry_parsing() {
+        let today = time::macros::date!(2026 - 03 - 15);
+        let bundle = |expires: &str| format!("age: age1x\nexpires: {}\n", expires);
+
+        assert_eq!(bundle_expiry("age: age1x\n").unwrap(), None);
+        assert!(!is_expired("age: age1x\n", today).unwrap());
+        assert!(is_expired(&bundle("2026-03-14"), today).unwrap());
+        assert!(!is_expired(&bundle("2026-03-15"), today).unwrap());
+        assert!(bundle_expiry(&bundle("2026-13-01")).is_err());
+        assert!(bundle_expiry(&bundle("next week")).is_err());
+    }
+
+    #[test]
+    fn fingerprint_formats_are_deterministic_and_agree() {
+        let id = EnsealIdentity::generate_from_seed([7; 32]);
+        let trusted = TrustedKey {
+            identity: "seven".to_string(),
+            age_recipient: id.age_recipient.clone(),
+            verifying_key: id.signing_key.verifying_key(),
+        };
+        let again = EnsealIdentity::generate_from_seed([7; 32]);
+
+        for format in [
+            FingerprintFormat::Base64,
+            FingerprintFormat::Hex,
+            FingerprintFormat::Colon,
+        ] {
+            assert_eq!(id.fingerprint_as(format), again.fingerprint_as(format));
+            assert_eq!(id.fingerprint_as(format), trusted.fingerprint_as(format));
+        }
+
+        // The canonical form is unchanged and is what keys compare by
+        assert_eq!(id.fingerprint(), trusted.fingerprint());
+        assert_eq!(
+            id.fingerprint(),
+            id.fingerprint_as(FingerprintFormat::Base64)
+        );
+        assert_ne!(
+            id.fingerprint(),
+            EnsealIdentity::generate_from_seed([8; 32]).fingerprint()
+        );
+
+        let hex = id.fingerprint_as(FingerprintFormat::Hex);
+        assert_eq!(hex.len(), 64);
+        let colon = id.fingerprint_as(FingerprintFormat::Colon);
+        assert_eq!(colon.split(':').count(), 16);
+        assert!(hex.starts_with(&colon.replace(':', "")));
+    }
+
+    /// Public key of RFC 8032 test vector 1.
+    fn rfc8032_key() -> ed25519_dalek::VerifyingKey {
+        let bytes = hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+            .unwrap();
+        ed25519_dalek::VerifyingKey::from_bytes(&bytes.try_into().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn sign_key_openssh_encoding() {
+        assert_eq!(
+            format_sign_key_openssh(&rfc8032_key(), "alice"),
+            "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAINdamAGCsQq31Uv+08lkBzoO4XLz2qYjJa8CGmj3B1Ea alice\n"
+        );
+    }
+
+    #[test]
+    fn sign_key_minisign_encoding() {
+        assert_eq!(
+            format_sign_key_minisign(&rfc8032_key()),
+            "untrusted comment: minisign public key 61A254A1DF31FE21\n\
+             RWQh/jHfoVSiYddamAGCsQq31Uv+08lkBzoO4XLz2qYjJa8CGmj3B1Ea\n"
+        );
+    }
+
+    #[test]
+    fn seeded_generation_is_deterministic() {
+        let a = EnsealIdentity::generate_from_seed([7; 32]);
+        let b = EnsealIdentity::generate_from_seed([7; 32]);
+        let other = EnsealIdentity::generate_from_seed([8; 32]);
+
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_eq!(a.age_recipient.to_string(), b.age_recipient.to_string());
+        assert_ne!(a.fingerprint(), other.fingerprint());
+        assert_ne!(a.age_recipient.to_string(), other.age_recipient.to_string());
+
+        // The derived keys are usable, not just stable
+        let wire = crate::api::encrypt_env_to("KEY=value\n", &[&other.age_recipient], &a).unwrap();
+        assert_eq!(
+            crate::api::decrypt_env(&wire, &other).unwrap(),
+            "KEY=value\n"
+        );
+    }
+
     #[test]
     fn generate_and_fingerprint() {
         let id = EnsealIdentity::generate();
@@ -236,12 +781,39 @@ mod tests {
         assert!(fp.len() > 10);
     }
 
+    #[test]
+    fn fingerprint_words_are_stable() {
+        // Fixed vector: guards against accidental changes to the word list
+        let words = fingerprint_words("SHA256:AAECA/8=").unwrap();
+        assert_eq!(words, vec!["acid", "acorn", "actor", "adobe", "puzzle"]);
+
+        let id = EnsealIdentity::generate();
+        let first = fingerprint_words(&id.fingerprint()).unwrap();
+        let second = fingerprint_words(&id.fingerprint()).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 16);
+    }
+
+    #[test]
+    fn fingerprint_words_differ_between_keys() {
+        let a = EnsealIdentity::generate();
+        let b = EnsealIdentity::generate();
+        assert_ne!(
+            fingerprint_words(&a.fingerprint()).unwrap(),
+            fingerprint_words(&b.fingerprint()).unwrap()
+        );
+    }
+
+    #[test]
+    fn fingerprint_words_rejects_garbage() {
+        assert!(fingerprint_words("SHA256:not base64!").is_err());
+    }
+
     #[test]
     fn pubkey_file_round_trip() {
         let id = EnsealIdentity::generate();
         let age_pub = id.age_recipient.to_string();
-        let sign_pub = base64::engine::general_purpose::STANDARD
-            .encode(id.signing_key.verifying_key().to_bytes());
+        let sign_pub = id.sign_pubkey_b64();
         let content = format_pubkey_file("test@example.com", &age_pub, &sign_pub);
 
         let parsed = TrustedKey::parse("test@example.com", &content).unwrap();
@@ -257,8 +829,7 @@ mod tests {
     fn fingerprints_match() {
         let id = EnsealIdentity::generate();
         let age_pub = id.age_recipient.to_string();
-        let sign_pub = base64::engine::general_purpose::STANDARD
-            .encode(id.signing_key.verifying_key().to_bytes());
+        let sign_pub = id.sign_pubkey_b64();
         let content = format_pubkey_file("test@example.com", &age_pub, &sign_pub);
         let parsed = TrustedKey::parse("test@example.com", &content).unwrap();
         assert_eq!(id.fingerprint(), parsed.fingerprint());
@@ -268,8 +839,7 @@ mod tests {
     fn channel_ids_match() {
         let id = EnsealIdentity::generate();
         let age_pub = id.age_recipient.to_string();
-        let sign_pub = base64::engine::general_purpose::STANDARD
-            .encode(id.signing_key.verifying_key().to_bytes());
+        let sign_pub = id.sign_pubkey_b64();
         let content = format_pubkey_file("test@example.com", &age_pub, &sign_pub);
         let parsed = TrustedKey::parse("test@example.com", &content).unwrap();
 
@@ -311,8 +881,7 @@ mod tests {
 
         let id = EnsealIdentity::generate();
         let age_pub = id.age_recipient.to_string();
-        let sign_pub = base64::engine::general_purpose::STANDARD
-            .encode(id.signing_key.verifying_key().to_bytes());
+        let sign_pub = id.sign_pubkey_b64();
         let content = format_pubkey_file("alice@example.com", &age_pub, &sign_pub);
 
         // Write to trusted dir
@@ -353,4 +922,60 @@ mod tests {
         assert_eq!(age_perms, 0o600);
         assert_eq!(sign_perms, 0o600);
     }
+
+    const SSH_KEY: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIJokjaEGZwk4eE1IRpXpEFJS5IX3urnbKLwMXQ0KqCtW";
+
+    #[test]
+    fn raw_recipient_detection() {
+        let id = EnsealIdentity::generate();
+        let raw = id.age_recipient.to_string();
+        assert_eq!(parse_raw_recipient(&raw).unwrap().to_string(), raw);
+        assert!(parse_raw_recipient("alice@example.com").is_none());
+        assert!(parse_raw_recipient("age1notavalidkey").is_none());
+    }
+
+    #[test]
+    fn ssh_fingerprint_matches_openssh() {
+        // Expected value from `ssh-keygen -lf`
+        assert_eq!(
+            ssh_fingerprint(SSH_KEY).unwrap(),
+            "SHA256:oX3FmWEatXESqsQazWlUhaG0bmFEwdYsws17e6lY2cU"
+        );
+    }
+
+    #[test]
+    fn ssh_bundle_is_encrypt_only() {
+        let content =
+            format_ssh_key_file("alice", "https://github.com/alice.keys", &[SSH_KEY]).unwrap();
+        assert_eq!(parse_ssh_keys(&content), vec![SSH_KEY]);
+        assert_eq!(parse_recipients("alice", &content).unwrap().len(), 1);
+
+        let err = TrustedKey::parse("alice", &content).err().unwrap();
+        assert!(err.to_string().contains("SSH key entry"));
+    }
+
+    #[test]
+    fn load_without_identity_is_key_not_found() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        assert!(matches!(
+            EnsealIdentity::load(&store),
+            Err(EnsealError::KeyNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn unreadable_key_error_names_the_file() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        EnsealIdentity::generate().save(&store).unwrap();
+        // Present (so the store counts as initialized) but unreadable as a file
+        std::fs::remove_file(store.sign_private_key_path()).unwrap();
+        std::fs::create_dir(store.sign_private_key_path()).unwrap();
+
+        let err = EnsealIdentity::load(&store).err().unwrap().to_string();
+        assert!(err.contains("failed to read signing private key"), "{err}");
+        assert!(err.contains("self.sign.key"), "{err}");
+    }
 }
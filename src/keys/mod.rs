@@ -1,29 +1,93 @@
 pub mod alias;
 pub mod group;
 pub mod identity;
+pub mod pin;
 pub mod store;
+pub mod tofu;
+
+use std::collections::HashMap;
 
 use anyhow::{bail, Result};
+use base64::Engine;
+use subtle::ConstantTimeEq;
 
 use crate::crypto::signing::SignedEnvelope;
 
 /// Resolve a recipient name to one or more identities.
 /// Checks: alias -> group -> trusted key -> error.
 /// Returns a Vec with 1 element for a single identity, N for a group.
-pub fn resolve_to_identities(name: &str) -> Result<Vec<String>> {
+pub fn resolve_to_identities(store: &store::KeyStore, name: &str) -> Result<Vec<String>> {
     store::validate_identity_name(name)?;
-    let store = store::KeyStore::open()?;
+    resolve_in(store, name)
+}
+
+/// Resolve `--to` values to a deduplicated list of identities.
+/// Each name resolves as in [`resolve_to_identities`]; with `literal`, only
+/// trusted keys match (for names that collide with an alias).
+/// A value of the form `@path` names a recipients file (see [`read_recipients_file`]).
+pub fn resolve_recipient_args(
+    store: &store::KeyStore,
+    args: &[String],
+    literal: bool,
+) -> Result<Vec<String>> {
+    let mut identities: Vec<String> = Vec::new();
+    for arg in args {
+        let names = match arg.strip_prefix('@') {
+            Some(path) => read_recipients_file(path)?,
+            None => vec![arg.clone()],
+        };
+        for name in &names {
+            store::validate_identity_name(name)?;
+            let resolved = if literal {
+                resolve_literal_in(store, name)?
+            } else {
+                resolve_in(store, name)?
+            };
+            for identity in resolved {
+                if !identities.contains(&identity) {
+                    identities.push(identity);
+                }
+            }
+        }
+    }
+    Ok(identities)
+}
+
+/// Read a recipients file: one name per line, blank lines and `#` comments ignored.
+pub fn read_recipients_file(path: &str) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("failed to read recipients file '{}': {}", path, e))?;
+    let names: Vec<String> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+    if names.is_empty() {
+        bail!("recipients file '{}' lists no recipients", path);
+    }
+    Ok(names)
+}
 
+fn resolve_literal_in(store: &store::KeyStore, name: &str) -> Result<Vec<String>> {
+    if store.trusted_key_path(name)?.exists() {
+        return Ok(vec![name.to_string()]);
+    }
+    bail!(
+        "no trusted key named '{}' (--literal skips aliases and groups). \
+         Import it with: enseal keys import <file>",
+        name
+    );
+}
+
+fn resolve_in(store: &store::KeyStore, name: &str) -> Result<Vec<String>> {
     // Try alias first
-    if let Some(identity) = alias::resolve(&store, name)? {
+    if let Some(identity) = alias::resolve(store, name)? {
         return Ok(vec![identity]);
     }
 
-    // Try group
-    if let Some(members) = group::get_members(&store, name)? {
-        if members.is_empty() {
-            bail!("group '{}' has no members", name);
-        }
+    // Try group (nested groups expand recursively)
+    if let Some(members) = group::expand(store, name)? {
         return Ok(members);
     }
 
@@ -42,23 +106,166 @@ pub fn resolve_to_identities(name: &str) -> Result<Vec<String>> {
     );
 }
 
-/// Look up the sender's signing key in the trusted key store.
-/// Returns the matching TrustedKey if found, None otherwise.
-pub fn find_trusted_sender(
-    store: &store::KeyStore,
-    signed: &SignedEnvelope,
-) -> Option<identity::TrustedKey> {
-    let trusted = store.list_trusted().ok()?;
-    for name in &trusted {
-        if let Ok(key) = identity::TrustedKey::load(store, name) {
-            let key_b64 = base64::Engine::encode(
-                &base64::engine::general_purpose::STANDARD,
-                key.verifying_key.to_bytes(),
-            );
-            if key_b64 == signed.sender_sign_pubkey {
-                return Some(key);
-            }
-        }
+/// Trusted keys indexed by their base64 signing public key, loaded once per
+/// command. Keys that fail to load (or have no signing key, like SSH
+/// entries) are skipped.
+pub struct TrustedIndex {
+    by_sign_key: HashMap<String, identity::TrustedKey>,
+}
+
+impl TrustedIndex {
+    pub fn load(store: &store::KeyStore) -> Self {
+        let by_sign_key = store
+            .list_trusted()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|name| identity::TrustedKey::load(store, name).ok())
+            .map(|key| {
+                let key_b64 =
+                    base64::engine::general_purpose::STANDARD.encode(key.verifying_key.to_bytes());
+                (key_b64, key)
+            })
+            .collect();
+        Self { by_sign_key }
+    }
+
+    /// The trusted key with this base64 signing key, if any. The map lookup
+    /// is confirmed with a constant-time comparison of the decoded key bytes.
+    pub fn find_key(&self, sign_pubkey: &str) -> Option<&identity::TrustedKey> {
+        let key = self.by_sign_key.get(sign_pubkey)?;
+        let claimed = base64::engine::general_purpose::STANDARD
+            .decode(sign_pubkey)
+            .ok()?;
+        bool::from(claimed.ct_eq(key.verifying_key.as_bytes())).then_some(key)
+    }
+
+    /// The trusted key that signed `signed`, if any.
+    pub fn find_sender(&self, signed: &SignedEnvelope) -> Option<&identity::TrustedKey> {
+        self.find_key(&signed.sender_sign_pubkey)
+    }
+}
+
+/// Generate a fresh identity and install its public key in `store` as `name`.
+#[cfg(test)]
+pub(crate) fn trust_new_identity(store: &store::KeyStore, name: &str) -> identity::EnsealIdentity {
+    let id = identity::EnsealIdentity::generate();
+    store.ensure_dirs().unwrap();
+    std::fs::write(store.trusted_key_path(name).unwrap(), id.pubkey_file(name)).unwrap();
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn literal_bypasses_colliding_alias() {
+        let dir = TempDir::new().unwrap();
+        let store = store::KeyStore::open_at(dir.path().to_path_buf());
+        trust_new_identity(&store, "sarah");
+        trust_new_identity(&store, "sarah@company.com");
+        alias::set(&store, "sarah", "sarah@company.com").unwrap();
+
+        assert_eq!(
+            resolve_in(&store, "sarah").unwrap(),
+            vec!["sarah@company.com"]
+        );
+        assert_eq!(resolve_literal_in(&store, "sarah").unwrap(), vec!["sarah"]);
+    }
+
+    #[test]
+    fn literal_requires_trusted_key() {
+        let dir = TempDir::new().unwrap();
+        let store = store::KeyStore::open_at(dir.path().to_path_buf());
+        trust_new_identity(&store, "alice@example.com");
+        group::create(&store, "team").unwrap();
+        group::add_member(&store, "team", "alice@example.com").unwrap();
+
+        assert!(resolve_literal_in(&store, "team").is_err());
+    }
+
+    #[test]
+    fn nested_group_wins_over_same_named_key() {
+        let dir = TempDir::new().unwrap();
+        let store = store::KeyStore::open_at(dir.path().to_path_buf());
+        trust_new_identity(&store, "alice");
+        trust_new_identity(&store, "backend");
+        group::create(&store, "backend").unwrap();
+        group::add_member(&store, "backend", "alice").unwrap();
+        group::create(&store, "all-eng").unwrap();
+        group::add_member(&store, "all-eng", "backend").unwrap();
+
+        assert_eq!(resolve_in(&store, "all-eng").unwrap(), vec!["alice"]);
+    }
+
+    #[test]
+    fn recipients_file_skips_comments_and_blanks() {
+        let dir = TempDir::new().unwrap();
+        let store = store::KeyStore::open_at(dir.path().join("store"));
+        trust_new_identity(&store, "alice@example.com");
+        trust_new_identity(&store, "bob@example.com");
+        let list = dir.path().join("recipients.txt");
+        std::fs::write(
+            &list,
+            "# platform team\nalice@example.com\n\n   \n  bob@example.com\n",
+        )
+        .unwrap();
+
+        let arg = format!("@{}", list.display());
+        assert_eq!(
+            resolve_recipient_args(&store, &[arg], false).unwrap(),
+            vec!["alice@example.com", "bob@example.com"]
+        );
+    }
+
+    #[test]
+    fn missing_recipients_file_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let store = store::KeyStore::open_at(dir.path().join("store"));
+        let arg = format!("@{}", dir.path().join("nope.txt").display());
+        let err = resolve_recipient_args(&store, &[arg], false).unwrap_err();
+        assert!(err.to_string().contains("failed to read recipients file"));
+    }
+
+    #[test]
+    fn recipients_deduplicated_across_aliases() {
+        let dir = TempDir::new().unwrap();
+        let store = store::KeyStore::open_at(dir.path().join("store"));
+        trust_new_identity(&store, "sarah@company.com");
+        alias::set(&store, "sarah", "sarah@company.com").unwrap();
+        alias::set(&store, "sj", "sarah@company.com").unwrap();
+        let list = dir.path().join("recipients.txt");
+        std::fs::write(&list, "sarah\nsj\n").unwrap();
+
+        let args = [
+            format!("@{}", list.display()),
+            "sarah@company.com".to_string(),
+        ];
+        assert_eq!(
+            resolve_recipient_args(&store, &args, false).unwrap(),
+            vec!["sarah@company.com"]
+        );
+    }
+
+    #[test]
+    fn finds_matching_sender_among_many() {
+        let dir = TempDir::new().unwrap();
+        let store = store::KeyStore::open_at(dir.path().join("store"));
+        let receiver = identity::EnsealIdentity::generate();
+        let senders: Vec<_> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|name| trust_new_identity(&store, name))
+            .collect();
+
+        let signed =
+            SignedEnvelope::seal(b"payload", &[&receiver.age_recipient], &senders[1]).unwrap();
+        let index = TrustedIndex::load(&store);
+        assert_eq!(index.find_sender(&signed).unwrap().identity, "bob");
+
+        let stranger = identity::EnsealIdentity::generate();
+        let signed =
+            SignedEnvelope::seal(b"payload", &[&receiver.age_recipient], &stranger).unwrap();
+        assert!(index.find_sender(&signed).is_none());
     }
-    None
 }
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+
+use super::identity::{parse_ssh_keys, read_trusted, ssh_fingerprint, TrustedKey};
+use super::store::KeyStore;
+use crate::ui::display;
+
+/// Fingerprint pinned for a trusted `.pub` bundle: the enseal key
+/// fingerprint, or the SSH key fingerprints for an encrypt-only entry.
+pub fn bundle_fingerprint(identity: &str, content: &str) -> Result<String> {
+    let ssh_keys = parse_ssh_keys(content);
+    if ssh_keys.is_empty() {
+        return Ok(TrustedKey::parse(identity, content)?.fingerprint());
+    }
+    let fingerprints = ssh_keys
+        .iter()
+        .map(|key| ssh_fingerprint(key))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(fingerprints.join(","))
+}
+
+/// Check each identity's trusted key against the fingerprint pinned the
+/// first time it was encrypted to. Unpinned keys are pinned now.
+///
+/// A changed key is an error unless `accept_change` is set, in which case
+/// the pin is updated to the new fingerprint.
+pub fn check(store: &KeyStore, identities: &[String], accept_change: bool) -> Result<()> {
+    let mut pins = load_pins(store)?;
+    let mut changed = false;
+
+    for identity in identities {
+        let current = bundle_fingerprint(identity, &read_trusted(store, identity)?)?;
+        match pins.get(identity) {
+            Some(pinned) if *pinned == current => continue,
+            Some(pinned) if !accept_change => bail!(
+                "RECIPIENT KEY CHANGED for '{}'\n  \
+                 pinned:  {}\n  \
+                 current: {}\n\
+                 The trusted key file was replaced since you last sent to them. This can mean \
+                 someone substituted their own key.\n\
+                 Confirm the new fingerprint with '{}' out of band, then rerun with --accept-key-change",
+                identity,
+                pinned,
+                current,
+                identity
+            ),
+            Some(pinned) => display::warning(&format!(
+                "accepted key change for '{}' ({} -> {})",
+                identity, pinned, current
+            )),
+            None => {}
+        }
+        pins.insert(identity.clone(), current);
+        changed = true;
+    }
+
+    if changed {
+        save_pins(store, &pins)?;
+    }
+    Ok(())
+}
+
+fn load_pins(store: &KeyStore) -> Result<BTreeMap<String, String>> {
+    let path = store.pins_path();
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(&path).context("failed to read pinned.toml")?;
+    toml::from_str(&content).context("failed to parse pinned.toml")
+}
+
+fn save_pins(store: &KeyStore, pins: &BTreeMap<String, String>) -> Result<()> {
+    store.ensure_dirs()?;
+    let content = toml::to_string_pretty(pins).context("failed to serialize pins")?;
+    // Atomic, so an interrupted write can't leave a truncated pin file that
+    // would then fail every later send
+    crate::fsutil::write_secret_file(store.pins_path(), content.as_bytes())
+        .context("failed to write pinned.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Install a fresh key for `name`, replacing any existing one.
+    fn trust_new_key(store: &KeyStore, name: &str) -> String {
+        crate::keys::trust_new_identity(store, name).fingerprint()
+    }
+
+    fn pinned(store: &KeyStore, name: &str) -> Option<String> {
+        load_pins(store).unwrap().get(name).cloned()
+    }
+
+    #[test]
+    fn unchanged_key_passes() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        let fingerprint = trust_new_key(&store, "alice");
+        let alice = ["alice".to_string()];
+
+        check(&store, &alice, false).unwrap();
+        assert_eq!(pinned(&store, "alice"), Some(fingerprint.clone()));
+        check(&store, &alice, false).unwrap();
+        assert_eq!(pinned(&store, "alice"), Some(fingerprint));
+    }
+
+    #[test]
+    fn changed_key_rejected() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        let original = trust_new_key(&store, "alice");
+        let alice = ["alice".to_string()];
+        check(&store, &alice, false).unwrap();
+
+        let replacement = trust_new_key(&store, "alice");
+        let err = check(&store, &alice, false).unwrap_err().to_string();
+        assert!(err.contains("RECIPIENT KEY CHANGED"));
+        assert!(err.contains(&original) && err.contains(&replacement));
+        assert_eq!(pinned(&store, "alice"), Some(original));
+    }
+
+    #[test]
+    fn accepted_change_updates_pin() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        trust_new_key(&store, "alice");
+        let alice = ["alice".to_string()];
+        check(&store, &alice, false).unwrap();
+
+        let replacement = trust_new_key(&store, "alice");
+        check(&store, &alice, true).unwrap();
+        assert_eq!(pinned(&store, "alice"), Some(replacement));
+        check(&store, &alice, false).unwrap();
+    }
+}
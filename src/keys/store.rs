@@ -36,32 +36,74 @@ pub fn validate_identity_name(identity: &str) -> Result<()> {
     Ok(())
 }
 
+/// Name of the local identity slot used when `--identity` is not given.
+pub const DEFAULT_IDENTITY: &str = "default";
+
+/// Subdirectories of `keys/` that hold store data rather than identity slots.
+const RESERVED_SLOT_NAMES: &[&str] = &["trusted", "archive"];
+
+/// Validate a local identity slot name. Names of store subdirectories are
+/// reserved because they share the `keys/` directory with the slots.
+fn validate_slot_name(name: &str) -> Result<()> {
+    validate_identity_name(name)?;
+    if RESERVED_SLOT_NAMES.contains(&name) {
+        bail!(
+            "'{}' is reserved and cannot be used as an identity name",
+            name
+        );
+    }
+    Ok(())
+}
+
 /// Manages the `~/.config/enseal/keys/` directory and file layout.
+///
+/// Own keys live in a named slot under `keys/<name>/`. A pre-existing
+/// top-level `keys/self.age.key` is treated as the `default` slot.
+#[derive(Clone)]
 pub struct KeyStore {
     base_dir: PathBuf,
+    identity: String,
 }
 
 impl KeyStore {
-    /// Open the key store at the default platform config directory.
-    pub fn open() -> Result<Self> {
+    /// Open the key store at the default platform config directory, using
+    /// the given identity slot (`--identity`) or the default one.
+    pub fn open(identity: Option<&str>) -> Result<Self> {
         let dirs = ProjectDirs::from("dev", "enseal", "enseal")
             .context("could not determine config directory")?;
-        let base_dir = dirs.config_dir().to_path_buf();
-        Ok(Self { base_dir })
+        let store = Self::open_at(dirs.config_dir().to_path_buf());
+        match identity {
+            Some(name) => store.with_identity(name),
+            None => Ok(store),
+        }
     }
 
-    /// Open the key store at a specific directory (for testing).
-    #[allow(dead_code)]
+    /// Open the key store at a specific directory.
     pub fn open_at(base_dir: PathBuf) -> Self {
-        Self { base_dir }
+        Self {
+            base_dir,
+            identity: DEFAULT_IDENTITY.to_string(),
+        }
+    }
+
+    /// Switch to a different local identity slot.
+    pub fn with_identity(mut self, name: &str) -> Result<Self> {
+        validate_slot_name(name)?;
+        self.identity = name.to_string();
+        Ok(self)
+    }
+
+    /// Name of the local identity slot this store reads own keys from.
+    pub fn identity_name(&self) -> &str {
+        &self.identity
     }
 
     /// Ensure the key store directory structure exists.
     pub fn ensure_dirs(&self) -> Result<()> {
-        let keys_dir = self.keys_dir();
+        let identity_dir = self.identity_dir();
         let trusted_dir = self.trusted_dir();
-        std::fs::create_dir_all(&keys_dir)
-            .with_context(|| format!("failed to create {}", keys_dir.display()))?;
+        std::fs::create_dir_all(&identity_dir)
+            .with_context(|| format!("failed to create {}", identity_dir.display()))?;
         std::fs::create_dir_all(&trusted_dir)
             .with_context(|| format!("failed to create {}", trusted_dir.display()))?;
         Ok(())
@@ -75,22 +117,32 @@ impl KeyStore {
         self.base_dir.join("keys").join("trusted")
     }
 
+    /// Directory holding own keys for the selected identity slot.
+    pub fn identity_dir(&self) -> PathBuf {
+        let keys_dir = self.keys_dir();
+        if self.identity == DEFAULT_IDENTITY && keys_dir.join("self.age.key").exists() {
+            // Legacy single-identity layout
+            return keys_dir;
+        }
+        keys_dir.join(&self.identity)
+    }
+
     // --- Own key paths ---
 
     pub fn age_private_key_path(&self) -> PathBuf {
-        self.keys_dir().join("self.age.key")
+        self.identity_dir().join("self.age.key")
     }
 
     pub fn age_public_key_path(&self) -> PathBuf {
-        self.keys_dir().join("self.age.pub")
+        self.identity_dir().join("self.age.pub")
     }
 
     pub fn sign_private_key_path(&self) -> PathBuf {
-        self.keys_dir().join("self.sign.key")
+        self.identity_dir().join("self.sign.key")
     }
 
     pub fn sign_public_key_path(&self) -> PathBuf {
-        self.keys_dir().join("self.sign.pub")
+        self.identity_dir().join("self.sign.pub")
     }
 
     // --- Trusted key paths ---
@@ -112,6 +164,78 @@ impl KeyStore {
         self.base_dir.join("groups.toml")
     }
 
+    /// Recipient fingerprints recorded on first use, to detect replaced keys.
+    pub fn pins_path(&self) -> PathBuf {
+        self.base_dir.join("pinned.toml")
+    }
+
+    /// Public keys of the last unknown sender, for `keys trust --tofu`.
+    pub fn last_sender_path(&self) -> PathBuf {
+        self.base_dir.join("last_sender.pub")
+    }
+
+    /// Nonces of envelopes already received, for replay protection.
+    pub fn seen_nonces_path(&self) -> PathBuf {
+        self.base_dir.join("seen_nonces.toml")
+    }
+
+    /// Opt-in JSONL record of file drops and relay pushes (`ENSEAL_AUDIT=1`).
+    pub fn audit_log_path(&self) -> PathBuf {
+        self.base_dir.join("audit.log")
+    }
+
+    /// Directory holding identities retired by `keys rotate-self`, one
+    /// `<timestamp>/` subdirectory each.
+    pub fn archive_dir(&self) -> PathBuf {
+        self.identity_dir().join("archive")
+    }
+
+    /// Archived key directories, newest first.
+    pub fn archived_key_dirs(&self) -> std::io::Result<Vec<PathBuf>> {
+        let archive_dir = self.archive_dir();
+        if !archive_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut dirs = Vec::new();
+        for entry in std::fs::read_dir(&archive_dir)? {
+            let path = entry?.path();
+            if path.join("self.age.key").exists() {
+                dirs.push(path);
+            }
+        }
+        // Timestamped names sort chronologically
+        dirs.sort();
+        dirs.reverse();
+        Ok(dirs)
+    }
+
+    /// Copy the current own keys into a new `archive/<timestamp>/` directory.
+    /// Private keys keep their 0600 permissions. Returns the archive directory.
+    pub fn archive_current(&self, timestamp: &str) -> Result<PathBuf> {
+        if !self.is_initialized() {
+            bail!("no identity to archive. Run `enseal keys init` first.");
+        }
+        let mut dest = self.archive_dir().join(timestamp);
+        let mut suffix = 1;
+        while dest.exists() {
+            dest = self.archive_dir().join(format!("{}-{}", timestamp, suffix));
+            suffix += 1;
+        }
+        std::fs::create_dir_all(&dest)
+            .with_context(|| format!("failed to create {}", dest.display()))?;
+        for path in [
+            self.age_private_key_path(),
+            self.age_public_key_path(),
+            self.sign_private_key_path(),
+            self.sign_public_key_path(),
+        ] {
+            let name = path.file_name().expect("key paths have file names");
+            std::fs::copy(&path, dest.join(name))
+                .with_context(|| format!("failed to archive {}", path.display()))?;
+        }
+        Ok(dest)
+    }
+
     /// Check whether own keys have been initialized (all four key files present).
     pub fn is_initialized(&self) -> bool {
         self.age_private_key_path().exists()
@@ -120,6 +244,31 @@ impl KeyStore {
             && self.sign_public_key_path().exists()
     }
 
+    /// List the local identity slots that hold keys.
+    pub fn list_identities(&self) -> Result<Vec<String>> {
+        let keys_dir = self.keys_dir();
+        if !keys_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names = Vec::new();
+        if keys_dir.join("self.age.key").exists() {
+            names.push(DEFAULT_IDENTITY.to_string());
+        }
+        for entry in std::fs::read_dir(&keys_dir)? {
+            let path = entry?.path();
+            if !path.join("self.age.key").exists() {
+                continue;
+            }
+            if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
+                if validate_slot_name(name).is_ok() && !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
     /// List all trusted identities (by filename stem).
     pub fn list_trusted(&self) -> Result<Vec<String>> {
         let trusted_dir = self.trusted_dir();
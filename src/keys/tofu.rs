@@ -0,0 +1,113 @@
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+
+use super::identity::{format_pubkey_file, TrustedKey};
+use super::store::{validate_identity_name, KeyStore};
+
+/// Remember the keys of a sender that isn't trusted yet, so `enseal keys
+/// trust <name> --tofu` can save them later. Overwrites the previous one.
+pub fn record_unknown_sender(store: &KeyStore, age_pubkey: &str, sign_pubkey: &str) -> Result<()> {
+    // Validate before writing so a malformed sender can't be trusted later
+    let content = format_pubkey_file("last unknown sender", age_pubkey, sign_pubkey);
+    TrustedKey::parse("last unknown sender", &content)?;
+    store.ensure_dirs()?;
+    let path = store.last_sender_path();
+    std::fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// The last unknown sender recorded by [`record_unknown_sender`], as
+/// `(age public key, base64 signing key)`.
+pub fn last_unknown_sender(store: &KeyStore) -> Result<(String, String)> {
+    let path = store.last_sender_path();
+    if !path.exists() {
+        bail!("no unknown sender recorded yet. Receive something first, or pass --age and --sign");
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let key = TrustedKey::parse("last unknown sender", &content)?;
+    Ok((
+        key.age_recipient.to_string(),
+        base64::engine::general_purpose::STANDARD.encode(key.verifying_key.to_bytes()),
+    ))
+}
+
+/// Save a sender's keys as trusted key `name`. Never replaces an existing key.
+pub fn trust(
+    store: &KeyStore,
+    name: &str,
+    age_pubkey: &str,
+    sign_pubkey: &str,
+) -> Result<TrustedKey> {
+    validate_identity_name(name)?;
+    let dest = store.trusted_key_path(name)?;
+    if dest.exists() {
+        bail!(
+            "'{}' is already trusted. Pick another name, or remove it first with: enseal keys remove {}",
+            name,
+            name
+        );
+    }
+
+    let content = format_pubkey_file(name, age_pubkey, sign_pubkey);
+    let key = TrustedKey::parse(name, &content)?;
+    store.ensure_dirs()?;
+    std::fs::write(&dest, content)
+        .with_context(|| format!("failed to write {}", dest.display()))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::signing::SignedEnvelope;
+    use crate::keys::identity::EnsealIdentity;
+    use crate::keys::TrustedIndex;
+    use tempfile::TempDir;
+
+    #[test]
+    fn tofu_key_matches_next_receive() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        let me = EnsealIdentity::generate();
+        let sender = EnsealIdentity::generate();
+
+        let first = SignedEnvelope::seal(b"one", &[&me.age_recipient], &sender).unwrap();
+        assert!(TrustedIndex::load(&store).find_sender(&first).is_none());
+        record_unknown_sender(&store, &first.sender_age_pubkey, &first.sender_sign_pubkey).unwrap();
+
+        let (age_pubkey, sign_pubkey) = last_unknown_sender(&store).unwrap();
+        let saved = trust(&store, "bob", &age_pubkey, &sign_pubkey).unwrap();
+        assert_eq!(saved.fingerprint(), sender.fingerprint());
+
+        let second = SignedEnvelope::seal(b"two", &[&me.age_recipient], &sender).unwrap();
+        assert_eq!(
+            TrustedIndex::load(&store)
+                .find_sender(&second)
+                .unwrap()
+                .identity,
+            "bob"
+        );
+    }
+
+    #[test]
+    fn trust_refuses_existing_name() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        let id = EnsealIdentity::generate();
+        let sign_pub = id.sign_pubkey_b64();
+        let age_pub = id.age_recipient.to_string();
+
+        trust(&store, "bob", &age_pub, &sign_pub).unwrap();
+        let err = trust(&store, "bob", &age_pub, &sign_pub).err().unwrap();
+        assert!(err.to_string().contains("already trusted"));
+    }
+
+    #[test]
+    fn nothing_recorded_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let store = KeyStore::open_at(dir.path().to_path_buf());
+        assert!(last_unknown_sender(&store).is_err());
+        assert!(record_unknown_sender(&store, "age1bogus", "AAAA").is_err());
+        assert!(last_unknown_sender(&store).is_err());
+    }
+}
@@ -2,10 +2,13 @@
 //!
 //! Secure, ephemeral secret sharing for developers.
 
+pub mod api;
 pub mod cli;
 pub mod config;
 pub mod crypto;
 pub mod env;
+pub mod error;
+pub mod fsutil;
 pub mod keys;
 #[cfg(feature = "server")]
 pub mod server;
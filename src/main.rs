@@ -1,10 +1,13 @@
 use anyhow::Result;
 use clap::Parser;
 
+mod api;
 mod cli;
 mod config;
 mod crypto;
 mod env;
+mod error;
+mod fsutil;
 mod keys;
 #[cfg(feature = "server")]
 mod server;
@@ -12,7 +15,17 @@ mod transfer;
 mod ui;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> std::process::ExitCode {
+    match run().await {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            cli::exit::ExitCode::of(&err).into()
+        }
+    }
+}
+
+async fn run() -> Result<()> {
     rustls::crypto::ring::default_provider()
         .install_default()
         .expect("failed to install rustls crypto provider");
@@ -33,20 +46,30 @@ async fn main() -> Result<()> {
         .without_time()
         .init();
 
+    // Key store for the selected identity slot, opened by commands that use keys
+    let identity = args.identity.as_deref();
+    let store = || keys::store::KeyStore::open(identity);
+
     match args.command {
-        cli::Command::Share(args) => cli::share::run(args).await,
-        cli::Command::Receive(args) => cli::receive::run(args).await,
-        cli::Command::Inject(args) => cli::inject::run(args).await,
+        cli::Command::Share(args) => cli::share::run(args, &store()?).await,
+        cli::Command::Receive(args) => cli::receive::run(args, &store()?).await,
+        cli::Command::Inject(args) => cli::inject::run(args, &store()?).await,
         cli::Command::Check(args) => cli::check::run(args),
-        cli::Command::Diff(args) => cli::diff::run(args),
+        cli::Command::Diff(args) => cli::diff::run(args, &store()?),
+        cli::Command::Convert(args) => cli::convert::run(args),
         cli::Command::Redact(args) => cli::redact::run(args),
         cli::Command::Validate(args) => cli::validate::run(args),
         cli::Command::Template(args) => cli::template::run(args),
-        cli::Command::Encrypt(args) => cli::encrypt::run(args),
-        cli::Command::Decrypt(args) => cli::decrypt::run(args),
-        cli::Command::Keys(args) => cli::keys::run(args),
+        cli::Command::Encrypt(args) => cli::encrypt::run(args, &store()?),
+        cli::Command::Decrypt(args) => cli::decrypt::run(args, &store()?),
+        cli::Command::Sign(args) => cli::sign::run(args, &store()?),
+        cli::Command::Verify(args) => cli::verify::run(args, &store()?),
+        cli::Command::VerifyManifest(args) => cli::verify_manifest::run(args),
+        cli::Command::Keys(args) => cli::keys::run(args, &store()?),
         #[cfg(feature = "server")]
         cli::Command::Serve(args) => cli::serve::run(args).await,
+        cli::Command::Doctor(args) => cli::doctor::run(args, &store()?),
+        cli::Command::Version => cli::version::run(),
         cli::Command::Completions { shell } => {
             let mut cmd = <cli::Cli as clap::CommandFactory>::command();
             clap_complete::generate(shell, &mut cmd, "enseal", &mut std::io::stdout());
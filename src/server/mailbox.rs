@@ -1,23 +1,57 @@
 use std::collections::HashMap;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use axum::extract::connect_info::ConnectInfo;
 use axum::extract::ws::{Message, WebSocket};
 use axum::extract::{Path, State, WebSocketUpgrade};
 use axum::response::IntoResponse;
-use tokio::sync::mpsc;
 use tokio::sync::Mutex;
+use tokio::sync::{mpsc, watch};
+
+use crate::transfer::relay::{BURN_FRAME, CLOSE_CODE_BURNED};
+
+/// Largest burst a single IP may spend at once, regardless of its rate.
+const MAX_BURST: f64 = 10.0;
+
+/// Forget IPs idle this long. A bucket never holds more than a minute's
+/// worth of tokens, so by then it has refilled and dropping it is lossless.
+const IDLE_IP_TTL: Duration = Duration::from_secs(60);
+
+/// Default interval between pings to a client waiting for its pair.
+pub const DEFAULT_PING_INTERVAL_SECS: u64 = 20;
+
+/// Default time a waiting client has to answer a ping.
+pub const DEFAULT_PONG_TIMEOUT_SECS: u64 = 10;
+
+/// How long a session stays open after one side has left, so the other can
+/// still send its burn frame.
+const BURN_GRACE: Duration = Duration::from_secs(2);
+
+/// Maximum number of distinct IPs tracked by the rate limiter.
+/// Beyond this, the least recently seen IP is evicted.
+const MAX_TRACKED_IPS: usize = 10_000;
 
 /// Shared relay state across all connections.
 pub struct RelayState {
     channels: Mutex<HashMap<String, Channel>>,
     max_channels: usize,
     channel_ttl_secs: u64,
-    connection_log: Mutex<HashMap<IpAddr, Vec<Instant>>>,
+    connection_log: Mutex<HashMap<IpAddr, TokenBucket>>,
     rate_limit_per_min: usize,
     max_payload_bytes: usize,
+    /// Deposited payloads awaiting pickup (store-and-forward mode only).
+    mailboxes: Mutex<HashMap<String, StoredPayload>>,
+    /// Codes retired by a sender's burn frame, refused until the channel TTL passes.
+    burned: Mutex<HashMap<String, Instant>>,
+    store_and_forward: bool,
+    /// How often a waiting client is pinged.
+    ping_interval: Duration,
+    /// How long a waiting client has to answer a ping before its channel is freed.
+    pong_timeout: Duration,
+    /// When the relay started, for `/health` uptime.
+    started_at: Instant,
 }
 
 struct Channel {
@@ -25,6 +59,73 @@ struct Channel {
     tx: mpsc::Sender<Message>,
     /// Receiver that the first client reads from (gets paired client's messages).
     rx: Option<mpsc::Receiver<Message>>,
+    /// Who delivered what, shared by both sides of the session.
+    delivery: Arc<std::sync::Mutex<Delivery>>,
+    created_at: Instant,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    First,
+    Second,
+}
+
+/// Tracks a paired session so a burn frame is only honored from the side
+/// that delivered the payload, after the other side acked it.
+#[derive(Default)]
+struct Delivery {
+    sender: Option<Side>,
+    acked: bool,
+}
+
+impl Delivery {
+    /// Note a message about to be forwarded from `side`: the first one makes
+    /// it the sender, a later one from the other side is the receiver's ack.
+    fn record(&mut self, side: Side) {
+        match self.sender {
+            None => self.sender = Some(side),
+            Some(sender) if sender != side => self.acked = true,
+            Some(_) => {}
+        }
+    }
+
+    fn may_burn(&self, side: Side) -> bool {
+        self.sender == Some(side) && self.acked
+    }
+}
+
+/// Per-IP token bucket: each connection spends one token, and tokens
+/// refill continuously at the configured per-minute rate.
+struct TokenBucket {
+    tokens: f64,
+    last_seen: Instant,
+}
+
+impl TokenBucket {
+    fn full(capacity: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            last_seen: now,
+        }
+    }
+
+    /// Refill for the time since the last call, then try to spend a token.
+    fn take(&mut self, now: Instant, capacity: f64, per_sec: f64) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_seen).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_sec).min(capacity);
+        self.last_seen = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A single binary message deposited by a sender whose peer was not online.
+struct StoredPayload {
+    data: Vec<u8>,
     created_at: Instant,
 }
 
@@ -34,6 +135,7 @@ impl RelayState {
         channel_ttl_secs: u64,
         max_payload_bytes: usize,
         rate_limit_per_min: usize,
+        store_and_forward: bool,
     ) -> Self {
         Self {
             channels: Mutex::new(HashMap::new()),
@@ -42,22 +144,142 @@ impl RelayState {
             connection_log: Mutex::new(HashMap::new()),
             rate_limit_per_min,
             max_payload_bytes,
+            mailboxes: Mutex::new(HashMap::new()),
+            burned: Mutex::new(HashMap::new()),
+            store_and_forward,
+            ping_interval: Duration::from_secs(DEFAULT_PING_INTERVAL_SECS),
+            pong_timeout: Duration::from_secs(DEFAULT_PONG_TIMEOUT_SECS),
+            started_at: Instant::now(),
         }
     }
 
+    /// Time since the relay started.
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Largest message the relay forwards.
+    pub fn max_payload_bytes(&self) -> usize {
+        self.max_payload_bytes
+    }
+
+    /// Channels with a client waiting for its pair.
+    pub async fn active_channels(&self) -> usize {
+        self.channels.lock().await.len()
+    }
+
+    /// Override how often waiting clients are pinged and how long they
+    /// have to answer.
+    pub fn with_keepalive(mut self, ping_interval: Duration, pong_timeout: Duration) -> Self {
+        self.ping_interval = ping_interval;
+        self.pong_timeout = pong_timeout;
+        self
+    }
+
+    /// Drop waiting channels, deposited payloads and burned codes older than
+    /// the channel TTL, and forget IPs that have been idle for [`IDLE_IP_TTL`].
+    pub async fn sweep_expired(&self) {
+        let ttl = Duration::from_secs(self.channel_ttl_secs);
+        self.channels
+            .lock()
+            .await
+            .retain(|_, ch| ch.created_at.elapsed() < ttl);
+        self.mailboxes
+            .lock()
+            .await
+            .retain(|_, stored| stored.created_at.elapsed() < ttl);
+        self.burned
+            .lock()
+            .await
+            .retain(|_, burned_at| burned_at.elapsed() < ttl);
+        self.prune_connection_log(Instant::now()).await;
+    }
+
+    /// Retire `code`: drop anything waiting on it and refuse it until the TTL passes.
+    async fn burn(&self, code: &str) {
+        self.channels.lock().await.remove(code);
+        self.mailboxes.lock().await.remove(code);
+        self.burned
+            .lock()
+            .await
+            .insert(code.to_string(), Instant::now());
+        tracing::debug!(code = %code, "code burned after delivery");
+    }
+
+    async fn is_burned(&self, code: &str) -> bool {
+        self.burned.lock().await.contains_key(code)
+    }
+
+    /// Remove buckets for IPs not seen within [`IDLE_IP_TTL`] of `now`.
+    async fn prune_connection_log(&self, now: Instant) {
+        prune_idle(&mut *self.connection_log.lock().await, now);
+    }
+
+    /// Take the deposited payload for `code`, if one exists and has not expired.
+    async fn take_stored(&self, code: &str) -> Option<Vec<u8>> {
+        let ttl = Duration::from_secs(self.channel_ttl_secs);
+        let mut mailboxes = self.mailboxes.lock().await;
+        let stored = mailboxes.remove(code)?;
+        if stored.created_at.elapsed() < ttl {
+            Some(stored.data)
+        } else {
+            None
+        }
+    }
+
+    /// Deposit a payload for later pickup. Returns false if the mailbox table is full.
+    async fn deposit(&self, code: &str, data: Vec<u8>) -> bool {
+        let mut mailboxes = self.mailboxes.lock().await;
+        if mailboxes.len() >= self.max_channels && !mailboxes.contains_key(code) {
+            return false;
+        }
+        mailboxes.insert(
+            code.to_string(),
+            StoredPayload {
+                data,
+                created_at: Instant::now(),
+            },
+        );
+        true
+    }
+
     /// Check if the given IP is within the rate limit.
     /// Returns true if the connection is allowed, false if rate-limited.
     async fn check_rate_limit(&self, ip: IpAddr) -> bool {
+        self.check_rate_limit_at(ip, Instant::now()).await
+    }
+
+    async fn check_rate_limit_at(&self, ip: IpAddr, now: Instant) -> bool {
+        let per_min = self.rate_limit_per_min as f64;
+        let capacity = per_min.min(MAX_BURST);
         let mut log = self.connection_log.lock().await;
-        let entries = log.entry(ip).or_default();
-        let cutoff = Instant::now() - std::time::Duration::from_secs(60);
-        entries.retain(|t| *t > cutoff);
-        if entries.len() >= self.rate_limit_per_min {
-            false
-        } else {
-            entries.push(Instant::now());
-            true
+        if !log.contains_key(&ip) && log.len() >= MAX_TRACKED_IPS {
+            // Full: drop every idle IP at once, and only fall back to
+            // evicting a live one if that freed nothing
+            prune_idle(&mut log, now);
+            if log.len() >= MAX_TRACKED_IPS {
+                evict_least_recent(&mut log);
+            }
         }
+        log.entry(ip)
+            .or_insert_with(|| TokenBucket::full(capacity, now))
+            .take(now, capacity, per_min / 60.0)
+    }
+}
+
+/// Remove buckets for IPs not seen within [`IDLE_IP_TTL`] of `now`.
+fn prune_idle(log: &mut HashMap<IpAddr, TokenBucket>, now: Instant) {
+    log.retain(|_, bucket| now.saturating_duration_since(bucket.last_seen) < IDLE_IP_TTL);
+}
+
+/// Evict the IP seen least recently (LRU).
+fn evict_least_recent(log: &mut HashMap<IpAddr, TokenBucket>) {
+    let oldest = log
+        .iter()
+        .min_by_key(|(_, bucket)| bucket.last_seen)
+        .map(|(ip, _)| *ip);
+    if let Some(ip) = oldest {
+        log.remove(&ip);
     }
 }
 
@@ -96,21 +318,35 @@ async fn handle_socket(
 
     let (mut ws_tx, mut ws_rx) = socket.split();
 
-    // Clean expired channels first
-    {
-        let mut channels = state.channels.lock().await;
-        let ttl = std::time::Duration::from_secs(state.channel_ttl_secs);
-        channels.retain(|_, ch| ch.created_at.elapsed() < ttl);
+    // Clean expired channels, deposited payloads, and stale rate limit entries first
+    state.sweep_expired().await;
+
+    if state.is_burned(&code).await {
+        tracing::debug!(code = %code, "rejecting burned code");
+        let _ = ws_tx
+            .send(Message::Close(Some(axum::extract::ws::CloseFrame {
+                code: CLOSE_CODE_BURNED,
+                reason: "code already used".into(),
+            })))
+            .await;
+        return;
     }
 
-    // Prune stale IPs from rate limit log to prevent unbounded memory growth
-    {
-        let mut log = state.connection_log.lock().await;
-        let cutoff = Instant::now() - std::time::Duration::from_secs(60);
-        log.retain(|_, entries| {
-            entries.retain(|t| *t > cutoff);
-            !entries.is_empty()
-        });
+    // Store-and-forward: hand over a deposited payload without needing the sender online
+    if state.store_and_forward {
+        if let Some(data) = state.take_stored(&code).await {
+            tracing::debug!(code = %code, "delivering stored payload");
+            if ws_tx.send(Message::Binary(data)).await.is_ok() {
+                // Wait for the receiver's ack (or close) before hanging up
+                while let Some(Ok(msg)) = ws_rx.next().await {
+                    if matches!(msg, Message::Binary(_) | Message::Close(_)) {
+                        break;
+                    }
+                }
+            }
+            let _ = ws_tx.send(Message::Close(None)).await;
+            return;
+        }
     }
 
     // Try to join an existing channel or create a new one
@@ -120,6 +356,7 @@ async fn handle_socket(
         // Second client: pair with the waiting client
         let first_client_tx = channel.tx;
         let first_client_rx = channel.rx.expect("channel should have rx");
+        let delivery = channel.delivery;
         drop(channels); // Release the lock
 
         tracing::debug!(code = %code, "second client connected, starting relay");
@@ -141,11 +378,21 @@ async fn handle_socket(
 
         // Forward: second client -> first client
         let max_payload_second = max_payload_bytes;
+        let burn_state = Arc::clone(&state);
+        let burn_code = code.clone();
         let mut forward_second = tokio::spawn(async move {
             while let Some(Ok(msg)) = ws_rx.next().await {
                 if matches!(msg, Message::Close(_)) {
                     break;
                 }
+                if is_burn_frame(&msg) {
+                    if may_burn(&burn_code, &delivery, Side::Second) {
+                        burn_state.burn(&burn_code).await;
+                        break;
+                    }
+                    tracing::debug!(code = %burn_code, "ignoring unauthorized burn frame");
+                    continue;
+                }
                 let msg_size = match &msg {
                     Message::Binary(data) => data.len(),
                     Message::Text(text) => text.len(),
@@ -159,6 +406,12 @@ async fn handle_socket(
                     );
                     break;
                 }
+                if is_data(&msg) {
+                    delivery
+                        .lock()
+                        .expect("delivery lock poisoned")
+                        .record(Side::Second);
+                }
                 if first_client_tx.send(msg).await.is_err() {
                     break;
                 }
@@ -170,7 +423,7 @@ async fn handle_socket(
         // Wait for either to finish, then abort the other
         tokio::select! {
             _ = &mut forward_first => {
-                forward_second.abort();
+                finish_within_grace(forward_second).await;
             }
             _ = &mut forward_second => {
                 forward_first.abort();
@@ -192,12 +445,17 @@ async fn handle_socket(
         // from_first_tx/from_first_rx: messages FROM the first client
         let (to_first_tx, mut to_first_rx) = mpsc::channel::<Message>(32);
         let (from_first_tx, from_first_rx) = mpsc::channel::<Message>(32);
+        let own_tx = to_first_tx.clone();
+        let ping_tx = to_first_tx.downgrade();
+        let (seen_tx, seen_rx) = watch::channel(Instant::now());
+        let delivery = Arc::new(std::sync::Mutex::new(Delivery::default()));
 
         channels.insert(
             code.clone(),
             Channel {
                 tx: to_first_tx,
                 rx: Some(from_first_rx),
+                delivery: Arc::clone(&delivery),
                 created_at: Instant::now(),
             },
         );
@@ -208,10 +466,25 @@ async fn handle_socket(
         // Forward: first client sends -> from_first_tx (stored for second client)
         let code_clone = code.clone();
         let max_payload_first = max_payload_bytes;
+        let deposit_state = Arc::clone(&state);
         let mut forward_outgoing = tokio::spawn(async move {
+            // Only our own handle is needed for depositing; drop it otherwise so the
+            // incoming forwarder ends when the paired client goes away.
+            let mut own_tx = deposit_state.store_and_forward.then_some(own_tx);
             while let Some(Ok(msg)) = ws_rx.next().await {
-                if matches!(msg, Message::Close(_)) {
-                    break;
+                seen_tx.send_replace(Instant::now());
+                match msg {
+                    Message::Close(_) => break,
+                    Message::Ping(_) | Message::Pong(_) => continue,
+                    _ => {}
+                }
+                if is_burn_frame(&msg) {
+                    if may_burn(&code, &delivery, Side::First) {
+                        deposit_state.burn(&code).await;
+                        break;
+                    }
+                    tracing::debug!(code = %code, "ignoring unauthorized burn frame");
+                    continue;
                 }
                 let msg_size = match &msg {
                     Message::Binary(data) => data.len(),
@@ -226,6 +499,21 @@ async fn handle_socket(
                     );
                     break;
                 }
+                if let Message::Binary(ref data) = msg {
+                    if let Some(tx) = own_tx.take() {
+                        // Nobody has joined yet: deposit the payload and let the sender go.
+                        // The incoming forwarder delivers the close frame and then ends.
+                        if deposit_unpaired(&deposit_state, &code, &tx, data).await {
+                            continue;
+                        }
+                    }
+                }
+                if is_data(&msg) {
+                    delivery
+                        .lock()
+                        .expect("delivery lock poisoned")
+                        .record(Side::First);
+                }
                 if from_first_tx.send(msg).await.is_err() {
                     break;
                 }
@@ -241,14 +529,27 @@ async fn handle_socket(
             }
         });
 
+        let mut keepalive = tokio::spawn(keepalive(
+            ping_tx,
+            seen_rx,
+            state.ping_interval,
+            state.pong_timeout,
+        ));
+
         tokio::select! {
             _ = &mut forward_outgoing => {
                 forward_incoming.abort();
             }
             _ = &mut forward_incoming => {
+                finish_within_grace(forward_outgoing).await;
+            }
+            _ = &mut keepalive => {
+                tracing::debug!(code = %code_clone, "waiting client stopped answering pings");
+                forward_incoming.abort();
                 forward_outgoing.abort();
             }
         }
+        keepalive.abort();
 
         // Clean up channel if still waiting (second client never connected)
         let mut channels = state.channels.lock().await;
@@ -257,3 +558,191 @@ async fn handle_socket(
         tracing::debug!(code = %code_clone, "first client disconnected");
     }
 }
+
+fn is_burn_frame(msg: &Message) -> bool {
+    matches!(msg, Message::Text(text) if text == BURN_FRAME)
+}
+
+fn is_data(msg: &Message) -> bool {
+    matches!(msg, Message::Binary(_) | Message::Text(_))
+}
+
+/// Whether `side` may retire `code`. Identity channels (bare hex ids, unlike
+/// `NNNN-word-word` codes) are reused for every push and are never burned.
+fn may_burn(code: &str, delivery: &std::sync::Mutex<Delivery>, side: Side) -> bool {
+    code.contains('-')
+        && delivery
+            .lock()
+            .expect("delivery lock poisoned")
+            .may_burn(side)
+}
+
+/// The peer has left: give this client's reader [`BURN_GRACE`] to forward
+/// anything still in flight (such as a burn frame) before cutting it off.
+async fn finish_within_grace(mut reader: tokio::task::JoinHandle<()>) {
+    if tokio::time::timeout(BURN_GRACE, &mut reader).await.is_err() {
+        reader.abort();
+    }
+}
+
+/// Ping a waiting client every `interval` and return once it has failed to
+/// send anything (normally a pong) within `timeout` of a ping.
+///
+/// Pings go through the client's outgoing queue, so they stop once the
+/// session has ended and that queue is gone.
+async fn keepalive(
+    ping_tx: mpsc::WeakSender<Message>,
+    seen_rx: watch::Receiver<Instant>,
+    interval: Duration,
+    timeout: Duration,
+) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let Some(tx) = ping_tx.upgrade() else {
+            return std::future::pending().await;
+        };
+        let sent = Instant::now();
+        if tx.send(Message::Ping(Vec::new())).await.is_err() {
+            return std::future::pending().await;
+        }
+        drop(tx);
+
+        tokio::time::sleep(timeout).await;
+        if *seen_rx.borrow() < sent {
+            return;
+        }
+    }
+}
+
+/// Deposit a first client's message if its channel is still unpaired.
+/// On success, the waiting channel is removed and the client is told to close.
+async fn deposit_unpaired(
+    state: &RelayState,
+    code: &str,
+    own_tx: &mpsc::Sender<Message>,
+    data: &[u8],
+) -> bool {
+    let mut channels = state.channels.lock().await;
+    let unpaired = channels
+        .get(code)
+        .is_some_and(|ch| ch.tx.same_channel(own_tx));
+    if !unpaired {
+        return false;
+    }
+    if !state.deposit(code, data.to_vec()).await {
+        tracing::warn!("mailbox storage full, keeping sender online");
+        return false;
+    }
+    channels.remove(code);
+    drop(channels);
+
+    tracing::debug!(code = %code, "payload deposited for later pickup");
+    let _ = own_tx.send(Message::Close(None)).await;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_state() -> RelayState {
+        RelayState::new(10, 300, 1024, 100, false)
+    }
+
+    #[tokio::test]
+    async fn steady_rate_is_accepted() {
+        // 60/min refills one token per second
+        let state = RelayState::new(10, 300, 1024, 60, false);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let start = Instant::now();
+        for i in 0..100 {
+            let now = start + Duration::from_secs(i);
+            assert!(state.check_rate_limit_at(ip, now).await, "request {}", i);
+        }
+    }
+
+    #[tokio::test]
+    async fn burst_beyond_capacity_is_rejected() {
+        let state = RelayState::new(10, 300, 1024, 600, false);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let now = Instant::now();
+        for _ in 0..MAX_BURST as usize {
+            assert!(state.check_rate_limit_at(ip, now).await);
+        }
+        assert!(!state.check_rate_limit_at(ip, now).await);
+
+        // Other IPs have their own bucket
+        assert!(
+            state
+                .check_rate_limit_at("10.0.0.2".parse().unwrap(), now)
+                .await
+        );
+
+        // 600/min refills a token every 100ms
+        let later = now + Duration::from_millis(100);
+        assert!(state.check_rate_limit_at(ip, later).await);
+        assert!(!state.check_rate_limit_at(ip, later).await);
+    }
+
+    #[tokio::test]
+    async fn idle_ips_are_evicted_after_ttl() {
+        let state = test_state();
+        let now = Instant::now();
+        assert!(
+            state
+                .check_rate_limit_at("10.0.0.1".parse().unwrap(), now)
+                .await
+        );
+        let later = now + Duration::from_secs(30);
+        assert!(
+            state
+                .check_rate_limit_at("10.0.0.2".parse().unwrap(), later)
+                .await
+        );
+        assert_eq!(state.connection_log.lock().await.len(), 2);
+
+        // Still inside the TTL: nothing is pruned
+        state.prune_connection_log(later).await;
+        assert_eq!(state.connection_log.lock().await.len(), 2);
+
+        // Only the IP idle past the TTL is dropped
+        state.prune_connection_log(now + IDLE_IP_TTL).await;
+        let log = state.connection_log.lock().await;
+        assert_eq!(log.len(), 1);
+        assert!(log.contains_key(&"10.0.0.2".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn connection_log_stays_bounded() {
+        let state = test_state();
+        let start = Instant::now();
+        let ip_at = |i: u32| IpAddr::from(std::net::Ipv4Addr::from(0x0a00_0000 + i));
+
+        // A slow scanner: every source IP is new, one every 10ms
+        let total = MAX_TRACKED_IPS as u32 + 5_000;
+        for i in 0..total {
+            let now = start + Duration::from_millis(10 * i as u64);
+            assert!(state.check_rate_limit_at(ip_at(i), now).await);
+            assert!(state.connection_log.lock().await.len() <= MAX_TRACKED_IPS);
+        }
+
+        // The most recent IPs are the ones kept
+        let log = state.connection_log.lock().await;
+        assert!(log.contains_key(&ip_at(total - 1)));
+        assert!(!log.contains_key(&ip_at(0)));
+    }
+
+    #[test]
+    fn evicts_least_recently_seen_ip() {
+        let mut log = HashMap::new();
+        let now = Instant::now();
+        let old: IpAddr = "10.0.0.1".parse().unwrap();
+        let recent: IpAddr = "10.0.0.2".parse().unwrap();
+        log.insert(old, TokenBucket::full(1.0, now - Duration::from_secs(30)));
+        log.insert(recent, TokenBucket::full(1.0, now));
+
+        evict_least_recent(&mut log);
+        assert!(!log.contains_key(&old));
+        assert!(log.contains_key(&recent));
+    }
+}
@@ -5,7 +5,12 @@ pub mod mailbox;
 use axum::Router;
 
 /// Server configuration.
+///
+/// Can be loaded from a TOML file (`enseal serve --config relay.toml`);
+/// any field left out keeps its default.
 #[cfg(feature = "server")]
+#[derive(Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ServerConfig {
     #[allow(dead_code)]
     pub port: u16,
@@ -15,6 +20,13 @@ pub struct ServerConfig {
     pub channel_ttl_secs: u64,
     pub max_payload_bytes: usize,
     pub rate_limit_per_min: usize,
+    /// Hold a sender's payload until the receiver connects, instead of
+    /// requiring both peers to be online at the same time.
+    pub store_and_forward: bool,
+    /// Ping a client waiting for its pair this often.
+    pub ping_interval_secs: u64,
+    /// Free a waiting client's channel if it doesn't answer a ping in time.
+    pub pong_timeout_secs: u64,
 }
 
 #[cfg(feature = "server")]
@@ -27,20 +39,45 @@ impl Default for ServerConfig {
             channel_ttl_secs: 300,
             max_payload_bytes: 1_048_576,
             rate_limit_per_min: 10,
+            store_and_forward: false,
+            ping_interval_secs: mailbox::DEFAULT_PING_INTERVAL_SECS,
+            pong_timeout_secs: mailbox::DEFAULT_PONG_TIMEOUT_SECS,
         }
     }
 }
 
+#[cfg(feature = "server")]
+impl ServerConfig {
+    /// Load a relay config file. Missing fields fall back to defaults.
+    pub fn from_file(path: &std::path::Path) -> anyhow::Result<Self> {
+        use anyhow::Context;
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+    }
+}
+
 /// Build the axum router for the relay server.
+/// Must be called from within a Tokio runtime (spawns the expiry sweep task).
 #[cfg(feature = "server")]
 pub fn build_router(config: ServerConfig) -> Router {
     use std::sync::Arc;
-    let state = Arc::new(mailbox::RelayState::new(
-        config.max_channels,
-        config.channel_ttl_secs,
-        config.max_payload_bytes,
-        config.rate_limit_per_min,
-    ));
+    use std::time::Duration;
+    let state = Arc::new(
+        mailbox::RelayState::new(
+            config.max_channels,
+            config.channel_ttl_secs,
+            config.max_payload_bytes,
+            config.rate_limit_per_min,
+            config.store_and_forward,
+        )
+        .with_keepalive(
+            Duration::from_secs(config.ping_interval_secs),
+            Duration::from_secs(config.pong_timeout_secs),
+        ),
+    );
+
+    spawn_sweep(Arc::downgrade(&state), config.channel_ttl_secs);
 
     Router::new()
         .route("/health", axum::routing::get(health))
@@ -48,11 +85,38 @@ pub fn build_router(config: ServerConfig) -> Router {
         .with_state(state)
 }
 
+/// Periodically expire stale channels and deposited payloads.
+/// Stops once the router (and with it the relay state) is dropped.
+#[cfg(feature = "server")]
+fn spawn_sweep(state: std::sync::Weak<mailbox::RelayState>, ttl_secs: u64) {
+    let interval = std::time::Duration::from_secs(ttl_secs.clamp(1, 30));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match state.upgrade() {
+                Some(state) => state.sweep_expired().await,
+                None => break,
+            }
+        }
+    });
+}
+
 #[cfg(feature = "server")]
-async fn health() -> axum::Json<serde_json::Value> {
+async fn health(
+    axum::extract::State(state): axum::extract::State<std::sync::Arc<mailbox::RelayState>>,
+) -> axum::Json<serde_json::Value> {
+    let unix_time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
     axum::Json(serde_json::json!({
         "status": "ok",
         "service": "enseal-relay",
         "version": env!("CARGO_PKG_VERSION"),
+        "unix_time": unix_time,
+        "uptime_secs": state.uptime().as_secs(),
+        "active_channels": state.active_channels().await,
+        "max_payload_bytes": state.max_payload_bytes(),
     }))
 }
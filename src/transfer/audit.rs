@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::envelope::Envelope;
+use crate::keys::identity::TrustedKey;
+use crate::keys::store::KeyStore;
+
+/// `mode` recorded for an encrypted file drop (`share --output`).
+pub const MODE_FILE_DROP: &str = "file-drop";
+/// `mode` recorded for an identity-mode relay push (`share --relay`).
+pub const MODE_RELAY: &str = "relay";
+
+/// Who a payload was encrypted to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecipient {
+    pub identity: String,
+    /// `None` for a raw `age1...` recipient, which has no signing key.
+    pub fingerprint: Option<String>,
+}
+
+impl AuditRecipient {
+    pub fn trusted(key: &TrustedKey) -> Self {
+        Self {
+            identity: key.identity.clone(),
+            fingerprint: Some(key.fingerprint()),
+        }
+    }
+
+    pub fn raw(recipient: &str) -> Self {
+        Self {
+            identity: recipient.to_string(),
+            fingerprint: None,
+        }
+    }
+}
+
+/// One line of the audit log. Describes a payload, never its values.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Unix epoch seconds when the payload was sent.
+    pub timestamp: u64,
+    pub mode: String,
+    pub recipients: Vec<AuditRecipient>,
+    /// SHA-256 of the plaintext payload, from the envelope metadata.
+    pub sha256: String,
+    pub var_count: Option<usize>,
+}
+
+impl AuditEntry {
+    pub fn new(mode: &str, envelope: &Envelope, recipients: &[AuditRecipient]) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            mode: mode.to_string(),
+            recipients: recipients.to_vec(),
+            sha256: envelope.metadata.sha256.clone(),
+            var_count: envelope.metadata.var_count,
+        }
+    }
+}
+
+/// Auditing is opt-in: `ENSEAL_AUDIT=1` or `audit = true` in the user config.
+pub fn enabled() -> Result<bool> {
+    if std::env::var("ENSEAL_AUDIT").is_ok_and(|v| v == "1") {
+        return Ok(true);
+    }
+    Ok(crate::config::user::get()?.audit)
+}
+
+/// The audit log to append to, or `None` when auditing is off.
+pub fn log_path(store: &KeyStore) -> Result<Option<PathBuf>> {
+    Ok(enabled()?.then(|| store.audit_log_path()))
+}
+
+/// Append `entry` as one JSON line, creating the log (mode 0600) if needed.
+pub fn append(path: &Path, entry: &AuditEntry) -> Result<()> {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    let mut line = serde_json::to_string(entry).context("failed to serialize audit entry")?;
+    line.push('\n');
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory: {}", parent.display()))?;
+    }
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("failed to open audit log: {}", path.display()))?;
+    file.write_all(line.as_bytes())
+        .with_context(|| format!("failed to write audit log: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::input::PayloadFormat;
+    use crate::keys::identity::EnsealIdentity;
+
+    fn read_entries(path: &Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn appends_one_line_per_send_without_values() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("audit.log");
+        let envelope = Envelope::seal(
+            "API_KEY=abc123\nDB_PASS=hunter2\n",
+            PayloadFormat::Env,
+            None,
+        )
+        .unwrap();
+        let alice = EnsealIdentity::generate();
+        let recipients = [
+            AuditRecipient::trusted(&TrustedKey {
+                identity: "alice".to_string(),
+                age_recipient: alice.age_recipient.clone(),
+                verifying_key: alice.signing_key.verifying_key(),
+            }),
+            AuditRecipient::raw("age1example"),
+        ];
+
+        append(
+            &path,
+            &AuditEntry::new(MODE_FILE_DROP, &envelope, &recipients),
+        )
+        .unwrap();
+        append(
+            &path,
+            &AuditEntry::new(MODE_RELAY, &envelope, &recipients[..1]),
+        )
+        .unwrap();
+
+        let entries = read_entries(&path);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["mode"], "file-drop");
+        assert_eq!(entries[1]["mode"], "relay");
+        assert_eq!(entries[0]["sha256"], envelope.metadata.sha256.as_str());
+        assert_eq!(entries[0]["var_count"], 2);
+        assert!(entries[0]["timestamp"].as_u64().unwrap() > 0);
+        assert_eq!(entries[0]["recipients"][0]["identity"], "alice");
+        assert_eq!(
+            entries[0]["recipients"][0]["fingerprint"],
+            alice.fingerprint().as_str()
+        );
+        assert!(entries[0]["recipients"][1]["fingerprint"].is_null());
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        for secret in ["abc123", "hunter2", "API_KEY", "DB_PASS"] {
+            assert!(!raw.contains(secret), "audit log leaked {secret}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn log_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("audit.log");
+        let envelope = Envelope::seal("hello", PayloadFormat::Raw, None).unwrap();
+        append(&path, &AuditEntry::new(MODE_RELAY, &envelope, &[])).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -3,21 +3,28 @@ use std::path::Path;
 use anyhow::{Context, Result};
 
 use crate::crypto::envelope::Envelope;
-use crate::crypto::signing::SignedEnvelope;
+use crate::error::EnsealError;
 use crate::keys::identity::{EnsealIdentity, TrustedKey};
+use crate::transfer::audit::{AuditEntry, AuditRecipient, MODE_FILE_DROP};
+
+/// Where and for whom to record a file drop in the audit log.
+pub struct Audit<'a> {
+    pub log: &'a Path,
+    pub recipients: &'a [AuditRecipient],
+}
 
 /// Write an encrypted file drop: encrypt to recipients, sign with sender key.
-/// Produces `<output_dir>/<filename>.env.age`.
+/// Produces `<output_dir>/<filename>.env.age`, and appends to the audit log
+/// when `audit` is given.
 pub fn write(
     envelope: &Envelope,
     recipients: &[&age::x25519::Recipient],
     sender: &EnsealIdentity,
     output_dir: &Path,
     filename: &str,
+    audit: Option<Audit<'_>>,
 ) -> Result<std::path::PathBuf> {
-    let inner_bytes = envelope.to_bytes()?;
-    let signed = SignedEnvelope::seal(&inner_bytes, recipients, sender)?;
-    let wire_bytes = signed.to_bytes()?;
+    let wire_bytes = crate::crypto::signing::seal_signed(envelope, recipients, sender)?;
 
     // Sanitize filename: strip path separators and '..' to prevent directory traversal
     let safe_filename = filename.replace(['/', '\\'], "_").replace("..", "_");
@@ -52,44 +59,122 @@ pub fn write(
             .with_context(|| format!("failed to write file: {}", dest.display()))?;
     }
 
+    if let Some(audit) = audit {
+        let entry = AuditEntry::new(MODE_FILE_DROP, envelope, audit.recipients);
+        super::audit::append(audit.log, &entry)?;
+    }
+
     Ok(dest)
 }
 
 /// Read and decrypt a file drop.
+/// `max_age` bounds the envelope's age in seconds; `None` skips the check.
 #[allow(dead_code)]
 pub fn read(
     path: &Path,
     own_identity: &EnsealIdentity,
     expected_sender: Option<&TrustedKey>,
-) -> Result<(Envelope, String)> {
-    let metadata = std::fs::metadata(path)
-        .with_context(|| format!("failed to read file: {}", path.display()))?;
+    max_age: Option<u64>,
+) -> Result<(Envelope, String), EnsealError> {
+    let read_error = |e: std::io::Error| {
+        std::io::Error::new(
+            e.kind(),
+            format!("failed to read file: {}: {}", path.display(), e),
+        )
+    };
+    let metadata = std::fs::metadata(path).map_err(read_error)?;
     if metadata.len() > 16 * 1024 * 1024 {
-        anyhow::bail!(
+        return Err(EnsealError::Parse(format!(
             "file too large ({} bytes, max 16 MiB): {}",
             metadata.len(),
             path.display()
-        );
+        )));
     }
-    let data =
-        std::fs::read(path).with_context(|| format!("failed to read file: {}", path.display()))?;
-    read_from_bytes(&data, own_identity, expected_sender)
+    let data = std::fs::read(path).map_err(read_error)?;
+    crate::crypto::signing::open_signed(&data, own_identity, expected_sender, max_age)
 }
 
-/// Read and decrypt a file drop from already-loaded bytes.
-/// Use this to avoid reading the file twice (TOCTOU).
-pub fn read_from_bytes(
-    data: &[u8],
-    own_identity: &EnsealIdentity,
-    expected_sender: Option<&TrustedKey>,
-) -> Result<(Envelope, String)> {
-    let signed = SignedEnvelope::from_bytes(data)?;
-    let sender_pubkey = signed.sender_sign_pubkey.clone();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::input::PayloadFormat;
+    use crate::crypto::signing::open_signed;
+
+    /// Write a file drop whose envelope was created `age_secs` ago.
+    fn write_aged_drop(dir: &Path, recipient: &EnsealIdentity, age_secs: u64) -> Vec<u8> {
+        let sender = EnsealIdentity::generate();
+        let mut envelope = Envelope::seal("KEY=value\n", PayloadFormat::Env, None).unwrap();
+        envelope.metadata.created_at -= age_secs;
+        let dest = write(
+            &envelope,
+            &[&recipient.age_recipient],
+            &sender,
+            dir,
+            "old",
+            None,
+        )
+        .unwrap();
+        std::fs::read(dest).unwrap()
+    }
+
+    #[test]
+    fn stale_drop_rejected_by_default_window() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let me = EnsealIdentity::generate();
+        let data = write_aged_drop(dir.path(), &me, 3 * 86400);
 
-    let inner_bytes = signed.open(own_identity, expected_sender)?;
-    let envelope = Envelope::from_bytes(&inner_bytes)?;
-    // Use a generous max age for file drops since files may sit on disk longer
-    envelope.check_age(86400)?;
+        let err = open_signed(&data, &me, None, Some(Envelope::FILEDROP_MAX_AGE)).unwrap_err();
+        assert!(err.to_string().contains("expired"));
+    }
+
+    #[test]
+    fn stale_drop_accepted_under_larger_max_age() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let me = EnsealIdentity::generate();
+        let data = write_aged_drop(dir.path(), &me, 3 * 86400);
+
+        let (envelope, _) = open_signed(&data, &me, None, Some(7 * 86400)).unwrap();
+        assert_eq!(envelope.payload, "KEY=value\n");
+    }
+
+    #[test]
+    fn ignored_age_skips_check() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let me = EnsealIdentity::generate();
+        let data = write_aged_drop(dir.path(), &me, 30 * 86400);
 
-    Ok((envelope, sender_pubkey))
+        assert!(open_signed(&data, &me, None, None).is_ok());
+    }
+
+    #[test]
+    fn audited_drop_appends_entry() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("audit.log");
+        let sender = EnsealIdentity::generate();
+        let me = EnsealIdentity::generate();
+        let envelope = Envelope::seal("TOKEN=s3cret\n", PayloadFormat::Env, None).unwrap();
+        let recipients = [AuditRecipient::raw(&me.age_recipient.to_string())];
+
+        write(
+            &envelope,
+            &[&me.age_recipient],
+            &sender,
+            dir.path(),
+            "me",
+            Some(Audit {
+                log: &log,
+                recipients: &recipients,
+            }),
+        )
+        .unwrap();
+
+        let logged = std::fs::read_to_string(&log).unwrap();
+        assert_eq!(logged.lines().count(), 1);
+        let entry: AuditEntry = serde_json::from_str(logged.trim()).unwrap();
+        assert_eq!(entry.mode, MODE_FILE_DROP);
+        assert_eq!(entry.recipients, recipients);
+        assert_eq!(entry.sha256, envelope.metadata.sha256);
+        assert_eq!(entry.var_count, Some(1));
+        assert!(!logged.contains("s3cret"));
+    }
 }
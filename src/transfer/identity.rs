@@ -1,8 +1,10 @@
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use magic_wormhole::{MailboxConnection, Wormhole};
 
 use crate::crypto::envelope::Envelope;
-use crate::crypto::signing::SignedEnvelope;
+use crate::error::EnsealError;
 use crate::keys::identity::{EnsealIdentity, TrustedKey};
 
 /// Create a mailbox for identity-mode wormhole transfer.
@@ -14,29 +16,39 @@ pub async fn create_mailbox(
     sender: &EnsealIdentity,
     relay_url: Option<&str>,
     code_words: usize,
+    retries: u32,
 ) -> Result<(String, Vec<u8>, MailboxConnection<serde_json::Value>)> {
-    let inner_bytes = envelope.to_bytes()?;
-
     // Encrypt + sign
-    let signed = SignedEnvelope::seal(&inner_bytes, recipients, sender)?;
-    let wire_bytes = signed.to_bytes()?;
+    let wire_bytes = crate::crypto::signing::seal_signed(envelope, recipients, sender)?;
 
-    let config = super::app_config(relay_url);
+    let app_config = super::app_config(relay_url)?;
 
-    tracing::debug!("connecting to rendezvous server (identity mode)...");
-    let mailbox = MailboxConnection::create(config, code_words)
-        .await
-        .context("failed to connect to rendezvous server")?;
+    let mailbox = super::retry::connect("rendezvous connect", retries, || async {
+        tracing::debug!("connecting to rendezvous server (identity mode)...");
+        MailboxConnection::create(app_config.clone(), code_words)
+            .await
+            .context("failed to connect to rendezvous server")
+    })
+    .await?;
 
     let code = mailbox.code().to_string();
 
     Ok((code, wire_bytes, mailbox))
 }
 
-/// Send signed wire bytes through an already-created identity-mode mailbox.
+/// Send signed wire bytes through an already-created identity-mode mailbox,
+/// failing if the receiver has not taken them within `timeout`.
 pub async fn send(
     wire_bytes: Vec<u8>,
     mailbox: MailboxConnection<serde_json::Value>,
+    timeout: Duration,
+) -> Result<()> {
+    super::with_timeout("wormhole send", timeout, send_inner(wire_bytes, mailbox)).await
+}
+
+async fn send_inner(
+    wire_bytes: Vec<u8>,
+    mailbox: MailboxConnection<serde_json::Value>,
 ) -> Result<()> {
     let mut wormhole = Wormhole::connect(mailbox)
         .await
@@ -58,55 +70,22 @@ pub async fn send(
 
 /// Receive an identity-mode envelope via wormhole relay.
 /// Verifies signature and decrypts with own age key.
+/// `max_age` bounds the envelope's age in seconds; `None` skips the check.
 #[allow(dead_code)]
 pub async fn receive(
     code: &str,
     own_identity: &EnsealIdentity,
     expected_sender: Option<&TrustedKey>,
     relay_url: Option<&str>,
-) -> Result<(Envelope, String)> {
-    let config = super::app_config(relay_url);
-
-    let code_parsed = code.parse().context("invalid wormhole code format")?;
-
-    tracing::debug!("connecting to rendezvous server (identity mode)...");
-    let mailbox = MailboxConnection::connect(config, code_parsed, true)
-        .await
-        .context("failed to connect to rendezvous server")?;
-
-    let mut wormhole = Wormhole::connect(mailbox)
-        .await
-        .context("failed to establish wormhole connection")?;
-
-    const MAX_WORMHOLE_PAYLOAD: usize = 16 * 1024 * 1024; // 16 MiB
-
-    tracing::debug!("waiting for data (identity mode)...");
-    let data = wormhole
-        .receive()
-        .await
-        .context("failed to receive data through wormhole")?;
-
-    if data.len() > MAX_WORMHOLE_PAYLOAD {
-        anyhow::bail!(
-            "wormhole payload too large ({} bytes, max {})",
-            data.len(),
-            MAX_WORMHOLE_PAYLOAD
-        );
-    }
-
-    wormhole
-        .close()
-        .await
-        .context("failed to close wormhole cleanly")?;
-
-    // Parse signed envelope
-    let signed = SignedEnvelope::from_bytes(&data)?;
-    let sender_pubkey = signed.sender_sign_pubkey.clone();
-
-    // Verify + decrypt
-    let inner_bytes = signed.open(own_identity, expected_sender)?;
-    let envelope = Envelope::from_bytes(&inner_bytes)?;
-    envelope.check_age(300)?;
-
-    Ok((envelope, sender_pubkey))
+    max_age: Option<u64>,
+) -> Result<(Envelope, String), EnsealError> {
+    let data = super::wormhole::receive_raw(
+        code,
+        relay_url,
+        super::retry::DEFAULT_RETRIES,
+        super::DEFAULT_TIMEOUT,
+    )
+    .await
+    .map_err(|e| EnsealError::Network(format!("{:#}", e)))?;
+    crate::crypto::signing::open_signed(&data, own_identity, expected_sender, max_age)
 }
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::audit::AuditRecipient;
+use crate::crypto::envelope::Envelope;
+use crate::env::{self, EnvFile};
+use crate::error::{EnsealError, Result};
+
+/// `mode` recorded for a wormhole transfer (anonymous or identity mode).
+pub const MODE_WORMHOLE: &str = "wormhole";
+
+/// A record of one share (`share --manifest`). Describes the payload, never
+/// its values, so a receiver can check what arrived (`verify-manifest`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShareManifest {
+    /// Unix epoch seconds when the envelope was created.
+    pub timestamp: u64,
+    pub mode: String,
+    /// Empty in anonymous mode.
+    pub recipients: Vec<AuditRecipient>,
+    /// Variable names, for .env and KEY=VALUE payloads.
+    pub vars: Vec<String>,
+    /// SHA-256 of the plaintext payload, from the envelope metadata.
+    pub sha256: String,
+    /// The wormhole code, if one was issued.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl ShareManifest {
+    pub fn new(
+        envelope: &Envelope,
+        mode: &str,
+        recipients: Vec<AuditRecipient>,
+        vars: Vec<String>,
+        code: Option<String>,
+    ) -> Self {
+        Self {
+            timestamp: envelope.metadata.created_at,
+            mode: mode.to_string(),
+            recipients,
+            vars,
+            sha256: envelope.metadata.sha256.clone(),
+            code,
+        }
+    }
+}
+
+/// A way a received file differs from its manifest.
+#[derive(Debug, PartialEq)]
+pub enum Discrepancy {
+    /// In the manifest but not the file.
+    Missing(String),
+    /// In the file but not the manifest.
+    Extra(String),
+    /// The file's SHA-256 isn't the one the sender recorded.
+    HashMismatch { expected: String, actual: String },
+}
+
+impl std::fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(key) => write!(f, "missing: {}", key),
+            Self::Extra(key) => write!(f, "extra: {}", key),
+            Self::HashMismatch { expected, actual } => write!(
+                f,
+                "sha256 mismatch: manifest has {}, file is {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+/// Compare a received .env file with `manifest`: the key set, then the
+/// payload hash. Returns every discrepancy; empty means it matches.
+pub fn verify(manifest: &ShareManifest, content: &[u8]) -> Result<Vec<Discrepancy>> {
+    let text = std::str::from_utf8(content)
+        .map_err(|_| EnsealError::Parse("received file is not valid UTF-8".to_string()))?;
+    let mut discrepancies = key_discrepancies(manifest, &env::parser::parse(text)?);
+
+    let actual = format!("{:x}", Sha256::digest(content));
+    if actual != manifest.sha256 {
+        discrepancies.push(Discrepancy::HashMismatch {
+            expected: manifest.sha256.clone(),
+            actual,
+        });
+    }
+    Ok(discrepancies)
+}
+
+/// Keys declared in `manifest` but absent from `env_file` (in manifest
+/// order), then keys in `env_file` the manifest doesn't declare.
+pub fn key_discrepancies(manifest: &ShareManifest, env_file: &EnvFile) -> Vec<Discrepancy> {
+    let keys = env_file.keys();
+    let mut discrepancies: Vec<Discrepancy> = manifest
+        .vars
+        .iter()
+        .filter(|var| !keys.contains(&var.as_str()))
+        .map(|var| Discrepancy::Missing(var.clone()))
+        .collect();
+    let mut extra: Vec<&str> = keys
+        .into_iter()
+        .filter(|key| !manifest.vars.iter().any(|var| var == key))
+        .collect();
+    extra.dedup();
+    discrepancies.extend(
+        extra
+            .into_iter()
+            .map(|key| Discrepancy::Extra(key.to_string())),
+    );
+    discrepancies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::input::PayloadFormat;
+
+    const SENT: &str = "API_KEY=abc123\nDB_PASS=hunter2\n";
+
+    fn manifest() -> ShareManifest {
+        let envelope = Envelope::seal(SENT, PayloadFormat::Env, None).unwrap();
+        let vars = vec!["API_KEY".to_string(), "DB_PASS".to_string()];
+        ShareManifest::new(&envelope, MODE_WORMHOLE, Vec::new(), vars, None)
+    }
+
+    #[test]
+    fn matching_file_passes() {
+        assert!(verify(&manifest(), SENT.as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn tampered_value_is_a_hash_mismatch() {
+        let found = verify(&manifest(), b"API_KEY=abc123\nDB_PASS=changed\n").unwrap();
+        assert!(matches!(
+            found.as_slice(),
+            [Discrepancy::HashMismatch { .. }]
+        ));
+        assert!(!found[0].to_string().contains("changed"));
+    }
+
+    #[test]
+    fn missing_and_extra_keys_are_reported() {
+        let found = verify(&manifest(), b"API_KEY=abc123\nEXTRA=1\n").unwrap();
+        assert_eq!(found[0], Discrepancy::Missing("DB_PASS".to_string()));
+        assert_eq!(found[1], Discrepancy::Extra("EXTRA".to_string()));
+        assert!(matches!(found[2], Discrepancy::HashMismatch { .. }));
+    }
+}
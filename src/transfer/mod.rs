@@ -1,25 +1,84 @@
+pub mod audit;
 pub mod filedrop;
 pub mod identity;
+pub mod manifest;
 pub mod relay;
+pub mod replay;
+pub mod retry;
 pub mod wormhole;
 
 use std::borrow::Cow;
+use std::future::Future;
+use std::time::Duration;
 
+use anyhow::Result;
 use magic_wormhole::{AppConfig, AppID};
 
 const ENSEAL_APPID: &str = "enseal.dev/transfer";
 const DEFAULT_RENDEZVOUS_URL: &str = "ws://relay.magic-wormhole.io:4000/v1";
 
+/// Default limit on a network transfer (`--timeout`).
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Run a transfer step, failing with "`what` timed out after Ns" if it
+/// takes longer than `limit`.
+pub async fn with_timeout<T>(
+    what: &str,
+    limit: Duration,
+    fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout(limit, fut).await.map_err(|_| {
+        crate::error::EnsealError::Network(format!("{} timed out after {}s", what, limit.as_secs()))
+    })?
+}
+
 /// Build the AppConfig for enseal wormhole connections.
-pub fn app_config(relay_url: Option<&str>) -> AppConfig<serde_json::Value> {
-    let rendezvous_url: Cow<'static, str> = match relay_url {
-        Some(url) => Cow::Owned(url.to_string()),
-        None => Cow::Borrowed(DEFAULT_RENDEZVOUS_URL),
+/// Without an explicit URL, the user config's `rendezvous_url` is tried
+/// before the public default.
+pub fn app_config(relay_url: Option<&str>) -> Result<AppConfig<serde_json::Value>> {
+    let configured = match relay_url {
+        Some(_) => None,
+        None => crate::config::user::get()?.rendezvous_url.as_deref(),
     };
-
-    AppConfig {
+    Ok(AppConfig {
         id: AppID::new(ENSEAL_APPID),
-        rendezvous_url,
+        rendezvous_url: rendezvous_url(relay_url, configured),
         app_version: serde_json::json!({"v": 1}),
+    })
+}
+
+fn rendezvous_url(explicit: Option<&str>, configured: Option<&str>) -> Cow<'static, str> {
+    match explicit.or(configured) {
+        Some(url) => Cow::Owned(url.to_string()),
+        None => Cow::Borrowed(DEFAULT_RENDEZVOUS_URL),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendezvous_precedence() {
+        let configured = Some("ws://rv.internal:4000/v1");
+        assert_eq!(
+            rendezvous_url(Some("ws://cli:4000/v1"), configured),
+            "ws://cli:4000/v1"
+        );
+        assert_eq!(rendezvous_url(None, configured), "ws://rv.internal:4000/v1");
+        assert_eq!(rendezvous_url(None, None), DEFAULT_RENDEZVOUS_URL);
+    }
+
+    #[tokio::test]
+    async fn with_timeout_reports_limit() {
+        tokio::time::pause();
+        let err = with_timeout(
+            "relay receive",
+            Duration::from_secs(1),
+            std::future::pending::<Result<()>>(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "relay receive timed out after 1s");
     }
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use futures_util::{SinkExt, StreamExt};
 use tokio_tungstenite::tungstenite;
@@ -11,38 +13,99 @@ fn ws_config() -> tungstenite::protocol::WebSocketConfig {
     }
 }
 
+/// Interval between client pings while waiting on the relay, so idle
+/// connections aren't reaped by proxies or load balancers.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(20);
+
+type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Text frame a code-mode sender sends once the receiver has acked, asking
+/// the relay to retire the code now instead of when its channel TTL runs out.
+pub const BURN_FRAME: &str = "enseal-burn";
+
+/// WebSocket close code the relay uses to turn away a retired code.
+pub const CLOSE_CODE_BURNED: u16 = 4410;
+
 /// Maximum payload size accepted from relay (16 MiB).
 /// Protects against a malicious relay or sender exhausting memory.
 const MAX_RELAY_PAYLOAD: usize = 16 * 1024 * 1024;
 
-/// Maximum time to wait for relay operations (5 minutes).
-const RELAY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+/// Text frame announcing a chunked payload: `enseal-chunks:<total bytes>:<count>`.
+/// The binary chunks follow, each acked by the receiver before the next is sent.
+pub const CHUNK_HEADER_PREFIX: &str = "enseal-chunks:";
+
+/// What the receiver sends back for each message (or chunk) it has read.
+const ACK: &[u8] = b"ack";
 
 /// Send bytes through an enseal relay server.
 /// Returns the channel code that the receiver needs.
-pub async fn send(data: &[u8], relay_url: &str, code: &str) -> Result<()> {
-    if !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
-        anyhow::bail!("invalid channel code: contains disallowed characters");
-    }
+/// The initial connection is retried up to `retries` times; the whole
+/// transfer fails after `timeout`. Once the receiver acks, the relay is
+/// told to retire the code, so a second receive with it fails at once.
+pub async fn send(
+    data: &[u8],
+    relay_url: &str,
+    code: &str,
+    retries: u32,
+    timeout: Duration,
+) -> Result<()> {
+    validate_code(code)?;
 
-    tokio::time::timeout(RELAY_TIMEOUT, send_inner(data, relay_url, code))
-        .await
-        .map_err(|_| {
-            anyhow::anyhow!(
-                "relay send timed out after {} seconds",
-                RELAY_TIMEOUT.as_secs()
-            )
-        })?
+    super::with_timeout(
+        "relay send",
+        timeout,
+        send_inner(data, relay_url, code, retries, true, None),
+    )
+    .await
 }
 
-async fn send_inner(data: &[u8], relay_url: &str, code: &str) -> Result<()> {
+/// Like [`send`], splitting payloads larger than `chunk_size` into chunks
+/// the receiver acks one at a time, for relays whose per-message limit is
+/// below the payload size. Both peers must be online: a store-and-forward
+/// relay only holds a single message.
+pub async fn send_chunked(
+    data: &[u8],
+    relay_url: &str,
+    code: &str,
+    chunk_size: usize,
+    retries: u32,
+    timeout: Duration,
+) -> Result<()> {
+    validate_code(code)?;
+
+    super::with_timeout(
+        "relay send",
+        timeout,
+        send_inner(data, relay_url, code, retries, true, Some(chunk_size)),
+    )
+    .await
+}
+
+/// Deliver `data` on a channel. With `burn`, a delivery the receiver acked
+/// is followed by [`BURN_FRAME`] so the code can't be used again. With a
+/// `chunk_size` smaller than `data`, it goes out in chunks.
+async fn send_inner(
+    data: &[u8],
+    relay_url: &str,
+    code: &str,
+    retries: u32,
+    burn: bool,
+    chunk_size: Option<usize>,
+) -> Result<()> {
     let ws_url = format!("{}/channel/{}", normalize_ws_url(relay_url), code);
+    let mut ws = connect(&ws_url, retries).await?;
 
-    tracing::debug!("connecting to enseal relay: {}", ws_url);
-    let (mut ws, _) =
-        tokio_tungstenite::connect_async_with_config(&ws_url, Some(ws_config()), false)
-            .await
-            .context("failed to connect to enseal relay")?;
+    if let Some(size) = chunk_size.filter(|&size| data.len() > size) {
+        send_chunks(&mut ws, data, size).await?;
+        if burn {
+            let _ = ws
+                .send(tungstenite::Message::Text(BURN_FRAME.to_string()))
+                .await;
+        }
+        let _ = ws.close(None).await;
+        return Ok(());
+    }
 
     // Send the data as a binary message
     ws.send(tungstenite::Message::Binary(data.to_vec()))
@@ -51,11 +114,19 @@ async fn send_inner(data: &[u8], relay_url: &str, code: &str) -> Result<()> {
 
     // Wait for acknowledgment (the receiver reading the message)
     // or the connection closing
-    while let Some(msg) = ws.next().await {
+    while let Some(msg) = next_message(&mut ws).await {
         match msg {
-            Ok(tungstenite::Message::Close(_)) => break,
+            Ok(tungstenite::Message::Close(frame)) => {
+                check_not_burned(frame.as_ref())?;
+                break;
+            }
             Ok(tungstenite::Message::Binary(_)) => {
                 // Got an ack or response, we're done
+                if burn {
+                    let _ = ws
+                        .send(tungstenite::Message::Text(BURN_FRAME.to_string()))
+                        .await;
+                }
                 break;
             }
             Err(_) => break,
@@ -67,33 +138,125 @@ async fn send_inner(data: &[u8], relay_url: &str, code: &str) -> Result<()> {
     Ok(())
 }
 
-/// Receive bytes from an enseal relay server using the given code.
-pub async fn receive(relay_url: &str, code: &str) -> Result<Vec<u8>> {
-    if !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
-        anyhow::bail!("invalid channel code: contains disallowed characters");
+/// Send the chunk header, then each chunk, waiting for the receiver's ack
+/// before the next so no more than one chunk is ever in flight.
+async fn send_chunks(ws: &mut WsStream, data: &[u8], chunk_size: usize) -> Result<()> {
+    if chunk_size == 0 {
+        anyhow::bail!("chunk size must be at least 1 byte");
     }
-
-    tokio::time::timeout(RELAY_TIMEOUT, receive_inner(relay_url, code))
+    let chunks: Vec<&[u8]> = data.chunks(chunk_size).collect();
+    let header = format!("{}{}:{}", CHUNK_HEADER_PREFIX, data.len(), chunks.len());
+    ws.send(tungstenite::Message::Text(header))
         .await
-        .map_err(|_| {
-            anyhow::anyhow!(
-                "relay receive timed out after {} seconds",
-                RELAY_TIMEOUT.as_secs()
-            )
-        })?
+        .context("failed to send data through relay")?;
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        ws.send(tungstenite::Message::Binary(chunk.to_vec()))
+            .await
+            .context("failed to send data through relay")?;
+        loop {
+            match next_message(ws).await {
+                Some(Ok(tungstenite::Message::Binary(_))) => break,
+                Some(Ok(tungstenite::Message::Close(frame))) => {
+                    check_not_burned(frame.as_ref())?;
+                    anyhow::bail!(
+                        "relay closed the connection after {} of {} chunks were acknowledged",
+                        i,
+                        chunks.len()
+                    );
+                }
+                Some(Err(e)) => anyhow::bail!("relay connection error: {}", e),
+                None => anyhow::bail!(
+                    "relay connection ended after {} of {} chunks were acknowledged",
+                    i,
+                    chunks.len()
+                ),
+                Some(Ok(_)) => continue,
+            }
+        }
+    }
+    Ok(())
 }
 
-async fn receive_inner(relay_url: &str, code: &str) -> Result<Vec<u8>> {
-    let ws_url = format!("{}/channel/{}", normalize_ws_url(relay_url), code);
+/// Parse a chunk header into `(total bytes, chunk count)`.
+fn parse_chunk_header(text: &str) -> Result<Option<(usize, usize)>> {
+    let Some(rest) = text.strip_prefix(CHUNK_HEADER_PREFIX) else {
+        return Ok(None);
+    };
+    let parsed = rest
+        .split_once(':')
+        .and_then(|(total, count)| Some((total.parse().ok()?, count.parse().ok()?)));
+    match parsed {
+        Some((total, count)) if count > 0 && count <= total && total <= MAX_RELAY_PAYLOAD => {
+            Ok(Some((total, count)))
+        }
+        Some((total, _)) if total > MAX_RELAY_PAYLOAD => anyhow::bail!(
+            "relay payload too large ({} bytes, max {})",
+            total,
+            MAX_RELAY_PAYLOAD
+        ),
+        _ => anyhow::bail!("invalid chunk header from relay: '{}'", text),
+    }
+}
 
-    tracing::debug!("connecting to enseal relay: {}", ws_url);
-    let (mut ws, _) =
-        tokio_tungstenite::connect_async_with_config(&ws_url, Some(ws_config()), false)
-            .await
-            .context("failed to connect to enseal relay")?;
+/// Read `count` chunks totalling `total` bytes, acking each one.
+async fn receive_chunks(ws: &mut WsStream, total: usize, count: usize) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(total);
+    let mut received = 0;
+    while received < count {
+        match next_message(ws).await {
+            Some(Ok(tungstenite::Message::Binary(chunk))) => {
+                if data.len() + chunk.len() > total {
+                    anyhow::bail!("chunked payload is larger than its header announced");
+                }
+                data.extend_from_slice(&chunk);
+                received += 1;
+                let _ = ws.send(tungstenite::Message::Binary(ACK.to_vec())).await;
+            }
+            Some(Ok(tungstenite::Message::Close(_))) | None => anyhow::bail!(
+                "relay closed connection after {} of {} chunks",
+                received,
+                count
+            ),
+            Some(Err(e)) => anyhow::bail!("relay connection error: {}", e),
+            Some(Ok(_)) => continue,
+        }
+    }
+    if data.len() != total {
+        anyhow::bail!(
+            "chunked payload is {} bytes, header announced {}",
+            data.len(),
+            total
+        );
+    }
+    Ok(data)
+}
+
+/// Receive bytes from an enseal relay server using the given code.
+/// The initial connection is retried up to `retries` times; the whole
+/// transfer fails after `timeout`.
+pub async fn receive(
+    relay_url: &str,
+    code: &str,
+    retries: u32,
+    timeout: Duration,
+) -> Result<Vec<u8>> {
+    validate_code(code)?;
+
+    super::with_timeout(
+        "relay receive",
+        timeout,
+        receive_inner(relay_url, code, retries),
+    )
+    .await
+}
+
+async fn receive_inner(relay_url: &str, code: &str, retries: u32) -> Result<Vec<u8>> {
+    let ws_url = format!("{}/channel/{}", normalize_ws_url(relay_url), code);
+    let mut ws = connect(&ws_url, retries).await?;
 
     // Wait for a binary message from the sender
-    while let Some(msg) = ws.next().await {
+    while let Some(msg) = next_message(&mut ws).await {
         match msg {
             Ok(tungstenite::Message::Binary(data)) => {
                 if data.len() > MAX_RELAY_PAYLOAD {
@@ -104,11 +267,19 @@ async fn receive_inner(relay_url: &str, code: &str) -> Result<Vec<u8>> {
                     );
                 }
                 // Send ack
-                let _ = ws.send(tungstenite::Message::Binary(b"ack".to_vec())).await;
+                let _ = ws.send(tungstenite::Message::Binary(ACK.to_vec())).await;
                 let _ = ws.close(None).await;
                 return Ok(data);
             }
-            Ok(tungstenite::Message::Close(_)) => {
+            Ok(tungstenite::Message::Text(text)) => {
+                if let Some((total, count)) = parse_chunk_header(&text)? {
+                    let data = receive_chunks(&mut ws, total, count).await?;
+                    let _ = ws.close(None).await;
+                    return Ok(data);
+                }
+            }
+            Ok(tungstenite::Message::Close(frame)) => {
+                check_not_burned(frame.as_ref())?;
                 anyhow::bail!("relay closed connection before data was received");
             }
             Err(e) => {
@@ -121,35 +292,314 @@ async fn receive_inner(relay_url: &str, code: &str) -> Result<Vec<u8>> {
     anyhow::bail!("relay connection ended without receiving data")
 }
 
+/// Fail if the relay closed the connection because the code was already used.
+fn check_not_burned(frame: Option<&tungstenite::protocol::CloseFrame<'_>>) -> Result<()> {
+    if frame.is_some_and(|f| u16::from(f.code) == CLOSE_CODE_BURNED) {
+        anyhow::bail!("this code has already been used. Ask the sender for a new one");
+    }
+    Ok(())
+}
+
+/// Open the WebSocket to a relay channel, retrying failed handshakes.
+async fn connect(ws_url: &str, retries: u32) -> Result<WsStream> {
+    super::retry::connect("relay connect", retries, || async {
+        tracing::debug!("connecting to enseal relay: {}", ws_url);
+        let (ws, _) =
+            tokio_tungstenite::connect_async_with_config(ws_url, Some(ws_config()), false)
+                .await
+                .context("failed to connect to enseal relay")?;
+        Ok(ws)
+    })
+    .await
+}
+
+/// Read the next message, pinging the relay every [`KEEPALIVE_INTERVAL`]
+/// while nothing arrives.
+async fn next_message(
+    ws: &mut WsStream,
+) -> Option<Result<tungstenite::Message, tungstenite::Error>> {
+    loop {
+        tokio::select! {
+            msg = ws.next() => return msg,
+            _ = tokio::time::sleep(KEEPALIVE_INTERVAL) => {
+                if let Err(e) = ws.send(tungstenite::Message::Ping(Vec::new())).await {
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
 /// Push data to a relay channel (identity mode sender).
-/// The channel_id is derived from the recipient's identity.
-pub async fn push(data: &[u8], relay_url: &str, channel_id: &str) -> Result<()> {
-    send(data, relay_url, channel_id).await
+/// The channel_id is derived from the recipient's identity, so unlike
+/// [`send`] it is reused for every push and never burned.
+pub async fn push(
+    data: &[u8],
+    relay_url: &str,
+    channel_id: &str,
+    retries: u32,
+    timeout: Duration,
+) -> Result<()> {
+    validate_code(channel_id)?;
+
+    super::with_timeout(
+        "relay send",
+        timeout,
+        send_inner(data, relay_url, channel_id, retries, false, None),
+    )
+    .await
+}
+
+/// Like [`push`], in acked chunks of at most `chunk_size` bytes when the
+/// payload is larger (see [`send_chunked`]).
+pub async fn push_chunked(
+    data: &[u8],
+    relay_url: &str,
+    channel_id: &str,
+    chunk_size: usize,
+    retries: u32,
+    timeout: Duration,
+) -> Result<()> {
+    validate_code(channel_id)?;
+
+    super::with_timeout(
+        "relay send",
+        timeout,
+        send_inner(
+            data,
+            relay_url,
+            channel_id,
+            retries,
+            false,
+            Some(chunk_size),
+        ),
+    )
+    .await
 }
 
 /// Listen on a relay channel for incoming data (identity mode receiver).
 /// The channel_id is derived from own identity.
-pub async fn listen(relay_url: &str, channel_id: &str) -> Result<Vec<u8>> {
-    receive(relay_url, channel_id).await
+pub async fn listen(
+    relay_url: &str,
+    channel_id: &str,
+    retries: u32,
+    timeout: Duration,
+) -> Result<Vec<u8>> {
+    receive(relay_url, channel_id, retries, timeout).await
+}
+
+/// Channel codes are limited to what the relay server accepts in a URL path.
+fn validate_code(code: &str) -> Result<()> {
+    if !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        anyhow::bail!("invalid channel code: contains disallowed characters");
+    }
+    Ok(())
+}
+
+/// Allowed range for the number of words in a generated channel code.
+pub const MIN_CODE_WORDS: usize = 2;
+pub const MAX_CODE_WORDS: usize = 6;
+
+/// Lists shorter than this are rejected outright.
+pub const MIN_WORDLIST_LEN: usize = 16;
+
+/// Lists shorter than this (the NATO alphabet) get a warning: each word
+/// then adds fewer than ~4.7 bits to the code.
+const SMALL_WORDLIST_LEN: usize = 26;
+
+/// The built-in word list.
+const DEFAULT_WORDS: [&str; 48] = [
+    "alpha", "bravo", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet", "kilo",
+    "lima", "mike", "nova", "oscar", "papa", "romeo", "sierra", "tango", "ultra", "victor",
+    "whiskey", "xray", "yankee", "zulu", "amber", "bronze", "coral", "dusk", "ember", "frost",
+    "glacier", "harbor", "ivory", "jade", "karma", "lemon", "marble", "nectar", "opal", "prism",
+    "quartz", "ruby", "sage", "topaz", "umbra", "velvet", "willow", "zenith",
+];
+
+/// The NATO phonetic alphabet (`[codes] nato = true`), easy to read aloud.
+const NATO_WORDS: [&str; 26] = [
+    "alfa", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliett",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+    "uniform", "victor", "whiskey", "xray", "yankee", "zulu",
+];
+
+/// The words channel codes are built from.
+#[derive(Debug, Clone)]
+pub struct CodeWords {
+    words: Vec<String>,
+}
+
+impl CodeWords {
+    pub fn builtin() -> Self {
+        Self::from_static(&DEFAULT_WORDS)
+    }
+
+    pub fn nato() -> Self {
+        Self::from_static(&NATO_WORDS)
+    }
+
+    fn from_static(words: &[&str]) -> Self {
+        Self {
+            words: words.iter().map(|w| w.to_string()).collect(),
+        }
+    }
+
+    /// Validate a custom list: duplicates are dropped and words lowercased.
+    /// Every word must be ASCII letters and digits only, since `-` separates
+    /// words and the relay accepts nothing else in a code.
+    pub fn new(words: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut unique: Vec<String> = Vec::new();
+        for word in words {
+            let word = word.to_ascii_lowercase();
+            if !word.chars().all(|c| c.is_ascii_alphanumeric()) {
+                anyhow::bail!(
+                    "code word '{}' can't be used in a relay code (use only a-z and 0-9)",
+                    word
+                );
+            }
+            if !unique.contains(&word) {
+                unique.push(word);
+            }
+        }
+        if unique.is_empty() {
+            anyhow::bail!("code word list is empty");
+        }
+        if unique.len() < MIN_WORDLIST_LEN {
+            anyhow::bail!(
+                "code word list has only {} distinct words; at least {} are needed for codes that are hard to guess",
+                unique.len(),
+                MIN_WORDLIST_LEN
+            );
+        }
+        Ok(Self { words: unique })
+    }
+
+    /// A warning for lists that pass validation but are still small enough
+    /// to make short codes guessable.
+    pub fn warning(&self) -> Option<String> {
+        (self.words.len() < SMALL_WORDLIST_LEN).then(|| {
+            format!(
+                "code word list has only {} distinct words; consider adding more or using longer codes",
+                self.words.len()
+            )
+        })
+    }
+
+    /// Read a list with one word per line. Blank lines and `#` comments are skipped.
+    pub fn from_file(path: &std::path::Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read code word list {}", path.display()))?;
+        Self::new(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(str::to_string),
+        )
+        .with_context(|| format!("invalid code word list {}", path.display()))
+    }
+
+    /// The list chosen in the user config's `[codes]` section, or the built-in one.
+    pub fn configured() -> Result<Self> {
+        let codes = &crate::config::user::get()?.codes;
+        match (&codes.wordlist, codes.nato) {
+            (Some(_), true) => {
+                anyhow::bail!("[codes] sets both wordlist and nato; pick one")
+            }
+            (Some(path), false) => Self::from_file(path),
+            (None, true) => Ok(Self::nato()),
+            (None, false) => Ok(Self::builtin()),
+        }
+    }
 }
 
-/// Generate a short channel code for relay transport.
-#[allow(dead_code)]
-pub fn generate_code() -> String {
+/// Generate a short channel code for relay transport: a four-digit number
+/// followed by `count` words from `words`, e.g. `4821-coral-tango` for two.
+pub fn generate_code(words: &CodeWords, count: usize) -> Result<String> {
     use rand::Rng;
+    if !(MIN_CODE_WORDS..=MAX_CODE_WORDS).contains(&count) {
+        anyhow::bail!(
+            "code word count must be between {} and {} (got {})",
+            MIN_CODE_WORDS,
+            MAX_CODE_WORDS,
+            count
+        );
+    }
     let mut rng = rand::thread_rng();
     let num: u32 = rng.gen_range(1000..10000);
-    // Use a simple word list for human-friendly codes
-    let words = [
-        "alpha", "bravo", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet", "kilo",
-        "lima", "mike", "nova", "oscar", "papa", "romeo", "sierra", "tango", "ultra", "victor",
-        "whiskey", "xray", "yankee", "zulu", "amber", "bronze", "coral", "dusk", "ember", "frost",
-        "glacier", "harbor", "ivory", "jade", "karma", "lemon", "marble", "nectar", "opal",
-        "prism", "quartz", "ruby", "sage", "topaz", "umbra", "velvet", "willow", "zenith",
-    ];
-    let w1 = words[rng.gen_range(0..words.len())];
-    let w2 = words[rng.gen_range(0..words.len())];
-    format!("{}-{}-{}", num, w1, w2)
+    let mut code = num.to_string();
+    for _ in 0..count {
+        code.push('-');
+        code.push_str(&words.words[rng.gen_range(0..words.words.len())]);
+    }
+    Ok(code)
+}
+
+/// What a relay's `/health` endpoint reports.
+#[derive(Debug, serde::Deserialize)]
+pub struct RelayHealth {
+    pub service: String,
+    pub version: String,
+    /// Relay clock in unix epoch seconds (absent on older relays).
+    pub unix_time: Option<u64>,
+    /// Seconds since the relay started (absent on older relays).
+    pub uptime_secs: Option<u64>,
+    /// Channels with a client waiting for its pair (absent on older relays).
+    pub active_channels: Option<usize>,
+    /// Largest message the relay forwards (absent on older relays).
+    pub max_payload_bytes: Option<usize>,
+}
+
+/// Fetch a relay's `/health` endpoint.
+pub fn health(relay_url: &str, timeout: Duration) -> Result<RelayHealth> {
+    let url = health_url(relay_url);
+    let body = ureq::get(&url)
+        .timeout(timeout)
+        .call()
+        .with_context(|| format!("failed to reach {}", url))?
+        .into_string()
+        .with_context(|| format!("failed to read response from {}", url))?;
+    serde_json::from_str(&body).with_context(|| format!("unexpected response from {}", url))
+}
+
+/// Seconds the local clock is ahead of the relay's (negative if behind),
+/// or `None` if the relay doesn't report its time.
+pub async fn clock_skew(relay_url: &str, timeout: Duration) -> Result<Option<i64>> {
+    let url = relay_url.to_string();
+    let health = tokio::task::spawn_blocking(move || health(&url, timeout)).await??;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    Ok(health
+        .unix_time
+        .map(|relay_time| now as i64 - relay_time as i64))
+}
+
+/// The relay's per-message limit from `/health`, or `None` if it can't be
+/// reached or doesn't report one.
+pub async fn frame_limit(relay_url: &str, timeout: Duration) -> Option<usize> {
+    let url = relay_url.to_string();
+    tokio::task::spawn_blocking(move || health(&url, timeout))
+        .await
+        .ok()?
+        .ok()?
+        .max_payload_bytes
+}
+
+/// The HTTP(S) `/health` URL for a relay given in any accepted form.
+fn health_url(relay_url: &str) -> String {
+    let url = relay_url.trim_end_matches('/');
+    let base = if let Some(rest) = url.strip_prefix("wss://") {
+        format!("https://{rest}")
+    } else if let Some(rest) = url.strip_prefix("ws://") {
+        format!("http://{rest}")
+    } else if url.starts_with("https://") || url.starts_with("http://") {
+        url.to_string()
+    } else {
+        format!("https://{}", url)
+    };
+    format!("{}/health", base)
 }
 
 /// Normalize relay URL to WebSocket format.
@@ -193,13 +643,119 @@ mod tests {
         );
     }
 
+    #[test]
+    fn health_urls() {
+        assert_eq!(
+            health_url("wss://relay.example.com/"),
+            "https://relay.example.com/health"
+        );
+        assert_eq!(
+            health_url("ws://127.0.0.1:4443"),
+            "http://127.0.0.1:4443/health"
+        );
+        assert_eq!(
+            health_url("http://localhost:4443"),
+            "http://localhost:4443/health"
+        );
+        assert_eq!(
+            health_url("relay.example.com"),
+            "https://relay.example.com/health"
+        );
+    }
+
     #[test]
     fn code_generation() {
-        let code = generate_code();
+        let code = generate_code(&CodeWords::builtin(), 2).unwrap();
         assert!(code.contains('-'));
         let parts: Vec<&str> = code.split('-').collect();
         assert_eq!(parts.len(), 3);
         // First part is a number
         assert!(parts[0].parse::<u32>().is_ok());
     }
+
+    #[test]
+    fn code_word_count_bounds() {
+        for count in MIN_CODE_WORDS..=MAX_CODE_WORDS {
+            let code = generate_code(&CodeWords::builtin(), count).unwrap();
+            assert_eq!(code.split('-').count(), count + 1);
+            validate_code(&code).unwrap();
+        }
+        assert!(generate_code(&CodeWords::builtin(), MIN_CODE_WORDS - 1).is_err());
+        assert!(generate_code(&CodeWords::builtin(), MAX_CODE_WORDS + 1).is_err());
+    }
+
+    #[test]
+    fn custom_word_list_is_used() {
+        let words: Vec<String> = (0..20).map(|i| format!("word{}", i)).collect();
+        let list = CodeWords::new(words.clone()).unwrap();
+        for _ in 0..20 {
+            let code = generate_code(&list, 3).unwrap();
+            validate_code(&code).unwrap();
+            for word in code.split('-').skip(1) {
+                assert!(words.iter().any(|w| w == word), "{word} not in list");
+            }
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("words.txt");
+        std::fs::write(&path, format!("# team words\n\n{}\n", words.join("\n"))).unwrap();
+        let code = generate_code(&CodeWords::from_file(&path).unwrap(), 2).unwrap();
+        assert!(code.split('-').skip(1).all(|w| w.starts_with("word")));
+    }
+
+    #[test]
+    fn small_or_empty_word_lists_rejected() {
+        let err = CodeWords::new(Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("empty"), "{err}");
+
+        let err = CodeWords::new(["one", "two", "three"].map(String::from)).unwrap_err();
+        assert!(err.to_string().contains("only 3 distinct words"), "{err}");
+
+        // Duplicates don't count toward the minimum
+        let err = CodeWords::new(vec!["same".to_string(); 40]).unwrap_err();
+        assert!(err.to_string().contains("only 1 distinct words"), "{err}");
+
+        let mut words: Vec<String> = (0..20).map(|i| format!("word{}", i)).collect();
+        words.push("two-part".to_string());
+        assert!(CodeWords::new(words).is_err());
+    }
+
+    #[test]
+    fn small_word_list_warns() {
+        let words: Vec<String> = (0..20).map(|i| format!("word{}", i)).collect();
+        let warning = CodeWords::new(words).unwrap().warning().unwrap();
+        assert!(warning.contains("only 20 distinct words"), "{warning}");
+        assert!(CodeWords::builtin().warning().is_none());
+        assert!(CodeWords::nato().warning().is_none());
+    }
+
+    #[test]
+    fn builtin_lists_are_valid() {
+        for list in [CodeWords::builtin(), CodeWords::nato()] {
+            CodeWords::new(list.words.clone()).unwrap();
+        }
+        let code = generate_code(&CodeWords::nato(), 2).unwrap();
+        assert!(code.split('-').skip(1).all(|w| NATO_WORDS.contains(&w)));
+    }
+
+    #[test]
+    fn chunk_headers_are_bounded() {
+        assert_eq!(
+            parse_chunk_header("enseal-chunks:10:3").unwrap(),
+            Some((10, 3))
+        );
+        assert_eq!(parse_chunk_header("enseal-burn").unwrap(), None);
+        assert!(parse_chunk_header("enseal-chunks:10:0").is_err());
+        assert!(parse_chunk_header("enseal-chunks:2:3").is_err());
+        assert!(parse_chunk_header("enseal-chunks:x:1").is_err());
+        let huge = format!("enseal-chunks:{}:1", MAX_RELAY_PAYLOAD + 1);
+        assert!(parse_chunk_header(&huge).is_err());
+    }
+
+    #[test]
+    fn code_charset_enforced() {
+        assert!(validate_code("4821-coral-tango").is_ok());
+        assert!(validate_code("4821/../tango").is_err());
+        assert!(validate_code("4821 coral").is_err());
+    }
 }
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::crypto::envelope::Envelope;
+use crate::error::EnsealError;
+
+/// Retention for a nonce whose envelope never goes stale (`--ignore-age`
+/// without a sender expiry). TOML integers are signed, so this is `i64::MAX`.
+const FOREVER: u64 = i64::MAX as u64;
+
+/// Reject `envelope` if its nonce is in the seen-nonce cache at `path`,
+/// otherwise remember it. Envelopes without a nonce (older senders) pass.
+///
+/// `max_age` is the freshness limit the envelope was checked against
+/// (`None` with `--ignore-age`). A nonce is kept until the envelope would
+/// fail that check anyway: its `expires_at` if the sender set one, else
+/// `created_at + max_age`, else forever. Expired entries are dropped on
+/// every call. The cache is locked for the whole check and replaced
+/// atomically, so concurrent receives can't both accept the same copy.
+pub fn check(path: &Path, envelope: &Envelope, max_age: Option<u64>, now: u64) -> Result<()> {
+    let Some(ref nonce) = envelope.metadata.nonce else {
+        return Ok(());
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory: {}", parent.display()))?;
+    }
+    let _lock = lock(path)?;
+
+    let mut seen = load(path)?;
+    seen.retain(|_, until| *until > now);
+    if seen.contains_key(nonce) {
+        return Err(EnsealError::Replay(
+            "this envelope was already received; rejecting the replayed copy".to_string(),
+        )
+        .into());
+    }
+
+    seen.insert(nonce.clone(), retain_until(envelope, max_age));
+    save(path, &seen)
+}
+
+/// When the envelope stops passing the freshness check on its own.
+fn retain_until(envelope: &Envelope, max_age: Option<u64>) -> u64 {
+    match (envelope.metadata.expires_at, max_age) {
+        (Some(expires_at), _) => expires_at,
+        (None, Some(max_age)) => envelope.metadata.created_at.saturating_add(max_age),
+        (None, None) => FOREVER,
+    }
+    .min(FOREVER)
+}
+
+/// Take an exclusive lock on a sidecar of `path`, held until the file is dropped.
+fn lock(path: &Path) -> Result<std::fs::File> {
+    let lock_path = sibling(path, "lock");
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("failed to open {}", lock_path.display()))?;
+    file.lock()
+        .with_context(|| format!("failed to lock {}", lock_path.display()))?;
+    Ok(file)
+}
+
+/// Load the cache. A file that can't be parsed is moved aside to
+/// `<name>.corrupt` and the cache starts over, rather than blocking every
+/// receive until someone deletes it.
+fn load(path: &Path) -> Result<BTreeMap<String, u64>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    match toml::from_str(&content) {
+        Ok(seen) => Ok(seen),
+        Err(e) => {
+            let backup = sibling(path, "corrupt");
+            tracing::warn!(
+                "{} is corrupt ({}); moved it to {} and starting a new one",
+                path.display(),
+                e,
+                backup.display()
+            );
+            std::fs::rename(path, &backup)
+                .with_context(|| format!("failed to move aside {}", path.display()))?;
+            Ok(BTreeMap::new())
+        }
+    }
+}
+
+fn save(path: &Path, seen: &BTreeMap<String, u64>) -> Result<()> {
+    let content = toml::to_string(seen).context("failed to serialize seen nonces")?;
+    crate::fsutil::write_secret_file(path, content.as_bytes())
+}
+
+/// `path` with `.<ext>` appended to its file name.
+fn sibling(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(ext);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::input::PayloadFormat;
+
+    const MAX_AGE: Option<u64> = Some(Envelope::NETWORK_MAX_AGE);
+
+    fn envelope() -> Envelope {
+        Envelope::seal("API_KEY=abc\n", PayloadFormat::Env, None).unwrap()
+    }
+
+    #[test]
+    fn replayed_envelope_is_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("seen_nonces.toml");
+        let first = envelope();
+        let now = first.metadata.created_at;
+
+        check(&path, &first, MAX_AGE, now).unwrap();
+        let replay = Envelope::from_bytes(&first.to_bytes().unwrap()).unwrap();
+        let err = check(&path, &replay, MAX_AGE, now + 10).unwrap_err();
+        assert!(err.to_string().contains("already received"));
+        assert!(matches!(
+            err.downcast_ref::<EnsealError>(),
+            Some(EnsealError::Replay(_))
+        ));
+
+        check(&path, &envelope(), MAX_AGE, now + 10).unwrap();
+    }
+
+    #[test]
+    fn nonces_are_kept_for_the_freshness_window() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("seen_nonces.toml");
+        let first = envelope();
+        let created = first.metadata.created_at;
+
+        // A longer --max-age keeps the nonce past the default window
+        check(&path, &first, Some(3600), created).unwrap();
+        assert!(check(&path, &first, Some(3600), created + 3000).is_err());
+
+        // Once the envelope would fail the age check anyway, it is forgotten
+        check(&path, &envelope(), MAX_AGE, created + 3601).unwrap();
+        assert_eq!(load(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn sender_expiry_sets_retention() {
+        let mut first = envelope();
+        first.metadata.expires_at = Some(first.metadata.created_at + 7 * 86400);
+        assert_eq!(
+            retain_until(&first, MAX_AGE),
+            first.metadata.created_at + 7 * 86400
+        );
+    }
+
+    #[test]
+    fn nonces_are_kept_forever_without_freshness() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("seen_nonces.toml");
+        let first = envelope();
+        let now = first.metadata.created_at;
+
+        check(&path, &first, None, now).unwrap();
+        assert!(check(&path, &first, None, now + 10 * 365 * 86400).is_err());
+    }
+
+    #[test]
+    fn corrupt_cache_is_moved_aside() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("seen_nonces.toml");
+        std::fs::write(&path, "not = [valid toml").unwrap();
+
+        let first = envelope();
+        check(&path, &first, MAX_AGE, first.metadata.created_at).unwrap();
+        assert_eq!(load(&path).unwrap().len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("seen_nonces.toml.corrupt")).unwrap(),
+            "not = [valid toml"
+        );
+    }
+
+    #[test]
+    fn envelopes_without_nonce_pass() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("seen_nonces.toml");
+        let mut legacy = envelope();
+        legacy.metadata.nonce = None;
+
+        check(&path, &legacy, MAX_AGE, 1).unwrap();
+        check(&path, &legacy, MAX_AGE, 2).unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,114 @@
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use rand::Rng;
+
+/// Default number of connection retries (`--retries`).
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// Delay before the first retry; doubles on each subsequent attempt.
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound on a single backoff delay.
+const MAX_DELAY: Duration = Duration::from_secs(8);
+
+/// Un-jittered backoff before retry number `attempt` (0-based).
+pub fn backoff(attempt: u32) -> Duration {
+    BASE_DELAY
+        .checked_mul(2u32.saturating_pow(attempt))
+        .map_or(MAX_DELAY, |d| d.min(MAX_DELAY))
+}
+
+/// Backoff with jitter: a random delay between half and all of [`backoff`],
+/// so clients that failed together don't reconnect in lockstep.
+fn jittered(attempt: u32) -> Duration {
+    let full = backoff(attempt);
+    let factor = rand::thread_rng().gen_range(0.5..=1.0);
+    full.mul_f64(factor)
+}
+
+/// Run a connection-establishment step, retrying up to `retries` times
+/// with jittered exponential backoff.
+///
+/// Only wrap the connect itself: once data has started flowing, a retry
+/// could deliver the payload twice.
+pub async fn connect<T, F, Fut>(what: &str, retries: u32, mut attempt: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut tries = 0;
+    loop {
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(e) if tries < retries => {
+                let delay = jittered(tries);
+                tries += 1;
+                tracing::debug!(
+                    "{} failed ({:#}), retry {}/{} in {}ms",
+                    what,
+                    e,
+                    tries,
+                    retries,
+                    delay.as_millis()
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_until_capped() {
+        assert_eq!(backoff(0), Duration::from_millis(500));
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(4), MAX_DELAY);
+        assert_eq!(backoff(40), MAX_DELAY);
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        for attempt in 0..6 {
+            let delay = jittered(attempt);
+            assert!(delay <= backoff(attempt));
+            assert!(delay >= backoff(attempt) / 2);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_retries() {
+        let mut calls = 0;
+        let result: Result<()> = connect("test", 2, || {
+            calls += 1;
+            async { anyhow::bail!("refused") }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn succeeds_after_transient_failure() {
+        let mut calls = 0;
+        let result = connect("test", 3, || {
+            calls += 1;
+            let n = calls;
+            async move {
+                if n < 2 {
+                    anyhow::bail!("refused")
+                }
+                Ok(n)
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 2);
+    }
+}
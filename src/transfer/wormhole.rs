@@ -1,32 +1,66 @@
+use std::time::Duration;
+
 use anyhow::{bail, Context, Result};
 use magic_wormhole::{MailboxConnection, Wormhole};
 
 use crate::crypto::envelope::Envelope;
+use crate::error::EnsealError;
 
 /// Maximum payload size accepted via wormhole (16 MiB).
 const MAX_WORMHOLE_PAYLOAD: usize = 16 * 1024 * 1024;
 
 /// Create a wormhole mailbox and return the share code and mailbox.
 /// The code is available immediately, before the receiver connects.
+/// The rendezvous connection is retried up to `retries` times.
 pub async fn create_mailbox(
     relay_url: Option<&str>,
     code_words: usize,
+    retries: u32,
 ) -> Result<(String, MailboxConnection<serde_json::Value>)> {
-    let config = super::app_config(relay_url);
-
-    tracing::debug!("connecting to rendezvous server...");
-    let mailbox = MailboxConnection::create(config, code_words)
-        .await
-        .context("failed to connect to rendezvous server")?;
+    let app_config = super::app_config(relay_url)?;
+    let mailbox = super::retry::connect("rendezvous connect", retries, || async {
+        tracing::debug!("connecting to rendezvous server...");
+        MailboxConnection::create(app_config.clone(), code_words)
+            .await
+            .context("failed to connect to rendezvous server")
+    })
+    .await?;
 
     let code = mailbox.code().to_string();
     Ok((code, mailbox))
 }
 
-/// Send an envelope through an already-created mailbox.
+/// Reattach to the mailbox behind an already-shared code (`share --resume`).
+///
+/// The wormhole protocol doesn't allow this. The rendezvous server binds a
+/// code's nameplate to the sides that claimed it and keeps the original
+/// sender's claim and PAKE message after its connection drops, and a new
+/// process can't reuse that side's identity. A second sender would either
+/// pair with the dead sender's key exchange or crowd the receiver out, so
+/// the best we can do is say clearly that the code is spent.
+pub fn resume(code: &str) -> Result<()> {
+    let _: magic_wormhole::Code = code.parse().context("invalid wormhole code format")?;
+    bail!(
+        "cannot resume '{}': a wormhole code is tied to the session of the share that created it, \
+         so once that share has exited the code is consumed or will expire unused. \
+         Run `enseal share` again and send the receiver the new code",
+        code
+    )
+}
+
+/// Send an envelope through an already-created mailbox, failing if the
+/// receiver has not taken it within `timeout`.
 pub async fn send(
     envelope: &Envelope,
     mailbox: MailboxConnection<serde_json::Value>,
+    timeout: Duration,
+) -> Result<()> {
+    super::with_timeout("wormhole send", timeout, send_inner(envelope, mailbox)).await
+}
+
+async fn send_inner(
+    envelope: &Envelope,
+    mailbox: MailboxConnection<serde_json::Value>,
 ) -> Result<()> {
     let mut wormhole = Wormhole::connect(mailbox)
         .await
@@ -50,15 +84,34 @@ pub async fn send(
 
 /// Receive raw bytes via magic-wormhole using the given code.
 /// Returns the raw data without attempting to parse it.
-pub async fn receive_raw(code: &str, relay_url: Option<&str>) -> Result<Vec<u8>> {
-    let config = super::app_config(relay_url);
+/// The rendezvous connection is retried up to `retries` times; the whole
+/// transfer fails after `timeout`.
+pub async fn receive_raw(
+    code: &str,
+    relay_url: Option<&str>,
+    retries: u32,
+    timeout: Duration,
+) -> Result<Vec<u8>> {
+    super::with_timeout(
+        "wormhole receive",
+        timeout,
+        receive_raw_inner(code, relay_url, retries),
+    )
+    .await
+}
 
-    let code = code.parse().context("invalid wormhole code format")?;
+async fn receive_raw_inner(code: &str, relay_url: Option<&str>, retries: u32) -> Result<Vec<u8>> {
+    let code: magic_wormhole::Code = code.parse().context("invalid wormhole code format")?;
 
-    tracing::debug!("connecting to rendezvous server...");
-    let mailbox = MailboxConnection::connect(config, code, true)
-        .await
-        .context("failed to connect to rendezvous server")?;
+    let app_config = super::app_config(relay_url)?;
+
+    let mailbox = super::retry::connect("rendezvous connect", retries, || async {
+        tracing::debug!("connecting to rendezvous server...");
+        MailboxConnection::connect(app_config.clone(), code.clone(), true)
+            .await
+            .context("failed to connect to rendezvous server")
+    })
+    .await?;
 
     let mut wormhole = Wormhole::connect(mailbox)
         .await
@@ -93,9 +146,16 @@ pub async fn receive_raw(code: &str, relay_url: Option<&str>) -> Result<Vec<u8>>
 
 /// Receive an envelope via magic-wormhole using the given code.
 #[allow(dead_code)]
-pub async fn receive(code: &str, relay_url: Option<&str>) -> Result<Envelope> {
-    let data = receive_raw(code, relay_url).await?;
+pub async fn receive(code: &str, relay_url: Option<&str>) -> Result<Envelope, EnsealError> {
+    let data = receive_raw(
+        code,
+        relay_url,
+        super::retry::DEFAULT_RETRIES,
+        super::DEFAULT_TIMEOUT,
+    )
+    .await
+    .map_err(|e| EnsealError::Network(format!("{:#}", e)))?;
     let envelope = Envelope::from_bytes(&data)?;
-    envelope.check_age(300)?;
+    envelope.check_age(Envelope::NETWORK_MAX_AGE)?;
     Ok(envelope)
 }
@@ -19,3 +19,69 @@ pub fn warning(message: &str) {
 pub fn info(label: &str, value: &str) {
     eprintln!("  {:<14}{}", style(label).bold(), value);
 }
+
+/// Format a unix timestamp as a local date/time with its age, e.g.
+/// `2025-06-01 14:03:22 +02:00 (3m ago)`. Falls back to UTC when the local
+/// offset cannot be determined.
+pub fn timestamp_with_age(unix_secs: u64, now_secs: u64) -> String {
+    let relative = relative_time(now_secs.saturating_sub(unix_secs));
+    let Ok(utc) = time::OffsetDateTime::from_unix_timestamp(unix_secs as i64) else {
+        return relative;
+    };
+    let local = time::UtcOffset::current_local_offset()
+        .map(|offset| utc.to_offset(offset))
+        .unwrap_or(utc);
+    let format = time::macros::format_description!(
+        "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory]:[offset_minute]"
+    );
+    match local.format(&format) {
+        Ok(formatted) => format!("{} ({})", formatted, relative),
+        Err(_) => relative,
+    }
+}
+
+/// Format an elapsed duration in seconds as a short relative time ("3m ago").
+pub fn relative_time(elapsed_secs: u64) -> String {
+    match elapsed_secs {
+        0..=59 => format!("{}s ago", elapsed_secs),
+        60..=3599 => format!("{}m ago", elapsed_secs / 60),
+        3600..=86399 => format!("{}h ago", elapsed_secs / 3600),
+        _ => format!("{}d ago", elapsed_secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_seconds() {
+        assert_eq!(relative_time(0), "0s ago");
+        assert_eq!(relative_time(59), "59s ago");
+    }
+
+    #[test]
+    fn relative_minutes() {
+        assert_eq!(relative_time(60), "1m ago");
+        assert_eq!(relative_time(3599), "59m ago");
+    }
+
+    #[test]
+    fn relative_hours() {
+        assert_eq!(relative_time(3600), "1h ago");
+        assert_eq!(relative_time(86399), "23h ago");
+    }
+
+    #[test]
+    fn relative_days() {
+        assert_eq!(relative_time(86400), "1d ago");
+        assert_eq!(relative_time(3 * 86400 + 5), "3d ago");
+    }
+
+    #[test]
+    fn timestamp_includes_date_and_age() {
+        let formatted = timestamp_with_age(1_700_000_000, 1_700_000_180);
+        assert!(formatted.contains("2023-11-1"), "{}", formatted);
+        assert!(formatted.ends_with("(3m ago)"), "{}", formatted);
+    }
+}
@@ -0,0 +1,50 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use assert_cmd::Command;
+use std::path::Path;
+
+/// Run enseal with its config directory inside `home`.
+pub fn enseal_at(home: &Path) -> Command {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("enseal");
+    cmd.env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join(".config"));
+    cmd
+}
+
+/// Create an identity in `home` and trust its own public key as "me".
+pub fn trusted_self(home: &Path) {
+    enseal_at(home).args(["keys", "init"]).assert().success();
+    let exported = enseal_at(home).args(["keys", "export"]).output().unwrap();
+    let pubkey = home.join("me.pub");
+    std::fs::write(&pubkey, exported.stdout).unwrap();
+    enseal_at(home)
+        .args(["keys", "import", pubkey.to_str().unwrap(), "--yes"])
+        .assert()
+        .success();
+}
+
+/// Create one identity per name in `home` and trust each of them.
+/// Returns the exported public key bundles, in order.
+pub fn init_and_import(home: &Path, names: &[&str]) -> Vec<String> {
+    names
+        .iter()
+        .map(|name| {
+            enseal_at(home)
+                .args(["--identity", name, "keys", "init"])
+                .assert()
+                .success();
+            let exported = enseal_at(home)
+                .args(["--identity", name, "keys", "export"])
+                .output()
+                .unwrap();
+            let pubkey = home.join(format!("{}.pub", name));
+            std::fs::write(&pubkey, &exported.stdout).unwrap();
+            enseal_at(home)
+                .args(["keys", "import", pubkey.to_str().unwrap(), "--yes"])
+                .assert()
+                .success();
+            String::from_utf8(exported.stdout).unwrap()
+        })
+        .collect()
+}
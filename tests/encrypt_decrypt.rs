@@ -1,10 +1,15 @@
+mod common;
+
 use assert_cmd::Command;
 use predicates::prelude::*;
 use std::fs;
 use tempfile::TempDir;
 
+#[cfg(unix)]
+use common::enseal_at;
+
 fn enseal() -> Command {
-    Command::cargo_bin("enseal").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("enseal")
 }
 
 // ---------------------------------------------------------------------------
@@ -162,3 +167,345 @@ fn no_plaintext_in_encrypted_output() {
     assert!(encrypted_str.contains("VERY_SECRET_TOKEN=")); // key IS visible
     assert!(!encrypted_str.contains("sk_live_should_not_appear")); // value NOT visible
 }
+
+// ---------------------------------------------------------------------------
+// Raw age recipients (`--to age1...`)
+// ---------------------------------------------------------------------------
+
+#[test]
+#[cfg(unix)]
+fn raw_age_recipient_encrypts_for_its_owner() {
+    let owner = TempDir::new().unwrap();
+    let sender = TempDir::new().unwrap();
+
+    enseal_at(owner.path())
+        .args(["keys", "init"])
+        .assert()
+        .success();
+    let bundle = enseal_at(owner.path())
+        .args(["keys", "export"])
+        .output()
+        .unwrap();
+    let recipient = String::from_utf8(bundle.stdout)
+        .unwrap()
+        .lines()
+        .find_map(|l| l.strip_prefix("age: ").map(str::to_string))
+        .unwrap();
+
+    // The sender has no keys at all: the raw recipient is the only one
+    let plain = sender.path().join(".env");
+    fs::write(&plain, "SECRET=hunter2\n").unwrap();
+    let encrypted = sender.path().join(".env.encrypted");
+    enseal_at(sender.path())
+        .args(["encrypt", plain.to_str().unwrap(), "--to", &recipient])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("raw age recipient"));
+
+    let decrypted = owner.path().join(".env.out");
+    enseal_at(owner.path())
+        .args([
+            "decrypt",
+            encrypted.to_str().unwrap(),
+            "--output",
+            decrypted.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(decrypted).unwrap(), "SECRET=hunter2\n");
+}
+
+// ---------------------------------------------------------------------------
+// diff --values
+// ---------------------------------------------------------------------------
+
+#[test]
+#[cfg(unix)]
+fn diff_values_reports_changed_per_var_values() {
+    let home = TempDir::new().unwrap();
+    enseal_at(home.path())
+        .args(["keys", "init"])
+        .assert()
+        .success();
+
+    let mut paths = Vec::new();
+    for (name, content) in [
+        ("a.env", "SECRET=hunter2\nAPI_KEY=abc123\n"),
+        ("b.env", "SECRET=hunter3\nAPI_KEY=abc123\n"),
+    ] {
+        let path = home.path().join(name);
+        fs::write(&path, content).unwrap();
+        enseal_at(home.path())
+            .args(["encrypt", path.to_str().unwrap(), "--per-var", "--force"])
+            .assert()
+            .success();
+        paths.push(path.to_str().unwrap().to_string());
+    }
+
+    // Keys alone are identical
+    enseal_at(home.path())
+        .args(["diff", &paths[0], &paths[1]])
+        .assert()
+        .success();
+
+    enseal_at(home.path())
+        .args(["diff", "--values", &paths[0], &paths[1]])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("SECRET"))
+        .stdout(predicate::str::contains("changed"))
+        .stdout(predicate::str::contains("API_KEY").not())
+        .stdout(predicate::str::contains("hunter").not());
+}
+
+#[test]
+#[cfg(unix)]
+fn diff_values_refuses_plaintext_without_acknowledgement() {
+    let home = TempDir::new().unwrap();
+    let a = home.path().join("a.env");
+    let b = home.path().join("b.env");
+    fs::write(&a, "SECRET=hunter2\n").unwrap();
+    fs::write(&b, "SECRET=hunter3\n").unwrap();
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+    enseal_at(home.path())
+        .args(["diff", "--values", a, b])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--i-understand"));
+    enseal_at(home.path())
+        .args(["diff", "--values", "--i-understand", a, b])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("SECRET"));
+}
+
+#[test]
+#[cfg(unix)]
+fn decrypt_try_archived_after_rotation() {
+    let home = TempDir::new().unwrap();
+    enseal_at(home.path())
+        .args(["keys", "init"])
+        .assert()
+        .success();
+
+    let plain = home.path().join(".env");
+    fs::write(&plain, "SECRET=hunter2\n").unwrap();
+    enseal_at(home.path())
+        .args(["encrypt", plain.to_str().unwrap()])
+        .assert()
+        .success();
+    let encrypted = home.path().join(".env.encrypted");
+
+    enseal_at(home.path())
+        .args(["keys", "rotate-self"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("new fingerprint"))
+        .stdout(predicate::str::contains("enseal keys export"));
+
+    let out = home.path().join("out.env");
+    let decrypt = |extra: &[&str]| {
+        let mut cmd = enseal_at(home.path());
+        cmd.args([
+            "decrypt",
+            encrypted.to_str().unwrap(),
+            "--output",
+            out.to_str().unwrap(),
+            "--force",
+        ])
+        .args(extra);
+        cmd
+    };
+    decrypt(&[]).assert().failure();
+    decrypt(&["--try-archived"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("archived key"));
+    assert_eq!(fs::read_to_string(&out).unwrap(), "SECRET=hunter2\n");
+}
+
+// ---------------------------------------------------------------------------
+// sign / verify
+// ---------------------------------------------------------------------------
+
+#[test]
+#[cfg(unix)]
+fn sign_verify_round_trip_names_trusted_signer() {
+    let signer = TempDir::new().unwrap();
+    let verifier = TempDir::new().unwrap();
+    enseal_at(signer.path())
+        .args(["keys", "init"])
+        .assert()
+        .success();
+
+    let config = signer.path().join("app.toml");
+    fs::write(&config, "replicas = 3\n").unwrap();
+    enseal_at(signer.path())
+        .args(["sign", config.to_str().unwrap()])
+        .assert()
+        .success();
+    let signed = signer.path().join("app.toml.signed");
+    // Content stays readable
+    assert!(fs::read_to_string(&signed)
+        .unwrap()
+        .ends_with("\n\nreplicas = 3\n"));
+
+    // Unknown signer: refused unless explicitly allowed
+    enseal_at(verifier.path())
+        .args(["verify", signed.to_str().unwrap()])
+        .assert()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("unknown key"))
+        .stderr(predicate::str::contains("--allow-unknown-signer"));
+    enseal_at(verifier.path())
+        .args(["verify", signed.to_str().unwrap(), "--allow-unknown-signer"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("signature is valid"))
+        .stderr(predicate::str::contains("unknown key"));
+
+    let exported = enseal_at(signer.path())
+        .args(["keys", "export"])
+        .output()
+        .unwrap();
+    let pubkey = verifier.path().join("ops.pub");
+    fs::write(&pubkey, exported.stdout).unwrap();
+    enseal_at(verifier.path())
+        .args(["keys", "import", pubkey.to_str().unwrap(), "--yes"])
+        .assert()
+        .success();
+
+    let out = verifier.path().join("app.toml");
+    enseal_at(verifier.path())
+        .args([
+            "verify",
+            signed.to_str().unwrap(),
+            "--output",
+            out.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Signed by"))
+        .stderr(predicate::str::contains("ops"))
+        .stderr(predicate::str::contains("unknown key").not());
+    assert_eq!(fs::read_to_string(out).unwrap(), "replicas = 3\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn verify_rejects_tampered_file() {
+    let home = TempDir::new().unwrap();
+    enseal_at(home.path())
+        .args(["keys", "init"])
+        .assert()
+        .success();
+    let config = home.path().join("app.toml");
+    fs::write(&config, "replicas = 3\n").unwrap();
+    enseal_at(home.path())
+        .args(["sign", config.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let signed = home.path().join("app.toml.signed");
+    let tampered = fs::read_to_string(&signed)
+        .unwrap()
+        .replace("replicas = 3", "replicas = 30");
+    fs::write(&signed, tampered).unwrap();
+    enseal_at(home.path())
+        .args(["verify", signed.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("signature verification failed"));
+}
+
+// ---------------------------------------------------------------------------
+// --armor
+// ---------------------------------------------------------------------------
+
+#[test]
+#[cfg(unix)]
+fn armored_encrypt_round_trips() {
+    let home = TempDir::new().unwrap();
+    enseal_at(home.path())
+        .args(["keys", "init"])
+        .assert()
+        .success();
+
+    let plain = home.path().join(".env");
+    fs::write(&plain, "SECRET=hunter2\n").unwrap();
+    enseal_at(home.path())
+        .args(["encrypt", plain.to_str().unwrap(), "--armor"])
+        .assert()
+        .success();
+
+    let encrypted = home.path().join(".env.encrypted");
+    let armored = fs::read_to_string(&encrypted).unwrap();
+    assert!(armored.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+
+    let decrypted = home.path().join(".env.out");
+    enseal_at(home.path())
+        .args([
+            "decrypt",
+            encrypted.to_str().unwrap(),
+            "--output",
+            decrypted.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(decrypted).unwrap(), "SECRET=hunter2\n");
+}
+
+// ---------------------------------------------------------------------------
+// --format compat (sealed box)
+// ---------------------------------------------------------------------------
+
+#[test]
+#[cfg(unix)]
+fn compat_format_round_trips_and_rejects_other_keys() {
+    let home = TempDir::new().unwrap();
+    let stranger = TempDir::new().unwrap();
+    for dir in [&home, &stranger] {
+        enseal_at(dir.path())
+            .args(["keys", "init"])
+            .assert()
+            .success();
+    }
+
+    let plain = home.path().join(".env");
+    fs::write(&plain, "SECRET=hunter2\n").unwrap();
+    enseal_at(home.path())
+        .args(["encrypt", plain.to_str().unwrap(), "--format", "compat"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("sealed box"));
+
+    let encrypted = home.path().join(".env.encrypted");
+    let sealed: serde_json::Value = serde_json::from_slice(&fs::read(&encrypted).unwrap()).unwrap();
+    assert_eq!(sealed["format"], "enseal-sealed-box/v1");
+    assert!(sealed["epk"].is_string() && sealed["nonce"].is_string());
+
+    let decrypted = home.path().join(".env.out");
+    enseal_at(home.path())
+        .args([
+            "decrypt",
+            encrypted.to_str().unwrap(),
+            "--output",
+            decrypted.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(decrypted).unwrap(), "SECRET=hunter2\n");
+
+    enseal_at(stranger.path())
+        .args([
+            "decrypt",
+            encrypted.to_str().unwrap(),
+            "--output",
+            stranger.path().join("out").to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no sealed box recipient matches"));
+}
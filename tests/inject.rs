@@ -1,8 +1,13 @@
+mod common;
+
 use assert_cmd::Command;
 use predicates::prelude::*;
 
+#[cfg(unix)]
+use common::enseal_at;
+
 fn enseal() -> Command {
-    Command::cargo_bin("enseal").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("enseal")
 }
 
 #[test]
@@ -25,3 +30,185 @@ fn inject_requires_command() {
         .failure()
         .stderr(predicate::str::contains("required"));
 }
+
+/// Create an identity in `home` that trusts itself and drop `content` to it.
+/// Returns the path of the encrypted file.
+#[cfg(unix)]
+fn file_drop_to_self(home: &std::path::Path, content: &str) -> std::path::PathBuf {
+    common::trusted_self(home);
+
+    let drop_dir = home.join("drop");
+    enseal_at(home)
+        .args([
+            "share",
+            "--to",
+            "me",
+            "--output",
+            drop_dir.to_str().unwrap(),
+        ])
+        .write_stdin(content)
+        .assert()
+        .success();
+    drop_dir.join("me.env.age")
+}
+
+#[test]
+#[cfg(unix)]
+fn inject_print_needs_no_command_and_evals_cleanly() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let home = dir.path();
+    let drop_file = file_drop_to_self(home, "GREETING=\"hello world\"\nQUOTE=\"it's here\"\n");
+    let output = enseal_at(home)
+        .args(["inject", drop_file.to_str().unwrap(), "--print"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let printed = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        printed,
+        "export GREETING='hello world'\nexport QUOTE='it'\\''s here'\n"
+    );
+
+    let evaluated = std::process::Command::new("sh")
+        .args([
+            "-c",
+            "eval \"$1\"; printf '%s|%s' \"$GREETING\" \"$QUOTE\"",
+            "sh",
+        ])
+        .arg(&printed)
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(evaluated.stdout).unwrap(),
+        "hello world|it's here"
+    );
+}
+
+#[test]
+fn inject_print_conflicts_with_command() {
+    enseal()
+        .args(["inject", "some-code", "--print", "--", "env"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+#[cfg(unix)]
+fn inject_exits_with_child_status() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let home = dir.path();
+    let drop_file = file_drop_to_self(home, "TOKEN=abc\n");
+
+    for extra in [None, Some("--no-signal-forward")] {
+        let mut cmd = enseal_at(home);
+        cmd.args(["inject", drop_file.to_str().unwrap(), "--quiet"]);
+        cmd.args(extra);
+        cmd.args(["--", "sh", "-c", "test \"$TOKEN\" = abc && exit 42"])
+            .assert()
+            .code(42);
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn inject_reads_code_from_stdin_and_passes_the_rest_on() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let home = dir.path();
+    let drop_file = file_drop_to_self(home, "TOKEN=abc\n");
+
+    enseal_at(home)
+        .args([
+            "inject",
+            "-",
+            "--quiet",
+            "--",
+            "sh",
+            "-c",
+            "printf '%s:' \"$TOKEN\"; cat",
+        ])
+        .write_stdin(format!("\n{}\nchild input\n", drop_file.display()))
+        .assert()
+        .success()
+        .stdout("abc:child input\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn inject_env_file_decrypts_both_formats_in_memory() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let home = dir.path();
+    // Sets up an identity that trusts itself
+    file_drop_to_self(home, "UNUSED=1\n");
+
+    let plain = home.join(".env.production");
+    std::fs::write(&plain, "DB_URL=postgres://prod\nAPI_KEY=\"k e y\"\n").unwrap();
+
+    for per_var in [false, true] {
+        let encrypted = home.join(if per_var { "per-var.env" } else { "whole.env" });
+        let mut cmd = enseal_at(home);
+        cmd.args(["encrypt", plain.to_str().unwrap(), "--to", "me", "--output"])
+            .arg(&encrypted);
+        if per_var {
+            cmd.arg("--per-var");
+        }
+        cmd.assert().success();
+        let before: Vec<_> = std::fs::read_dir(home).unwrap().collect();
+
+        enseal_at(home)
+            .args([
+                "inject",
+                "--env-file",
+                encrypted.to_str().unwrap(),
+                "--quiet",
+            ])
+            .args(["--", "sh", "-c", "printf '%s|%s' \"$DB_URL\" \"$API_KEY\""])
+            .assert()
+            .success()
+            .stdout("postgres://prod|k e y");
+        assert_eq!(std::fs::read_dir(home).unwrap().count(), before.len());
+    }
+}
+
+#[test]
+fn inject_env_file_conflicts_with_code() {
+    enseal()
+        .args(["inject", "some-code", "--env-file", "x.env", "--", "env"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+#[cfg(unix)]
+fn inject_refuses_ld_preload_unless_allowed() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let home = dir.path();
+    file_drop_to_self(home, "UNUSED=1\n");
+
+    let plain = home.join(".env.hostile");
+    std::fs::write(&plain, "LD_PRELOAD=/nonexistent/evil.so\nTOKEN=abc\n").unwrap();
+    let encrypted = home.join("hostile.env.encrypted");
+    enseal_at(home)
+        .args(["encrypt", plain.to_str().unwrap(), "--to", "me", "--output"])
+        .arg(&encrypted)
+        .assert()
+        .success();
+
+    enseal_at(home)
+        .args(["inject", "--env-file", encrypted.to_str().unwrap()])
+        .args(["--", "sh", "-c", "echo ran"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("refusing to inject LD_PRELOAD"));
+
+    enseal_at(home)
+        .args(["inject", "--env-file", encrypted.to_str().unwrap()])
+        .arg("--allow-unsafe-names")
+        .args(["--", "sh", "-c", "printf '%s' \"$TOKEN\""])
+        .assert()
+        .success()
+        .stdout("abc")
+        .stderr(predicate::str::contains("--allow-unsafe-names"));
+}
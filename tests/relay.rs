@@ -1,7 +1,17 @@
+#[cfg(feature = "server")]
+mod common;
+
 #[cfg(feature = "server")]
 mod relay_tests {
+    use crate::common;
     use tokio::time::{sleep, Duration};
 
+    /// A fresh two-word relay code from the built-in word list.
+    fn new_code() -> String {
+        let words = enseal::transfer::relay::CodeWords::builtin();
+        enseal::transfer::relay::generate_code(&words, 2).unwrap()
+    }
+
     /// Start a relay server on a random port and return the port.
     async fn start_relay(ttl: u64) -> u16 {
         start_relay_with_config(ttl, 1_048_576, 100).await
@@ -13,15 +23,36 @@ mod relay_tests {
         max_payload_bytes: usize,
         rate_limit_per_min: usize,
     ) -> u16 {
-        let config = enseal::server::ServerConfig {
+        serve(enseal::server::ServerConfig {
             port: 0,
             bind: "127.0.0.1".to_string(),
             max_channels: 10,
             channel_ttl_secs: ttl,
             max_payload_bytes,
             rate_limit_per_min,
-        };
+            store_and_forward: false,
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Start a relay server in store-and-forward mode and return the port.
+    async fn start_store_and_forward_relay(ttl: u64) -> u16 {
+        serve(enseal::server::ServerConfig {
+            port: 0,
+            bind: "127.0.0.1".to_string(),
+            max_channels: 10,
+            channel_ttl_secs: ttl,
+            max_payload_bytes: 1_048_576,
+            rate_limit_per_min: 100,
+            store_and_forward: true,
+            ..Default::default()
+        })
+        .await
+    }
 
+    /// Serve the relay router with the given config on a random port.
+    async fn serve(config: enseal::server::ServerConfig) -> u16 {
         let app = enseal::server::build_router(config);
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
         let port = listener.local_addr().unwrap().port();
@@ -44,24 +75,35 @@ mod relay_tests {
     async fn relay_round_trip() {
         let port = start_relay(30).await;
         let relay_url = format!("ws://127.0.0.1:{}", port);
-        let code = enseal::transfer::relay::generate_code();
+        let code = new_code();
 
         let data = b"SECRET=hunter2\nAPI_KEY=abc123\n";
 
         let relay_url_send = relay_url.clone();
         let code_send = code.clone();
         let send_handle = tokio::spawn(async move {
-            enseal::transfer::relay::send(data, &relay_url_send, &code_send)
-                .await
-                .unwrap();
+            enseal::transfer::relay::send(
+                data,
+                &relay_url_send,
+                &code_send,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+            .unwrap();
         });
 
         // Small delay to let sender connect first
         sleep(Duration::from_millis(100)).await;
 
-        let received = enseal::transfer::relay::receive(&relay_url, &code)
-            .await
-            .unwrap();
+        let received = enseal::transfer::relay::receive(
+            &relay_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
 
         assert_eq!(received, data);
         send_handle.await.unwrap();
@@ -71,29 +113,417 @@ mod relay_tests {
     async fn relay_receiver_first() {
         let port = start_relay(30).await;
         let relay_url = format!("ws://127.0.0.1:{}", port);
-        let code = enseal::transfer::relay::generate_code();
+        let code = new_code();
 
         let data = b"FIRST=receiver_connects_first";
 
         let relay_url_recv = relay_url.clone();
         let code_recv = code.clone();
         let recv_handle = tokio::spawn(async move {
-            enseal::transfer::relay::receive(&relay_url_recv, &code_recv)
-                .await
-                .unwrap()
+            enseal::transfer::relay::receive(
+                &relay_url_recv,
+                &code_recv,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+            .unwrap()
         });
 
         // Small delay to let receiver connect first
         sleep(Duration::from_millis(100)).await;
 
-        enseal::transfer::relay::send(data, &relay_url, &code)
-            .await
-            .unwrap();
+        enseal::transfer::relay::send(
+            data,
+            &relay_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
 
         let received = recv_handle.await.unwrap();
         assert_eq!(received, data);
     }
 
+    #[tokio::test]
+    async fn used_code_cannot_be_received_again() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let code = new_code();
+
+        let relay_url_send = relay_url.clone();
+        let code_send = code.clone();
+        let send_handle = tokio::spawn(async move {
+            enseal::transfer::relay::send(
+                b"BURN=after_read",
+                &relay_url_send,
+                &code_send,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+            .unwrap();
+        });
+        sleep(Duration::from_millis(100)).await;
+
+        let received = enseal::transfer::relay::receive(
+            &relay_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(received, b"BURN=after_read");
+        send_handle.await.unwrap();
+
+        // The relay refuses the code straight away instead of waiting for a sender
+        let second = tokio::time::timeout(
+            Duration::from_secs(2),
+            enseal::transfer::relay::receive(
+                &relay_url,
+                &code,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            ),
+        )
+        .await
+        .expect("second receive should fail immediately");
+        let err = second.unwrap_err().to_string();
+        assert!(err.contains("already been used"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn share_relay_code_round_trip_burns_code() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let home = tempfile::TempDir::new().unwrap();
+        let home_path = home.path().to_path_buf();
+        let enseal_at = move || {
+            let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_enseal"));
+            cmd.env("HOME", &home_path)
+                .env("XDG_CONFIG_HOME", home_path.join(".config"));
+            cmd
+        };
+
+        // The sender prints the code (--quiet) and waits for the receiver
+        let (code, share) = tokio::task::spawn_blocking({
+            let enseal_at = enseal_at.clone();
+            let relay_url = relay_url.clone();
+            move || {
+                use std::io::{BufRead, Write};
+                let mut share = enseal_at()
+                    .args(["share", "--relay-code", "--words", "3"])
+                    .args(["--relay", &relay_url, "--quiet"])
+                    .stdin(std::process::Stdio::piped())
+                    .stdout(std::process::Stdio::piped())
+                    .spawn()
+                    .unwrap();
+                share
+                    .stdin
+                    .take()
+                    .unwrap()
+                    .write_all(b"TOKEN=relay_code\n")
+                    .unwrap();
+                let mut code = String::new();
+                std::io::BufReader::new(share.stdout.take().unwrap())
+                    .read_line(&mut code)
+                    .unwrap();
+                (code.trim().to_string(), share)
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(code.split('-').count(), 4, "{}", code);
+
+        let receive = {
+            let enseal_at = enseal_at.clone();
+            let relay_url = relay_url.clone();
+            let code = code.clone();
+            move || {
+                enseal_at()
+                    .args(["receive", &code, "--relay-code", "--relay", &relay_url])
+                    .args(["--output", "-"])
+                    .output()
+                    .unwrap()
+            }
+        };
+        let first = tokio::task::spawn_blocking(receive.clone()).await.unwrap();
+        assert!(
+            first.status.success(),
+            "{}",
+            String::from_utf8_lossy(&first.stderr)
+        );
+        assert!(String::from_utf8_lossy(&first.stdout).contains("TOKEN=relay_code"));
+        let sent = tokio::task::spawn_blocking(move || {
+            let mut share = share;
+            share.wait().unwrap()
+        })
+        .await
+        .unwrap();
+        assert!(sent.success());
+
+        // The sender burned the code after the ack
+        let second = tokio::task::spawn_blocking(receive).await.unwrap();
+        assert!(!second.status.success());
+        assert!(
+            String::from_utf8_lossy(&second.stderr).contains("already been used"),
+            "{}",
+            String::from_utf8_lossy(&second.stderr)
+        );
+    }
+
+    #[tokio::test]
+    async fn share_relay_code_uses_configured_relay_and_words() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let home = tempfile::TempDir::new().unwrap();
+        let config_dir = home.path().join(".config").join("enseal");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(
+            config_dir.join("config.toml"),
+            format!("relay_url = \"{}\"\n\n[codes]\nnato = true\n", relay_url),
+        )
+        .unwrap();
+        let home_path = home.path().to_path_buf();
+        let enseal_at = move || {
+            let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_enseal"));
+            cmd.env("HOME", &home_path)
+                .env("XDG_CONFIG_HOME", home_path.join(".config"))
+                .env_remove("ENSEAL_RELAY");
+            cmd
+        };
+
+        let (code, share) = tokio::task::spawn_blocking({
+            let enseal_at = enseal_at.clone();
+            move || {
+                use std::io::{BufRead, Write};
+                let mut share = enseal_at()
+                    .args(["share", "--relay-code", "--quiet"])
+                    .stdin(std::process::Stdio::piped())
+                    .stdout(std::process::Stdio::piped())
+                    .spawn()
+                    .unwrap();
+                share
+                    .stdin
+                    .take()
+                    .unwrap()
+                    .write_all(b"TOKEN=nato\n")
+                    .unwrap();
+                let mut code = String::new();
+                std::io::BufReader::new(share.stdout.take().unwrap())
+                    .read_line(&mut code)
+                    .unwrap();
+                (code.trim().to_string(), share)
+            }
+        })
+        .await
+        .unwrap();
+        const NATO: [&str; 26] = [
+            "alfa", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india",
+            "juliett", "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo",
+            "sierra", "tango", "uniform", "victor", "whiskey", "xray", "yankee", "zulu",
+        ];
+        assert!(
+            code.split('-').skip(1).all(|word| NATO.contains(&word)),
+            "{}",
+            code
+        );
+
+        let received = tokio::task::spawn_blocking(move || {
+            let output = enseal_at()
+                .args(["receive", &code, "--relay-code", "--output", "-"])
+                .output()
+                .unwrap();
+            let mut share = share;
+            assert!(share.wait().unwrap().success());
+            output
+        })
+        .await
+        .unwrap();
+        assert!(received.status.success());
+        assert!(String::from_utf8_lossy(&received.stdout).contains("TOKEN=nato"));
+    }
+
+    #[tokio::test]
+    async fn burn_before_delivery_is_ignored() {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let code = new_code();
+        let url = format!("{}/channel/{}", relay_url, code);
+
+        // A peer that joins a code and burns it without delivering anything
+        let (mut first, _) = connect_async(&url).await.unwrap();
+        sleep(Duration::from_millis(100)).await;
+        let (mut second, _) = connect_async(&url).await.unwrap();
+        sleep(Duration::from_millis(100)).await;
+        second
+            .send(Message::Text(
+                enseal::transfer::relay::BURN_FRAME.to_string(),
+            ))
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(100)).await;
+        let _ = second.close(None).await;
+        let _ = first.close(None).await;
+        sleep(Duration::from_millis(200)).await;
+
+        // The code still works for a real transfer
+        let relay_url_send = relay_url.clone();
+        let code_send = code.clone();
+        let send_handle = tokio::spawn(async move {
+            enseal::transfer::relay::send(
+                b"STILL=usable",
+                &relay_url_send,
+                &code_send,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+            .unwrap();
+        });
+        sleep(Duration::from_millis(100)).await;
+        let received = enseal::transfer::relay::receive(
+            &relay_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(received, b"STILL=usable");
+        send_handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn identity_channel_is_never_burned() {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let channel_id = "0123456789abcdef0123456789abcdef";
+        let url = format!("{}/channel/{}", relay_url, channel_id);
+
+        // A full delivery with an ack, followed by a burn frame from the sender
+        let (mut sender, _) = connect_async(&url).await.unwrap();
+        sleep(Duration::from_millis(100)).await;
+        let (mut listener, _) = connect_async(&url).await.unwrap();
+        sender
+            .send(Message::Binary(b"FIRST=push".to_vec()))
+            .await
+            .unwrap();
+        loop {
+            match listener.next().await.unwrap().unwrap() {
+                Message::Binary(data) => {
+                    assert_eq!(data, b"FIRST=push");
+                    break;
+                }
+                _ => continue,
+            }
+        }
+        listener
+            .send(Message::Binary(b"ack".to_vec()))
+            .await
+            .unwrap();
+        loop {
+            if let Message::Binary(_) = sender.next().await.unwrap().unwrap() {
+                break;
+            }
+        }
+        sender
+            .send(Message::Text(
+                enseal::transfer::relay::BURN_FRAME.to_string(),
+            ))
+            .await
+            .unwrap();
+        let _ = sender.close(None).await;
+        let _ = listener.close(None).await;
+        sleep(Duration::from_millis(200)).await;
+
+        // The identity channel keeps accepting pushes
+        let relay_url_push = relay_url.clone();
+        let push_handle = tokio::spawn(async move {
+            enseal::transfer::relay::push(
+                b"SECOND=push",
+                &relay_url_push,
+                channel_id,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+            .unwrap();
+        });
+        sleep(Duration::from_millis(100)).await;
+        let received = enseal::transfer::relay::listen(
+            &relay_url,
+            channel_id,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(received, b"SECOND=push");
+        push_handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn sender_can_resume_code_after_reconnect() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let code = new_code();
+
+        // The first sender dies after the code went out, before anyone received
+        let relay_url_send = relay_url.clone();
+        let code_send = code.clone();
+        let first = tokio::spawn(async move {
+            enseal::transfer::relay::send(
+                b"FIRST=attempt",
+                &relay_url_send,
+                &code_send,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+        });
+        sleep(Duration::from_millis(100)).await;
+        first.abort();
+        let _ = first.await;
+        sleep(Duration::from_millis(100)).await;
+
+        // Reconnecting with the same code still reaches the receiver
+        let relay_url_send = relay_url.clone();
+        let code_send = code.clone();
+        let resumed = tokio::spawn(async move {
+            enseal::transfer::relay::send(
+                b"RESUMED=1",
+                &relay_url_send,
+                &code_send,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+            .unwrap();
+        });
+        sleep(Duration::from_millis(100)).await;
+
+        let received = enseal::transfer::relay::receive(
+            &relay_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(received, b"RESUMED=1");
+        resumed.await.unwrap();
+    }
+
     #[tokio::test]
     async fn relay_multiple_channels() {
         let port = start_relay(30).await;
@@ -102,7 +532,7 @@ mod relay_tests {
         let mut handles = Vec::new();
         for i in 0..3 {
             let url = relay_url.clone();
-            let code = enseal::transfer::relay::generate_code();
+            let code = new_code();
             let data = format!("CHANNEL_{i}=value_{i}");
 
             handles.push(tokio::spawn(async move {
@@ -111,14 +541,27 @@ mod relay_tests {
                 let send_data = data.as_bytes().to_vec();
 
                 let sender = tokio::spawn(async move {
-                    enseal::transfer::relay::send(&send_data, &send_url, &send_code)
-                        .await
-                        .unwrap();
+                    enseal::transfer::relay::send(
+                        &send_data,
+                        &send_url,
+                        &send_code,
+                        0,
+                        enseal::transfer::DEFAULT_TIMEOUT,
+                    )
+                    .await
+                    .unwrap();
                 });
 
                 sleep(Duration::from_millis(100)).await;
 
-                let received = enseal::transfer::relay::receive(&url, &code).await.unwrap();
+                let received = enseal::transfer::relay::receive(
+                    &url,
+                    &code,
+                    0,
+                    enseal::transfer::DEFAULT_TIMEOUT,
+                )
+                .await
+                .unwrap();
                 assert_eq!(received, data.as_bytes());
                 sender.await.unwrap();
             }));
@@ -164,18 +607,29 @@ mod relay_tests {
         let recv_url = relay_url.clone();
         let recv_channel = receiver_channel.clone();
         let recv_handle = tokio::spawn(async move {
-            enseal::transfer::relay::listen(&recv_url, &recv_channel)
-                .await
-                .unwrap()
+            enseal::transfer::relay::listen(
+                &recv_url,
+                &recv_channel,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+            .unwrap()
         });
 
         // Small delay to let receiver connect
         sleep(Duration::from_millis(100)).await;
 
         // Sender pushes
-        enseal::transfer::relay::push(&wire_bytes, &relay_url, &receiver_channel)
-            .await
-            .unwrap();
+        enseal::transfer::relay::push(
+            &wire_bytes,
+            &relay_url,
+            &receiver_channel,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
 
         // Receiver gets data
         let received_bytes = recv_handle.await.unwrap();
@@ -212,12 +666,75 @@ mod relay_tests {
         assert!(response.contains("enseal-relay"));
     }
 
+    #[tokio::test]
+    async fn health_reports_uptime_and_active_channels() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let fetch = |url: String| async move {
+            tokio::task::spawn_blocking(move || {
+                enseal::transfer::relay::health(&url, Duration::from_secs(5))
+            })
+            .await
+            .unwrap()
+            .unwrap()
+        };
+
+        let idle = fetch(relay_url.clone()).await;
+        assert_eq!(idle.active_channels, Some(0));
+        assert!(idle.uptime_secs.is_some());
+
+        // A sender waiting for its receiver holds one channel open
+        let code = new_code();
+        let url = relay_url.clone();
+        let waiting = tokio::spawn(async move {
+            enseal::transfer::relay::send(
+                b"WAITING=1",
+                &url,
+                &code,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+        });
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(fetch(relay_url.clone()).await.active_channels, Some(1));
+        waiting.abort();
+    }
+
+    #[tokio::test]
+    async fn health_reports_relay_time() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+
+        let url = relay_url.clone();
+        let health = tokio::task::spawn_blocking(move || {
+            enseal::transfer::relay::health(&url, Duration::from_secs(5))
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(health.service, "enseal-relay");
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let relay_time = health.unix_time.expect("health should include unix_time");
+        assert!(relay_time.abs_diff(now) <= 5, "relay time {}", relay_time);
+
+        let skew = enseal::transfer::relay::clock_skew(&relay_url, Duration::from_secs(5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(skew.abs() <= 5, "skew {}", skew);
+    }
+
     #[tokio::test]
     async fn relay_payload_size_limit() {
         // Start relay with 1KB max payload
         let port = start_relay_with_config(30, 1024, 100).await;
         let relay_url = format!("ws://127.0.0.1:{}", port);
-        let code = enseal::transfer::relay::generate_code();
+        let code = new_code();
 
         // Send 2KB payload — should exceed the limit
         let data = vec![0x42u8; 2048];
@@ -226,7 +743,14 @@ mod relay_tests {
         let code_send = code.clone();
         let send_handle = tokio::spawn(async move {
             // send may return an error or succeed (the relay drops the oversized message)
-            let _ = enseal::transfer::relay::send(&data, &relay_url_send, &code_send).await;
+            let _ = enseal::transfer::relay::send(
+                &data,
+                &relay_url_send,
+                &code_send,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await;
         });
 
         sleep(Duration::from_millis(100)).await;
@@ -234,7 +758,12 @@ mod relay_tests {
         // Receiver should either get an error or timeout — the oversized message is dropped
         let recv_result = tokio::time::timeout(
             Duration::from_secs(2),
-            enseal::transfer::relay::receive(&relay_url, &code),
+            enseal::transfer::relay::receive(
+                &relay_url,
+                &code,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            ),
         )
         .await;
 
@@ -247,6 +776,74 @@ mod relay_tests {
         send_handle.abort();
     }
 
+    #[tokio::test]
+    async fn chunked_send_fits_payload_under_relay_limit() {
+        // Same 1KB relay as above, with a payload several times over it
+        let port = start_relay_with_config(30, 1024, 100).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let code = new_code();
+        let data: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+
+        let url = relay_url.clone();
+        let code_send = code.clone();
+        let payload = data.clone();
+        let send_handle = tokio::spawn(async move {
+            enseal::transfer::relay::send_chunked(
+                &payload,
+                &url,
+                &code_send,
+                1024,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+        });
+        sleep(Duration::from_millis(100)).await;
+
+        let received = enseal::transfer::relay::receive(
+            &relay_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(received, data);
+        send_handle.await.unwrap().unwrap();
+
+        // The relay advertises its limit so senders can pick a chunk size
+        let limit = enseal::transfer::relay::frame_limit(&relay_url, Duration::from_secs(5)).await;
+        assert_eq!(limit, Some(1024));
+    }
+
+    #[tokio::test]
+    async fn chunked_push_to_listener_round_trip() {
+        let port = start_relay_with_config(30, 1024, 100).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let channel = "chunked-listen-channel";
+        let data = vec![0x5au8; 3000];
+
+        let url = relay_url.clone();
+        let listen_handle = tokio::spawn(async move {
+            enseal::transfer::relay::listen(&url, channel, 0, enseal::transfer::DEFAULT_TIMEOUT)
+                .await
+                .unwrap()
+        });
+        sleep(Duration::from_millis(100)).await;
+
+        enseal::transfer::relay::push_chunked(
+            &data,
+            &relay_url,
+            channel,
+            1000,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(listen_handle.await.unwrap(), data);
+    }
+
     #[tokio::test]
     async fn relay_rate_limit() {
         use tokio_tungstenite::connect_async;
@@ -270,4 +867,500 @@ mod relay_tests {
         // tungstenite returns an error when the server responds with a non-101 status
         assert!(conn3.is_err(), "third connection should be rate-limited");
     }
+
+    #[tokio::test]
+    async fn store_and_forward_deposit_then_retrieve() {
+        let port = start_store_and_forward_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let code = new_code();
+
+        let data = b"DEPOSITED=while_receiver_offline";
+
+        // Sender completes without the receiver ever being online
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            enseal::transfer::relay::send(
+                data,
+                &relay_url,
+                &code,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            ),
+        )
+        .await
+        .expect("deposit should not wait for a receiver")
+        .unwrap();
+
+        sleep(Duration::from_millis(200)).await;
+
+        let received = enseal::transfer::relay::receive(
+            &relay_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(received, data);
+    }
+
+    #[tokio::test]
+    async fn store_and_forward_payload_is_delivered_once() {
+        let port = start_store_and_forward_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let code = new_code();
+
+        enseal::transfer::relay::send(
+            b"ONCE=1",
+            &relay_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        let received = enseal::transfer::relay::receive(
+            &relay_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(received, b"ONCE=1");
+
+        // A second pickup finds nothing and just waits
+        let second = tokio::time::timeout(
+            Duration::from_secs(1),
+            enseal::transfer::relay::receive(
+                &relay_url,
+                &code,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            ),
+        )
+        .await;
+        assert!(second.is_err(), "payload should only be delivered once");
+    }
+
+    #[tokio::test]
+    async fn store_and_forward_undelivered_payload_expires() {
+        let port = start_store_and_forward_relay(1).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let code = new_code();
+
+        enseal::transfer::relay::send(
+            b"EXPIRES=soon",
+            &relay_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+
+        // Wait past the TTL so the deposited payload is swept
+        sleep(Duration::from_millis(2500)).await;
+
+        let recv_result = tokio::time::timeout(
+            Duration::from_secs(1),
+            enseal::transfer::relay::receive(
+                &relay_url,
+                &code,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            ),
+        )
+        .await;
+        assert!(
+            recv_result.is_err(),
+            "expired payload should not be delivered"
+        );
+    }
+
+    /// Forward TCP connections to `port`, dropping the first `reject` of them
+    /// before the WebSocket handshake. Returns the proxy port.
+    async fn start_flaky_proxy(port: u16, reject: usize) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let mut seen = 0;
+            loop {
+                let (mut inbound, _) = listener.accept().await.unwrap();
+                seen += 1;
+                if seen <= reject {
+                    drop(inbound);
+                    continue;
+                }
+                tokio::spawn(async move {
+                    let mut upstream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+                        .await
+                        .unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut inbound, &mut upstream).await;
+                });
+            }
+        });
+
+        proxy_port
+    }
+
+    #[tokio::test]
+    async fn relay_send_retries_rejected_connection() {
+        let port = start_relay(30).await;
+        let proxy_port = start_flaky_proxy(port, 1).await;
+        let code = new_code();
+
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let code_recv = code.clone();
+        let recv_handle = tokio::spawn(async move {
+            enseal::transfer::relay::receive(
+                &relay_url,
+                &code_recv,
+                0,
+                enseal::transfer::DEFAULT_TIMEOUT,
+            )
+            .await
+            .unwrap()
+        });
+        sleep(Duration::from_millis(100)).await;
+
+        let proxy_url = format!("ws://127.0.0.1:{}", proxy_port);
+        enseal::transfer::relay::send(
+            b"RETRIED=1",
+            &proxy_url,
+            &code,
+            3,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+
+        let received = recv_handle.await.unwrap();
+        assert_eq!(received, b"RETRIED=1");
+    }
+
+    #[tokio::test]
+    async fn relay_send_without_retries_fails_on_rejection() {
+        let port = start_relay(30).await;
+        let proxy_port = start_flaky_proxy(port, 1).await;
+        let code = new_code();
+
+        let proxy_url = format!("ws://127.0.0.1:{}", proxy_port);
+        let result = enseal::transfer::relay::send(
+            b"NOPE=1",
+            &proxy_url,
+            &code,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn listen_times_out_when_no_peer_connects() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let home = tempfile::TempDir::new().unwrap();
+
+        let output = tokio::task::spawn_blocking(move || {
+            common::enseal_at(home.path())
+                .args(["keys", "init"])
+                .assert()
+                .success();
+
+            let started = std::time::Instant::now();
+            let output = common::enseal_at(home.path())
+                .args(["inject", "--listen", "--relay", &relay_url])
+                .args(["--timeout", "1", "--print"])
+                .output()
+                .unwrap();
+            (output, started.elapsed())
+        })
+        .await
+        .unwrap();
+
+        let (output, elapsed) = output;
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("timed out after 1s"), "stderr: {}", stderr);
+        assert!(elapsed < Duration::from_secs(10), "took {:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn unresponsive_waiting_client_frees_channel_before_ttl() {
+        use futures_util::StreamExt;
+        use tokio_tungstenite::connect_async;
+
+        let port = serve(enseal::server::ServerConfig {
+            max_channels: 1,
+            channel_ttl_secs: 300,
+            ping_interval_secs: 1,
+            pong_timeout_secs: 1,
+            ..Default::default()
+        })
+        .await;
+        let base_url = format!("ws://127.0.0.1:{}", port);
+
+        // Connect and never poll again: the socket stays open but pings go unanswered
+        let (_dead, _) = connect_async(format!("{}/channel/dead-peer", base_url))
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(100)).await;
+
+        // The only slot is taken, so the next waiting client is closed at once
+        let (mut probe, _) = connect_async(format!("{}/channel/probe-1", base_url))
+            .await
+            .unwrap();
+        let msg = tokio::time::timeout(Duration::from_secs(1), probe.next())
+            .await
+            .expect("full relay should close the connection");
+        assert!(matches!(
+            msg,
+            Some(Ok(tokio_tungstenite::tungstenite::Message::Close(_))) | None
+        ));
+
+        // After a missed pong the slot is free again, long before the 300s TTL
+        sleep(Duration::from_secs(3)).await;
+        let (mut probe, _) = connect_async(format!("{}/channel/probe-2", base_url))
+            .await
+            .unwrap();
+        let waited = tokio::time::timeout(Duration::from_millis(500), probe.next()).await;
+        assert!(waited.is_err(), "probe should be waiting for its pair");
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn inject_dry_run_lists_keys_without_running_command() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let home = tempfile::TempDir::new().unwrap();
+        let home_path = home.path().to_path_buf();
+        let marker = home_path.join("ran");
+
+        let enseal_at = move || {
+            let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_enseal"));
+            cmd.env("HOME", &home_path)
+                .env("XDG_CONFIG_HOME", home_path.join(".config"));
+            cmd
+        };
+
+        common::trusted_self(home.path());
+
+        let listener = enseal_at()
+            .args(["inject", "--listen", "--dry-run", "--relay", &relay_url])
+            .args(["--", "touch", marker.to_str().unwrap()])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        sleep(Duration::from_millis(300)).await;
+
+        let pushed = tokio::task::spawn_blocking({
+            let enseal_at = enseal_at.clone();
+            let relay_url = relay_url.clone();
+            move || {
+                let mut share = enseal_at()
+                    .args(["share", "--to", "me", "--relay", &relay_url, "--quiet"])
+                    .stdin(std::process::Stdio::piped())
+                    .spawn()
+                    .unwrap();
+                use std::io::Write;
+                share
+                    .stdin
+                    .take()
+                    .unwrap()
+                    .write_all(b"TOKEN=hunter2\nDB_URL=postgres://db\n")
+                    .unwrap();
+                share.wait().unwrap()
+            }
+        })
+        .await
+        .unwrap();
+        assert!(pushed.success());
+
+        let output = tokio::task::spawn_blocking(move || listener.wait_with_output())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(stdout, "DB_URL\nTOKEN\n");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("2 variables"));
+        assert!(!stderr.contains("hunter2"));
+        assert!(!marker.exists());
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn inject_listen_loop_survives_listen_timeouts() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let home = tempfile::TempDir::new().unwrap();
+        let enseal_at = || {
+            let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_enseal"));
+            cmd.env("HOME", home.path())
+                .env("XDG_CONFIG_HOME", home.path().join(".config"));
+            cmd
+        };
+        assert!(enseal_at()
+            .args(["keys", "init"])
+            .output()
+            .unwrap()
+            .status
+            .success());
+
+        let mut listener = enseal_at()
+            .args(["inject", "--listen", "--loop", "--relay", &relay_url])
+            .args(["--timeout", "1", "--quiet", "--", "true"])
+            .stdin(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+
+        // Several listens have timed out by now; the loop keeps going
+        sleep(Duration::from_secs(3)).await;
+        let still_running = listener.try_wait().unwrap().is_none();
+        let _ = listener.kill();
+        let _ = listener.wait();
+        assert!(still_running, "listen loop exited after a timeout");
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn inject_listen_loop_skips_bad_push_and_receives_successive_pushes() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let home = tempfile::TempDir::new().unwrap();
+        let home_path = home.path().to_path_buf();
+        let out = home_path.join("received.txt");
+
+        let listener = spawn_listen_loop(&home_path, &relay_url, &out).await;
+
+        // A push that isn't a valid envelope is skipped, not fatal
+        let me = std::fs::read_to_string(home_path.join("me.pub")).unwrap();
+        let channel_id = enseal::keys::identity::TrustedKey::parse("me", &me)
+            .unwrap()
+            .channel_id();
+        enseal::transfer::relay::push(
+            b"not an envelope",
+            &relay_url,
+            &channel_id,
+            0,
+            enseal::transfer::DEFAULT_TIMEOUT,
+        )
+        .await
+        .unwrap();
+
+        for token in ["first", "second"] {
+            share_to_me(&home_path, &relay_url, &format!("TOKEN={}\n", token)).await;
+        }
+
+        let received = wait_for_lines(&out, 2).await;
+        stop(listener);
+        assert_eq!(received, "first\nsecond\n");
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn inject_listen_loop_skips_refused_transfer() {
+        let port = start_relay(30).await;
+        let relay_url = format!("ws://127.0.0.1:{}", port);
+        let home = tempfile::TempDir::new().unwrap();
+        let home_path = home.path().to_path_buf();
+        let out = home_path.join("received.txt");
+
+        let listener = spawn_listen_loop(&home_path, &relay_url, &out).await;
+        // A reserved name is refused without --allow-unsafe-names
+        share_to_me(
+            &home_path,
+            &relay_url,
+            "LD_PRELOAD=/tmp/evil.so\nTOKEN=refused\n",
+        )
+        .await;
+        share_to_me(&home_path, &relay_url, "TOKEN=accepted\n").await;
+
+        let received = wait_for_lines(&out, 1).await;
+        stop(listener);
+        assert_eq!(received, "accepted\n");
+    }
+
+    /// Run enseal with its config directory inside `home`.
+    fn enseal_cmd(home: &std::path::Path) -> std::process::Command {
+        let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_enseal"));
+        cmd.env("HOME", home)
+            .env("XDG_CONFIG_HOME", home.join(".config"));
+        cmd
+    }
+
+    /// Trust our own key in `home`, then start `inject --listen --loop`
+    /// appending each injected `$TOKEN` to `out`.
+    async fn spawn_listen_loop(
+        home: &std::path::Path,
+        relay_url: &str,
+        out: &std::path::Path,
+    ) -> std::process::Child {
+        let (home, relay_url) = (home.to_path_buf(), relay_url.to_string());
+        let append = format!("echo \"$TOKEN\" >> {}", out.display());
+        tokio::task::spawn_blocking(move || {
+            // Trust our own key so pushes to "me" verify
+            common::trusted_self(&home);
+            enseal_cmd(&home)
+                .args([
+                    "inject", "--listen", "--loop", "--relay", &relay_url, "--quiet",
+                ])
+                .args(["--", "sh", "-c", &append])
+                .stdin(std::process::Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Push `content` to our own listen channel with `share --to me`.
+    async fn share_to_me(home: &std::path::Path, relay_url: &str, content: &str) {
+        let (home, relay_url) = (home.to_path_buf(), relay_url.to_string());
+        let payload = content.to_string();
+        let pushed = tokio::task::spawn_blocking(move || {
+            let mut share = enseal_cmd(&home)
+                .args(["share", "--to", "me", "--relay", &relay_url, "--quiet"])
+                .stdin(std::process::Stdio::piped())
+                .spawn()
+                .unwrap();
+            use std::io::Write;
+            share
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(payload.as_bytes())
+                .unwrap();
+            share.wait().unwrap()
+        })
+        .await
+        .unwrap();
+        assert!(pushed.success(), "push of {:?} failed", content);
+    }
+
+    /// Read `out` once it has `lines` lines; the last command may still be
+    /// running when its push completes.
+    async fn wait_for_lines(out: &std::path::Path, lines: usize) -> String {
+        let mut received = String::new();
+        for _ in 0..50 {
+            received = std::fs::read_to_string(out).unwrap_or_default();
+            if received.lines().count() >= lines {
+                break;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        received
+    }
+
+    fn stop(mut child: std::process::Child) {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
 }
@@ -1,10 +1,15 @@
+mod common;
+
 use assert_cmd::Command;
 use predicates::prelude::*;
 use std::fs;
 use tempfile::TempDir;
 
+#[cfg(unix)]
+use common::enseal_at;
+
 fn enseal() -> Command {
-    Command::cargo_bin("enseal").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("enseal")
 }
 
 // --- redact ---
@@ -25,6 +30,32 @@ fn redact_replaces_values() {
         .stdout(predicate::str::contains("3000").not());
 }
 
+#[test]
+fn redact_keep_and_secrets_only_leave_config_readable() {
+    let dir = TempDir::new().unwrap();
+    let env_path = dir.path().join(".env");
+    fs::write(
+        &env_path,
+        "API_TOKEN=hunter2\nPUBLIC_KEY=pk_live\nPORT=3000\n",
+    )
+    .unwrap();
+
+    enseal()
+        .args([
+            "redact",
+            env_path.to_str().unwrap(),
+            "--secrets-only",
+            "--keep",
+            "^PUBLIC_",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("API_TOKEN=<REDACTED>"))
+        .stdout(predicate::str::contains("PUBLIC_KEY=pk_live"))
+        .stdout(predicate::str::contains("PORT=3000"))
+        .stdout(predicate::str::contains("hunter2").not());
+}
+
 #[test]
 fn redact_to_output_file() {
     let dir = TempDir::new().unwrap();
@@ -98,6 +129,81 @@ fn check_missing_vars() {
         .stderr(predicate::str::contains("C"));
 }
 
+#[test]
+fn check_strict_reports_extra_vars() {
+    let dir = TempDir::new().unwrap();
+    let env_path = dir.path().join(".env");
+    let example_path = dir.path().join(".env.example");
+    fs::write(&env_path, "A=1\nB=2\nOLD_NAME=3\n").unwrap();
+    fs::write(&example_path, "A=\nB=\n").unwrap();
+
+    enseal()
+        .args([
+            "check",
+            env_path.to_str().unwrap(),
+            "--example",
+            example_path.to_str().unwrap(),
+            "--strict",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("extra in"))
+        .stderr(predicate::str::contains("OLD_NAME"));
+}
+
+#[test]
+fn check_ignores_extra_vars_without_strict() {
+    let dir = TempDir::new().unwrap();
+    let env_path = dir.path().join(".env");
+    let example_path = dir.path().join(".env.example");
+    fs::write(&env_path, "A=1\nB=2\nOLD_NAME=3\n").unwrap();
+    fs::write(&example_path, "A=\nB=\n").unwrap();
+
+    enseal()
+        .args([
+            "check",
+            env_path.to_str().unwrap(),
+            "--example",
+            example_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("OLD_NAME").not());
+}
+
+#[test]
+fn check_against_manifest() {
+    let dir = TempDir::new().unwrap();
+    let manifest = dir.path().join("share.json");
+    fs::write(
+        &manifest,
+        r#"{"timestamp":0,"mode":"file-drop","recipients":[],"vars":["A","B"],"sha256":"00"}"#,
+    )
+    .unwrap();
+    let check = |env: &str| {
+        let env_path = dir.path().join(".env");
+        fs::write(&env_path, env).unwrap();
+        enseal()
+            .args([
+                "check",
+                env_path.to_str().unwrap(),
+                "--against",
+                manifest.to_str().unwrap(),
+            ])
+            .assert()
+    };
+
+    check("B=other\nA=1\n")
+        .success()
+        .stderr(predicate::str::contains("exactly the 2 vars"));
+    check("A=1\nC=3\n")
+        .code(3)
+        .stderr(predicate::str::contains("missing from"))
+        .stderr(predicate::str::contains("extra in"))
+        .stderr(predicate::str::contains("  B\n"))
+        .stderr(predicate::str::contains("  C\n"));
+}
+
 // --- diff ---
 
 #[test]
@@ -151,3 +257,984 @@ fn diff_never_shows_values() {
     assert!(!stdout.contains("another_secret"));
     assert!(!stderr.contains("another_secret"));
 }
+
+#[test]
+fn diff_normalizes_case_and_prefix() {
+    let dir = TempDir::new().unwrap();
+    let f1 = dir.path().join("a.env");
+    let f2 = dir.path().join("b.env");
+    fs::write(&f1, "APP_API_KEY=1\nAPP_DB_URL=2\nDB_URL=3\n").unwrap();
+    fs::write(&f2, "api_key=1\ndb_url=2\n").unwrap();
+
+    enseal()
+        .args(["diff", f1.to_str().unwrap(), f2.to_str().unwrap()])
+        .assert()
+        .code(1);
+    enseal()
+        .args([
+            "diff",
+            f1.to_str().unwrap(),
+            f2.to_str().unwrap(),
+            "--ignore-case",
+            "--strip-prefix",
+            "APP_",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "APP_DB_URL, DB_URL all normalize to 'DB_URL'",
+        ))
+        .stderr(predicate::str::contains("no differences"));
+}
+
+// --- share ---
+
+#[test]
+fn share_dry_run_lists_keys_without_sending() {
+    // Piped stdin is the input (it takes precedence over a file argument)
+    enseal()
+        .args(["share", "--exclude", "^PUBLIC_", "--dry-run"])
+        .write_stdin(
+            "DB_HOST=db.internal\nAPI_KEY=sk_live_9f8e7d6c5b4a\nPUBLIC_URL=https://example.com\n",
+        )
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("DB_HOST"))
+        .stdout(predicate::str::contains("API_KEY"))
+        .stdout(predicate::str::contains("PUBLIC_URL").not())
+        .stdout(predicate::str::contains("sk_live").not())
+        .stderr(predicate::str::contains("sk_live").not())
+        .stderr(predicate::str::contains("Share code").not())
+        .stderr(predicate::str::contains("2 variables"));
+}
+
+#[test]
+fn share_six_word_codes_need_relay_code() {
+    enseal()
+        .args(["share", "--secret", "TOKEN=abc", "--words", "6"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "--words above 5 requires --relay-code",
+        ));
+}
+
+#[test]
+fn share_resume_reports_spent_wormhole_code() {
+    enseal()
+        .args(["share", "--resume", "7-guitarist-revenge"])
+        .write_stdin("TOKEN=abc\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "cannot resume '7-guitarist-revenge'",
+        ))
+        .stderr(predicate::str::contains("consumed or will expire"));
+}
+
+#[test]
+#[cfg(unix)]
+fn share_manifest_records_file_drop_without_values() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::trusted_self(home);
+    let fingerprint = enseal_at(home)
+        .args(["keys", "fingerprint"])
+        .output()
+        .unwrap()
+        .stdout;
+
+    let manifest_path = home.join("share.json");
+    enseal_at(home)
+        .args([
+            "share",
+            "--to",
+            "me",
+            "--output",
+            home.join("drop").to_str().unwrap(),
+            "--manifest",
+            manifest_path.to_str().unwrap(),
+        ])
+        .write_stdin("TOKEN=abc123\nDB_PASS=hunter2\n")
+        .assert()
+        .success();
+
+    let raw = fs::read_to_string(&manifest_path).unwrap();
+    let manifest: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    assert_eq!(manifest["mode"], "file-drop");
+    assert_eq!(manifest["vars"], serde_json::json!(["TOKEN", "DB_PASS"]));
+    assert_eq!(
+        manifest["recipients"][0]["fingerprint"],
+        String::from_utf8(fingerprint).unwrap().trim()
+    );
+    assert!(!raw.contains("abc123") && !raw.contains("hunter2"));
+}
+
+#[test]
+#[cfg(unix)]
+fn verify_manifest_matches_received_file_and_flags_tampering() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::trusted_self(home);
+
+    let manifest = home.join("share.json");
+    let drop_dir = home.join("drop");
+    enseal_at(home)
+        .args([
+            "share",
+            "--to",
+            "me",
+            "--output",
+            drop_dir.to_str().unwrap(),
+            "--manifest",
+            manifest.to_str().unwrap(),
+        ])
+        .write_stdin("TOKEN=abc123\nDB_PASS=hunter2\n")
+        .assert()
+        .success();
+    let received = enseal_at(home)
+        .args([
+            "receive",
+            drop_dir.join("me.env.age").to_str().unwrap(),
+            "--raw",
+        ])
+        .output()
+        .unwrap()
+        .stdout;
+    let env_file = home.join(".env");
+    fs::write(&env_file, received).unwrap();
+
+    let verify = |file: &std::path::Path| {
+        enseal()
+            .args([
+                "verify-manifest",
+                manifest.to_str().unwrap(),
+                file.to_str().unwrap(),
+            ])
+            .assert()
+    };
+    verify(&env_file)
+        .success()
+        .stderr(predicate::str::contains("matches"));
+
+    fs::write(&env_file, "TOKEN=abc123\nDB_PASS=changed\nEXTRA=1\n").unwrap();
+    verify(&env_file)
+        .code(3)
+        .stderr(predicate::str::contains("extra: EXTRA"))
+        .stderr(predicate::str::contains("sha256 mismatch"))
+        .stderr(predicate::str::contains("changed").not());
+}
+
+#[test]
+#[cfg(unix)]
+fn share_to_role_expands_identities_and_groups() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::init_and_import(home, &["alice", "bob", "carol"]);
+    enseal_at(home)
+        .args(["keys", "group", "create", "ops"])
+        .assert()
+        .success();
+    for member in ["bob", "carol"] {
+        enseal_at(home)
+            .args(["keys", "group", "add", "ops", member])
+            .assert()
+            .success();
+    }
+    fs::write(
+        home.join(".enseal.toml"),
+        "[roles]\nprod = [\"alice\", \"ops\", \"bob\"]\nstaging = [\"alice\"]\n",
+    )
+    .unwrap();
+
+    let output = enseal_at(home)
+        .current_dir(home)
+        .args(["share", "--to-role", "prod", "--dry-run"])
+        .write_stdin("API_KEY=abc123\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    for name in ["alice", "bob", "carol"] {
+        assert_eq!(
+            stderr.matches(&format!("{} (", name)).count(),
+            1,
+            "{name} should be listed once: {stderr}"
+        );
+    }
+
+    enseal_at(home)
+        .current_dir(home)
+        .args(["share", "--to-role", "qa", "--dry-run"])
+        .write_stdin("API_KEY=abc123\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown role 'qa' (available: prod, staging)",
+        ));
+}
+
+#[test]
+fn share_interactive_needs_a_terminal() {
+    enseal()
+        .args(["share", "-i", "--secret", "TOKEN=abc"])
+        .write_stdin("")
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("--interactive needs a terminal"));
+}
+
+#[test]
+fn share_interactive_conflicts_with_to() {
+    enseal()
+        .args(["share", "-i", "--to", "alice", "--secret", "TOKEN=abc"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+#[cfg(unix)]
+fn share_uses_configured_relay_unless_overridden() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::trusted_self(home);
+    fs::write(
+        home.join(".config/enseal/config.toml"),
+        "relay_url = \"wss://relay.internal\"\n",
+    )
+    .unwrap();
+
+    enseal_at(home)
+        .env_remove("ENSEAL_RELAY")
+        .args(["share", "--to", "me", "--dry-run"])
+        .write_stdin("TOKEN=abc\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "relay push via wss://relay.internal",
+        ));
+    enseal_at(home)
+        .env("ENSEAL_RELAY", "wss://relay.env")
+        .args(["share", "--to", "me", "--dry-run"])
+        .write_stdin("TOKEN=abc\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("relay push via wss://relay.env"));
+    enseal_at(home)
+        .args([
+            "share",
+            "--to",
+            "me",
+            "--dry-run",
+            "--relay",
+            "wss://relay.cli",
+        ])
+        .write_stdin("TOKEN=abc\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("relay push via wss://relay.cli"));
+}
+
+#[test]
+fn share_rejects_broken_project_config() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join(".enseal.toml"), "[metadata\n").unwrap();
+    enseal()
+        .current_dir(dir.path())
+        .args(["share", "--secret", "TOKEN=abc", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to parse .enseal.toml"));
+}
+
+#[test]
+#[cfg(unix)]
+fn broken_user_config_only_fails_commands_that_read_it() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+    enseal_at(home).args(["keys", "init"]).assert().success();
+    fs::write(home.join(".config/enseal/config.toml"), "relay_url = [\n").unwrap();
+
+    enseal_at(home).args(["keys", "list"]).assert().success();
+    enseal_at(home)
+        .args(["keys", "fingerprint"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to parse"));
+}
+
+// --- version ---
+
+#[test]
+fn version_reports_server_feature() {
+    let expected = if cfg!(feature = "server") {
+        "server: enabled"
+    } else {
+        "server: disabled"
+    };
+    enseal()
+        .arg("version")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(env!("CARGO_PKG_VERSION")))
+        .stdout(predicate::str::contains(expected))
+        .stdout(predicate::str::contains("target:"));
+}
+
+// --- receive ---
+
+#[test]
+#[cfg(unix)]
+fn receive_raw_dumps_exact_payload() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::trusted_self(home);
+
+    let content = "DB_URL=postgres://localhost/app\nTOKEN=abc123\n";
+    let drop_dir = home.join("drop");
+    enseal_at(home)
+        .args([
+            "share",
+            "--to",
+            "me",
+            "--output",
+            drop_dir.to_str().unwrap(),
+        ])
+        .write_stdin(content)
+        .assert()
+        .success();
+
+    let drop_file = drop_dir.join("me.env.age");
+    enseal_at(home)
+        .current_dir(home)
+        .args(["receive", drop_file.to_str().unwrap(), "--raw"])
+        .assert()
+        .success()
+        .stdout(content)
+        .stderr("");
+    // Warnings stay off stderr too, as with --quiet
+    enseal_at(home)
+        .current_dir(home)
+        .args([
+            "receive",
+            drop_file.to_str().unwrap(),
+            "--raw",
+            "--ignore-age",
+        ])
+        .assert()
+        .success()
+        .stdout(content)
+        .stderr("");
+    assert!(!home.join(".env").exists());
+}
+
+#[test]
+#[cfg(unix)]
+fn receive_raw_dumps_binary_payload() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::trusted_self(home);
+
+    // `share <file>` prefers piped stdin, so drop the binary through the library
+    use enseal::crypto::envelope::Envelope;
+    use enseal::keys::{identity::EnsealIdentity, store::KeyStore};
+    let store = KeyStore::open_at(home.join(".config").join("enseal"));
+    let me = EnsealIdentity::load(&store).unwrap();
+    let blob: Vec<u8> = vec![0x00, 0xff, 0xfe, b'\n', 0x80, 0x01];
+    let envelope = Envelope::seal_binary(&blob, None).unwrap();
+    let drop_file = enseal::transfer::filedrop::write(
+        &envelope,
+        &[&me.age_recipient],
+        &me,
+        &home.join("drop"),
+        "me",
+        None,
+    )
+    .unwrap();
+
+    enseal_at(home)
+        .current_dir(home)
+        .args(["receive", drop_file.to_str().unwrap(), "--raw"])
+        .assert()
+        .success()
+        .stdout(blob)
+        .stderr("");
+}
+
+#[test]
+#[cfg(unix)]
+fn receive_output_dash_writes_payload_to_stdout_for_every_format() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::trusted_self(home);
+
+    // (payload, how it's shared): .env on stdin, a raw token, a KEY=VALUE pair
+    let cases: [(&str, Option<&str>); 3] = [
+        ("DB_URL=postgres://localhost/app\nTOKEN=abc123\n", None),
+        ("sk_live_abc123", Some("sk_live_abc123")),
+        ("API_KEY=abc123", Some("API_KEY=abc123")),
+    ];
+    for (i, (payload, secret)) in cases.into_iter().enumerate() {
+        let drop_dir = home.join(format!("drop{}", i));
+        let mut share = enseal_at(home);
+        share.args([
+            "share",
+            "--to",
+            "me",
+            "--output",
+            drop_dir.to_str().unwrap(),
+        ]);
+        match secret {
+            Some(secret) => share.args(["--secret", secret]),
+            None => share.write_stdin(payload),
+        };
+        share.assert().success();
+
+        let drop_file = drop_dir.join("me.env.age");
+        enseal_at(home)
+            .current_dir(home)
+            .args(["receive", drop_file.to_str().unwrap(), "--output", "-"])
+            .assert()
+            .success()
+            .stdout(payload)
+            .stderr("");
+        assert!(!home.join("-").exists() && !home.join(".env").exists());
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn receive_reads_code_from_stdin() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::trusted_self(home);
+
+    let drop_dir = home.join("drop");
+    enseal_at(home)
+        .args([
+            "share",
+            "--to",
+            "me",
+            "--output",
+            drop_dir.to_str().unwrap(),
+        ])
+        .write_stdin("TOKEN=abc123\n")
+        .assert()
+        .success();
+
+    let drop_file = drop_dir.join("me.env.age");
+    enseal_at(home)
+        .args(["receive", "-", "--raw"])
+        .write_stdin(format!("  {}  \n", drop_file.display()))
+        .assert()
+        .success()
+        .stdout("TOKEN=abc123\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn receive_shows_project_tag() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::trusted_self(home);
+
+    let project_dir = home.join("billing-api");
+    fs::create_dir(&project_dir).unwrap();
+    for (project, drop) in [(Some("myapp"), "tagged"), (None, "fallback")] {
+        let drop_dir = home.join(drop);
+        let mut cmd = enseal_at(home);
+        cmd.current_dir(&project_dir).args([
+            "share",
+            "--to",
+            "me",
+            "--output",
+            drop_dir.to_str().unwrap(),
+        ]);
+        if let Some(project) = project {
+            cmd.args(["--project", project]);
+        }
+        cmd.write_stdin("TOKEN=abc123\n").assert().success();
+
+        let expected = project.unwrap_or("billing-api");
+        enseal_at(home)
+            .current_dir(home)
+            .args([
+                "receive",
+                drop_dir.join("me.env.age").to_str().unwrap(),
+                "--no-write",
+            ])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Project:").and(predicate::str::contains(expected)));
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn receive_apply_defaults_fills_missing_keys_only() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::trusted_self(home);
+
+    let drop_dir = home.join("drop");
+    enseal_at(home)
+        .args([
+            "share",
+            "--to",
+            "me",
+            "--output",
+            drop_dir.to_str().unwrap(),
+        ])
+        .write_stdin("PORT=3000\n")
+        .assert()
+        .success();
+
+    fs::write(
+        home.join(".enseal.toml"),
+        "[schema.rules.PORT]\ndefault = \"8080\"\n\n[schema.rules.LOG_LEVEL]\ndefault = \"info\"\n",
+    )
+    .unwrap();
+    enseal_at(home)
+        .current_dir(home)
+        .args(["receive", drop_dir.join("me.env.age").to_str().unwrap()])
+        .args(["--no-write", "--apply-defaults"])
+        .assert()
+        .success()
+        .stdout("PORT=3000\nLOG_LEVEL=info\n");
+}
+
+#[test]
+fn receive_raw_conflicts_with_clipboard() {
+    enseal()
+        .args(["receive", "some-code", "--raw", "--clipboard"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+// --- keys export ---
+
+#[test]
+#[cfg(unix)]
+fn keys_export_output_matches_stdout() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    enseal_at(home).args(["keys", "init"]).assert().success();
+    let stdout = enseal_at(home).args(["keys", "export"]).output().unwrap();
+    let path = home.join("me.pub");
+    enseal_at(home)
+        .args(["keys", "export", "--output", path.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(fs::read(&path).unwrap(), stdout.stdout);
+}
+
+// --- keys group ---
+
+#[test]
+#[cfg(unix)]
+fn group_add_requires_known_member() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::init_and_import(home, &["alice"]);
+    enseal_at(home)
+        .args(["keys", "group", "create", "backend"])
+        .assert()
+        .success();
+
+    enseal_at(home)
+        .args(["keys", "group", "add", "backend", "alice"])
+        .assert()
+        .success();
+    enseal_at(home)
+        .args(["keys", "group", "add", "backend", "ghost"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("enseal keys import"));
+    enseal_at(home)
+        .args([
+            "keys",
+            "group",
+            "add",
+            "backend",
+            "ghost",
+            "--allow-missing",
+        ])
+        .assert()
+        .success();
+
+    enseal_at(home)
+        .args(["keys", "group", "list", "backend"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("alice").and(predicate::str::contains("ghost")));
+
+    let fingerprint = enseal_at(home)
+        .args(["keys", "fingerprint"])
+        .output()
+        .unwrap()
+        .stdout;
+    let fingerprint = String::from_utf8(fingerprint).unwrap();
+    enseal_at(home)
+        .args(["keys", "group", "show", "backend", "--fingerprints"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "alice  {}",
+            fingerprint.trim()
+        )))
+        .stdout(predicate::str::contains("ghost  (no key / unresolved)"));
+}
+
+#[test]
+#[cfg(unix)]
+fn export_recipients_prints_each_group_member_once() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    let expected: Vec<String> = common::init_and_import(home, &["alice", "bob"])
+        .iter()
+        .map(|bundle| {
+            let age = bundle
+                .lines()
+                .find_map(|l| l.strip_prefix("age: "))
+                .unwrap();
+            format!("{}\n", age)
+        })
+        .collect();
+    enseal_at(home)
+        .args(["keys", "group", "create", "backend"])
+        .assert()
+        .success();
+    for member in ["alice", "bob"] {
+        enseal_at(home)
+            .args(["keys", "group", "add", "backend", member])
+            .assert()
+            .success();
+    }
+    enseal_at(home)
+        .args(["keys", "export-recipients", "backend"])
+        .assert()
+        .success()
+        .stdout(expected.concat());
+
+    // alice reached twice through a nested group is still printed once
+    enseal_at(home)
+        .args(["keys", "group", "create", "everyone"])
+        .assert()
+        .success();
+    for member in ["backend", "alice"] {
+        enseal_at(home)
+            .args(["keys", "group", "add", "everyone", member])
+            .assert()
+            .success();
+    }
+    enseal_at(home)
+        .args(["keys", "export-recipients", "everyone"])
+        .assert()
+        .success()
+        .stdout(expected.concat());
+    enseal_at(home)
+        .args(["keys", "export-recipients", "bob"])
+        .assert()
+        .success()
+        .stdout(expected[1].clone());
+}
+
+// --- validate ---
+
+#[test]
+fn validate_warn_severity_reports_without_failing() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join(".env"), "API_KEY=short\n").unwrap();
+    fs::write(
+        dir.path().join(".enseal.toml"),
+        "[schema.rules.API_KEY]\nmin_length = 32\nseverity = \"warn\"\n",
+    )
+    .unwrap();
+
+    enseal()
+        .current_dir(dir.path())
+        .args(["validate", ".env"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "API_KEY: length 5 is below minimum 32",
+        ));
+
+    fs::write(
+        dir.path().join(".enseal.toml"),
+        "[schema.rules.API_KEY]\nmin_length = 32\n",
+    )
+    .unwrap();
+    enseal()
+        .current_dir(dir.path())
+        .args(["validate", ".env"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn empty_secret_warns_unless_no_allow_empty() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join(".env"), "API_KEY=\nPORT=3000\n").unwrap();
+
+    enseal()
+        .current_dir(dir.path())
+        .args(["validate", ".env"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "'API_KEY' looks like a secret but is empty",
+        ));
+    enseal()
+        .current_dir(dir.path())
+        .args(["share", "--dry-run"])
+        .write_stdin("API_KEY=\nPORT=3000\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "'API_KEY' looks like a secret but is empty",
+        ));
+
+    enseal()
+        .current_dir(dir.path())
+        .args(["share", "--dry-run", "--no-allow-empty"])
+        .write_stdin("API_KEY=\nPORT=3000\n")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains(
+            "'API_KEY' looks like a secret but is empty",
+        ));
+    enseal()
+        .current_dir(dir.path())
+        .args(["share", "--dry-run", "--no-allow-empty", "--allow-empty"])
+        .write_stdin("API_KEY=\nPORT=3000\n")
+        .assert()
+        .success();
+}
+
+// --- exit codes ---
+
+#[test]
+fn missing_file_exits_not_found() {
+    enseal()
+        .args(["validate", "/nonexistent/.env"])
+        .assert()
+        .code(6)
+        .stderr(predicate::str::contains("failed to read"));
+    let dir = TempDir::new().unwrap();
+    let present = dir.path().join(".env");
+    fs::write(&present, "A=1\n").unwrap();
+    for args in [
+        vec!["diff", "/nonexistent/.env", present.to_str().unwrap()],
+        vec!["diff", present.to_str().unwrap(), "/nonexistent/.env"],
+        vec!["redact", "/nonexistent/.env"],
+    ] {
+        enseal()
+            .args(&args)
+            .assert()
+            .code(6)
+            .stderr(predicate::str::contains("/nonexistent/.env not found"));
+    }
+}
+
+#[test]
+fn schema_failure_exits_validation() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".env"),
+        "API_KEY=short
+",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join(".enseal.toml"),
+        "[schema.rules.API_KEY]\nmin_length = 32\n",
+    )
+    .unwrap();
+
+    enseal()
+        .current_dir(dir.path())
+        .args(["validate", ".env"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("validation failed"));
+}
+
+#[test]
+#[cfg(unix)]
+fn decryption_failure_exits_crypto() {
+    let owner = TempDir::new().unwrap();
+    let stranger = TempDir::new().unwrap();
+    for dir in [&owner, &stranger] {
+        enseal_at(dir.path())
+            .args(["keys", "init"])
+            .assert()
+            .success();
+    }
+
+    let plain = owner.path().join(".env");
+    fs::write(&plain, "SECRET=hunter2\n").unwrap();
+    enseal_at(owner.path())
+        .args(["encrypt", plain.to_str().unwrap()])
+        .assert()
+        .success();
+
+    enseal_at(stranger.path())
+        .args([
+            "decrypt",
+            owner.path().join(".env.encrypted").to_str().unwrap(),
+            "--output",
+            stranger.path().join("out.env").to_str().unwrap(),
+        ])
+        .assert()
+        .code(4);
+}
+
+// --- doctor ---
+
+#[test]
+#[cfg(unix)]
+fn doctor_fails_without_identity_then_passes() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+    enseal_at(home)
+        .current_dir(home)
+        .env_remove("ENSEAL_RELAY")
+        .arg("doctor")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[fail] identity"))
+        .stdout(predicate::str::contains("enseal keys init"));
+
+    enseal_at(home).args(["keys", "init"]).assert().success();
+    enseal_at(home)
+        .current_dir(home)
+        .env_remove("ENSEAL_RELAY")
+        .arg("doctor")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[pass] identity"))
+        .stdout(predicate::str::contains("[pass] key permissions"))
+        .stdout(predicate::str::contains("[warn] trusted keys"));
+}
+
+#[test]
+#[cfg(unix)]
+fn audit_log_records_file_drops_when_enabled() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path();
+
+    common::trusted_self(home);
+
+    let drop_dir = home.join("drop");
+    let share = |audit: bool| {
+        let mut cmd = enseal_at(home);
+        if audit {
+            cmd.env("ENSEAL_AUDIT", "1");
+        }
+        cmd.args([
+            "share",
+            "--to",
+            "me",
+            "--output",
+            drop_dir.to_str().unwrap(),
+        ])
+        .write_stdin("TOKEN=abc123\n")
+        .assert()
+        .success();
+    };
+
+    let log = home.join(".config/enseal/audit.log");
+    share(false);
+    assert!(!log.exists());
+    share(true);
+
+    let logged = fs::read_to_string(&log).unwrap();
+    assert_eq!(logged.lines().count(), 1);
+    let entry: serde_json::Value = serde_json::from_str(logged.trim()).unwrap();
+    assert_eq!(entry["mode"], "file-drop");
+    assert_eq!(entry["recipients"][0]["identity"], "me");
+    assert!(entry["recipients"][0]["fingerprint"]
+        .as_str()
+        .unwrap()
+        .starts_with("SHA256:"));
+    assert_eq!(entry["var_count"], 1);
+    assert!(!logged.contains("abc123"));
+}
+
+#[test]
+#[cfg(unix)]
+fn trusted_tofu_sender_is_named_on_next_receive() {
+    let receiver = TempDir::new().unwrap();
+    let sender = TempDir::new().unwrap();
+
+    enseal_at(receiver.path())
+        .args(["keys", "init"])
+        .assert()
+        .success();
+    enseal_at(sender.path())
+        .args(["keys", "init"])
+        .assert()
+        .success();
+    let exported = enseal_at(receiver.path())
+        .args(["keys", "export"])
+        .output()
+        .unwrap();
+    let pubkey = sender.path().join("receiver.pub");
+    fs::write(&pubkey, exported.stdout).unwrap();
+    enseal_at(sender.path())
+        .args(["keys", "import", pubkey.to_str().unwrap(), "--yes"])
+        .assert()
+        .success();
+
+    let drop_dir = sender.path().join("drop");
+    enseal_at(sender.path())
+        .args([
+            "share",
+            "--to",
+            "receiver",
+            "--output",
+            drop_dir.to_str().unwrap(),
+        ])
+        .write_stdin("TOKEN=abc123\n")
+        .assert()
+        .success();
+    let drop_file = drop_dir.join("receiver.env.age");
+
+    let receive = || {
+        enseal_at(receiver.path())
+            .args(["receive", drop_file.to_str().unwrap(), "--no-write"])
+            .assert()
+            .success()
+    };
+    receive()
+        .stderr(predicate::str::contains("unknown sender"))
+        .stderr(predicate::str::contains("enseal keys trust <name> --tofu"));
+
+    enseal_at(receiver.path())
+        .args(["keys", "trust", "bob", "--tofu", "--yes"])
+        .assert()
+        .success();
+    receive()
+        .stderr(predicate::str::contains("From:"))
+        .stderr(predicate::str::contains("bob"))
+        .stderr(predicate::str::contains("unknown sender").not());
+}
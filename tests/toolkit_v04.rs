@@ -4,7 +4,7 @@ use std::fs;
 use tempfile::TempDir;
 
 fn enseal() -> Command {
-    Command::cargo_bin("enseal").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("enseal")
 }
 
 // --- validate ---
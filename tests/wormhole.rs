@@ -16,7 +16,7 @@ use tokio::sync::oneshot;
 /// Helper: send an envelope through wormhole, returning the code via a channel
 /// so the receiver can connect concurrently.
 async fn send_with_code(envelope: Envelope, code_tx: oneshot::Sender<String>) {
-    let config = transfer::app_config(None);
+    let config = transfer::app_config(None).unwrap();
     let mailbox = magic_wormhole::MailboxConnection::create(config, 2)
         .await
         .unwrap();
@@ -144,7 +144,7 @@ async fn identity_mode_relay_round_trip() {
 
     // Sender: create mailbox, send code, then send signed envelope
     tokio::spawn(async move {
-        let config = transfer::app_config(None);
+        let config = transfer::app_config(None).unwrap();
         let mailbox = magic_wormhole::MailboxConnection::create(config, 2)
             .await
             .unwrap();
@@ -159,7 +159,7 @@ async fn identity_mode_relay_round_trip() {
     let code = code_rx.await.unwrap();
 
     // Receive and verify + decrypt
-    let config = transfer::app_config(None);
+    let config = transfer::app_config(None).unwrap();
     let code_parsed = code.parse().unwrap();
     let mailbox = magic_wormhole::MailboxConnection::connect(config, code_parsed, true)
         .await